@@ -0,0 +1,240 @@
+use crate::storage_models;
+use eframe::egui;
+use std::collections::HashMap;
+
+/// folder with total size of all nested files
+#[derive(Debug, Clone)]
+pub struct SizeNode {
+    pub name: String,
+    /// path without leading '/', "" for root of storage
+    pub path: String,
+    pub size: u64,
+    pub files: u64,
+    pub children: Vec<SizeNode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SizeSort {
+    Size,
+    Name,
+    Files,
+}
+
+impl SizeNode {
+    /// finds nested folder by path (case insensitive)
+    pub fn find(&self, path: &str) -> Option<&SizeNode> {
+        if self.path.to_lowercase().eq(&path.to_lowercase()) {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(path))
+    }
+
+    pub fn sort(&mut self, sort: SizeSort) {
+        match sort {
+            SizeSort::Size => self
+                .children
+                .sort_by_key(|child| std::cmp::Reverse(child.size)),
+            SizeSort::Name => self.children.sort_by_key(|child| child.name.to_lowercase()),
+            SizeSort::Files => self
+                .children
+                .sort_by_key(|child| std::cmp::Reverse(child.files)),
+        }
+        for child in &mut self.children {
+            child.sort(sort);
+        }
+    }
+}
+
+#[derive(Default)]
+struct BuildNode {
+    name: String,
+    size: u64,
+    files: u64,
+    /// keyed by lowercased name
+    children: HashMap<String, BuildNode>,
+}
+
+impl BuildNode {
+    fn into_size_node(self, path: String) -> SizeNode {
+        let mut children: Vec<SizeNode> = self
+            .children
+            .into_values()
+            .map(|child| {
+                let child_path = if path.is_empty() {
+                    child.name.clone()
+                } else {
+                    format!("{}/{}", path, child.name)
+                };
+                child.into_size_node(child_path)
+            })
+            .collect();
+        children.sort_by_key(|child| std::cmp::Reverse(child.size));
+        SizeNode {
+            name: self.name,
+            path,
+            size: self.size,
+            files: self.files,
+            children,
+        }
+    }
+}
+
+/// aggregates sizes of recursive listing of `root_path`
+pub fn build_tree(root_path: &str, items: &[storage_models::Item]) -> SizeNode {
+    let prefix_len = if root_path.is_empty() {
+        1
+    } else {
+        root_path.len() + 2
+    };
+    let mut root = BuildNode {
+        name: root_path.rsplit('/').next().unwrap_or_default().to_string(),
+        ..BuildNode::default()
+    };
+    for item in items {
+        let relative = match item.path_display.get(prefix_len..) {
+            Some(relative) if !relative.is_empty() => relative,
+            _ => continue,
+        };
+        let mut parts: Vec<&str> = relative.split('/').collect();
+        let file_size = if item.is_folder {
+            None
+        } else {
+            parts.pop();
+            Some(item.size.unwrap_or(0))
+        };
+        let mut node = &mut root;
+        if let Some(size) = file_size {
+            node.size += size;
+            node.files += 1;
+        }
+        for part in parts {
+            node = node
+                .children
+                .entry(part.to_lowercase())
+                .or_insert_with(|| BuildNode {
+                    name: part.to_string(),
+                    ..BuildNode::default()
+                });
+            if let Some(size) = file_size {
+                node.size += size;
+                node.files += 1;
+            }
+        }
+    }
+    root.into_size_node(root_path.to_string())
+}
+
+/// squarified treemap: rects for `sizes` (sorted descending) inside `rect`
+pub fn treemap_layout(sizes: &[u64], rect: egui::Rect) -> Vec<egui::Rect> {
+    let total: f64 = sizes.iter().map(|v| *v as f64).sum();
+    if total <= 0.0 || rect.area() <= 0.0 {
+        return vec![];
+    }
+    let scale = rect.area() as f64 / total;
+    let areas: Vec<f64> = sizes.iter().map(|v| *v as f64 * scale).collect();
+
+    let mut res = Vec::with_capacity(areas.len());
+    let mut free = rect;
+    let mut start = 0;
+    while start < areas.len() {
+        let side = free.width().min(free.height()) as f64;
+        // grow the row while the worst aspect ratio improves
+        let mut end = start + 1;
+        let mut row_sum = areas[start];
+        while end < areas.len() {
+            let next_sum = row_sum + areas[end];
+            if worst_ratio(&areas[start..end + 1], next_sum, side)
+                > worst_ratio(&areas[start..end], row_sum, side)
+            {
+                break;
+            }
+            row_sum = next_sum;
+            end += 1;
+        }
+        let thickness = if side > 0.0 { row_sum / side } else { 0.0 } as f32;
+        let horizontal = free.width() >= free.height();
+        let mut offset = 0.0_f32;
+        for area in &areas[start..end] {
+            let length = if thickness > 0.0 {
+                *area as f32 / thickness
+            } else {
+                0.0
+            };
+            res.push(if horizontal {
+                egui::Rect::from_min_size(
+                    egui::pos2(free.min.x, free.min.y + offset),
+                    egui::vec2(thickness, length),
+                )
+            } else {
+                egui::Rect::from_min_size(
+                    egui::pos2(free.min.x + offset, free.min.y),
+                    egui::vec2(length, thickness),
+                )
+            });
+            offset += length;
+        }
+        if horizontal {
+            free.min.x += thickness;
+        } else {
+            free.min.y += thickness;
+        }
+        start = end;
+    }
+    res
+}
+
+fn worst_ratio(row: &[f64], sum: f64, side: f64) -> f64 {
+    let max = row.iter().cloned().fold(f64::MIN, f64::max);
+    let min = row.iter().cloned().fold(f64::MAX, f64::min);
+    let side2 = side * side;
+    let sum2 = sum * sum;
+    (side2 * max / sum2).max(sum2 / (side2 * min))
+}
+
+/// files with equal size and content hash
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    pub size: u64,
+    pub content_hash: String,
+    /// oldest first
+    pub items: Vec<storage_models::Item>,
+}
+
+impl DuplicateGroup {
+    /// space taken by all copies except one
+    pub fn wasted(&self) -> u64 {
+        self.size * (self.items.len().max(1) as u64 - 1)
+    }
+}
+
+/// groups files by (size, content_hash), most wasted space first
+pub fn find_duplicates(items: Vec<storage_models::Item>) -> Vec<DuplicateGroup> {
+    let mut groups: HashMap<(u64, String), Vec<storage_models::Item>> = HashMap::new();
+    for item in items {
+        if item.is_folder || item.content_hash.is_empty() {
+            continue;
+        }
+        match item.size {
+            // all empty files are "equal", not interesting
+            Some(size) if size > 0 => groups
+                .entry((size, item.content_hash.clone()))
+                .or_default()
+                .push(item),
+            _ => {}
+        }
+    }
+    let mut res: Vec<DuplicateGroup> = groups
+        .into_iter()
+        .filter(|(_, items)| items.len() > 1)
+        .map(|((size, content_hash), mut items)| {
+            items.sort_by_key(|item| item.modified);
+            DuplicateGroup {
+                size,
+                content_hash,
+                items,
+            }
+        })
+        .collect();
+    res.sort_by_key(|group| std::cmp::Reverse(group.wasted()));
+    res
+}
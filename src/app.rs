@@ -0,0 +1,2909 @@
+use eframe::{
+    egui::{self, Ui},
+    epi,
+};
+
+use crate::common_types::*;
+use crate::storage_instance::{self, StorageInstance};
+use crate::{
+    analyze, call_states, cli, config, control, formatting, init, items_filter, preview,
+    storage_models, stream_proxy,
+};
+
+enum RowAction {
+    Open { path: String, is_folder: bool },
+    GoToFolder { path: String },
+    Properties { path: String },
+    Preview { path: String },
+    Restore { path: String },
+    History { path: String },
+    Share { path: String },
+    CopyTemporaryLink { path: String },
+    OpenExternally { path: String },
+    StreamInPlayer { path: String },
+    Analyze { path: String },
+    Duplicates { path: String },
+    Compare { path: String },
+}
+
+fn item_context_menu(
+    ui: &mut Ui,
+    item: &storage_models::Item,
+    remote_path: &str,
+    row_action: &mut Option<RowAction>,
+) {
+    if !item.is_folder && ui.button("Version history...").clicked() {
+        *row_action = Some(RowAction::History {
+            path: remote_path.to_string(),
+        });
+        ui.close_menu();
+    }
+    if item.is_deleted {
+        // kind of deleted entries is unknown, restore of a folder is refused by the server
+        if ui
+            .button("Restore")
+            .on_hover_text("only files can be restored")
+            .clicked()
+        {
+            *row_action = Some(RowAction::Restore {
+                path: remote_path.to_string(),
+            });
+            ui.close_menu();
+        }
+        return;
+    }
+    if !item.is_folder && ui.button("Preview").clicked() {
+        *row_action = Some(RowAction::Preview {
+            path: remote_path.to_string(),
+        });
+        ui.close_menu();
+    }
+    if !item.is_folder && item.is_downloadable {
+        if ui.button("Open in external player").clicked() {
+            *row_action = Some(RowAction::OpenExternally {
+                path: remote_path.to_string(),
+            });
+            ui.close_menu();
+        }
+        if ui.button("Stream in player").clicked() {
+            *row_action = Some(RowAction::StreamInPlayer {
+                path: remote_path.to_string(),
+            });
+            ui.close_menu();
+        }
+        if ui.button("Copy temporary link").clicked() {
+            *row_action = Some(RowAction::CopyTemporaryLink {
+                path: remote_path.to_string(),
+            });
+            ui.close_menu();
+        }
+    }
+    if item.is_folder && ui.button("Analyze size...").clicked() {
+        *row_action = Some(RowAction::Analyze {
+            path: remote_path.to_string(),
+        });
+        ui.close_menu();
+    }
+    if item.is_folder && ui.button("Find duplicates...").clicked() {
+        *row_action = Some(RowAction::Duplicates {
+            path: remote_path.to_string(),
+        });
+        ui.close_menu();
+    }
+    if item.is_folder && ui.button("Compare with local folder...").clicked() {
+        *row_action = Some(RowAction::Compare {
+            path: remote_path.to_string(),
+        });
+        ui.close_menu();
+    }
+    if ui.button("Share...").clicked() {
+        *row_action = Some(RowAction::Share {
+            path: remote_path.to_string(),
+        });
+        ui.close_menu();
+    }
+    if ui.button("Properties...").clicked() {
+        *row_action = Some(RowAction::Properties {
+            path: remote_path.to_string(),
+        });
+        ui.close_menu();
+    }
+}
+
+fn short_name(name: &str, max_chars: usize) -> String {
+    if name.chars().count() <= max_chars {
+        name.to_string()
+    } else {
+        name.chars().take(max_chars - 3).collect::<String>() + "..."
+    }
+}
+
+fn item_remote_path(folder_path: &str, name: &str) -> String {
+    if folder_path.is_empty() {
+        name.to_string()
+    } else {
+        folder_path.to_string() + "/" + name
+    }
+}
+
+fn parent_remote_path(path: &str) -> &str {
+    match path.rsplit_once('/') {
+        Some((parent, _)) => parent,
+        None => "",
+    }
+}
+
+fn remote_file_name(path: &str) -> &str {
+    match path.rsplit_once('/') {
+        Some((_, name)) => name,
+        None => path,
+    }
+}
+
+/// renders name, download button, size and time cells of the item (without end_row)
+fn render_item_row(
+    ui: &mut Ui,
+    item: &storage_models::Item,
+    remote_path: &str,
+    view: &config::ViewConfig,
+    row_action: &mut Option<RowAction>,
+) {
+    if item.is_deleted {
+        ui.add(egui::Label::new(
+            egui::RichText::new(&item.name).weak().strikethrough(),
+        ))
+        .on_hover_text("deleted file or folder, right click to restore a file")
+        .context_menu(|ui| {
+            item_context_menu(ui, item, remote_path, row_action);
+        });
+        ui.label("");
+        ui.label("");
+        ui.label("");
+        return;
+    }
+    let name_response = ui.selectable_label(false, &item.name);
+    if name_response.clicked() {
+        *row_action = Some(RowAction::Open {
+            path: remote_path.to_string(),
+            is_folder: item.is_folder,
+        });
+    }
+    name_response.context_menu(|ui| {
+        item_context_menu(ui, item, remote_path, row_action);
+    });
+    if item.is_folder {
+        ui.label("");
+    } else if ui.button("download").clicked() {
+        *row_action = Some(RowAction::Open {
+            path: remote_path.to_string(),
+            is_folder: false,
+        });
+    }
+    match item.size {
+        Some(size) => ui.label(formatting::format_size(size, view.size_units)),
+        None => ui.label("dir"),
+    };
+    match item.modified {
+        Some(modified) => ui.label(formatting::format_time(&modified, view)),
+        None => ui.label(""),
+    };
+}
+
+pub(crate) struct AppState {
+    pub(crate) config: config::AppConfig,
+    pub(crate) rth: RuntimeHolder,
+    pub(crate) storages: Vec<StorageInstance>,
+    /// started on first "Stream in player"
+    pub(crate) stream_proxy: Option<stream_proxy::StreamProxy>,
+}
+
+impl AppState {
+    fn render_downloads(storage: &mut StorageInstance, ui: &mut Ui, view: &config::ViewConfig) {
+        for (call_id, state) in storage.call_states.iter() {
+            match &state.data {
+                call_states::Data::ListFolder { .. } => {}
+                call_states::Data::GetMetadata { .. } => {}
+                call_states::Data::GetThumbnails { .. } => {}
+                call_states::Data::Preview { .. } => {}
+                call_states::Data::Search { .. } => {}
+                call_states::Data::WatchFolder { .. } => {}
+                call_states::Data::ListRevisions { .. } => {}
+                call_states::Data::Restore { .. } => {}
+                call_states::Data::SharedLinks { .. } => {}
+                call_states::Data::TemporaryLink { .. } => {}
+                call_states::Data::AccountInfo { .. } => {}
+                call_states::Data::ListRecursive { .. } => {}
+                call_states::Data::FileOps { .. } => {}
+                call_states::Data::Compare { .. } => {}
+                call_states::Data::Mirror { .. } => {}
+                call_states::Data::Sync { .. } => {}
+                call_states::Data::Backup { .. } => {}
+                call_states::Data::WebDav { .. } => {}
+                call_states::Data::Stream { .. } => {}
+                call_states::Data::Auth { .. } => {}
+                call_states::Data::Transfer {
+                    data: call_states::Transfer::InProgress,
+                    total,
+                    done,
+                    purpose: _,
+                } => {
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::ProgressBar::new(*done as f32 / (*total).max(1) as f32)
+                                .desired_width(100.0)
+                                .show_percentage(),
+                        );
+                        ui.label(format!("transfer: {} of {} files", done, total));
+                    });
+                }
+                call_states::Data::Transfer { .. } => {}
+                call_states::Data::DownloadFile {
+                    data,
+                    remote_path,
+                    local_path: _,
+                    size,
+                    downloaded,
+                    open: _,
+                } => match data {
+                    call_states::DownloadFile::Ok => {}
+                    call_states::DownloadFile::Failed(_) => {}
+                    //                    call_states::DownloadFile::Cancelled => {}
+                    call_states::DownloadFile::Started => {}
+                    call_states::DownloadFile::SizeInfo { .. } => {}
+                    call_states::DownloadFile::InProgress { .. } => {
+                        if let Some(size) = size {
+                            let progress = *downloaded as f32 / *size as f32;
+                            let progress_bar = egui::ProgressBar::new(progress)
+                                .desired_width(100.0)
+                                .show_percentage();
+                            ui.horizontal(|ui| {
+                                ui.add(progress_bar);
+                                if ui.button("x").clicked() {
+                                    storage.cancel_download_file(*call_id);
+                                }
+                                ui.label(remote_path);
+                                ui.label(format!(
+                                    "{} of {}",
+                                    formatting::format_size(*downloaded, view.size_units),
+                                    formatting::format_size(*size, view.size_units)
+                                ));
+                            });
+                        }
+                    }
+                    call_states::DownloadFile::RefreshToken => {}
+                    call_states::DownloadFile::RefreshTokenComplete => {}
+                },
+            }
+        }
+    }
+
+    /// opened files changed locally, with upload back offered
+    fn render_edited_files(storage: &mut StorageInstance, ui: &mut Ui) {
+        enum EditAction {
+            Upload(usize),
+            UploadAsCopy(usize),
+            Forget(usize),
+        }
+        let mut edit_action: Option<EditAction> = None;
+        for (idx, file) in storage.edited_files.iter().enumerate() {
+            if file.status == storage_instance::EditedFileStatus::Unchanged {
+                continue;
+            }
+            ui.horizontal(|ui| {
+                match &file.status {
+                    storage_instance::EditedFileStatus::Unchanged => {}
+                    storage_instance::EditedFileStatus::Modified => {
+                        ui.label(format!("/{} was changed locally", &file.remote_path));
+                        if ui.button("upload").clicked() {
+                            edit_action = Some(EditAction::Upload(idx));
+                        }
+                    }
+                    storage_instance::EditedFileStatus::Uploading { .. } => {
+                        ui.label(format!("/{} is uploading", &file.remote_path));
+                    }
+                    storage_instance::EditedFileStatus::Conflict => {
+                        ui.colored_label(
+                            egui::Color32::YELLOW,
+                            format!("/{} was changed remotely after download", &file.remote_path),
+                        );
+                        if ui
+                            .button("upload as copy")
+                            .on_hover_text("uploads local file as \"conflicted copy\" next to it")
+                            .clicked()
+                        {
+                            edit_action = Some(EditAction::UploadAsCopy(idx));
+                        }
+                    }
+                    storage_instance::EditedFileStatus::Failed(e) => {
+                        ui.colored_label(
+                            egui::Color32::RED,
+                            format!("/{} upload failed: {}", &file.remote_path, e),
+                        );
+                        if ui.button("retry").clicked() {
+                            edit_action = Some(EditAction::Upload(idx));
+                        }
+                    }
+                }
+                if ui
+                    .button("forget")
+                    .on_hover_text("stop watching local file")
+                    .clicked()
+                {
+                    edit_action = Some(EditAction::Forget(idx));
+                }
+            });
+        }
+        match edit_action {
+            Some(EditAction::Upload(idx)) => storage.upload_edited(idx),
+            Some(EditAction::UploadAsCopy(idx)) => storage.upload_edited_as_copy(idx),
+            Some(EditAction::Forget(idx)) => storage.forget_edited(idx),
+            None => {}
+        }
+    }
+
+    fn render_folder(storage: &mut StorageInstance, ui: &mut Ui, view: &config::ViewConfig) {
+        let scroll_area = egui::ScrollArea::vertical()
+            .max_height(4000.0)
+            .auto_shrink([false; 2]);
+        let mut row_action: Option<RowAction> = None;
+
+        let (_current_scroll, _max_scroll) = scroll_area.show(ui, |ui| {
+            ui.vertical(|ui| {
+                egui::Grid::new("files.")
+                    .num_columns(4)
+                    .min_col_width(5.0)
+                    .start_row(2)
+                    //                    .max_col_width(100.0)
+                    .show(ui, |ui| {
+                        let level_up_needed = {
+                            let folder = storage.visual_state.folder.as_ref().unwrap();
+                            !folder.path.is_empty()
+                        };
+
+                        if ui.selectable_label(false, "Name").clicked() {
+                            let folder = storage.visual_state.folder.as_mut().unwrap();
+                            folder.items.sort_by(|a, b| a.name.cmp(&b.name));
+                        }
+                        ui.label("");
+                        if ui.selectable_label(false, "Size").clicked() {
+                            let folder = storage.visual_state.folder.as_mut().unwrap();
+                            folder.items.sort_by(|a, b| a.size.cmp(&b.size));
+                        }
+                        if ui.selectable_label(false, "Time").clicked() {
+                            let folder = storage.visual_state.folder.as_mut().unwrap();
+                            folder.items.sort_by(|a, b| a.modified.cmp(&b.modified));
+                        }
+                        ui.end_row();
+
+                        if level_up_needed {
+                            if ui.selectable_label(false, "..").clicked() {
+                                storage.nav_back();
+                            }
+                            if ui.selectable_label(false, "up ").clicked() {
+                                storage.nav_back();
+                            }
+                            ui.label("");
+                            ui.end_row();
+                        }
+                        let folder = storage.visual_state.folder.as_ref().unwrap();
+                        let filter = &storage.visual_state.filter;
+                        for item in folder.items.iter().filter(|item| filter.matches(item)) {
+                            let remote_path = item_remote_path(&folder.path, &item.name);
+                            render_item_row(ui, item, &remote_path, view, &mut row_action);
+                            ui.end_row();
+                        }
+                    });
+            });
+
+            let margin = ui.visuals().clip_rect_margin;
+            let current_scroll = ui.clip_rect().top() - ui.min_rect().top() + margin;
+            let max_scroll = ui.min_rect().height() - ui.clip_rect().height() + 2.0 * margin;
+            (current_scroll, max_scroll)
+        });
+
+        if let Some(row_action) = row_action {
+            Self::apply_row_action(storage, row_action, view);
+        }
+    }
+
+    fn apply_row_action(
+        storage: &mut StorageInstance,
+        row_action: RowAction,
+        view: &config::ViewConfig,
+    ) {
+        match row_action {
+            RowAction::Open { path, is_folder } => {
+                if is_folder && storage.visual_state.search_results.is_some() {
+                    storage.clear_search();
+                    storage.nav_to(path);
+                } else if is_folder {
+                    storage.nav_forward(remote_file_name(&path).to_string());
+                } else {
+                    let local_file_path = storage.save_to_path.clone() + remote_file_name(&path);
+                    storage.download_file(path, local_file_path);
+                }
+            }
+            RowAction::GoToFolder { path } => {
+                storage.clear_search();
+                storage.nav_to(path);
+            }
+            RowAction::Properties { path } => storage.show_properties(path),
+            RowAction::Preview { path } => {
+                storage.show_preview(path, view.preview_max_kbytes * 1024)
+            }
+            RowAction::Restore { path } => storage.restore_deleted(path),
+            RowAction::History { path } => storage.show_revisions(path),
+            RowAction::Share { path } => storage.show_shared_links(path),
+            RowAction::CopyTemporaryLink { path } => storage.copy_temporary_link(path),
+            RowAction::OpenExternally { path } => storage.open_externally(path),
+            RowAction::StreamInPlayer { path } => {
+                storage.visual_state.stream_in_player = Some(path)
+            }
+            RowAction::Analyze { path } => storage.analyze_folder(path),
+            RowAction::Duplicates { path } => storage.find_duplicates(path),
+            RowAction::Compare { path } => storage.show_compare(path),
+        }
+    }
+
+    /// frees textures of previous folder and uploads decoded thumbnails as textures
+    fn prepare_thumbnails(storage: &mut StorageInstance, frame: &epi::Frame) {
+        let current_path = match &storage.visual_state.folder {
+            Some(folder) => folder.path.clone(),
+            None => return,
+        };
+        let thumbnails = &mut storage.visual_state.thumbnails;
+        if !thumbnails.folder_path.eq(&current_path) {
+            for (_, state) in thumbnails.items.drain() {
+                if let storage_instance::ThumbnailState::Texture { id, .. } = state {
+                    frame.free_texture(id);
+                }
+            }
+            thumbnails.folder_path = current_path;
+        }
+        for state in thumbnails.items.values_mut() {
+            if let storage_instance::ThumbnailState::Decoded(image) = state {
+                let id = frame
+                    .alloc_texture(epi::Image::from_rgba_unmultiplied(image.size, &image.rgba));
+                *state = storage_instance::ThumbnailState::Texture {
+                    id,
+                    size: image.size,
+                };
+            }
+        }
+        storage.request_thumbnails();
+    }
+
+    fn prepare_preview(storage: &mut StorageInstance, frame: &epi::Frame) {
+        for texture_id in storage.visual_state.textures_to_free.drain(..) {
+            frame.free_texture(texture_id);
+        }
+        if let Some(preview) = &mut storage.visual_state.preview {
+            if let (Some(preview::PreviewData::Image(image)), None) =
+                (&preview.data, &preview.texture)
+            {
+                let id = frame
+                    .alloc_texture(epi::Image::from_rgba_unmultiplied(image.size, &image.rgba));
+                preview.texture = Some((id, image.size));
+            }
+        }
+    }
+
+    fn render_preview(ui: &mut Ui, storage: &mut StorageInstance, view: &config::ViewConfig) {
+        let mut close = false;
+        if let Some(preview) = &storage.visual_state.preview {
+            egui::SidePanel::right("preview_panel")
+                .resizable(true)
+                .default_width(400.0)
+                .show_inside(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        if ui.button("x").clicked() {
+                            close = true;
+                        }
+                        ui.label(&preview.path);
+                    });
+                    if let Some(size) = preview.size {
+                        ui.label(formatting::format_size(size, view.size_units));
+                    }
+                    ui.separator();
+                    if let Some(e) = &preview.error {
+                        ui.colored_label(egui::Color32::RED, e);
+                        return;
+                    }
+                    egui::ScrollArea::both()
+                        .auto_shrink([false; 2])
+                        .show(ui, |ui| match &preview.data {
+                            None => {
+                                ui.label("loading...");
+                            }
+                            Some(preview::PreviewData::Text(text))
+                            | Some(preview::PreviewData::Json(text))
+                            | Some(preview::PreviewData::Binary(text)) => {
+                                ui.monospace(text);
+                            }
+                            Some(preview::PreviewData::Image(_)) => {
+                                if let Some((id, size)) = preview.texture {
+                                    let scale = (ui.available_width() / size[0] as f32).min(1.0);
+                                    ui.image(id, [size[0] as f32 * scale, size[1] as f32 * scale]);
+                                }
+                            }
+                        });
+                });
+        }
+        if close {
+            storage.close_preview();
+        }
+    }
+
+    fn render_folder_icons(storage: &mut StorageInstance, ui: &mut Ui, view: &config::ViewConfig) {
+        const CELL_SIZE: f32 = 140.0;
+        const ICON_SIZE: f32 = 128.0;
+        let mut row_action: Option<RowAction> = None;
+
+        egui::ScrollArea::vertical()
+            .max_height(4000.0)
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                let columns = ((ui.available_width() / CELL_SIZE) as usize).max(1);
+                egui::Grid::new("files.icons")
+                    .num_columns(columns)
+                    .min_col_width(CELL_SIZE)
+                    .max_col_width(CELL_SIZE)
+                    .show(ui, |ui| {
+                        let folder = storage.visual_state.folder.as_ref().unwrap();
+                        let filter = &storage.visual_state.filter;
+                        let thumbnails = &storage.visual_state.thumbnails;
+                        let mut column = 0;
+                        if !folder.path.is_empty() {
+                            ui.vertical_centered(|ui| {
+                                if ui
+                                    .add_sized([ICON_SIZE, ICON_SIZE], egui::Button::new(".."))
+                                    .clicked()
+                                {
+                                    storage.nav_back();
+                                }
+                                ui.label("up");
+                            });
+                            column += 1;
+                        }
+                        for item in folder.items.iter().filter(|item| filter.matches(item)) {
+                            ui.vertical_centered(|ui| {
+                                let texture = match thumbnails.items.get(&item.rev) {
+                                    Some(storage_instance::ThumbnailState::Texture {
+                                        id,
+                                        size,
+                                    }) if !item.is_folder => Some((*id, *size)),
+                                    _ => None,
+                                };
+                                let icon_response = match texture {
+                                    Some((id, size)) => {
+                                        let scale = ICON_SIZE / (size[0].max(size[1]) as f32);
+                                        ui.add(egui::ImageButton::new(
+                                            id,
+                                            [size[0] as f32 * scale, size[1] as f32 * scale],
+                                        ))
+                                    }
+                                    None => {
+                                        let caption = if item.is_deleted {
+                                            "[deleted]".to_string()
+                                        } else if item.is_folder {
+                                            "[dir]".to_string()
+                                        } else {
+                                            match item.name.rsplit_once('.') {
+                                                Some((_, ext)) => format!("[{}]", ext),
+                                                None => "[file]".to_string(),
+                                            }
+                                        };
+                                        ui.add_sized(
+                                            [ICON_SIZE, ICON_SIZE],
+                                            egui::Button::new(caption),
+                                        )
+                                    }
+                                };
+                                let caption = egui::RichText::new(short_name(&item.name, 16));
+                                let name_response = ui.selectable_label(
+                                    false,
+                                    if item.is_deleted {
+                                        caption.weak().strikethrough()
+                                    } else {
+                                        caption
+                                    },
+                                );
+                                let remote_path = item_remote_path(&folder.path, &item.name);
+                                if !item.is_deleted
+                                    && (icon_response.clicked() || name_response.clicked())
+                                {
+                                    row_action = Some(RowAction::Open {
+                                        path: remote_path.clone(),
+                                        is_folder: item.is_folder,
+                                    });
+                                }
+                                name_response.on_hover_text(&item.name).context_menu(|ui| {
+                                    item_context_menu(ui, item, &remote_path, &mut row_action);
+                                });
+                            });
+                            column += 1;
+                            if column == columns {
+                                ui.end_row();
+                                column = 0;
+                            }
+                        }
+                    });
+            });
+
+        if let Some(row_action) = row_action {
+            Self::apply_row_action(storage, row_action, view);
+        }
+    }
+
+    fn render_properties(
+        ctx: &egui::CtxRef,
+        storage: &mut StorageInstance,
+        view: &config::ViewConfig,
+    ) {
+        let mut open = true;
+        if let Some(properties) = &storage.visual_state.properties {
+            egui::Window::new("Properties")
+                .open(&mut open)
+                .resizable(false)
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    if let Some(e) = &properties.error {
+                        ui.colored_label(egui::Color32::RED, e);
+                        return;
+                    }
+                    let item = match &properties.item {
+                        Some(item) => item,
+                        None => {
+                            ui.label(format!("loading '{}'...", &properties.path));
+                            return;
+                        }
+                    };
+                    let copyable = |ui: &mut Ui, caption: &str, value: &str| {
+                        ui.label(caption);
+                        ui.horizontal(|ui| {
+                            ui.label(value);
+                            if !value.is_empty() && ui.small_button("copy").clicked() {
+                                ui.output().copied_text = value.to_string();
+                            }
+                        });
+                        ui.end_row();
+                    };
+                    egui::Grid::new("properties.")
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            ui.label("name");
+                            ui.label(&item.name);
+                            ui.end_row();
+                            copyable(ui, "path", &item.path_display);
+                            copyable(ui, "id", &item.id);
+                            if !item.is_folder {
+                                ui.label("size");
+                                ui.label(match item.size {
+                                    Some(size) => formatting::format_size(size, view.size_units),
+                                    None => "".to_string(),
+                                });
+                                ui.end_row();
+                                ui.label("modified");
+                                ui.label(match &item.modified {
+                                    Some(time) => formatting::format_time(time, view),
+                                    None => "".to_string(),
+                                });
+                                ui.end_row();
+                                ui.label("client modified");
+                                ui.label(match &item.client_modified {
+                                    Some(time) => formatting::format_time(time, view),
+                                    None => "".to_string(),
+                                });
+                                ui.end_row();
+                                ui.label("rev");
+                                ui.label(&item.rev);
+                                ui.end_row();
+                                ui.label("downloadable");
+                                ui.label(if item.is_downloadable { "yes" } else { "no" });
+                                ui.end_row();
+                                copyable(ui, "content hash", &item.content_hash);
+                            }
+                            if let Some(media) = &item.media_info {
+                                ui.label("media");
+                                ui.label(&media.kind);
+                                ui.end_row();
+                                if let Some((width, height)) = media.dimensions {
+                                    ui.label("dimensions");
+                                    ui.label(format!("{} x {}", width, height));
+                                    ui.end_row();
+                                }
+                                if let Some(time_taken) = &media.time_taken {
+                                    ui.label("taken");
+                                    ui.label(formatting::format_time(time_taken, view));
+                                    ui.end_row();
+                                }
+                                if let Some((latitude, longitude)) = media.location {
+                                    ui.label("location");
+                                    ui.label(format!("{:.6}, {:.6}", latitude, longitude));
+                                    ui.end_row();
+                                }
+                                if let Some(duration) = media.duration {
+                                    ui.label("duration");
+                                    ui.label(format!("{:.1} s", duration as f64 / 1000.0));
+                                    ui.end_row();
+                                }
+                            }
+                        });
+                });
+        }
+        if !open {
+            storage.visual_state.properties = None;
+        }
+    }
+
+    fn render_revisions(
+        ctx: &egui::CtxRef,
+        storage: &mut StorageInstance,
+        view: &config::ViewConfig,
+    ) {
+        let mut open = true;
+        let mut download: Option<(String, String)> = None;
+        let mut restore: Option<(String, String)> = None;
+        if let Some(revisions) = &storage.visual_state.revisions {
+            egui::Window::new("Version history")
+                .open(&mut open)
+                .resizable(true)
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    ui.label(&revisions.path);
+                    if revisions.is_deleted {
+                        ui.label("file is deleted");
+                    }
+                    if let Some(rev) = &revisions.restored_rev {
+                        ui.label(format!("restored, new rev: {}", rev));
+                    }
+                    if let Some(e) = &revisions.error {
+                        ui.colored_label(egui::Color32::RED, e);
+                    }
+                    let entries = match &revisions.entries {
+                        Some(entries) => entries,
+                        None => {
+                            ui.label("loading...");
+                            return;
+                        }
+                    };
+                    egui::ScrollArea::vertical()
+                        .max_height(400.0)
+                        .show(ui, |ui| {
+                            egui::Grid::new("revisions.")
+                                .num_columns(5)
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    ui.label("Time");
+                                    ui.label("Size");
+                                    ui.label("Rev");
+                                    ui.label("");
+                                    ui.label("");
+                                    ui.end_row();
+                                    for (idx, item) in entries.iter().enumerate() {
+                                        ui.label(match &item.modified {
+                                            Some(time) => formatting::format_time(time, view),
+                                            None => "".to_string(),
+                                        });
+                                        ui.label(match item.size {
+                                            Some(size) => {
+                                                formatting::format_size(size, view.size_units)
+                                            }
+                                            None => "".to_string(),
+                                        });
+                                        ui.monospace(&item.rev);
+                                        if ui.button("download").clicked() {
+                                            download = Some((item.name.clone(), item.rev.clone()));
+                                        }
+                                        // the newest revision is the current one unless deleted
+                                        if idx > 0 || revisions.is_deleted {
+                                            if ui.button("restore").clicked() {
+                                                restore = Some((
+                                                    revisions.path.clone(),
+                                                    item.rev.clone(),
+                                                ));
+                                            }
+                                        } else {
+                                            ui.label("current");
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                });
+        }
+        if let Some((name, rev)) = download {
+            storage.download_revision(&name, &rev);
+        }
+        if let Some((path, rev)) = restore {
+            storage.restore_revision(path, rev);
+        }
+        if !open {
+            storage.visual_state.revisions = None;
+        }
+    }
+
+    fn render_shared_links(
+        ctx: &egui::CtxRef,
+        storage: &mut StorageInstance,
+        view: &config::ViewConfig,
+    ) {
+        enum LinkAction {
+            Create,
+            Modify(String),
+            Revoke(String),
+        }
+        let mut open = true;
+        let mut link_action: Option<LinkAction> = None;
+        if let Some(dialog) = &mut storage.visual_state.shared_links {
+            egui::Window::new("Shared links")
+                .open(&mut open)
+                .resizable(true)
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    if let Some(url) = dialog.copy_to_clipboard.take() {
+                        ui.output().copied_text = url;
+                    }
+                    ui.label(&dialog.path);
+                    egui::Grid::new("shared_links.settings")
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            ui.label("Audience");
+                            ui.horizontal(|ui| {
+                                for audience in [
+                                    storage_models::LinkAudience::Public,
+                                    storage_models::LinkAudience::Team,
+                                    storage_models::LinkAudience::NoOne,
+                                ] {
+                                    ui.radio_value(
+                                        &mut dialog.audience,
+                                        audience,
+                                        audience.caption(),
+                                    );
+                                }
+                            });
+                            ui.end_row();
+                            ui.label("Expires in");
+                            ui.horizontal(|ui| {
+                                ui.add(
+                                    egui::DragValue::new(&mut dialog.expires_in_days)
+                                        .clamp_range(0..=3650),
+                                );
+                                ui.label("days (0 - never)");
+                            });
+                            ui.end_row();
+                            ui.checkbox(&mut dialog.use_password, "Password");
+                            ui.add_enabled(
+                                dialog.use_password,
+                                egui::TextEdit::singleline(&mut dialog.password).password(true),
+                            );
+                            ui.end_row();
+                        });
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(!dialog.in_progress, egui::Button::new("Create link"))
+                            .clicked()
+                        {
+                            link_action = Some(LinkAction::Create);
+                        }
+                        if dialog.in_progress {
+                            ui.label("in progress...");
+                        }
+                    });
+                    if let Some(e) = &dialog.error {
+                        ui.colored_label(egui::Color32::RED, e);
+                    }
+                    ui.separator();
+                    let links = match &dialog.links {
+                        Some(links) => links,
+                        None => {
+                            ui.label("loading...");
+                            return;
+                        }
+                    };
+                    if links.is_empty() {
+                        ui.label("no links");
+                        return;
+                    }
+                    egui::ScrollArea::vertical()
+                        .max_height(300.0)
+                        .show(ui, |ui| {
+                            egui::Grid::new("shared_links.")
+                                .num_columns(6)
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    ui.label("Url");
+                                    ui.label("Audience");
+                                    ui.label("Expires");
+                                    ui.label("");
+                                    ui.label("");
+                                    ui.label("");
+                                    ui.end_row();
+                                    for link in links {
+                                        ui.hyperlink_to(short_name(&link.url, 50), &link.url)
+                                            .on_hover_text(&link.url);
+                                        ui.label(format!(
+                                            "{}{}",
+                                            link.audience
+                                                .map(|audience| audience.caption())
+                                                .unwrap_or(""),
+                                            if link.password_protected {
+                                                ", password"
+                                            } else {
+                                                ""
+                                            }
+                                        ));
+                                        ui.label(match &link.expires {
+                                            Some(time) => formatting::format_time(time, view),
+                                            None => "never".to_string(),
+                                        });
+                                        if ui.button("copy").clicked() {
+                                            ui.output().copied_text = link.url.clone();
+                                        }
+                                        if ui
+                                            .add_enabled(
+                                                !dialog.in_progress,
+                                                egui::Button::new("apply settings"),
+                                            )
+                                            .clicked()
+                                        {
+                                            link_action =
+                                                Some(LinkAction::Modify(link.url.clone()));
+                                        }
+                                        if ui
+                                            .add_enabled(
+                                                !dialog.in_progress && link.can_revoke,
+                                                egui::Button::new("revoke"),
+                                            )
+                                            .clicked()
+                                        {
+                                            link_action =
+                                                Some(LinkAction::Revoke(link.url.clone()));
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                });
+        }
+        match link_action {
+            Some(LinkAction::Create) => storage.create_shared_link(),
+            Some(LinkAction::Modify(url)) => storage.modify_shared_link(url),
+            Some(LinkAction::Revoke(url)) => storage.revoke_shared_link(url),
+            None => {}
+        }
+        if !open {
+            storage.visual_state.shared_links = None;
+        }
+    }
+
+    fn render_analyze(
+        ctx: &egui::CtxRef,
+        storage: &mut StorageInstance,
+        view: &config::ViewConfig,
+    ) {
+        let mut open = true;
+        let mut row_action: Option<RowAction> = None;
+        if let Some(analyze) = &mut storage.visual_state.analyze {
+            egui::Window::new("Folder sizes")
+                .open(&mut open)
+                .resizable(true)
+                .collapsible(false)
+                .default_width(500.0)
+                .show(ctx, |ui| {
+                    ui.label(format!("/{}", &analyze.path));
+                    if let Some(e) = &analyze.error {
+                        ui.colored_label(egui::Color32::RED, e);
+                    }
+                    let root = match &mut analyze.root {
+                        Some(root) => root,
+                        None => {
+                            ui.label(format!("listing... {} entries", analyze.count));
+                            return;
+                        }
+                    };
+                    ui.horizontal(|ui| {
+                        ui.label("sort by:");
+                        let sort = analyze.sort;
+                        ui.radio_value(&mut analyze.sort, analyze::SizeSort::Size, "size");
+                        ui.radio_value(&mut analyze.sort, analyze::SizeSort::Name, "name");
+                        ui.radio_value(&mut analyze.sort, analyze::SizeSort::Files, "files");
+                        if sort != analyze.sort {
+                            root.sort(analyze.sort);
+                        }
+                    });
+                    let node = match root.find(&analyze.current) {
+                        Some(node) => node,
+                        None => root,
+                    };
+                    let mut drill: Option<String> = None;
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(
+                                !node.path.eq_ignore_ascii_case(&analyze.path),
+                                egui::Button::new("up"),
+                            )
+                            .clicked()
+                        {
+                            drill = Some(parent_remote_path(&node.path).to_string());
+                        }
+                        ui.label(format!(
+                            "/{}: {}, {} files",
+                            &node.path,
+                            formatting::format_size(node.size, view.size_units),
+                            node.files
+                        ));
+                        if ui.button("open").clicked() {
+                            row_action = Some(RowAction::GoToFolder {
+                                path: node.path.clone(),
+                            });
+                        }
+                    });
+                    egui::ScrollArea::vertical()
+                        .max_height(250.0)
+                        .show(ui, |ui| {
+                            egui::Grid::new("analyze.")
+                                .num_columns(5)
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    ui.label("Name");
+                                    ui.label("Size");
+                                    ui.label("%");
+                                    ui.label("Files");
+                                    ui.label("");
+                                    ui.end_row();
+                                    for child in &node.children {
+                                        if ui.selectable_label(false, &child.name).clicked() {
+                                            drill = Some(child.path.clone());
+                                        }
+                                        ui.label(formatting::format_size(
+                                            child.size,
+                                            view.size_units,
+                                        ));
+                                        ui.label(if node.size > 0 {
+                                            format!(
+                                                "{:.1}",
+                                                child.size as f64 * 100.0 / node.size as f64
+                                            )
+                                        } else {
+                                            "".to_string()
+                                        });
+                                        ui.label(child.files.to_string());
+                                        if ui.button("open").clicked() {
+                                            row_action = Some(RowAction::GoToFolder {
+                                                path: child.path.clone(),
+                                            });
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                    ui.separator();
+                    let (rect, _) = ui.allocate_exact_size(
+                        egui::vec2(ui.available_width(), 250.0),
+                        egui::Sense::hover(),
+                    );
+                    let mut children: Vec<&analyze::SizeNode> = node
+                        .children
+                        .iter()
+                        .filter(|child| child.size > 0)
+                        .collect();
+                    children.sort_by_key(|child| std::cmp::Reverse(child.size));
+                    let sizes: Vec<u64> = children.iter().map(|child| child.size).collect();
+                    let rects = analyze::treemap_layout(&sizes, rect);
+                    let painter = ui.painter_at(rect);
+                    for (idx, (child, child_rect)) in children.iter().zip(rects).enumerate() {
+                        let hue = (idx as f32 * 0.13) % 1.0;
+                        let color: egui::Color32 =
+                            egui::color::Hsva::new(hue, 0.45, 0.65, 1.0).into();
+                        painter.rect(
+                            child_rect.shrink(1.0),
+                            2.0,
+                            color,
+                            egui::Stroke::new(1.0, egui::Color32::BLACK),
+                        );
+                        if child_rect.width() > 40.0 && child_rect.height() > 16.0 {
+                            painter.text(
+                                child_rect.left_top() + egui::vec2(4.0, 2.0),
+                                egui::Align2::LEFT_TOP,
+                                short_name(&child.name, (child_rect.width() / 8.0) as usize),
+                                egui::TextStyle::Small,
+                                egui::Color32::WHITE,
+                            );
+                        }
+                        let response = ui.interact(
+                            child_rect,
+                            ui.id().with(("treemap", idx)),
+                            egui::Sense::click(),
+                        );
+                        let response = response.on_hover_text(format!(
+                            "{}\n{}, {} files",
+                            &child.name,
+                            formatting::format_size(child.size, view.size_units),
+                            child.files
+                        ));
+                        if response.clicked() {
+                            drill = Some(child.path.clone());
+                        }
+                    }
+                    if let Some(path) = drill {
+                        analyze.current = path;
+                    }
+                });
+        }
+        if let Some(row_action) = row_action {
+            Self::apply_row_action(storage, row_action, view);
+        }
+        if !open {
+            storage.visual_state.analyze = None;
+        }
+    }
+
+    fn render_duplicates(
+        ctx: &egui::CtxRef,
+        storage: &mut StorageInstance,
+        view: &config::ViewConfig,
+    ) {
+        enum DuplicatesAction {
+            Delete,
+            Move,
+        }
+        let mut open = true;
+        let mut row_action: Option<RowAction> = None;
+        let mut duplicates_action: Option<DuplicatesAction> = None;
+        if let Some(duplicates) = &mut storage.visual_state.duplicates {
+            egui::Window::new("Duplicates")
+                .open(&mut open)
+                .resizable(true)
+                .collapsible(false)
+                .default_width(600.0)
+                .show(ctx, |ui| {
+                    ui.label(format!("/{}", &duplicates.path));
+                    if let Some(e) = &duplicates.error {
+                        ui.colored_label(egui::Color32::RED, e);
+                    }
+                    if duplicates.groups.is_none() {
+                        ui.label(format!("listing... {} entries", duplicates.count));
+                        return;
+                    }
+                    let (groups_count, wasted) = match &duplicates.groups {
+                        Some(groups) => (
+                            groups.len(),
+                            groups.iter().map(|group| group.wasted()).sum::<u64>(),
+                        ),
+                        None => (0, 0),
+                    };
+                    ui.label(format!(
+                        "{} files scanned, {} groups of duplicates, wasted: {}",
+                        duplicates.count,
+                        groups_count,
+                        formatting::format_size(wasted, view.size_units)
+                    ));
+                    ui.horizontal(|ui| {
+                        if ui.button("select all but oldest").clicked() {
+                            duplicates.select_all_but_oldest();
+                        }
+                        if ui.button("clear selection").clicked() {
+                            duplicates.selected.clear();
+                        }
+                        ui.label(format!("selected: {}", duplicates.selected.len()));
+                    });
+                    let enabled = !duplicates.ops_in_progress && !duplicates.selected.is_empty();
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(enabled, egui::Button::new("delete selected"))
+                            .clicked()
+                        {
+                            duplicates_action = Some(DuplicatesAction::Delete);
+                        }
+                        if ui
+                            .add_enabled(enabled, egui::Button::new("move selected to /"))
+                            .clicked()
+                        {
+                            duplicates_action = Some(DuplicatesAction::Move);
+                        }
+                        ui.add(
+                            egui::TextEdit::singleline(&mut duplicates.move_to)
+                                .desired_width(150.0),
+                        );
+                        if duplicates.ops_in_progress {
+                            ui.label("in progress...");
+                        }
+                    });
+                    for e in &duplicates.ops_errors {
+                        ui.colored_label(egui::Color32::RED, e);
+                    }
+                    ui.separator();
+                    let groups = match &duplicates.groups {
+                        Some(groups) => groups,
+                        None => return,
+                    };
+                    let selected = &mut duplicates.selected;
+                    egui::ScrollArea::vertical()
+                        .max_height(400.0)
+                        .show(ui, |ui| {
+                            for group in groups {
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "{} x {}, wasted {}",
+                                        group.items.len(),
+                                        formatting::format_size(group.size, view.size_units),
+                                        formatting::format_size(group.wasted(), view.size_units)
+                                    ))
+                                    .strong(),
+                                )
+                                .on_hover_text(format!("content hash: {}", &group.content_hash));
+                                for item in &group.items {
+                                    let remote_path =
+                                        item.path_display.trim_start_matches('/').to_string();
+                                    ui.horizontal(|ui| {
+                                        let mut checked = selected.contains(&remote_path);
+                                        if ui.checkbox(&mut checked, "").changed() {
+                                            if checked {
+                                                selected.insert(remote_path.clone());
+                                            } else {
+                                                selected.remove(&remote_path);
+                                            }
+                                        }
+                                        if ui
+                                            .selectable_label(false, &item.path_display)
+                                            .on_hover_text("go to containing folder")
+                                            .clicked()
+                                        {
+                                            row_action = Some(RowAction::GoToFolder {
+                                                path: parent_remote_path(&remote_path).to_string(),
+                                            });
+                                        }
+                                        if let Some(time) = &item.modified {
+                                            ui.label(
+                                                egui::RichText::new(formatting::format_time(
+                                                    time, view,
+                                                ))
+                                                .weak(),
+                                            );
+                                        }
+                                    });
+                                }
+                                ui.separator();
+                            }
+                        });
+                });
+        }
+        match duplicates_action {
+            Some(DuplicatesAction::Delete) => storage.delete_selected_duplicates(),
+            Some(DuplicatesAction::Move) => storage.move_selected_duplicates(),
+            None => {}
+        }
+        if let Some(row_action) = row_action {
+            Self::apply_row_action(storage, row_action, view);
+        }
+        if !open {
+            storage.visual_state.duplicates = None;
+        }
+    }
+
+    fn render_compare(
+        ctx: &egui::CtxRef,
+        storage: &mut StorageInstance,
+        view: &config::ViewConfig,
+    ) {
+        enum CompareAction {
+            Compare,
+            DownloadMissing,
+            UploadChanged,
+        }
+        let mut open = true;
+        let mut compare_action: Option<CompareAction> = None;
+        if let Some(compare) = &mut storage.visual_state.compare {
+            egui::Window::new("Compare with local folder")
+                .open(&mut open)
+                .resizable(true)
+                .collapsible(false)
+                .default_width(600.0)
+                .show(ctx, |ui| {
+                    let busy = compare.in_progress || compare.transfer_in_progress;
+                    egui::Grid::new("compare_paths").show(ui, |ui| {
+                        ui.label("remote folder: /");
+                        ui.add_enabled(
+                            !busy,
+                            egui::TextEdit::singleline(&mut compare.remote_path)
+                                .desired_width(300.0),
+                        );
+                        ui.end_row();
+                        ui.label("local folder:");
+                        ui.add_enabled(
+                            !busy,
+                            egui::TextEdit::singleline(&mut compare.local_path)
+                                .desired_width(300.0),
+                        );
+                        ui.end_row();
+                    });
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(!busy, egui::Button::new("compare"))
+                            .clicked()
+                        {
+                            compare_action = Some(CompareAction::Compare);
+                        }
+                        if compare.in_progress {
+                            ui.label(format!(
+                                "listed {} remote entries, checked {} local files...",
+                                compare.listed, compare.checked
+                            ));
+                        }
+                    });
+                    if let Some(e) = &compare.error {
+                        ui.colored_label(egui::Color32::RED, e);
+                    }
+                    if compare.entries.is_none() {
+                        return;
+                    }
+                    ui.separator();
+                    let only_local = compare.count(storage_models::CompareStatus::OnlyLocal);
+                    let only_remote = compare.count(storage_models::CompareStatus::OnlyRemote);
+                    let changed = compare.count(storage_models::CompareStatus::Changed);
+                    let identical = compare.count(storage_models::CompareStatus::Identical);
+                    ui.label(format!(
+                        "only local: {}, only remote: {}, changed: {}, identical: {}",
+                        only_local, only_remote, changed, identical
+                    ));
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(
+                                !busy && only_remote > 0,
+                                egui::Button::new(format!("download missing ({})", only_remote)),
+                            )
+                            .clicked()
+                        {
+                            compare_action = Some(CompareAction::DownloadMissing);
+                        }
+                        if ui
+                            .add_enabled(
+                                !busy && changed + only_local > 0,
+                                egui::Button::new(format!(
+                                    "upload changed ({})",
+                                    changed + only_local
+                                )),
+                            )
+                            .on_hover_text(
+                                "uploads changed and local only files, \
+                                 remote file is not replaced if it was modified after compare",
+                            )
+                            .clicked()
+                        {
+                            compare_action = Some(CompareAction::UploadChanged);
+                        }
+                        if compare.transfer_in_progress {
+                            ui.label("in progress...");
+                        }
+                        ui.checkbox(&mut compare.show_identical, "show identical");
+                    });
+                    for e in &compare.transfer_errors {
+                        ui.colored_label(egui::Color32::RED, e);
+                    }
+                    ui.separator();
+                    let show_identical = compare.show_identical;
+                    egui::ScrollArea::vertical()
+                        .max_height(400.0)
+                        .show(ui, |ui| {
+                            egui::Grid::new("compare_entries")
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    for entry in compare.entries.iter().flatten() {
+                                        let (status, color) = match entry.status {
+                                            storage_models::CompareStatus::Identical
+                                                if !show_identical =>
+                                            {
+                                                continue
+                                            }
+                                            storage_models::CompareStatus::Identical => {
+                                                ("identical", egui::Color32::GRAY)
+                                            }
+                                            storage_models::CompareStatus::Changed => {
+                                                ("changed", egui::Color32::YELLOW)
+                                            }
+                                            storage_models::CompareStatus::OnlyLocal => {
+                                                ("only local", egui::Color32::LIGHT_BLUE)
+                                            }
+                                            storage_models::CompareStatus::OnlyRemote => {
+                                                ("only remote", egui::Color32::LIGHT_GREEN)
+                                            }
+                                        };
+                                        ui.colored_label(color, status);
+                                        ui.label(&entry.relative_path);
+                                        ui.label(
+                                            entry
+                                                .local_size
+                                                .map(|v| {
+                                                    formatting::format_size(v, view.size_units)
+                                                })
+                                                .unwrap_or_default(),
+                                        );
+                                        ui.label(
+                                            entry
+                                                .remote
+                                                .as_ref()
+                                                .and_then(|item| item.size)
+                                                .map(|v| {
+                                                    formatting::format_size(v, view.size_units)
+                                                })
+                                                .unwrap_or_default(),
+                                        );
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                });
+        }
+        match compare_action {
+            Some(CompareAction::Compare) => storage.compare_folders(),
+            Some(CompareAction::DownloadMissing) => storage.download_missing(),
+            Some(CompareAction::UploadChanged) => storage.upload_changed(),
+            None => {}
+        }
+        if !open {
+            storage.visual_state.compare = None;
+        }
+    }
+
+    fn render_mirrors(
+        ctx: &egui::CtxRef,
+        storage: &mut StorageInstance,
+        view: &config::ViewConfig,
+    ) {
+        enum MirrorAction {
+            Run(usize),
+            Reset(usize),
+        }
+        if !storage.visual_state.show_mirrors {
+            return;
+        }
+        let mut open = true;
+        let mut mirror_action: Option<MirrorAction> = None;
+        let mirrors = &storage.mirrors;
+        egui::Window::new("Mirror jobs")
+            .open(&mut open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(600.0)
+            .show(ctx, |ui| {
+                if mirrors.is_empty() {
+                    ui.label("no mirror jobs, add them to \"mirrors\" of storage in config file");
+                    return;
+                }
+                for (idx, job) in mirrors.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(&job.config.name).strong());
+                        ui.label(format!(
+                            "/{} -> {}",
+                            &job.config.remote_path, &job.config.local_path
+                        ));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(if job.config.interval_minutes == 0 {
+                            "on demand".to_string()
+                        } else {
+                            format!("every {} min", job.config.interval_minutes)
+                        });
+                        if job.config.delete_extraneous {
+                            ui.label("deletes extraneous");
+                        }
+                        if job.started.is_some() {
+                            ui.label(format!(
+                                "running: listed {}, downloaded {}",
+                                job.listed, job.downloaded
+                            ));
+                        } else {
+                            if ui.button("run now").clicked() {
+                                mirror_action = Some(MirrorAction::Run(idx));
+                            }
+                            if ui
+                                .add_enabled(
+                                    !job.config.cursor.is_empty(),
+                                    egui::Button::new("reset"),
+                                )
+                                .on_hover_text("next run compares whole folder")
+                                .clicked()
+                            {
+                                mirror_action = Some(MirrorAction::Reset(idx));
+                            }
+                        }
+                    });
+                    egui::CollapsingHeader::new(format!("runs ({})", job.config.runs.len()))
+                        .id_source(("mirror_runs", idx))
+                        .show(ui, |ui| {
+                            egui::Grid::new(("mirror_runs_grid", idx))
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    ui.label("started");
+                                    ui.label("duration");
+                                    ui.label("listing");
+                                    ui.label("downloaded");
+                                    ui.label("deleted");
+                                    ui.label("failed");
+                                    ui.end_row();
+                                    for run in job.config.runs.iter().rev() {
+                                        ui.label(formatting::format_time(&run.started, view));
+                                        ui.label(format!(
+                                            "{}s",
+                                            (run.finished - run.started).num_seconds()
+                                        ));
+                                        ui.label(if run.full { "full" } else { "changes" });
+                                        ui.label(format!(
+                                            "{} ({})",
+                                            run.downloaded,
+                                            formatting::format_size(
+                                                run.downloaded_bytes,
+                                                view.size_units
+                                            )
+                                        ));
+                                        ui.label(run.deleted.to_string());
+                                        match &run.error {
+                                            Some(e) => {
+                                                ui.colored_label(egui::Color32::RED, e);
+                                            }
+                                            None => {
+                                                ui.label(run.failed.to_string());
+                                            }
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                    ui.separator();
+                }
+            });
+        match mirror_action {
+            Some(MirrorAction::Run(idx)) => storage.run_mirror(idx),
+            Some(MirrorAction::Reset(idx)) => storage.reset_mirror(idx),
+            None => {}
+        }
+        if !open {
+            storage.visual_state.show_mirrors = false;
+        }
+    }
+
+    fn render_syncs(ctx: &egui::CtxRef, storage: &mut StorageInstance, view: &config::ViewConfig) {
+        enum SyncAction {
+            Run(usize),
+            Reset(usize),
+        }
+        if !storage.visual_state.show_syncs {
+            return;
+        }
+        let mut open = true;
+        let mut sync_action: Option<SyncAction> = None;
+        let syncs = &storage.syncs;
+        egui::Window::new("Sync jobs")
+            .open(&mut open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(600.0)
+            .show(ctx, |ui| {
+                if syncs.is_empty() {
+                    ui.label("no sync jobs, add them to \"syncs\" of storage in config file");
+                    return;
+                }
+                for (idx, job) in syncs.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(&job.config.name).strong());
+                        ui.label(format!(
+                            "/{} <-> {}",
+                            &job.config.remote_path, &job.config.local_path
+                        ));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(if job.config.interval_minutes == 0 {
+                            "on demand".to_string()
+                        } else {
+                            format!("every {} min", job.config.interval_minutes)
+                        });
+                        if job.started.is_some() {
+                            ui.label(format!("running: {} of {}", job.done, job.total));
+                        } else {
+                            if ui.button("run now").clicked() {
+                                sync_action = Some(SyncAction::Run(idx));
+                            }
+                            if ui
+                                .add_enabled(
+                                    !job.config.cursor.is_empty(),
+                                    egui::Button::new("reset"),
+                                )
+                                .on_hover_text("next run compares whole folder")
+                                .clicked()
+                            {
+                                sync_action = Some(SyncAction::Reset(idx));
+                            }
+                        }
+                    });
+                    egui::CollapsingHeader::new(format!("runs ({})", job.config.runs.len()))
+                        .id_source(("sync_runs", idx))
+                        .show(ui, |ui| {
+                            egui::Grid::new(("sync_runs_grid", idx))
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    ui.label("started");
+                                    ui.label("duration");
+                                    ui.label("listing");
+                                    ui.label("uploaded");
+                                    ui.label("downloaded");
+                                    ui.label("deleted local");
+                                    ui.label("deleted remote");
+                                    ui.label("conflicts");
+                                    ui.label("failed");
+                                    ui.end_row();
+                                    for run in job.config.runs.iter().rev() {
+                                        ui.label(formatting::format_time(&run.started, view));
+                                        ui.label(format!(
+                                            "{}s",
+                                            (run.finished - run.started).num_seconds()
+                                        ));
+                                        ui.label(if run.full { "full" } else { "changes" });
+                                        ui.label(run.uploaded.to_string());
+                                        ui.label(run.downloaded.to_string());
+                                        ui.label(run.deleted_local.to_string());
+                                        ui.label(run.deleted_remote.to_string());
+                                        if run.conflicts > 0 {
+                                            ui.colored_label(
+                                                egui::Color32::YELLOW,
+                                                run.conflicts.to_string(),
+                                            )
+                                            .on_hover_text(
+                                                "local copies are kept as \"conflicted copy\" files",
+                                            );
+                                        } else {
+                                            ui.label("0");
+                                        }
+                                        match &run.error {
+                                            Some(e) => {
+                                                ui.colored_label(egui::Color32::RED, e);
+                                            }
+                                            None => {
+                                                ui.label(run.failed.to_string());
+                                            }
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                    ui.separator();
+                }
+            });
+        match sync_action {
+            Some(SyncAction::Run(idx)) => storage.run_sync(idx),
+            Some(SyncAction::Reset(idx)) => storage.reset_sync(idx),
+            None => {}
+        }
+        if !open {
+            storage.visual_state.show_syncs = false;
+        }
+    }
+
+    fn render_webdav(ctx: &egui::CtxRef, storage: &mut StorageInstance) {
+        if !storage.visual_state.show_webdav {
+            return;
+        }
+        let mut open = true;
+        let mut changed = false;
+        let mut restart = false;
+        let mut webdav = storage.webdav.clone();
+        let state = storage.webdav_state();
+        egui::Window::new("WebDAV server")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                changed |= ui
+                    .checkbox(&mut webdav.enabled, "serve this storage")
+                    .on_hover_text("started with the app, listens on 127.0.0.1 only")
+                    .changed();
+                egui::Grid::new("webdav_settings").show(ui, |ui| {
+                    ui.label("port");
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut webdav.port).clamp_range(1..=65535))
+                        .changed();
+                    ui.end_row();
+                    ui.label("user");
+                    changed |= ui
+                        .text_edit_singleline(&mut webdav.user)
+                        .on_hover_text("required unless anonymous access is allowed")
+                        .changed();
+                    ui.end_row();
+                    ui.label("password");
+                    changed |= ui
+                        .add(egui::TextEdit::singleline(&mut webdav.password).password(true))
+                        .changed();
+                    ui.end_row();
+                    ui.label("");
+                    changed |= ui
+                        .checkbox(&mut webdav.allow_anonymous, "allow anonymous access")
+                        .on_hover_text(
+                            "serve without authentication when user is empty, \
+                             any local process can then read and change the storage",
+                        )
+                        .changed();
+                    ui.end_row();
+                });
+                ui.separator();
+                match state {
+                    Some((call_states::WebDav::Failed(e), ..)) => {
+                        ui.colored_label(egui::Color32::RED, e.to_string());
+                    }
+                    Some((_, Some(addr), served, last_request)) => {
+                        let url = format!("http://{}/", addr);
+                        ui.horizontal(|ui| {
+                            ui.label("serving at");
+                            ui.hyperlink(&url);
+                            if ui.small_button("copy").clicked() {
+                                ui.output().copied_text = url.clone();
+                            }
+                        });
+                        ui.label(format!("requests served: {}", served));
+                        if let Some(request) = last_request {
+                            ui.label(egui::RichText::new(request).weak());
+                        }
+                    }
+                    Some(_) => {
+                        ui.label("starting...");
+                    }
+                    None => {
+                        ui.label("stopped");
+                    }
+                }
+                if webdav.enabled && ui.button("restart").clicked() {
+                    restart = true;
+                }
+            });
+        storage.visual_state.show_webdav = open;
+        if changed {
+            storage.webdav = webdav;
+            storage.config_changed = true;
+            if !storage.webdav.enabled {
+                storage.stop_webdav();
+            }
+        }
+        if restart {
+            storage.start_webdav();
+        }
+    }
+
+    fn render_backups(
+        ctx: &egui::CtxRef,
+        storage: &mut StorageInstance,
+        view: &config::ViewConfig,
+    ) {
+        if !storage.visual_state.show_backups {
+            return;
+        }
+        let mut open = true;
+        let mut run: Option<usize> = None;
+        let backups = &storage.backups;
+        egui::Window::new("Backup sets")
+            .open(&mut open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(600.0)
+            .show(ctx, |ui| {
+                if backups.is_empty() {
+                    ui.label("no backup sets, add them to \"backups\" of storage in config file");
+                    return;
+                }
+                for (idx, job) in backups.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(&job.config.name).strong());
+                        ui.label(format!(
+                            "{} -> /{}",
+                            &job.config.local_path, &job.config.remote_path
+                        ));
+                    });
+                    if !job.config.include.is_empty() || !job.config.exclude.is_empty() {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "include: {}; exclude: {}",
+                                job.config.include.join(", "),
+                                job.config.exclude.join(", ")
+                            ))
+                            .weak(),
+                        );
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label(if job.config.interval_minutes == 0 {
+                            "on demand".to_string()
+                        } else {
+                            format!("every {} min", job.config.interval_minutes)
+                        });
+                        if job.started.is_some() {
+                            ui.label(format!("running: {} of {}", job.done, job.total));
+                        } else if ui.button("run now").clicked() {
+                            run = Some(idx);
+                        }
+                    });
+                    if !job.skipped.is_empty() || !job.failed.is_empty() {
+                        egui::CollapsingHeader::new(format!(
+                            "last run: skipped {}, failed {}",
+                            job.skipped.len(),
+                            job.failed.len()
+                        ))
+                        .id_source(("backup_last", idx))
+                        .show(ui, |ui| {
+                            egui::ScrollArea::vertical()
+                                .id_source(("backup_last_scroll", idx))
+                                .max_height(200.0)
+                                .show(ui, |ui| {
+                                    egui::Grid::new(("backup_last_grid", idx))
+                                        .striped(true)
+                                        .show(ui, |ui| {
+                                            for (path, e) in &job.failed {
+                                                ui.label(path);
+                                                ui.colored_label(egui::Color32::RED, e);
+                                                ui.end_row();
+                                            }
+                                            for (path, reason) in &job.skipped {
+                                                ui.label(path);
+                                                ui.label(reason);
+                                                ui.end_row();
+                                            }
+                                        });
+                                });
+                        });
+                    }
+                    egui::CollapsingHeader::new(format!("runs ({})", job.config.runs.len()))
+                        .id_source(("backup_runs", idx))
+                        .show(ui, |ui| {
+                            egui::Grid::new(("backup_runs_grid", idx))
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    ui.label("started");
+                                    ui.label("duration");
+                                    ui.label("uploaded");
+                                    ui.label("unchanged");
+                                    ui.label("skipped");
+                                    ui.label("failed");
+                                    ui.label("");
+                                    ui.end_row();
+                                    for run in job.config.runs.iter().rev() {
+                                        ui.label(formatting::format_time(&run.started, view));
+                                        ui.label(format!(
+                                            "{}s",
+                                            (run.finished - run.started).num_seconds()
+                                        ));
+                                        ui.label(format!(
+                                            "{} ({})",
+                                            run.uploaded,
+                                            formatting::format_size(
+                                                run.uploaded_bytes,
+                                                view.size_units
+                                            )
+                                        ));
+                                        ui.label(run.unchanged.to_string());
+                                        ui.label(run.skipped.to_string());
+                                        match &run.error {
+                                            Some(e) => {
+                                                ui.colored_label(egui::Color32::RED, e);
+                                            }
+                                            None => {
+                                                ui.label(run.failed.to_string());
+                                            }
+                                        }
+                                        match &run.manifest {
+                                            Some(manifest) => {
+                                                if ui
+                                                    .button("manifest")
+                                                    .on_hover_text(
+                                                        manifest.to_string_lossy().to_string(),
+                                                    )
+                                                    .clicked()
+                                                {
+                                                    let _ = open::that_in_background(manifest);
+                                                }
+                                            }
+                                            None => {
+                                                ui.label("");
+                                            }
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                    ui.separator();
+                }
+            });
+        if let Some(idx) = run {
+            storage.run_backup(idx);
+        }
+        if !open {
+            storage.visual_state.show_backups = false;
+        }
+    }
+
+    fn render_search(storage: &mut StorageInstance, ui: &mut Ui) {
+        let mut start = false;
+        let mut clear = false;
+        let current_path = storage
+            .visual_state
+            .folder
+            .as_ref()
+            .map(|folder| folder.path.clone())
+            .unwrap_or_default();
+        let search = &mut storage.visual_state.search;
+        ui.horizontal(|ui| {
+            ui.label("Search: ");
+            let response = ui.text_edit_singleline(&mut search.query);
+            if response.lost_focus() && ui.input().key_pressed(egui::Key::Enter) {
+                start = true;
+            }
+            ui.label("in /");
+            ui.add(egui::TextEdit::singleline(&mut search.path).desired_width(120.0));
+            if ui
+                .small_button("here")
+                .on_hover_text("search in current folder")
+                .clicked()
+            {
+                search.path = current_path;
+            }
+            ui.checkbox(&mut search.filename_only, "names only");
+            ui.label("ext:");
+            ui.add(
+                egui::TextEdit::singleline(&mut search.extensions)
+                    .desired_width(80.0)
+                    .hint_text("jpg, png"),
+            );
+            let categories_caption = if search.categories.is_empty() {
+                "any category".to_string()
+            } else {
+                search.categories.join(", ")
+            };
+            ui.menu_button(categories_caption, |ui| {
+                for category in storage_models::SEARCH_CATEGORIES {
+                    let mut checked = search.categories.iter().any(|v| v.eq(category));
+                    if ui.checkbox(&mut checked, *category).changed() {
+                        if checked {
+                            search.categories.push(category.to_string());
+                        } else {
+                            search.categories.retain(|v| !v.eq(category));
+                        }
+                    }
+                }
+            });
+            if ui.button("Search").clicked() {
+                start = true;
+            }
+            if storage.visual_state.search_results.is_some() && ui.button("x").clicked() {
+                clear = true;
+            }
+        });
+        if let Some(e) = &storage.visual_state.search.error {
+            ui.colored_label(egui::Color32::RED, e);
+        }
+        if start {
+            storage.start_search();
+        }
+        if clear {
+            storage.clear_search();
+        }
+    }
+
+    fn render_search_results(
+        storage: &mut StorageInstance,
+        ui: &mut Ui,
+        view: &config::ViewConfig,
+    ) {
+        let mut row_action: Option<RowAction> = None;
+        if let Some(results) = &storage.visual_state.search_results {
+            ui.label(format!(
+                "{}{} results for '{}'",
+                results.items.len(),
+                if results.limited { "+" } else { "" },
+                &results.query
+            ));
+            egui::ScrollArea::vertical()
+                .max_height(4000.0)
+                .auto_shrink([false; 2])
+                .show(ui, |ui| {
+                    egui::Grid::new("search.results")
+                        .num_columns(5)
+                        .min_col_width(5.0)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label("Name");
+                            ui.label("");
+                            ui.label("Size");
+                            ui.label("Time");
+                            ui.label("Folder");
+                            ui.end_row();
+                            for item in &results.items {
+                                let remote_path =
+                                    item.path_display.trim_start_matches('/').to_string();
+                                render_item_row(ui, item, &remote_path, view, &mut row_action);
+                                let parent = parent_remote_path(&remote_path);
+                                if ui
+                                    .selectable_label(false, "/".to_owned() + parent)
+                                    .on_hover_text("go to containing folder")
+                                    .clicked()
+                                {
+                                    row_action = Some(RowAction::GoToFolder {
+                                        path: parent.to_string(),
+                                    });
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+        }
+        if let Some(row_action) = row_action {
+            Self::apply_row_action(storage, row_action, view);
+        }
+    }
+
+    fn render_freshness(storage: &StorageInstance, ui: &mut Ui, view: &config::ViewConfig) {
+        if let Some(folder) = &storage.visual_state.folder {
+            match &folder.freshness {
+                storage_instance::FolderFreshness::Fresh => {}
+                storage_instance::FolderFreshness::Refreshing { saved } => {
+                    ui.label(format!(
+                        "cached {}, refreshing...",
+                        formatting::format_time(saved, view)
+                    ));
+                }
+                storage_instance::FolderFreshness::Stale { saved, error } => {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(230, 140, 0),
+                        format!("stale (cached {})", formatting::format_time(saved, view)),
+                    )
+                    .on_hover_text(error);
+                }
+            }
+        }
+    }
+
+    fn render_filter(storage: &mut StorageInstance, ui: &mut Ui) {
+        let mut show_deleted = storage.visual_state.show_deleted;
+        let filter = &mut storage.visual_state.filter;
+        ui.horizontal(|ui| {
+            ui.label("Filter: ");
+            ui.text_edit_singleline(&mut filter.text);
+            egui::ComboBox::from_id_source("filter.mode")
+                .selected_text(filter.mode.caption())
+                .show_ui(ui, |ui| {
+                    for mode in [
+                        items_filter::FilterMode::Substring,
+                        items_filter::FilterMode::Glob,
+                        items_filter::FilterMode::Regex,
+                    ] {
+                        ui.selectable_value(&mut filter.mode, mode, mode.caption());
+                    }
+                });
+            ui.radio_value(&mut filter.kind, items_filter::FilterKind::All, "all");
+            ui.radio_value(
+                &mut filter.kind,
+                items_filter::FilterKind::OnlyFiles,
+                "files",
+            );
+            ui.radio_value(
+                &mut filter.kind,
+                items_filter::FilterKind::OnlyFolders,
+                "folders",
+            );
+            if filter.is_active() && ui.button("x").clicked() {
+                filter.text.clear();
+                filter.kind = items_filter::FilterKind::All;
+            }
+            ui.separator();
+            ui.checkbox(&mut show_deleted, "show deleted");
+        });
+        filter.prepare();
+
+        if let Some(folder) = &storage.visual_state.folder {
+            let total = folder.items.len();
+            let matched = if filter.is_active() {
+                folder
+                    .items
+                    .iter()
+                    .filter(|item| filter.matches(item))
+                    .count()
+            } else {
+                total
+            };
+            ui.horizontal(|ui| {
+                ui.label(format!("{} of {} items", matched, total));
+                if let Some(e) = &filter.error {
+                    ui.colored_label(egui::Color32::RED, e);
+                }
+            });
+        }
+        if show_deleted != storage.visual_state.show_deleted {
+            storage.set_show_deleted(show_deleted);
+        }
+    }
+
+    fn render_state(ui: &mut Ui, storage: &mut StorageInstance, view: &config::ViewConfig) {
+        let in_progress = {
+            let res: Vec<String> = storage
+                .call_states
+                .iter()
+                .map(|(_, state)| match &state.data {
+                    call_states::Data::ListFolder { data, path } => {
+                        let action = format!("ListFolder({})", path);
+                        match data {
+                            call_states::ListFolder::Ok => format!("{}(Ok)", action),
+                            call_states::ListFolder::Failed(e) => {
+                                format!("{}(Failed: {})", action, e)
+                            }
+                            call_states::ListFolder::InProgress { value, value_str } => format!(
+                                "{}(InProgress: (value: {:?}, value_str: {:?}))",
+                                action, value, value_str
+                            ),
+                            call_states::ListFolder::RefreshToken => {
+                                format!("{}(RefreshToken)", action)
+                            }
+                            call_states::ListFolder::RefreshTokenComplete => {
+                                format!("{}(RefreshTokenComplete)", action)
+                            }
+                        }
+                    }
+                    call_states::Data::DownloadFile {
+                        data,
+                        remote_path,
+                        local_path: _,
+                        size: _,
+                        downloaded: _,
+                        open: _,
+                    } => {
+                        let action = format!("Download({})", remote_path);
+                        match data {
+                            call_states::DownloadFile::Ok => format!("{}(Ok)", action),
+                            call_states::DownloadFile::Failed(e) => {
+                                format!("{}(Failed: {})", action, e)
+                            }
+                            call_states::DownloadFile::Started => format!("{}(Started)", action),
+                            call_states::DownloadFile::SizeInfo { size } => format!(
+                                "{}(SizeInfo: {})",
+                                action,
+                                size.map(|v| formatting::format_size(v, view.size_units))
+                                    .unwrap_or_else(|| "unknown".to_string())
+                            ),
+                            call_states::DownloadFile::InProgress { progress } => format!(
+                                "{}(InProgress: {})",
+                                action,
+                                formatting::format_size(*progress, view.size_units)
+                            ),
+                            call_states::DownloadFile::RefreshToken => {
+                                format!("{}(RefreshToken)", action)
+                            }
+                            call_states::DownloadFile::RefreshTokenComplete => {
+                                format!("{}(RefreshTokenComplete)", action)
+                            }
+                        }
+                    }
+                    call_states::Data::GetMetadata { data, path } => {
+                        let action = format!("GetMetadata({})", path);
+                        match data {
+                            call_states::GetMetadata::Ok => format!("{}(Ok)", action),
+                            call_states::GetMetadata::Failed(e) => {
+                                format!("{}(Failed: {})", action, e)
+                            }
+                            call_states::GetMetadata::InProgress => {
+                                format!("{}(InProgress)", action)
+                            }
+                            call_states::GetMetadata::RefreshToken => {
+                                format!("{}(RefreshToken)", action)
+                            }
+                            call_states::GetMetadata::RefreshTokenComplete => {
+                                format!("{}(RefreshTokenComplete)", action)
+                            }
+                        }
+                    }
+                    call_states::Data::GetThumbnails { data, total, done } => {
+                        let action = format!("GetThumbnails({} of {})", done, total);
+                        match data {
+                            call_states::GetThumbnails::Ok => format!("{}(Ok)", action),
+                            call_states::GetThumbnails::Failed(e) => {
+                                format!("{}(Failed: {})", action, e)
+                            }
+                            call_states::GetThumbnails::InProgress => {
+                                format!("{}(InProgress)", action)
+                            }
+                            call_states::GetThumbnails::RefreshToken => {
+                                format!("{}(RefreshToken)", action)
+                            }
+                            call_states::GetThumbnails::RefreshTokenComplete => {
+                                format!("{}(RefreshTokenComplete)", action)
+                            }
+                        }
+                    }
+                    call_states::Data::Preview { data, path } => {
+                        let action = format!("Preview({})", path);
+                        match data {
+                            call_states::Preview::Ok => format!("{}(Ok)", action),
+                            call_states::Preview::Failed(e) => {
+                                format!("{}(Failed: {})", action, e)
+                            }
+                            call_states::Preview::InProgress => format!("{}(InProgress)", action),
+                            call_states::Preview::RefreshToken => {
+                                format!("{}(RefreshToken)", action)
+                            }
+                            call_states::Preview::RefreshTokenComplete => {
+                                format!("{}(RefreshTokenComplete)", action)
+                            }
+                        }
+                    }
+                    call_states::Data::Search { data, query } => {
+                        let action = format!("Search({})", query);
+                        match data {
+                            call_states::Search::Ok => format!("{}(Ok)", action),
+                            call_states::Search::Failed(e) => {
+                                format!("{}(Failed: {})", action, e)
+                            }
+                            call_states::Search::InProgress { count } => {
+                                format!("{}(InProgress: {} found)", action, count)
+                            }
+                            call_states::Search::RefreshToken => {
+                                format!("{}(RefreshToken)", action)
+                            }
+                            call_states::Search::RefreshTokenComplete => {
+                                format!("{}(RefreshTokenComplete)", action)
+                            }
+                        }
+                    }
+                    call_states::Data::WatchFolder { data, path } => {
+                        let action = format!("WatchFolder({})", path);
+                        match data {
+                            // permanent background call, not shown as progress
+                            call_states::WatchFolder::Watching => "".to_string(),
+                            call_states::WatchFolder::Failed(e) => {
+                                format!("{}(Failed: {})", action, e)
+                            }
+                            call_states::WatchFolder::RefreshToken => {
+                                format!("{}(RefreshToken)", action)
+                            }
+                        }
+                    }
+                    call_states::Data::ListRevisions { data, path } => {
+                        let action = format!("ListRevisions({})", path);
+                        match data {
+                            call_states::ListRevisions::Ok => format!("{}(Ok)", action),
+                            call_states::ListRevisions::Failed(e) => {
+                                format!("{}(Failed: {})", action, e)
+                            }
+                            call_states::ListRevisions::InProgress => {
+                                format!("{}(InProgress)", action)
+                            }
+                            call_states::ListRevisions::RefreshToken => {
+                                format!("{}(RefreshToken)", action)
+                            }
+                            call_states::ListRevisions::RefreshTokenComplete => {
+                                format!("{}(RefreshTokenComplete)", action)
+                            }
+                        }
+                    }
+                    call_states::Data::Restore { data, path } => {
+                        let action = format!("Restore({})", path);
+                        match data {
+                            call_states::Restore::Ok => format!("{}(Ok)", action),
+                            call_states::Restore::Failed(e) => {
+                                format!("{}(Failed: {})", action, e)
+                            }
+                            call_states::Restore::InProgress => format!("{}(InProgress)", action),
+                            call_states::Restore::RefreshToken => {
+                                format!("{}(RefreshToken)", action)
+                            }
+                            call_states::Restore::RefreshTokenComplete => {
+                                format!("{}(RefreshTokenComplete)", action)
+                            }
+                        }
+                    }
+                    call_states::Data::SharedLinks { data, path } => {
+                        let action = format!("SharedLinks({})", path);
+                        match data {
+                            call_states::SharedLinks::Ok => format!("{}(Ok)", action),
+                            call_states::SharedLinks::Failed(e) => {
+                                format!("{}(Failed: {})", action, e)
+                            }
+                            call_states::SharedLinks::InProgress => {
+                                format!("{}(InProgress)", action)
+                            }
+                            call_states::SharedLinks::RefreshToken => {
+                                format!("{}(RefreshToken)", action)
+                            }
+                            call_states::SharedLinks::RefreshTokenComplete => {
+                                format!("{}(RefreshTokenComplete)", action)
+                            }
+                        }
+                    }
+                    call_states::Data::TemporaryLink { data, path } => {
+                        let action = format!("TemporaryLink({})", path);
+                        match data {
+                            call_states::TemporaryLink::Ok => format!("{}(Ok)", action),
+                            call_states::TemporaryLink::Failed(e) => {
+                                format!("{}(Failed: {})", action, e)
+                            }
+                            call_states::TemporaryLink::InProgress => {
+                                format!("{}(InProgress)", action)
+                            }
+                            call_states::TemporaryLink::RefreshToken => {
+                                format!("{}(RefreshToken)", action)
+                            }
+                            call_states::TemporaryLink::RefreshTokenComplete => {
+                                format!("{}(RefreshTokenComplete)", action)
+                            }
+                        }
+                    }
+                    call_states::Data::ListRecursive { data, path, .. } => {
+                        let action = format!("ListRecursive({})", path);
+                        match data {
+                            call_states::ListRecursive::Ok => format!("{}(Ok)", action),
+                            call_states::ListRecursive::Failed(e) => {
+                                format!("{}(Failed: {})", action, e)
+                            }
+                            call_states::ListRecursive::InProgress { count } => {
+                                format!("{}(InProgress: {} entries)", action, count)
+                            }
+                            call_states::ListRecursive::RefreshToken => {
+                                format!("{}(RefreshToken)", action)
+                            }
+                            call_states::ListRecursive::RefreshTokenComplete => {
+                                format!("{}(RefreshTokenComplete)", action)
+                            }
+                        }
+                    }
+                    call_states::Data::FileOps { data, total, done } => {
+                        let action = format!("FileOps({} of {})", done, total);
+                        match data {
+                            call_states::FileOps::Ok => format!("{}(Ok)", action),
+                            call_states::FileOps::Failed(e) => {
+                                format!("{}(Failed: {})", action, e)
+                            }
+                            call_states::FileOps::InProgress => format!("{}(InProgress)", action),
+                            call_states::FileOps::RefreshToken => {
+                                format!("{}(RefreshToken)", action)
+                            }
+                            call_states::FileOps::RefreshTokenComplete => {
+                                format!("{}(RefreshTokenComplete)", action)
+                            }
+                        }
+                    }
+                    call_states::Data::Transfer {
+                        data,
+                        total,
+                        done,
+                        purpose,
+                    } => {
+                        let action = format!("Transfer({:?}, {} of {})", purpose, done, total);
+                        match data {
+                            call_states::Transfer::Ok => format!("{}(Ok)", action),
+                            call_states::Transfer::Failed(e) => {
+                                format!("{}(Failed: {})", action, e)
+                            }
+                            call_states::Transfer::InProgress => format!("{}(InProgress)", action),
+                            call_states::Transfer::RefreshToken => {
+                                format!("{}(RefreshToken)", action)
+                            }
+                            call_states::Transfer::RefreshTokenComplete => {
+                                format!("{}(RefreshTokenComplete)", action)
+                            }
+                        }
+                    }
+                    call_states::Data::Mirror { data, name } => {
+                        let action = format!("Mirror({})", name);
+                        match data {
+                            call_states::Mirror::Ok => format!("{}(Ok)", action),
+                            call_states::Mirror::Failed(e) => {
+                                format!("{}(Failed: {})", action, e)
+                            }
+                            call_states::Mirror::InProgress { listed, downloaded } => format!(
+                                "{}(InProgress: listed {}, downloaded {})",
+                                action, listed, downloaded
+                            ),
+                            call_states::Mirror::RefreshToken => {
+                                format!("{}(RefreshToken)", action)
+                            }
+                            call_states::Mirror::RefreshTokenComplete => {
+                                format!("{}(RefreshTokenComplete)", action)
+                            }
+                        }
+                    }
+                    call_states::Data::Sync { data, name } => {
+                        let action = format!("Sync({})", name);
+                        match data {
+                            call_states::Sync::Ok => format!("{}(Ok)", action),
+                            call_states::Sync::Failed(e) => {
+                                format!("{}(Failed: {})", action, e)
+                            }
+                            call_states::Sync::InProgress { done, total } => {
+                                format!("{}(InProgress: {} of {})", action, done, total)
+                            }
+                            call_states::Sync::RefreshToken => {
+                                format!("{}(RefreshToken)", action)
+                            }
+                            call_states::Sync::RefreshTokenComplete => {
+                                format!("{}(RefreshTokenComplete)", action)
+                            }
+                        }
+                    }
+                    call_states::Data::Backup { data, name } => {
+                        let action = format!("Backup({})", name);
+                        match data {
+                            call_states::Backup::Ok => format!("{}(Ok)", action),
+                            call_states::Backup::Failed(e) => {
+                                format!("{}(Failed: {})", action, e)
+                            }
+                            call_states::Backup::InProgress { done, total } => {
+                                format!("{}(InProgress: {} of {})", action, done, total)
+                            }
+                            call_states::Backup::RefreshToken => {
+                                format!("{}(RefreshToken)", action)
+                            }
+                            call_states::Backup::RefreshTokenComplete => {
+                                format!("{}(RefreshTokenComplete)", action)
+                            }
+                        }
+                    }
+                    call_states::Data::WebDav { data, port, .. } => {
+                        let action = format!("WebDav({})", port);
+                        match data {
+                            call_states::WebDav::Starting => format!("{}(Starting)", action),
+                            // permanent background call, shown in WebDAV window
+                            call_states::WebDav::Serving => "".to_string(),
+                            call_states::WebDav::Failed(e) => {
+                                format!("{}(Failed: {})", action, e)
+                            }
+                            call_states::WebDav::RefreshToken => {
+                                format!("{}(RefreshToken)", action)
+                            }
+                        }
+                    }
+                    call_states::Data::Stream { data } => match data {
+                        // permanent background call
+                        call_states::Stream::Serving => "".to_string(),
+                        call_states::Stream::RefreshToken => "Stream(RefreshToken)".to_string(),
+                    },
+                    call_states::Data::Compare { data, local_path } => {
+                        let action = format!("Compare({})", local_path);
+                        match data {
+                            call_states::Compare::Ok => format!("{}(Ok)", action),
+                            call_states::Compare::Failed(e) => {
+                                format!("{}(Failed: {})", action, e)
+                            }
+                            call_states::Compare::InProgress { count } => {
+                                format!("{}(InProgress: {} files)", action, count)
+                            }
+                        }
+                    }
+                    call_states::Data::AccountInfo { data } => {
+                        let action = "AccountInfo".to_string();
+                        match data {
+                            call_states::AccountInfo::Ok => format!("{}(Ok)", action),
+                            call_states::AccountInfo::Failed(e) => {
+                                format!("{}(Failed: {})", action, e)
+                            }
+                            call_states::AccountInfo::InProgress => {
+                                format!("{}(InProgress)", action)
+                            }
+                            call_states::AccountInfo::RefreshToken => {
+                                format!("{}(RefreshToken)", action)
+                            }
+                            call_states::AccountInfo::RefreshTokenComplete => {
+                                format!("{}(RefreshTokenComplete)", action)
+                            }
+                        }
+                    }
+                    call_states::Data::Auth { data } => {
+                        let action = "Auth".to_string();
+                        match data {
+                            call_states::Auth::Ok => format!("{}(Ok)", action),
+                            call_states::Auth::Failed(e) => format!("{}(Failed: {})", action, e),
+                            call_states::Auth::InProgress(progress) => {
+                                format!("{}(InProgress({:?}))", action, progress)
+                            }
+                        }
+                    }
+                })
+                .filter(|v| !v.is_empty())
+                .collect();
+            res
+        };
+
+        if !in_progress.is_empty() {
+            egui::TopBottomPanel::bottom("bottom_panel")
+                .resizable(false)
+                .min_height(0.0)
+                .show_inside(ui, |ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.heading("progress");
+                        for item in &in_progress {
+                            ui.label(item);
+                        }
+                    });
+                });
+        }
+    }
+
+    fn render_menu(ui: &mut Ui, storage: &mut StorageInstance, view: &mut config::ViewConfig) {
+        egui::menu::bar(ui, |ui| {
+            ui.menu_button("cloud", |ui| {
+                if ui.button("log in...").clicked() {
+                    storage.start_auth();
+                    ui.close_menu();
+                }
+                let current_path = storage
+                    .visual_state
+                    .folder
+                    .as_ref()
+                    .map(|folder| folder.path.clone())
+                    .unwrap_or_default();
+                if ui.button("analyze folder sizes...").clicked() {
+                    storage.analyze_folder(current_path.clone());
+                    ui.close_menu();
+                }
+                if ui.button("find duplicates...").clicked() {
+                    storage.find_duplicates(current_path.clone());
+                    ui.close_menu();
+                }
+                if ui.button("compare with local folder...").clicked() {
+                    storage.show_compare(current_path);
+                    ui.close_menu();
+                }
+                if ui.button("mirror jobs...").clicked() {
+                    storage.visual_state.show_mirrors = true;
+                    ui.close_menu();
+                }
+                if ui.button("sync jobs...").clicked() {
+                    storage.visual_state.show_syncs = true;
+                    ui.close_menu();
+                }
+                if ui.button("backup sets...").clicked() {
+                    storage.visual_state.show_backups = true;
+                    ui.close_menu();
+                }
+                if ui.button("WebDAV server...").clicked() {
+                    storage.visual_state.show_webdav = true;
+                    ui.close_menu();
+                }
+                ui.separator();
+                if ui
+                    .checkbox(
+                        &mut storage.auto_upload_edited,
+                        "upload edited files automatically",
+                    )
+                    .on_hover_text("opened files changed locally are uploaded back without asking")
+                    .changed()
+                {
+                    storage.config_changed = true;
+                }
+            });
+            ui.menu_button("view", |ui| {
+                ui.label("folder:");
+                ui.radio_value(&mut view.folder_view, config::FolderView::List, "list");
+                ui.radio_value(&mut view.folder_view, config::FolderView::Icons, "icons");
+                ui.separator();
+                ui.label("sizes:");
+                ui.radio_value(&mut view.size_units, config::SizeUnits::Bytes, "bytes");
+                ui.radio_value(
+                    &mut view.size_units,
+                    config::SizeUnits::Binary,
+                    "binary (KiB, MiB)",
+                );
+                ui.radio_value(
+                    &mut view.size_units,
+                    config::SizeUnits::Decimal,
+                    "decimal (kB, MB)",
+                );
+                ui.separator();
+                ui.label("time:");
+                ui.checkbox(&mut view.local_time, "local time zone");
+                ui.checkbox(&mut view.relative_time, "relative (\"3 hours ago\")");
+            });
+        });
+    }
+
+    fn render_account(ui: &mut Ui, storage: &StorageInstance, view: &config::ViewConfig) {
+        if let Some(account) = &storage.visual_state.account {
+            ui.horizontal(|ui| {
+                ui.label(format!("{} <{}>", &account.name, &account.email));
+                let used = formatting::format_size(account.used, view.size_units);
+                match account.allocated {
+                    Some(allocated) if allocated > 0 => {
+                        ui.add(
+                            egui::ProgressBar::new(account.used as f32 / allocated as f32)
+                                .desired_width(200.0)
+                                .text(format!(
+                                    "{} of {}",
+                                    used,
+                                    formatting::format_size(allocated, view.size_units)
+                                )),
+                        );
+                    }
+                    _ => {
+                        ui.label(format!("{} used", used));
+                    }
+                }
+            });
+            if let Some(expected) = &account.expected_account_id {
+                ui.colored_label(
+                    egui::Color32::RED,
+                    format!(
+                        "tokens belong to another account ({}), expected {}; log in again",
+                        &account.account_id, expected
+                    ),
+                );
+            }
+        }
+    }
+
+    fn render_storage(
+        ctx: &egui::CtxRef,
+        frame: &epi::Frame,
+        ui: &mut Ui,
+        storage: &mut StorageInstance,
+        view: &mut config::ViewConfig,
+    ) {
+        if let Some(text) = storage.visual_state.copy_to_clipboard.take() {
+            ui.output().copied_text = text;
+        }
+        Self::render_menu(ui, storage, view);
+        Self::render_account(ui, storage, view);
+        Self::render_state(ui, storage, view);
+        Self::render_properties(ctx, storage, view);
+        Self::render_revisions(ctx, storage, view);
+        Self::render_shared_links(ctx, storage, view);
+        Self::render_analyze(ctx, storage, view);
+        Self::render_duplicates(ctx, storage, view);
+        Self::render_compare(ctx, storage, view);
+        Self::render_mirrors(ctx, storage, view);
+        Self::render_syncs(ctx, storage, view);
+        Self::render_backups(ctx, storage, view);
+        Self::render_webdav(ctx, storage);
+        Self::prepare_preview(storage, frame);
+        Self::render_preview(ui, storage, view);
+
+        if let Some(auth_state) = &storage.visual_state.auth_process_state {
+            ui.horizontal(|ui| {
+                if ui.button("Cancel auth").clicked() {
+                    storage.cancel_auth(auth_state.call_id);
+                }
+            });
+        } else {
+            egui::CentralPanel::default().show_inside(ui, |ui| {
+                match &storage.visual_state.folder {
+                    Some(_folder) => {
+                        ui.horizontal(|ui| {
+                            ui.label("Path: ");
+                            ui.text_edit_singleline(&mut storage.visual_state.v_path);
+                            Self::render_freshness(storage, ui, view);
+                        });
+                        Self::render_downloads(storage, ui, view);
+                        Self::render_edited_files(storage, ui);
+                        ui.add_space(3.0);
+                        Self::render_search(storage, ui);
+                        if storage.visual_state.search_results.is_some() {
+                            Self::render_search_results(storage, ui, view);
+                            return;
+                        }
+                        Self::render_filter(storage, ui);
+                        match view.folder_view {
+                            config::FolderView::List => Self::render_folder(storage, ui, view),
+                            config::FolderView::Icons => {
+                                Self::prepare_thumbnails(storage, frame);
+                                Self::render_folder_icons(storage, ui, view);
+                            }
+                        }
+                    }
+                    _ => {}
+                };
+            });
+        }
+    }
+
+    fn render(&mut self, ctx: &egui::CtxRef, frame: &epi::Frame) {
+        let Self {
+            storages,
+            config,
+            rth,
+            stream_proxy,
+        } = self;
+        egui::CentralPanel::default().show(ctx, |ui| {
+            for (idx, storage) in storages.iter_mut().enumerate() {
+                storage.prepare_visual_state();
+                if storage.config_changed {
+                    storage.config_changed = false;
+                    Self::store_storage_config(config, storage);
+                    let _ = config::save_config(config);
+                }
+                if let Some(path) = storage.visual_state.stream_in_player.take() {
+                    Self::stream_in_player(stream_proxy, rth, config, storage, path);
+                }
+                if idx == 0 {
+                    Self::render_storage(ctx, frame, ui, storage, &mut config.view);
+                }
+            }
+        });
+
+        //        frame.set_window_size(ctx.used_size());
+    }
+
+    /// opens proxy url in default player, proxy is started if needed;
+    /// falls back to temporary link if proxy can't be started
+    fn stream_in_player(
+        stream_proxy: &mut Option<stream_proxy::StreamProxy>,
+        rth: &RuntimeHolder,
+        config: &config::AppConfig,
+        storage: &mut StorageInstance,
+        path: String,
+    ) {
+        if stream_proxy.is_none() {
+            match stream_proxy::StreamProxy::start(rth.clone(), config.stream_proxy.port) {
+                Ok(proxy) => *stream_proxy = Some(proxy),
+                Err(e) => {
+                    log::error!("can't start stream proxy: {}", e);
+                    storage.open_externally(path);
+                    return;
+                }
+            }
+        }
+        if let Some(proxy) = stream_proxy {
+            if !proxy.has_target(&storage.id) {
+                proxy.add_target(storage.id.clone(), storage.stream_target());
+            }
+            let url = proxy.url(&storage.id, &path);
+            log::debug!("stream in player: {}", &url);
+            open::that_in_background(url);
+        }
+    }
+
+    /// starts control api for all storages, token is generated and saved on first start
+    fn start_control(&mut self, frame: &epi::Frame) {
+        if self.config.control.token.is_empty() {
+            use rand::Rng;
+            self.config.control.token = rand::thread_rng()
+                .sample_iter(rand::distributions::Alphanumeric)
+                .map(char::from)
+                .take(32)
+                .collect();
+            let _ = config::save_config(&self.config);
+        }
+        let targets = self
+            .storages
+            .iter()
+            .map(|storage| storage.control_target())
+            .collect();
+        match control::start(
+            self.rth.clone(),
+            self.config.control.port,
+            &self.config.control.token,
+            targets,
+            frame.lock().repaint_signal.clone(),
+        ) {
+            Ok(addr) => log::info!("control api at http://{}", addr),
+            Err(e) => log::error!("can't start control api: {}", e),
+        }
+    }
+
+    /// copies settings, state of mirror, sync and backup jobs to config
+    fn store_storage_config(config: &mut config::AppConfig, storage: &StorageInstance) {
+        if let Some(storage_config) = config.storage_by_id_mut(storage.id.clone()) {
+            storage_config.auto_upload_edited = storage.auto_upload_edited;
+            storage_config.webdav = storage.webdav.clone();
+            storage_config.mirrors = storage
+                .mirrors
+                .iter()
+                .map(|job| job.config.clone())
+                .collect();
+            storage_config.syncs = storage.syncs.iter().map(|job| job.config.clone()).collect();
+            storage_config.backups = storage
+                .backups
+                .iter()
+                .map(|job| job.config.clone())
+                .collect();
+        }
+    }
+
+    fn cleanup(&mut self) {
+        self.rth
+            .block_on(async {
+                for storage in &self.storages {
+                    let auth_info = { (*storage.auth_info_holder.read().await).clone() };
+                    if let Some(storage_config) = self.config.storage_by_id_mut(storage.id.clone())
+                    {
+                        storage_config.tokens.token = auth_info.token;
+                        storage_config.tokens.refresh_token = auth_info.refresh_token;
+                        storage_config.tokens.account_id = auth_info.account_id;
+                        if let Some(folder) = &storage.visual_state.folder {
+                            storage_config.current_path = folder.path.clone()
+                        }
+                    }
+                }
+            })
+            .unwrap();
+
+        for storage in &self.storages {
+            Self::store_storage_config(&mut self.config, storage);
+        }
+        let _ = config::save_config(&self.config);
+
+        self.rth.print_hyper_futures_states();
+
+        log::debug!("rt.shutdown.started");
+        let _ = self
+            .rth
+            .shutdown_timeout(std::time::Duration::from_secs(10));
+        log::debug!("rt.shutdown.finished");
+    }
+}
+
+impl epi::App for AppState {
+    fn update(&mut self, ctx: &egui::CtxRef, frame: &epi::Frame) {
+        self.render(ctx, frame);
+        ctx.request_repaint();
+    }
+
+    fn setup(
+        &mut self,
+        _ctx: &egui::CtxRef,
+        frame: &epi::Frame,
+        _storage: Option<&dyn epi::Storage>,
+    ) {
+        if self.config.control.enabled {
+            self.start_control(frame);
+        }
+
+        let mut fonts = egui::FontDefinitions::default();
+        let font_bytes = include_bytes!("../resources/fonts/mplus-1p-regular.ttf");
+        fonts.font_data.insert(
+            "mplus1p".to_owned(),
+            egui::FontData::from_static(font_bytes),
+        );
+
+        fonts
+            .fonts_for_family
+            .get_mut(&egui::FontFamily::Monospace)
+            .unwrap()
+            .insert(0, "mplus1p".to_owned());
+        fonts
+            .fonts_for_family
+            .get_mut(&egui::FontFamily::Proportional)
+            .unwrap()
+            .insert(0, "mplus1p".to_owned());
+
+        for style in egui::TextStyle::all() {
+            fonts
+                .family_and_size
+                .insert(style, (egui::FontFamily::Proportional, 18.0));
+        }
+
+        _ctx.set_fonts(fonts);
+    }
+
+    fn on_exit(&mut self) {
+        self.cleanup();
+    }
+
+    fn name(&self) -> &str {
+        "Clouds viewer "
+    }
+}
+
+/// runs command of headless mode or the app
+pub fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if cli::is_headless(&args) {
+        std::process::exit(cli::run(args));
+    }
+
+    let mut app_state = init::init()?;
+
+    for storage in app_state.storages.iter_mut() {
+        if storage.visual_state.auth_needed {
+            storage.visual_state.auth_needed = false;
+            storage.start_auth();
+        }
+
+        if let Some(storage_config) = app_state.config.storage_by_id(storage.id.clone()) {
+            storage.nav_to(storage_config.current_path.clone());
+        } else {
+            storage.nav_to("".to_string());
+        }
+        storage.load_account_info();
+    }
+    let options = eframe::NativeOptions::default();
+    eframe::run_native(Box::new(app_state), options);
+}
@@ -0,0 +1,6 @@
+//! tokens of Dropbox account: authorization code exchange and access token refresh
+
+pub use crate::clouds::dropbox::{
+    auth_code_to_tokens, refresh_token, CodeToTokensResult, RefreshTokenResult,
+};
+pub use crate::clouds::{AuthCodeToTokensCallError, AuthInfo, RefreshTokenCallError};
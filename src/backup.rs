@@ -0,0 +1,173 @@
+use crate::compare;
+use crate::glob;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+struct Pattern {
+    regex: regex::Regex,
+    /// pattern without '/' is matched against name only
+    name_only: bool,
+}
+
+impl Pattern {
+    fn new(glob: &str) -> Result<Self, regex::Error> {
+        let glob = glob.trim_matches('/');
+        Ok(Self {
+            regex: regex::Regex::new(&glob::glob_to_regex(glob))?,
+            name_only: !glob.contains('/'),
+        })
+    }
+
+    fn matches(&self, relative_path: &str) -> bool {
+        if self.name_only {
+            let name = relative_path.rsplit('/').next().unwrap_or(relative_path);
+            self.regex.is_match(name)
+        } else {
+            self.regex.is_match(relative_path)
+        }
+    }
+}
+
+/// include/exclude patterns of backup set
+pub struct Filter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl Filter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, String> {
+        let compile = |globs: &[String]| {
+            globs
+                .iter()
+                .map(|glob| glob.trim())
+                .filter(|glob| !glob.is_empty())
+                .map(|glob| Pattern::new(glob).map_err(|e| format!("pattern '{}': {}", glob, e)))
+                .collect::<Result<Vec<Pattern>, String>>()
+        };
+        Ok(Self {
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+        })
+    }
+
+    fn is_excluded(&self, relative_path: &str) -> bool {
+        self.exclude.iter().any(|p| p.matches(relative_path))
+    }
+
+    fn is_included(&self, relative_path: &str) -> bool {
+        self.include.is_empty() || self.include.iter().any(|p| p.matches(relative_path))
+    }
+}
+
+pub struct ScanResult {
+    pub files: Vec<compare::LocalFile>,
+    /// relative path and reason
+    pub skipped: Vec<(String, String)>,
+}
+
+/// files of local folder (recursively) selected by `filter`.
+/// Excluded folders are not entered, unreadable entries are skipped
+pub fn scan(root: &Path, filter: &Filter) -> std::io::Result<ScanResult> {
+    let mut res = ScanResult {
+        files: vec![],
+        skipped: vec![],
+    };
+    let mut folders = vec![(root.to_path_buf(), "".to_string())];
+    while let Some((folder, relative)) = folders.pop() {
+        let entries = match std::fs::read_dir(&folder) {
+            Ok(entries) => entries,
+            Err(e) if !relative.is_empty() => {
+                res.skipped.push((relative, e.to_string()));
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        for entry in entries {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    res.skipped.push((relative.clone(), e.to_string()));
+                    continue;
+                }
+            };
+            let name = entry.file_name().to_string_lossy().to_string();
+            let relative_path = if relative.is_empty() {
+                name
+            } else {
+                format!("{}/{}", relative, name)
+            };
+            if filter.is_excluded(&relative_path) {
+                res.skipped.push((relative_path, "excluded".to_string()));
+                continue;
+            }
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    res.skipped.push((relative_path, e.to_string()));
+                    continue;
+                }
+            };
+            if metadata.is_dir() {
+                folders.push((entry.path(), relative_path));
+            } else if metadata.is_file() && filter.is_included(&relative_path) {
+                res.files.push(compare::LocalFile {
+                    relative_path,
+                    size: metadata.len(),
+                    modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+                });
+            }
+        }
+    }
+    res.files
+        .sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    Ok(res)
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ManifestStatus {
+    Uploaded,
+    Unchanged,
+    Skipped { reason: String },
+    Failed { error: String },
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ManifestFile {
+    /// relative to backed up folder, '/' separated
+    pub path: String,
+    pub size: Option<u64>,
+    pub modified: Option<DateTime<Utc>>,
+    pub content_hash: Option<String>,
+    /// remote revision holding this content
+    pub rev: Option<String>,
+    pub status: ManifestStatus,
+}
+
+/// what one run of backup set did with every file
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Manifest {
+    pub name: String,
+    pub local_path: String,
+    pub remote_path: String,
+    pub started: DateTime<Utc>,
+    pub finished: DateTime<Utc>,
+    pub files: Vec<ManifestFile>,
+}
+
+impl Manifest {
+    pub fn load(file: &Path) -> Option<Self> {
+        let text = std::fs::read_to_string(file).ok()?;
+        serde_json::from_str(&text)
+            .map_err(|e| log::warn!("manifest '{}' parse error: {}", file.display(), e))
+            .ok()
+    }
+
+    pub fn save(&self, file: &Path) -> std::io::Result<()> {
+        if let Some(dir) = file.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(file, serde_json::to_string_pretty(self).unwrap())
+    }
+}
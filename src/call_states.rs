@@ -23,6 +23,173 @@ pub enum DownloadFile {
     RefreshTokenComplete,
 }
 
+#[derive(Debug)]
+pub enum GetMetadata {
+    Ok,
+    Failed(storage_models::GetMetadataError),
+    InProgress,
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum GetThumbnails {
+    Ok,
+    Failed(storage_models::GetThumbnailsError),
+    InProgress,
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum Preview {
+    Ok,
+    Failed(storage_models::PreviewError),
+    InProgress,
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum Search {
+    Ok,
+    Failed(storage_models::SearchError),
+    InProgress { count: u64 },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum WatchFolder {
+    /// also set on refresh token complete
+    Watching,
+    Failed(storage_models::WatchFolderError),
+    RefreshToken,
+}
+
+#[derive(Debug)]
+pub enum SharedLinks {
+    Ok,
+    Failed(storage_models::SharedLinksError),
+    InProgress,
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum ListRevisions {
+    Ok,
+    Failed(storage_models::ListRevisionsError),
+    InProgress,
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum FileOps {
+    Ok,
+    Failed(storage_models::FileOpsError),
+    InProgress,
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum Transfer {
+    Ok,
+    Failed(storage_models::TransferError),
+    InProgress,
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum Mirror {
+    Ok,
+    Failed(storage_models::MirrorError),
+    InProgress { listed: u64, downloaded: u64 },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum Sync {
+    Ok,
+    Failed(storage_models::SyncError),
+    InProgress { done: u64, total: u64 },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum Backup {
+    Ok,
+    Failed(storage_models::BackupError),
+    InProgress { done: u64, total: u64 },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum Compare {
+    Ok,
+    Failed(storage_models::CompareError),
+    InProgress { count: u64 },
+}
+
+#[derive(Debug)]
+pub enum ListRecursive {
+    Ok,
+    Failed(storage_models::ListRecursiveError),
+    InProgress { count: u64 },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum AccountInfo {
+    Ok,
+    Failed(storage_models::AccountInfoError),
+    InProgress,
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum TemporaryLink {
+    Ok,
+    Failed(storage_models::TemporaryLinkError),
+    InProgress,
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum Restore {
+    Ok,
+    Failed(storage_models::RestoreError),
+    InProgress,
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum WebDav {
+    /// before bind
+    Starting,
+    /// also set on refresh token complete
+    Serving,
+    Failed(storage_models::WebDavError),
+    RefreshToken,
+}
+
+#[derive(Debug)]
+pub enum Stream {
+    /// also set on refresh token complete
+    Serving,
+    RefreshToken,
+}
+
 #[derive(Debug)]
 pub enum AuthProgress {
     /// before bind
@@ -61,6 +228,91 @@ pub enum Data {
         local_path: String,
         size: Option<u64>,
         downloaded: u64,
+        /// opened and watched for edits when finished
+        open: bool,
+    },
+    GetMetadata {
+        data: GetMetadata,
+        path: String,
+    },
+    GetThumbnails {
+        data: GetThumbnails,
+        total: u64,
+        done: u64,
+    },
+    Preview {
+        data: Preview,
+        path: String,
+    },
+    Search {
+        data: Search,
+        query: String,
+    },
+    WatchFolder {
+        data: WatchFolder,
+        path: String,
+    },
+    ListRevisions {
+        data: ListRevisions,
+        path: String,
+    },
+    Restore {
+        data: Restore,
+        path: String,
+    },
+    SharedLinks {
+        data: SharedLinks,
+        path: String,
+    },
+    TemporaryLink {
+        data: TemporaryLink,
+        path: String,
+    },
+    AccountInfo {
+        data: AccountInfo,
+    },
+    ListRecursive {
+        data: ListRecursive,
+        path: String,
+        purpose: storage_models::ListRecursivePurpose,
+    },
+    FileOps {
+        data: FileOps,
+        total: u64,
+        done: u64,
+    },
+    Transfer {
+        data: Transfer,
+        total: u64,
+        done: u64,
+        purpose: storage_models::TransferPurpose,
+    },
+    Compare {
+        data: Compare,
+        local_path: String,
+    },
+    Mirror {
+        data: Mirror,
+        name: String,
+    },
+    Sync {
+        data: Sync,
+        name: String,
+    },
+    Backup {
+        data: Backup,
+        name: String,
+    },
+    WebDav {
+        data: WebDav,
+        port: u16,
+        addr: Option<std::net::SocketAddr>,
+        /// requests served since start
+        served: u64,
+        last_request: Option<String>,
+    },
+    Stream {
+        data: Stream,
     },
     Auth {
         data: Auth,
@@ -79,5 +331,3 @@ pub struct State {
     pub cancellers: Vec<Canceller>,
     pub data: Data,
 }
-
-
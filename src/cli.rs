@@ -0,0 +1,627 @@
+use crate::clouds::{self, call_messages, dropbox};
+use crate::common_types::*;
+use crate::compare;
+use crate::config;
+use crate::formatting;
+use crate::storage_models;
+use crate::storages;
+use serde::Serialize;
+
+const USAGE: &str = "usage: clouds_viewer [--storage <id or caption>] <command> [args]
+
+commands:
+  ls [-r] [<remote path>]                list folder, recursively with -r
+  get <remote path> [<local path>]       download file or folder
+  put <local path> [<remote path>]       upload file or folder
+  rm <remote path>                       delete file or folder
+  mv <remote path> <new remote path>     move or rename file or folder
+
+Results are printed to stdout as json, progress and errors to stderr.
+Exit codes: 0 - success, 1 - failed, 2 - wrong arguments, 3 - authorization error.
+Storage must be authorized in the app first.";
+
+const EXIT_FAILED: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_AUTH: i32 = 3;
+
+#[derive(thiserror::Error, Debug)]
+enum CliError {
+    #[error("{0}")]
+    Usage(String),
+    #[error("authorization error: {0}")]
+    Auth(String),
+    #[error("{0}")]
+    Failed(String),
+}
+
+impl CliError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            Self::Usage(_) => EXIT_USAGE,
+            Self::Auth(_) => EXIT_AUTH,
+            Self::Failed(_) => EXIT_FAILED,
+        }
+    }
+
+    /// token errors are reported as authorization errors
+    fn from_call<E: clouds::BaseErrorAccess + std::fmt::Display>(e: E) -> Self {
+        match e.get_base_error() {
+            Some(base) if base.is_token_error() => Self::Auth(e.to_string()),
+            _ => Self::Failed(e.to_string()),
+        }
+    }
+}
+
+enum Command {
+    Ls {
+        path: String,
+        recursive: bool,
+    },
+    Get {
+        remote_path: String,
+        local_path: Option<String>,
+    },
+    Put {
+        local_path: String,
+        remote_path: String,
+    },
+    Rm {
+        path: String,
+    },
+    Mv {
+        from_path: String,
+        to_path: String,
+    },
+}
+
+/// result of one file of `get` or `put`
+#[derive(Serialize)]
+struct TransferResult {
+    remote_path: String,
+    local_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    item: Option<storage_models::Item>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// remote path without leading and trailing '/'
+fn remote_path(path: &str) -> String {
+    path.trim_matches('/').to_string()
+}
+
+/// path for api calls: "" for root, "/path" otherwise
+fn api_path(path: &str) -> String {
+    if path.is_empty() {
+        "".to_string()
+    } else {
+        "/".to_owned() + path
+    }
+}
+
+const COMMANDS: [&str; 5] = ["ls", "get", "put", "rm", "mv"];
+
+/// arguments select headless mode if they contain a known command or help,
+/// other arguments (e.g. `-psn_*` added by macOS launcher) start the app
+pub fn is_headless(args: &[String]) -> bool {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--storage" => {
+                args.next();
+            }
+            "-h" | "--help" | "help" => return true,
+            arg if COMMANDS.contains(&arg) => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+fn parse_args(args: Vec<String>) -> Result<(Option<String>, Command), CliError> {
+    let mut storage = None;
+    let mut recursive = false;
+    let mut positional = vec![];
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--storage" => match args.next() {
+                Some(value) => storage = Some(value),
+                None => return Err(CliError::Usage("--storage needs a value".to_string())),
+            },
+            "-r" => recursive = true,
+            "-h" | "--help" | "help" => return Err(CliError::Usage("".to_string())),
+            _ => positional.push(arg),
+        }
+    }
+    let mut positional = positional.into_iter();
+    let command = positional
+        .next()
+        .ok_or_else(|| CliError::Usage("command is missing".to_string()))?;
+    let mut next = |name: &str| {
+        positional
+            .next()
+            .ok_or_else(|| CliError::Usage(format!("{}: {} is missing", command, name)))
+    };
+    let res = match command.as_str() {
+        "ls" => Command::Ls {
+            path: remote_path(&next("path").unwrap_or_default()),
+            recursive,
+        },
+        "get" => Command::Get {
+            remote_path: remote_path(&next("remote path")?),
+            local_path: next("local path").ok(),
+        },
+        "put" => Command::Put {
+            local_path: next("local path")?,
+            remote_path: remote_path(&next("remote path").unwrap_or_default()),
+        },
+        "rm" => Command::Rm {
+            path: remote_path(&next("remote path")?),
+        },
+        "mv" => Command::Mv {
+            from_path: remote_path(&next("remote path")?),
+            to_path: remote_path(&next("new remote path")?),
+        },
+        _ => return Err(CliError::Usage(format!("unknown command '{}'", command))),
+    };
+    Ok((storage, res))
+}
+
+struct Cli {
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    messages: MessagesSender<call_messages::Message>,
+}
+
+impl Cli {
+    async fn get_metadata(&self, path: &str) -> Result<Option<dropbox::Meta>, CliError> {
+        match dropbox::get_metadata(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            dropbox::GetMetadataParams {
+                path: api_path(path),
+                include_media_info: None,
+                include_deleted: None,
+                include_has_explicit_shared_members: None,
+            },
+            self.messages.clone(),
+            dropbox::token_messages::<call_messages::GetMetadata>(0),
+        )
+        .await
+        {
+            Ok(meta) => Ok(Some(meta)),
+            Err(dropbox::GetMetadataCallError::PathNotFound(_)) => Ok(None),
+            Err(e) => Err(CliError::from_call(e)),
+        }
+    }
+
+    async fn list(&self, path: &str, recursive: bool) -> Result<Vec<dropbox::Meta>, CliError> {
+        let mut res = dropbox::list_folder(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            dropbox::ListFolderParams {
+                path: api_path(path),
+                recursive: Some(recursive),
+                include_deleted: Some(false),
+                include_has_explicit_shared_members: None,
+                include_mounted_folders: None,
+                limit: Some(2000),
+                include_non_downloadable_files: Some(true),
+            },
+            self.messages.clone(),
+            dropbox::token_messages::<call_messages::ListFolder>(0),
+        )
+        .await
+        .map_err(CliError::from_call)?;
+        let mut entries = std::mem::take(&mut res.entries);
+        while res.has_more {
+            res = dropbox::list_folder_continue(
+                self.rth.clone(),
+                self.auth_info_holder.clone(),
+                dropbox::ListFolderContinueParams { cursor: res.cursor },
+                self.messages.clone(),
+                dropbox::token_messages::<call_messages::ListFolder>(0),
+            )
+            .await
+            .map_err(CliError::from_call)?;
+            entries.append(&mut res.entries);
+            eprint!("\rlisted {}", entries.len());
+            if !res.has_more {
+                eprintln!();
+            }
+        }
+        Ok(entries)
+    }
+
+    async fn ls(&self, path: &str, recursive: bool) -> Result<serde_json::Value, CliError> {
+        let mut items: Vec<storage_models::Item> = self
+            .list(path, recursive)
+            .await?
+            .into_iter()
+            .filter_map(storages::item_from_meta)
+            .filter(|item| !remote_path(&item.path_display).eq_ignore_ascii_case(path))
+            .collect();
+        items.sort_by_key(|item| item.path_display.to_lowercase());
+        Ok(serde_json::to_value(items).unwrap())
+    }
+
+    async fn download(
+        &self,
+        remote_path: &str,
+        local_path: std::path::PathBuf,
+    ) -> Result<TransferResult, CliError> {
+        eprintln!("get: /{} -> {}", remote_path, local_path.display());
+        let mut res = TransferResult {
+            remote_path: remote_path.to_string(),
+            local_path: local_path.to_string_lossy().to_string(),
+            item: None,
+            error: None,
+        };
+        if let Some(Err(e)) = local_path.parent().map(std::fs::create_dir_all) {
+            res.error = Some(e.to_string());
+            return Ok(res);
+        }
+        match dropbox::download_file(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            dropbox::DownloadFileParams {
+                path: api_path(remote_path),
+                save_to: res.local_path.clone(),
+            },
+            0,
+            self.messages.clone(),
+        )
+        .await
+        {
+            Ok(meta) => res.item = Some(storages::item_from_download_result(meta)),
+            Err(dropbox::DownloadFileCallError::Base(base)) if base.is_token_error() => {
+                return Err(CliError::Auth(base.to_string()));
+            }
+            Err(e) => res.error = Some(e.to_string()),
+        }
+        eprintln!();
+        Ok(res)
+    }
+
+    async fn get(
+        &self,
+        remote_path: &str,
+        local_path: Option<String>,
+    ) -> Result<Vec<TransferResult>, CliError> {
+        // root can't be asked for metadata
+        let is_file = if remote_path.is_empty() {
+            false
+        } else {
+            match self.get_metadata(remote_path).await? {
+                Some(dropbox::Meta::File(_)) => true,
+                Some(dropbox::Meta::Folder(_)) => false,
+                _ => {
+                    return Err(CliError::Failed(format!(
+                        "path not found: '/{}'",
+                        remote_path
+                    )))
+                }
+            }
+        };
+        let name = remote_path.rsplit('/').next().unwrap_or_default();
+        // into existing local folder or as given path
+        let target = match local_path {
+            Some(path) if std::path::Path::new(&path).is_dir() => {
+                std::path::Path::new(&path).join(name)
+            }
+            Some(path) => std::path::PathBuf::from(path),
+            None if name.is_empty() => std::path::PathBuf::from("."),
+            None => std::path::PathBuf::from(name),
+        };
+        if is_file {
+            return Ok(vec![self.download(remote_path, target).await?]);
+        }
+        std::fs::create_dir_all(&target).map_err(|e| CliError::Failed(e.to_string()))?;
+        let prefix_len = if remote_path.is_empty() {
+            1
+        } else {
+            remote_path.len() + 2
+        };
+        let mut results = vec![];
+        for meta in self.list(remote_path, true).await? {
+            match meta {
+                dropbox::Meta::Folder(folder) => {
+                    if let Some(relative) = folder.path_display.get(prefix_len..) {
+                        let local = compare::local_file_path(&target.to_string_lossy(), relative);
+                        std::fs::create_dir_all(&local)
+                            .map_err(|e| CliError::Failed(e.to_string()))?;
+                    }
+                }
+                dropbox::Meta::File(file) => {
+                    let relative = match file.path_display.get(prefix_len..) {
+                        Some(relative) => relative,
+                        None => continue,
+                    };
+                    let local = compare::local_file_path(&target.to_string_lossy(), relative);
+                    let remote = file.path_display.trim_start_matches('/');
+                    results.push(self.download(remote, local).await?);
+                }
+                dropbox::Meta::Deleted(_) => {}
+            }
+        }
+        Ok(results)
+    }
+
+    async fn upload(
+        &self,
+        local_path: &std::path::Path,
+        remote_path: String,
+        rev: Option<String>,
+    ) -> Result<TransferResult, CliError> {
+        eprintln!("put: {} -> /{}", local_path.display(), &remote_path);
+        let mut res = TransferResult {
+            remote_path,
+            local_path: local_path.to_string_lossy().to_string(),
+            item: None,
+            error: None,
+        };
+        let commit = dropbox::CommitInfo {
+            path: api_path(&res.remote_path),
+            mode: match rev {
+                Some(rev) => dropbox::WriteMode::Update { update: rev },
+                None => dropbox::WriteMode::Add,
+            },
+            autorename: false,
+            client_modified: std::fs::metadata(local_path)
+                .and_then(|m| m.modified())
+                .ok()
+                .map(|t| {
+                    chrono::DateTime::<chrono::Utc>::from(t)
+                        .format("%Y-%m-%dT%H:%M:%SZ")
+                        .to_string()
+                }),
+            mute: false,
+        };
+        match dropbox::upload(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            res.local_path.clone(),
+            commit,
+            self.messages.clone(),
+            dropbox::token_messages::<call_messages::Transfer>(0),
+        )
+        .await
+        {
+            Ok(meta) => res.item = storages::item_from_meta(dropbox::Meta::File(meta)),
+            Err(dropbox::UploadCallError::Base(base)) if base.is_token_error() => {
+                return Err(CliError::Auth(base.to_string()));
+            }
+            Err(e) => res.error = Some(e.to_string()),
+        }
+        Ok(res)
+    }
+
+    async fn put(
+        &self,
+        local_path: &str,
+        remote_path: &str,
+    ) -> Result<Vec<TransferResult>, CliError> {
+        let local = std::path::Path::new(local_path);
+        let metadata = std::fs::metadata(local)
+            .map_err(|e| CliError::Failed(format!("{}: {}", local_path, e)))?;
+        // "." has no name of its own
+        let name = std::fs::canonicalize(local)
+            .map_err(|e| CliError::Failed(format!("{}: {}", local_path, e)))?
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .ok_or_else(|| CliError::Usage(format!("no file name in '{}'", local_path)))?;
+        // into existing remote folder or as given path
+        let target = if remote_path.is_empty() {
+            name
+        } else {
+            match self.get_metadata(remote_path).await? {
+                Some(dropbox::Meta::Folder(_)) => compare::remote_file_path(remote_path, &name),
+                _ => remote_path.to_string(),
+            }
+        };
+        let existing = self.get_metadata(&target).await?;
+        if metadata.is_file() {
+            let rev = match existing {
+                Some(dropbox::Meta::File(file)) => Some(file.rev),
+                Some(dropbox::Meta::Folder(_)) => {
+                    return Err(CliError::Failed(format!("'/{}' is a folder", target)));
+                }
+                _ => None,
+            };
+            return Ok(vec![self.upload(local, target, rev).await?]);
+        }
+        // existing files are replaced
+        let mut revs = std::collections::HashMap::new();
+        if let Some(dropbox::Meta::Folder(_)) = existing {
+            let prefix_len = target.len() + 2;
+            for meta in self.list(&target, true).await? {
+                if let dropbox::Meta::File(file) = meta {
+                    if let Some(relative) = file.path_display.get(prefix_len..) {
+                        revs.insert(relative.to_lowercase(), file.rev);
+                    }
+                }
+            }
+        }
+        let mut files = compare::scan_local(local).map_err(|e| CliError::Failed(e.to_string()))?;
+        files.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+        let mut results = vec![];
+        for file in files {
+            let rev = revs.remove(&file.relative_path.to_lowercase());
+            results.push(
+                self.upload(
+                    &compare::local_file_path(local_path, &file.relative_path),
+                    compare::remote_file_path(&target, &file.relative_path),
+                    rev,
+                )
+                .await?,
+            );
+        }
+        Ok(results)
+    }
+
+    async fn rm(&self, path: &str) -> Result<serde_json::Value, CliError> {
+        let res = dropbox::delete(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            dropbox::DeleteParams {
+                path: api_path(path),
+                parent_rev: None,
+            },
+            self.messages.clone(),
+            dropbox::token_messages::<call_messages::FileOps>(0),
+        )
+        .await
+        .map_err(CliError::from_call)?;
+        Ok(serde_json::to_value(storages::item_from_meta(res.metadata)).unwrap())
+    }
+
+    async fn mv(&self, from_path: &str, to_path: &str) -> Result<serde_json::Value, CliError> {
+        let res = dropbox::move_file(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            dropbox::MoveParams {
+                from_path: api_path(from_path),
+                to_path: api_path(to_path),
+                autorename: false,
+            },
+            self.messages.clone(),
+            dropbox::token_messages::<call_messages::FileOps>(0),
+        )
+        .await
+        .map_err(CliError::from_call)?;
+        Ok(serde_json::to_value(storages::item_from_meta(res.metadata)).unwrap())
+    }
+
+    /// json result, fails if any file of `get` or `put` failed
+    async fn execute(&self, command: Command) -> Result<(serde_json::Value, bool), CliError> {
+        let transfer = |results: Vec<TransferResult>| {
+            let ok = results.iter().all(|res| res.error.is_none());
+            (serde_json::to_value(results).unwrap(), ok)
+        };
+        Ok(match command {
+            Command::Ls { path, recursive } => (self.ls(&path, recursive).await?, true),
+            Command::Get {
+                remote_path,
+                local_path,
+            } => transfer(self.get(&remote_path, local_path).await?),
+            Command::Put {
+                local_path,
+                remote_path,
+            } => transfer(self.put(&local_path, &remote_path).await?),
+            Command::Rm { path } => (self.rm(&path).await?, true),
+            Command::Mv { from_path, to_path } => (self.mv(&from_path, &to_path).await?, true),
+        })
+    }
+}
+
+/// prints download progress to stderr
+async fn print_progress(
+    mut receiver: MessagesReceiver<call_messages::Message>,
+    size_units: config::SizeUnits,
+) {
+    let mut size = None;
+    while let Some(msg) = receiver.recv().await {
+        match msg.data {
+            call_messages::Data::DownloadFile(call_messages::DownloadFile::SizeInfo {
+                size: value,
+            }) => size = value,
+            call_messages::Data::DownloadFile(call_messages::DownloadFile::Progress { value }) => {
+                match size {
+                    Some(size) => eprint!(
+                        "\r  {} of {}   ",
+                        formatting::format_size(value, size_units),
+                        formatting::format_size(size, size_units)
+                    ),
+                    None => eprint!("\r  {}   ", formatting::format_size(value, size_units)),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// runs command given in `args`, returns exit code
+pub fn run(args: Vec<String>) -> i32 {
+    let (storage_id, command) = match parse_args(args) {
+        Ok(res) => res,
+        Err(e) => {
+            if !e.to_string().is_empty() {
+                eprintln!("{}", e);
+            }
+            eprintln!("{}", USAGE);
+            return e.exit_code();
+        }
+    };
+    let app_config = match config::load_config() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("config error: {}", e);
+            return EXIT_FAILED;
+        }
+    };
+    let storage_config = match &storage_id {
+        Some(id) => app_config
+            .storages
+            .iter()
+            .find(|storage| storage.id.eq(id) || storage.caption.eq(id)),
+        None => app_config.storages.first(),
+    };
+    let storage_config = match storage_config {
+        Some(storage_config) => storage_config.clone(),
+        None => {
+            eprintln!("storage not found: {}", storage_id.unwrap_or_default());
+            return EXIT_USAGE;
+        }
+    };
+    if storage_config.tokens.token.is_empty() && storage_config.tokens.refresh_token.is_empty() {
+        eprintln!(
+            "storage '{}' is not authorized, log in with the app first",
+            &storage_config.caption
+        );
+        return EXIT_AUTH;
+    }
+
+    let rth = RuntimeHolder::new();
+    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+    let cli = Cli {
+        rth: rth.clone(),
+        auth_info_holder: std::sync::Arc::new(tokio::sync::RwLock::new(
+            clouds::AuthInfo::from_config(&storage_config),
+        )),
+        messages: sender,
+    };
+    let _ = rth.spawn(print_progress(receiver, app_config.view.size_units));
+    let res = rth.block_on(cli.execute(command)).unwrap();
+
+    // refreshed token is kept for next runs
+    let auth_info = rth
+        .block_on(async { (*cli.auth_info_holder.read().await).clone() })
+        .unwrap();
+    if !auth_info.token.eq(&storage_config.tokens.token) {
+        if let Err(e) = config::save_storage_tokens(
+            &storage_config.id,
+            &auth_info.token,
+            &auth_info.refresh_token,
+        ) {
+            eprintln!("can't save refreshed token: {}", e);
+        }
+    }
+    drop(cli);
+    let _ = rth.shutdown_timeout(std::time::Duration::from_millis(100));
+
+    match res {
+        Ok((value, ok)) => {
+            println!("{}", serde_json::to_string_pretty(&value).unwrap());
+            if ok {
+                0
+            } else {
+                EXIT_FAILED
+            }
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            e.exit_code()
+        }
+    }
+}
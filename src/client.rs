@@ -0,0 +1,182 @@
+//! Dropbox client for other tools: calls of the API with access token refreshed when it
+//! expires. Refreshed tokens can be read by `auth_info` to be saved.
+use crate::clouds::{self, call_messages, dropbox};
+use crate::common_types::*;
+use crate::config;
+use crate::models::*;
+
+pub struct Client {
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    /// token refresh messages of the app, receiver is dropped
+    messages: MessagesSender<call_messages::Message>,
+}
+
+impl Client {
+    pub fn new(rth: RuntimeHolder, auth_info: clouds::AuthInfo) -> Self {
+        let (messages, _) = tokio::sync::mpsc::unbounded_channel();
+        Self {
+            rth,
+            auth_info_holder: std::sync::Arc::new(tokio::sync::RwLock::new(auth_info)),
+            messages,
+        }
+    }
+
+    /// client of storage from app config
+    pub fn from_config(rth: RuntimeHolder, storage_config: &config::StorageConfig) -> Self {
+        Self::new(rth, clouds::AuthInfo::from_config(storage_config))
+    }
+
+    /// current tokens, access token is changed by refresh
+    pub async fn auth_info(&self) -> clouds::AuthInfo {
+        self.auth_info_holder.read().await.clone()
+    }
+
+    fn token_messages(&self) -> dropbox::TokenMessages<call_messages::ListFolder> {
+        dropbox::token_messages(0)
+    }
+
+    pub async fn list_folder(
+        &self,
+        params: ListFolderParams,
+    ) -> Result<ListFolderCallResult, ListFolderCallError> {
+        dropbox::list_folder(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            params,
+            self.messages.clone(),
+            self.token_messages(),
+        )
+        .await
+    }
+
+    pub async fn list_folder_continue(
+        &self,
+        params: ListFolderContinueParams,
+    ) -> Result<ListFolderCallResult, ListFolderCallError> {
+        dropbox::list_folder_continue(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            params,
+            self.messages.clone(),
+            self.token_messages(),
+        )
+        .await
+    }
+
+    pub async fn get_metadata(
+        &self,
+        params: GetMetadataParams,
+    ) -> Result<Meta, GetMetadataCallError> {
+        dropbox::get_metadata(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            params,
+            self.messages.clone(),
+            self.token_messages(),
+        )
+        .await
+    }
+
+    /// downloads to `params.save_to`
+    pub async fn download(
+        &self,
+        params: DownloadFileParams,
+    ) -> Result<DownloadFileCallResult, DownloadFileCallError> {
+        dropbox::download(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            params,
+            self.messages.clone(),
+            self.token_messages(),
+        )
+        .await
+    }
+
+    /// uploads local file, big files by upload session
+    pub async fn upload(
+        &self,
+        local_path: String,
+        commit: CommitInfo,
+    ) -> Result<FileMeta, UploadCallError> {
+        dropbox::upload(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            local_path,
+            commit,
+            self.messages.clone(),
+            self.token_messages(),
+        )
+        .await
+    }
+
+    pub async fn create_folder(
+        &self,
+        params: CreateFolderParams,
+    ) -> Result<CreateFolderResult, FileOpCallError> {
+        dropbox::create_folder(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            params,
+            self.messages.clone(),
+            self.token_messages(),
+        )
+        .await
+    }
+
+    pub async fn delete(&self, params: DeleteParams) -> Result<MetadataResult, FileOpCallError> {
+        dropbox::delete(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            params,
+            self.messages.clone(),
+            self.token_messages(),
+        )
+        .await
+    }
+
+    pub async fn move_file(&self, params: MoveParams) -> Result<MetadataResult, FileOpCallError> {
+        dropbox::move_file(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            params,
+            self.messages.clone(),
+            self.token_messages(),
+        )
+        .await
+    }
+
+    pub async fn get_temporary_link(
+        &self,
+        params: GetTemporaryLinkParams,
+    ) -> Result<GetTemporaryLinkCallResult, GetTemporaryLinkCallError> {
+        dropbox::get_temporary_link(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            params,
+            self.messages.clone(),
+            self.token_messages(),
+        )
+        .await
+    }
+
+    pub async fn get_current_account(&self) -> Result<FullAccount, AccountInfoCallError> {
+        dropbox::get_current_account(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            self.messages.clone(),
+            self.token_messages(),
+        )
+        .await
+    }
+
+    pub async fn get_space_usage(&self) -> Result<SpaceUsage, AccountInfoCallError> {
+        dropbox::get_space_usage(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            self.messages.clone(),
+            self.token_messages(),
+        )
+        .await
+    }
+}
@@ -1,4 +1,6 @@
 use crate::storage_models;
+use crate::thumbnails;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug)]
 pub enum ListFolder {
@@ -26,6 +28,7 @@ pub enum DownloadFile {
         handle: tokio::task::JoinHandle<()>,
         remote_path: String,
         local_path: String,
+        open: bool,
     },
     SizeInfo {
         size: Option<u64>,
@@ -41,6 +44,375 @@ pub enum DownloadFile {
     RefreshTokenComplete,
 }
 
+#[derive(Debug)]
+pub enum GetMetadata {
+    Started {
+        handle: tokio::task::JoinHandle<()>,
+        path: String,
+    },
+    Finished {
+        result: Result<storage_models::get_metadata_out_data, storage_models::GetMetadataError>,
+    },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum GetThumbnails {
+    Started {
+        handle: tokio::task::JoinHandle<()>,
+        total: u64,
+    },
+    Thumbnail {
+        rev: String,
+        image: Result<thumbnails::DecodedImage, String>,
+    },
+    Finished {
+        result: Result<storage_models::get_thumbnails_out_data, storage_models::GetThumbnailsError>,
+    },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum Preview {
+    Started {
+        handle: tokio::task::JoinHandle<()>,
+        path: String,
+    },
+    Finished {
+        result: Result<storage_models::preview_out_data, storage_models::PreviewError>,
+    },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum Search {
+    Started {
+        handle: tokio::task::JoinHandle<()>,
+        query: String,
+    },
+    Progress {
+        count: u64,
+    },
+    Finished {
+        result: Result<storage_models::search_out_data, storage_models::SearchError>,
+    },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum WatchFolder {
+    Start {
+        path: String,
+        cursor: String,
+    },
+    Started {
+        handle: tokio::task::JoinHandle<()>,
+        path: String,
+    },
+    Changes {
+        deltas: Vec<storage_models::ItemDelta>,
+    },
+    /// cursor was reset, folder is listed again
+    Relisted {
+        items: Vec<storage_models::Item>,
+    },
+    Finished {
+        result: Result<(), storage_models::WatchFolderError>,
+    },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum SharedLinks {
+    Started {
+        handle: tokio::task::JoinHandle<()>,
+        path: String,
+    },
+    Finished {
+        result: Result<storage_models::shared_links_out_data, storage_models::SharedLinksError>,
+    },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum FileOps {
+    Started {
+        handle: tokio::task::JoinHandle<()>,
+        total: u64,
+    },
+    Progress {
+        done: u64,
+    },
+    Finished {
+        result: Result<storage_models::file_ops_out_data, storage_models::FileOpsError>,
+    },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum Transfer {
+    Started {
+        handle: tokio::task::JoinHandle<()>,
+        total: u64,
+        purpose: storage_models::TransferPurpose,
+    },
+    Progress {
+        done: u64,
+    },
+    Finished {
+        result: Result<storage_models::transfer_out_data, storage_models::TransferError>,
+    },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum Mirror {
+    Started {
+        handle: tokio::task::JoinHandle<()>,
+        name: String,
+    },
+    Progress {
+        listed: u64,
+        downloaded: u64,
+    },
+    Finished {
+        result: Result<storage_models::mirror_out_data, storage_models::MirrorError>,
+    },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum Sync {
+    Started {
+        handle: tokio::task::JoinHandle<()>,
+        name: String,
+    },
+    Progress {
+        done: u64,
+        total: u64,
+    },
+    Finished {
+        result: Result<storage_models::sync_out_data, storage_models::SyncError>,
+    },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum Backup {
+    Started {
+        handle: tokio::task::JoinHandle<()>,
+        name: String,
+    },
+    Progress {
+        done: u64,
+        total: u64,
+    },
+    Finished {
+        result: Result<storage_models::backup_out_data, storage_models::BackupError>,
+    },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum WebDav {
+    Started {
+        handle: tokio::task::JoinHandle<()>,
+        port: u16,
+    },
+    Binded {
+        addr: std::net::SocketAddr,
+    },
+    LocalServerShutdowner {
+        cancel_informer: tokio::sync::oneshot::Sender<()>,
+        cancel_awaiter: tokio::sync::oneshot::Receiver<()>,
+    },
+    /// request served, e.g. "PUT /folder/file.txt 201"
+    Served {
+        request: String,
+    },
+    Finished {
+        result: Result<(), storage_models::WebDavError>,
+    },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+/// requests of stream proxy, which runs outside of storage calls
+#[derive(Debug)]
+pub enum Stream {
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+/// request of control api, `{"method": "navigate", "path": "folder"}` on the wire
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum ControlRequest {
+    /// answered by control server without storage
+    Storages,
+    /// current folder with its items
+    Folder,
+    Navigate {
+        path: String,
+    },
+    /// into download folder of the storage, `to_path` is relative to it
+    Download {
+        path: String,
+        #[serde(default)]
+        to_path: Option<String>,
+    },
+    Transfers,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ControlStorage {
+    pub id: String,
+    pub caption: String,
+}
+
+/// download in progress or finished with error
+#[derive(Serialize, Debug, Clone)]
+pub struct ControlTransfer {
+    pub call_id: u64,
+    pub remote_path: String,
+    pub local_path: String,
+    pub size: Option<u64>,
+    pub downloaded: u64,
+    /// "started", "in_progress", "refresh_token", "ok" or "failed"
+    pub state: String,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum ControlResponse {
+    Storages {
+        storages: Vec<ControlStorage>,
+    },
+    Folder {
+        path: String,
+        items: Vec<storage_models::Item>,
+    },
+    /// call is started, its state can be queried by call_id
+    Started {
+        call_id: u64,
+    },
+    Transfers {
+        transfers: Vec<ControlTransfer>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// sent without call_id, answered on next frame
+#[derive(Debug)]
+pub enum Control {
+    Request {
+        request: ControlRequest,
+        reply: tokio::sync::oneshot::Sender<ControlResponse>,
+    },
+}
+
+#[derive(Debug)]
+pub enum Compare {
+    /// compare local folder with listed remote folder
+    Start {
+        local_path: String,
+        remote_path: String,
+        remote_items: Vec<storage_models::Item>,
+    },
+    Started {
+        handle: tokio::task::JoinHandle<()>,
+        local_path: String,
+    },
+    /// local files hashed
+    Progress { count: u64 },
+    Finished {
+        result: Result<storage_models::compare_out_data, storage_models::CompareError>,
+    },
+}
+
+#[derive(Debug)]
+pub enum ListRecursive {
+    Started {
+        handle: tokio::task::JoinHandle<()>,
+        path: String,
+        purpose: storage_models::ListRecursivePurpose,
+    },
+    Progress {
+        count: u64,
+    },
+    Finished {
+        result: Result<storage_models::list_recursive_out_data, storage_models::ListRecursiveError>,
+    },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum AccountInfo {
+    Start,
+    Started {
+        handle: tokio::task::JoinHandle<()>,
+    },
+    Finished {
+        result: Result<storage_models::account_info_out_data, storage_models::AccountInfoError>,
+    },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum TemporaryLink {
+    Started {
+        handle: tokio::task::JoinHandle<()>,
+        path: String,
+    },
+    Finished {
+        result: Result<storage_models::temporary_link_out_data, storage_models::TemporaryLinkError>,
+    },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum ListRevisions {
+    Started {
+        handle: tokio::task::JoinHandle<()>,
+        path: String,
+    },
+    Finished {
+        result: Result<storage_models::list_revisions_out_data, storage_models::ListRevisionsError>,
+    },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
+#[derive(Debug)]
+pub enum Restore {
+    Started {
+        handle: tokio::task::JoinHandle<()>,
+        path: String,
+    },
+    Finished {
+        result: Result<storage_models::restore_out_data, storage_models::RestoreError>,
+    },
+    RefreshToken,
+    RefreshTokenComplete,
+}
+
 #[derive(Debug)]
 pub enum Auth {
     Start,
@@ -72,6 +444,26 @@ pub enum Auth {
 pub enum Data {
     ListFolder(ListFolder),
     DownloadFile(DownloadFile),
+    GetMetadata(GetMetadata),
+    GetThumbnails(GetThumbnails),
+    Preview(Preview),
+    Search(Search),
+    WatchFolder(WatchFolder),
+    ListRevisions(ListRevisions),
+    Restore(Restore),
+    SharedLinks(SharedLinks),
+    TemporaryLink(TemporaryLink),
+    AccountInfo(AccountInfo),
+    ListRecursive(ListRecursive),
+    FileOps(FileOps),
+    Transfer(Transfer),
+    Compare(Compare),
+    Mirror(Mirror),
+    Sync(Sync),
+    Backup(Backup),
+    WebDav(WebDav),
+    Stream(Stream),
+    Control(Control),
     Auth(Auth),
 }
 
@@ -80,3 +472,54 @@ pub struct Message {
     pub call_id: Option<u64>,
     pub data: Data,
 }
+
+/// message kinds with token refresh notifications, see `dropbox::TokenMessages`
+pub trait RefreshTokenMessages: Into<Data> {
+    fn refresh_token() -> Self;
+    fn refresh_token_complete() -> Self;
+}
+
+macro_rules! refresh_token_messages {
+    ($($kind:ident),* $(,)?) => {
+        $(
+            impl From<$kind> for Data {
+                fn from(data: $kind) -> Self {
+                    Data::$kind(data)
+                }
+            }
+
+            impl RefreshTokenMessages for $kind {
+                fn refresh_token() -> Self {
+                    $kind::RefreshToken
+                }
+
+                fn refresh_token_complete() -> Self {
+                    $kind::RefreshTokenComplete
+                }
+            }
+        )*
+    };
+}
+
+refresh_token_messages!(
+    ListFolder,
+    DownloadFile,
+    GetMetadata,
+    GetThumbnails,
+    Preview,
+    Search,
+    WatchFolder,
+    ListRevisions,
+    Restore,
+    SharedLinks,
+    TemporaryLink,
+    AccountInfo,
+    ListRecursive,
+    FileOps,
+    Transfer,
+    Mirror,
+    Sync,
+    Backup,
+    WebDav,
+    Stream,
+);
@@ -1,5 +1,5 @@
-use crate::call_messages;
 use crate::clouds;
+use crate::clouds::call_messages;
 use crate::common_types::*;
 use backoff::{future::retry, ExponentialBackoff};
 use chrono::prelude::*;
@@ -29,6 +29,9 @@ pub enum ListFolderCallError {
     Base(clouds::BaseError),
     #[error("path not found: {0}")]
     PathNotFound(String),
+    /// cursor is invalidated, listing should be restarted with list_folder
+    #[error("cursor reset")]
+    Reset,
 }
 
 impl clouds::BaseErrorAccess for ListFolderCallError {
@@ -51,6 +54,332 @@ impl clouds::BaseErrorAccess for ListFolderCallError {
     }
 }
 
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum GetMetadataCallError {
+    #[error(transparent)]
+    Base(clouds::BaseError),
+    #[error("path not found: {0}")]
+    PathNotFound(String),
+}
+
+impl clouds::BaseErrorAccess for GetMetadataCallError {
+    fn get_base_error(&self) -> Option<&clouds::BaseError> {
+        match self {
+            Self::Base(res) => Some(res),
+            _ => None,
+        }
+    }
+
+    fn get_base_error_mut(&mut self) -> Option<&mut clouds::BaseError> {
+        match self {
+            Self::Base(res) => Some(res),
+            _ => None,
+        }
+    }
+
+    fn from_base(base: clouds::BaseError) -> Self {
+        Self::Base(base)
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum GetThumbnailBatchCallError {
+    #[error(transparent)]
+    Base(clouds::BaseError),
+    #[error("too many files in batch")]
+    TooManyFiles,
+}
+
+impl clouds::BaseErrorAccess for GetThumbnailBatchCallError {
+    fn get_base_error(&self) -> Option<&clouds::BaseError> {
+        match self {
+            Self::Base(res) => Some(res),
+            _ => None,
+        }
+    }
+
+    fn get_base_error_mut(&mut self) -> Option<&mut clouds::BaseError> {
+        match self {
+            Self::Base(res) => Some(res),
+            _ => None,
+        }
+    }
+
+    fn from_base(base: clouds::BaseError) -> Self {
+        Self::Base(base)
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum ListRevisionsCallError {
+    #[error(transparent)]
+    Base(clouds::BaseError),
+    #[error("path not found: {0}")]
+    PathNotFound(String),
+    #[error("not a file: {0}")]
+    NotFile(String),
+    #[error("list revisions error: {0}")]
+    Other(String),
+}
+
+impl clouds::BaseErrorAccess for ListRevisionsCallError {
+    fn get_base_error(&self) -> Option<&clouds::BaseError> {
+        match self {
+            Self::Base(res) => Some(res),
+            _ => None,
+        }
+    }
+
+    fn get_base_error_mut(&mut self) -> Option<&mut clouds::BaseError> {
+        match self {
+            Self::Base(res) => Some(res),
+            _ => None,
+        }
+    }
+
+    fn from_base(base: clouds::BaseError) -> Self {
+        Self::Base(base)
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum UploadCallError {
+    #[error(transparent)]
+    Base(clouds::BaseError),
+    #[error("destination conflict: {0}")]
+    Conflict(String),
+    #[error("insufficient space")]
+    InsufficientSpace,
+    #[error("disallowed name: {0}")]
+    DisallowedName(String),
+    #[error("local file error ({file}): {error}")]
+    LocalFile { file: String, error: String },
+    #[error("upload error: {0}")]
+    Other(String),
+}
+
+impl clouds::BaseErrorAccess for UploadCallError {
+    fn get_base_error(&self) -> Option<&clouds::BaseError> {
+        match self {
+            Self::Base(res) => Some(res),
+            _ => None,
+        }
+    }
+
+    fn get_base_error_mut(&mut self) -> Option<&mut clouds::BaseError> {
+        match self {
+            Self::Base(res) => Some(res),
+            _ => None,
+        }
+    }
+
+    fn from_base(base: clouds::BaseError) -> Self {
+        Self::Base(base)
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum FileOpCallError {
+    #[error(transparent)]
+    Base(clouds::BaseError),
+    #[error("path not found: {0}")]
+    PathNotFound(String),
+    #[error("destination conflict for: {0}")]
+    Conflict(String),
+    #[error("too many write operations, retry later")]
+    TooManyWriteOperations,
+    #[error("file operation error: {0}")]
+    Other(String),
+}
+
+impl clouds::BaseErrorAccess for FileOpCallError {
+    fn get_base_error(&self) -> Option<&clouds::BaseError> {
+        match self {
+            Self::Base(res) => Some(res),
+            _ => None,
+        }
+    }
+
+    fn get_base_error_mut(&mut self) -> Option<&mut clouds::BaseError> {
+        match self {
+            Self::Base(res) => Some(res),
+            _ => None,
+        }
+    }
+
+    fn from_base(base: clouds::BaseError) -> Self {
+        Self::Base(base)
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum AccountInfoCallError {
+    #[error(transparent)]
+    Base(clouds::BaseError),
+    #[error("account info error")]
+    Other,
+}
+
+impl clouds::BaseErrorAccess for AccountInfoCallError {
+    fn get_base_error(&self) -> Option<&clouds::BaseError> {
+        match self {
+            Self::Base(res) => Some(res),
+            _ => None,
+        }
+    }
+
+    fn get_base_error_mut(&mut self) -> Option<&mut clouds::BaseError> {
+        match self {
+            Self::Base(res) => Some(res),
+            _ => None,
+        }
+    }
+
+    fn from_base(base: clouds::BaseError) -> Self {
+        Self::Base(base)
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum GetTemporaryLinkCallError {
+    #[error(transparent)]
+    Base(clouds::BaseError),
+    #[error("path not found: {0}")]
+    PathNotFound(String),
+    #[error("email is not verified")]
+    EmailNotVerified,
+    #[error("unsupported file: {0}")]
+    UnsupportedFile(String),
+    #[error("temporary links are not allowed")]
+    NotAllowed,
+    #[error("get temporary link error: {0}")]
+    Other(String),
+}
+
+impl clouds::BaseErrorAccess for GetTemporaryLinkCallError {
+    fn get_base_error(&self) -> Option<&clouds::BaseError> {
+        match self {
+            Self::Base(res) => Some(res),
+            _ => None,
+        }
+    }
+
+    fn get_base_error_mut(&mut self) -> Option<&mut clouds::BaseError> {
+        match self {
+            Self::Base(res) => Some(res),
+            _ => None,
+        }
+    }
+
+    fn from_base(base: clouds::BaseError) -> Self {
+        Self::Base(base)
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum RestoreCallError {
+    #[error(transparent)]
+    Base(clouds::BaseError),
+    #[error("path not found: {0}")]
+    PathNotFound(String),
+    #[error("invalid revision")]
+    InvalidRevision,
+    #[error("restore is in progress")]
+    InProgress,
+    #[error("restore error: {0}")]
+    Other(String),
+}
+
+impl clouds::BaseErrorAccess for RestoreCallError {
+    fn get_base_error(&self) -> Option<&clouds::BaseError> {
+        match self {
+            Self::Base(res) => Some(res),
+            _ => None,
+        }
+    }
+
+    fn get_base_error_mut(&mut self) -> Option<&mut clouds::BaseError> {
+        match self {
+            Self::Base(res) => Some(res),
+            _ => None,
+        }
+    }
+
+    fn from_base(base: clouds::BaseError) -> Self {
+        Self::Base(base)
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum SharedLinkCallError {
+    #[error(transparent)]
+    Base(clouds::BaseError),
+    #[error("path not found: {0}")]
+    PathNotFound(String),
+    #[error("shared link already exists")]
+    AlreadyExists,
+    #[error("shared link not found")]
+    LinkNotFound,
+    #[error("email is not verified")]
+    EmailNotVerified,
+    #[error("access denied")]
+    AccessDenied,
+    #[error("invalid link settings")]
+    SettingsError,
+    #[error("shared link error: {0}")]
+    Other(String),
+}
+
+impl clouds::BaseErrorAccess for SharedLinkCallError {
+    fn get_base_error(&self) -> Option<&clouds::BaseError> {
+        match self {
+            Self::Base(res) => Some(res),
+            _ => None,
+        }
+    }
+
+    fn get_base_error_mut(&mut self) -> Option<&mut clouds::BaseError> {
+        match self {
+            Self::Base(res) => Some(res),
+            _ => None,
+        }
+    }
+
+    fn from_base(base: clouds::BaseError) -> Self {
+        Self::Base(base)
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum SearchCallError {
+    #[error(transparent)]
+    Base(clouds::BaseError),
+    #[error("path not found: {0}")]
+    PathNotFound(String),
+    #[error("invalid search argument")]
+    InvalidArgument,
+}
+
+impl clouds::BaseErrorAccess for SearchCallError {
+    fn get_base_error(&self) -> Option<&clouds::BaseError> {
+        match self {
+            Self::Base(res) => Some(res),
+            _ => None,
+        }
+    }
+
+    fn get_base_error_mut(&mut self) -> Option<&mut clouds::BaseError> {
+        match self {
+            Self::Base(res) => Some(res),
+            _ => None,
+        }
+    }
+
+    fn from_base(base: clouds::BaseError) -> Self {
+        Self::Base(base)
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum DownloadFileCallError {
     #[error(transparent)]
@@ -93,6 +422,8 @@ pub enum DownloadFileCallError {
     },
     #[error("cloud file not found: {0}")]
     NotFound(String),
+    #[error("range not satisfiable: {0}")]
+    RangeNotSatisfiable(String),
 }
 
 impl DownloadFileCallError {
@@ -217,8 +548,397 @@ pub struct ListFolderContinueParams {
     pub cursor: String,
 }
 
-#[derive(Deserialize, Debug)]
-pub struct FileMeta {
+#[derive(Default, Serialize, Debug, Clone)]
+pub struct GetMetadataParams {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_media_info: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_deleted: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_has_explicit_shared_members: Option<bool>,
+}
+
+/// max entries in one files/get_thumbnail_batch call
+pub const THUMBNAIL_BATCH_LIMIT: usize = 25;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ThumbnailArg {
+    pub path: String,
+    /// "jpeg" | "png"
+    pub format: String,
+    /// "w32h32" | "w64h64" | "w128h128" | "w256h256" | ...
+    pub size: String,
+    /// "strict" | "bestfit" | "fitone_bestfit"
+    pub mode: String,
+}
+
+#[derive(Default, Serialize, Debug, Clone)]
+pub struct GetThumbnailBatchParams {
+    pub entries: Vec<ThumbnailArg>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = ".tag")]
+pub enum GetThumbnailBatchResultEntry {
+    #[serde(rename = "success")]
+    Success {
+        metadata: Box<FileMeta>,
+        thumbnail: String,
+    },
+    #[serde(rename = "failure")]
+    Failure { failure: serde_json::Value },
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GetThumbnailBatchCallResult {
+    pub entries: Vec<GetThumbnailBatchResultEntry>,
+}
+
+#[derive(Default, Serialize, Debug, Clone)]
+pub struct ListRevisionsParams {
+    pub path: String,
+    /// "path" | "id"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>, //  = 10, min=1, max=100
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ListRevisionsCallResult {
+    pub is_deleted: bool,
+    #[serde(default)]
+    pub server_deleted: Option<DateTime<Utc>>,
+    /// newest first
+    pub entries: Vec<FileMeta>,
+}
+
+#[derive(Default, Serialize, Debug, Clone)]
+pub struct RestoreParams {
+    pub path: String,
+    pub rev: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = ".tag")]
+pub enum WriteMode {
+    #[serde(rename = "add")]
+    Add,
+    /// overwrite only if current revision matches
+    #[serde(rename = "update")]
+    Update { update: String },
+    #[serde(rename = "overwrite")]
+    Overwrite,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CommitInfo {
+    pub path: String,
+    pub mode: WriteMode,
+    pub autorename: bool,
+    /// "%Y-%m-%dT%H:%M:%SZ"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_modified: Option<String>,
+    pub mute: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+struct UploadSessionStartParams {
+    close: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct UploadSessionStartResult {
+    session_id: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+struct UploadSessionCursor {
+    session_id: String,
+    offset: u64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+struct UploadSessionAppendParams {
+    cursor: UploadSessionCursor,
+    close: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+struct UploadSessionFinishParams {
+    cursor: UploadSessionCursor,
+    commit: CommitInfo,
+}
+
+#[derive(Default, Serialize, Debug, Clone)]
+pub struct DeleteParams {
+    pub path: String,
+    /// delete only if file has this revision
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_rev: Option<String>,
+}
+
+#[derive(Default, Serialize, Debug, Clone)]
+pub struct MoveParams {
+    pub from_path: String,
+    pub to_path: String,
+    pub autorename: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct MetadataResult {
+    pub metadata: Meta,
+}
+
+#[derive(Default, Serialize, Debug, Clone)]
+pub struct CreateFolderParams {
+    pub path: String,
+    pub autorename: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CreateFolderResult {
+    pub metadata: FolderMeta,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AccountName {
+    pub display_name: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct FullAccount {
+    pub account_id: String,
+    pub name: AccountName,
+    pub email: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = ".tag")]
+pub enum SpaceAllocation {
+    #[serde(rename = "individual")]
+    Individual { allocated: u64 },
+    /// whole team space
+    #[serde(rename = "team")]
+    Team { used: u64, allocated: u64 },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SpaceUsage {
+    pub used: u64,
+    pub allocation: SpaceAllocation,
+}
+
+#[derive(Default, Serialize, Debug, Clone)]
+pub struct GetTemporaryLinkParams {
+    pub path: String,
+}
+
+/// link is valid for 4 hours
+#[derive(Deserialize, Debug)]
+pub struct GetTemporaryLinkCallResult {
+    pub link: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(tag = ".tag")]
+pub enum LinkAudience {
+    #[serde(rename = "public")]
+    Public,
+    #[serde(rename = "team")]
+    Team,
+    #[serde(rename = "no_one")]
+    NoOne,
+    #[serde(rename = "password")]
+    Password,
+    #[serde(rename = "members")]
+    Members,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Default, Serialize, Debug, Clone)]
+pub struct SharedLinkSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audience: Option<LinkAudience>,
+    /// "%Y-%m-%dT%H:%M:%SZ"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub require_password: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_password: Option<String>,
+}
+
+#[derive(Default, Serialize, Debug, Clone)]
+pub struct CreateSharedLinkParams {
+    pub path: String,
+    pub settings: SharedLinkSettings,
+}
+
+#[derive(Default, Serialize, Debug, Clone)]
+pub struct ListSharedLinksParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direct_only: Option<bool>,
+}
+
+#[derive(Default, Serialize, Debug, Clone)]
+pub struct ModifySharedLinkParams {
+    pub url: String,
+    pub settings: SharedLinkSettings,
+    pub remove_expiration: bool,
+}
+
+#[derive(Default, Serialize, Debug, Clone)]
+pub struct RevokeSharedLinkParams {
+    pub url: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct LinkPermissions {
+    #[serde(default)]
+    pub effective_audience: Option<LinkAudience>,
+    #[serde(default)]
+    pub require_password: Option<bool>,
+    #[serde(default)]
+    pub can_revoke: bool,
+}
+
+/// file or folder link metadata, ".tag" is ignored
+#[derive(Deserialize, Debug)]
+pub struct SharedLinkMeta {
+    pub url: String,
+    pub name: String,
+    #[serde(default)]
+    pub expires: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub link_permissions: LinkPermissions,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ListSharedLinksCallResult {
+    pub links: Vec<SharedLinkMeta>,
+    pub has_more: bool,
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+#[derive(Default, Serialize, Debug, Clone)]
+pub struct ListFolderLongpollParams {
+    pub cursor: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>, // = 30, min=30, max=480
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ListFolderLongpollCallResult {
+    pub changes: bool,
+    /// seconds to wait before the next longpoll call
+    #[serde(default)]
+    pub backoff: Option<u64>,
+}
+
+#[derive(Default, Serialize, Debug, Clone)]
+pub struct SearchOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_results: Option<u64>, //  = 100, min=1, max=1000
+    /// "active" | "deleted"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_extensions: Option<Vec<String>>,
+    /// "image" | "document" | "pdf" | "spreadsheet" | "presentation" | "audio" | "video" | "folder" | "paper" | "others"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_categories: Option<Vec<String>>,
+}
+
+#[derive(Default, Serialize, Debug, Clone)]
+pub struct SearchParams {
+    pub query: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<SearchOptions>,
+}
+
+#[derive(Default, Serialize, Debug, Clone)]
+pub struct SearchContinueParams {
+    pub cursor: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = ".tag")]
+pub enum SearchMatchMetadata {
+    #[serde(rename = "metadata")]
+    Metadata { metadata: Box<Meta> },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SearchMatch {
+    pub metadata: SearchMatchMetadata,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SearchCallResult {
+    pub matches: Vec<SearchMatch>,
+    pub has_more: bool,
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Dimensions {
+    pub height: u64,
+    pub width: u64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct GpsCoordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = ".tag")]
+pub enum MediaMetadata {
+    #[serde(rename = "photo")]
+    Photo {
+        dimensions: Option<Dimensions>,
+        location: Option<GpsCoordinates>,
+        time_taken: Option<DateTime<Utc>>,
+    },
+    #[serde(rename = "video")]
+    Video {
+        dimensions: Option<Dimensions>,
+        location: Option<GpsCoordinates>,
+        time_taken: Option<DateTime<Utc>>,
+        /// milliseconds
+        duration: Option<u64>,
+    },
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = ".tag")]
+pub enum MediaInfo {
+    #[serde(rename = "pending")]
+    Pending,
+    #[serde(rename = "metadata")]
+    Metadata { metadata: MediaMetadata },
+}
+
+#[derive(Deserialize, Debug)]
+pub struct FileMeta {
     pub name: String,
     pub path_lower: String,
     pub path_display: String,
@@ -233,6 +953,8 @@ pub struct FileMeta {
     pub is_downloadable: bool,
     #[serde(default)]
     pub content_hash: String,
+    #[serde(default)]
+    pub media_info: Option<MediaInfo>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -243,6 +965,15 @@ pub struct FolderMeta {
     pub id: String,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct DeletedMeta {
+    pub name: String,
+    #[serde(default)]
+    pub path_lower: String,
+    #[serde(default)]
+    pub path_display: String,
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(tag = ".tag")]
 pub enum Meta {
@@ -250,6 +981,8 @@ pub enum Meta {
     File(FileMeta),
     #[serde(rename = "folder")]
     Folder(FolderMeta),
+    #[serde(rename = "deleted")]
+    Deleted(DeletedMeta),
 }
 
 #[derive(Default, Deserialize, Debug)]
@@ -294,6 +1027,8 @@ pub struct DownloadFileCallResult {
     pub size: u64,
     pub path_lower: String,
     pub path_display: String,
+    #[serde(default)]
+    pub content_hash: String,
 }
 
 #[derive(Deserialize, Debug)]
@@ -316,67 +1051,254 @@ pub enum ListFolderPathErrorTag {
 pub enum ListFolderErrorTag {
     #[serde(rename = "path")]
     Path { path: ListFolderPathErrorTag },
+    #[serde(rename = "reset")]
+    Reset,
 }
 
 #[derive(Deserialize, Debug)]
 #[serde(tag = ".tag")]
-pub enum DownloadFilePathErrorTag {
+pub enum ListFolderLongpollErrorTag {
+    #[serde(rename = "reset")]
+    Reset,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = ".tag")]
+pub enum SharedLinkErrorTag {
+    #[serde(rename = "path")]
+    Path { path: RevisionsLookupErrorTag },
+    #[serde(rename = "shared_link_already_exists")]
+    SharedLinkAlreadyExists,
+    #[serde(rename = "shared_link_not_found")]
+    SharedLinkNotFound,
+    #[serde(rename = "email_not_verified")]
+    EmailNotVerified,
+    #[serde(rename = "access_denied")]
+    AccessDenied,
+    #[serde(rename = "shared_link_access_denied")]
+    SharedLinkAccessDenied,
+    #[serde(rename = "settings_error")]
+    SettingsError,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = ".tag")]
+pub enum RevisionsLookupErrorTag {
     #[serde(rename = "not_found")]
     NotFound,
+    #[serde(rename = "not_file")]
+    NotFile,
+    #[serde(other)]
+    Other,
 }
 
 #[derive(Deserialize, Debug)]
 #[serde(tag = ".tag")]
-pub enum DownloadFileErrorTag {
+pub enum ListRevisionsErrorTag {
     #[serde(rename = "path")]
-    Path { path: DownloadFilePathErrorTag },
+    Path { path: RevisionsLookupErrorTag },
+    #[serde(other)]
+    Other,
 }
 
 #[derive(Deserialize, Debug)]
 #[serde(tag = ".tag")]
-pub enum RefreshTokenErrorTag {
-    NotImplemented,
+pub enum WriteErrorTag {
+    #[serde(rename = "conflict")]
+    Conflict,
+    #[serde(rename = "insufficient_space")]
+    InsufficientSpace,
+    #[serde(rename = "disallowed_name")]
+    DisallowedName,
+    #[serde(other)]
+    Other,
 }
 
 #[derive(Deserialize, Debug)]
 #[serde(tag = ".tag")]
-pub enum AuthCodeToTokenErrorTag {
-    NotImplemented,
+pub enum UploadErrorTag {
+    #[serde(rename = "path")]
+    Path { reason: WriteErrorTag },
+    #[serde(other)]
+    Other,
 }
 
+/// errors of upload_session/* calls
 #[derive(Deserialize, Debug)]
-#[serde(untagged)]
-pub enum ErrorTag<T> {
-    Base(BaseErrorTag),
-    Routine(T), // ListFolderErrorTag, etc
+#[serde(tag = ".tag")]
+pub enum UploadSessionErrorTag {
+    #[serde(rename = "path")]
+    Path { path: WriteErrorTag },
+    #[serde(other)]
+    Other,
 }
 
-impl<T> Default for ErrorTag<T> {
-    fn default() -> Self {
-        Self::Base(BaseErrorTag::Unknown)
-    }
+/// errors of delete_v2 and move_v2
+#[derive(Deserialize, Debug)]
+#[serde(tag = ".tag")]
+pub enum FileOpErrorTag {
+    #[serde(rename = "path_lookup")]
+    PathLookup {
+        path_lookup: RevisionsLookupErrorTag,
+    },
+    #[serde(rename = "from_lookup")]
+    FromLookup {
+        from_lookup: RevisionsLookupErrorTag,
+    },
+    #[serde(rename = "to")]
+    To { to: WriteErrorTag },
+    #[serde(rename = "too_many_write_operations")]
+    TooManyWriteOperations,
+    #[serde(other)]
+    Other,
 }
 
-#[derive(Default, Deserialize, Debug)]
-pub struct ApiCallError<T> {
-    pub error_summary: String,
-    pub error: ErrorTag<T>,
+/// users/* calls without arguments have no specific errors
+#[derive(Deserialize, Debug)]
+#[serde(tag = ".tag")]
+pub enum AccountInfoErrorTag {
+    #[serde(other)]
+    Other,
 }
 
-fn https_connector() -> hyper_rustls::HttpsConnector<hyper::client::HttpConnector> {
-    let mut http_connector = hyper::client::HttpConnector::new();
-    http_connector.enforce_http(false);
+#[derive(Deserialize, Debug)]
+#[serde(tag = ".tag")]
+pub enum GetTemporaryLinkErrorTag {
+    #[serde(rename = "path")]
+    Path { path: RevisionsLookupErrorTag },
+    #[serde(rename = "email_not_verified")]
+    EmailNotVerified,
+    #[serde(rename = "unsupported_file")]
+    UnsupportedFile,
+    #[serde(rename = "not_allowed")]
+    NotAllowed,
+    #[serde(other)]
+    Other,
+}
 
-    hyper_rustls::HttpsConnectorBuilder::new()
-        .with_native_roots()
-        .https_only()
-        .enable_http2()
-        .wrap_connector(http_connector)
+#[derive(Deserialize, Debug)]
+#[serde(tag = ".tag")]
+pub enum RestoreErrorTag {
+    #[serde(rename = "path_lookup")]
+    PathLookup {
+        path_lookup: RevisionsLookupErrorTag,
+    },
+    #[serde(rename = "invalid_revision")]
+    InvalidRevision,
+    #[serde(rename = "in_progress")]
+    InProgress,
+    #[serde(other)]
+    Other,
 }
 
-fn client_http2(
-    rth: &RuntimeHolder,
-) -> Client<hyper_rustls::HttpsConnector<hyper::client::HttpConnector>> {
+#[derive(Deserialize, Debug)]
+#[serde(tag = ".tag")]
+pub enum GetMetadataPathErrorTag {
+    #[serde(rename = "not_found")]
+    NotFound,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = ".tag")]
+pub enum GetMetadataErrorTag {
+    #[serde(rename = "path")]
+    Path { path: GetMetadataPathErrorTag },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = ".tag")]
+pub enum GetThumbnailBatchErrorTag {
+    #[serde(rename = "too_many_files")]
+    TooManyFiles,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = ".tag")]
+pub enum SearchPathErrorTag {
+    #[serde(rename = "not_found")]
+    NotFound,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = ".tag")]
+pub enum SearchErrorTag {
+    #[serde(rename = "path")]
+    Path { path: SearchPathErrorTag },
+    #[serde(rename = "invalid_argument")]
+    InvalidArgument,
+}
+
+/// errors of create_folder_v2
+#[derive(Deserialize, Debug)]
+#[serde(tag = ".tag")]
+pub enum CreateFolderErrorTag {
+    #[serde(rename = "path")]
+    Path { path: WriteErrorTag },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = ".tag")]
+pub enum DownloadFilePathErrorTag {
+    #[serde(rename = "not_found")]
+    NotFound,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = ".tag")]
+pub enum DownloadFileErrorTag {
+    #[serde(rename = "path")]
+    Path { path: DownloadFilePathErrorTag },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = ".tag")]
+pub enum RefreshTokenErrorTag {
+    NotImplemented,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = ".tag")]
+pub enum AuthCodeToTokenErrorTag {
+    NotImplemented,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum ErrorTag<T> {
+    Base(BaseErrorTag),
+    Routine(T), // ListFolderErrorTag, etc
+}
+
+impl<T> Default for ErrorTag<T> {
+    fn default() -> Self {
+        Self::Base(BaseErrorTag::Unknown)
+    }
+}
+
+#[derive(Default, Deserialize, Debug)]
+pub struct ApiCallError<T> {
+    pub error_summary: String,
+    pub error: ErrorTag<T>,
+}
+
+fn https_connector() -> hyper_rustls::HttpsConnector<hyper::client::HttpConnector> {
+    let mut http_connector = hyper::client::HttpConnector::new();
+    http_connector.enforce_http(false);
+
+    hyper_rustls::HttpsConnectorBuilder::new()
+        .with_native_roots()
+        .https_only()
+        .enable_http2()
+        .wrap_connector(http_connector)
+}
+
+fn client_http2(
+    rth: &RuntimeHolder,
+) -> Client<hyper_rustls::HttpsConnector<hyper::client::HttpConnector>> {
     let https = https_connector();
     Client::builder()
         .http2_only(true)
@@ -410,7 +1332,10 @@ where
         match serde_json::from_str::<Code400ErrorData>(&error_data) {
             Ok(data) => {
                 if data.error == "invalid_grant"
-                    && ["refresh token is malformed", "refresh token is invalid or revoked"]
+                    && [
+                        "refresh token is malformed",
+                        "refresh token is invalid or revoked",
+                    ]
                     .contains(&data.error_description.as_str())
                 {
                     T::from_base(clouds::BaseError::e_refresh_token_malformed(action))
@@ -510,8 +1435,6 @@ async fn int_list_folder(
     rth: &RuntimeHolder,
     auth_info_holder: clouds::AuthInfoHolder,
     params: ListFolderParams,
-    _call_id: u64,
-    _messages: MessagesSender<call_messages::Message>,
 ) -> Result<ListFolderCallResult, ListFolderCallError> {
     let token = { auth_info_holder.read().await.token.clone() };
 
@@ -532,141 +1455,1182 @@ async fn int_list_folder(
         ListFolderErrorTag::Path {
             path: ListFolderPathErrorTag::NotFound,
         } => ListFolderCallError::PathNotFound(path),
+        ListFolderErrorTag::Reset => ListFolderCallError::Reset,
+    })
+    .await
+}
+
+async fn int_list_folder_continue(
+    rth: &RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: ListFolderContinueParams,
+) -> Result<ListFolderCallResult, ListFolderCallError> {
+    let token = { auth_info_holder.read().await.token.clone() };
+
+    let action = "dropbox.list_folder_continue";
+    let serialized = serde_json::to_string(&params).unwrap();
+
+    let client = client_http2(rth);
+    let req = request_builder_http2()
+        .method("POST")
+        .header("Authorization", "Bearer ".to_owned() + &token)
+        .header("Content-Type", "application/json")
+        .uri("https://api.dropboxapi.com/2/files/list_folder/continue")
+        .body(Body::from(serialized))
+        .expect("request builder");
+
+    process_simple_request(client, req, action, |tr| match tr {
+        ListFolderErrorTag::Path {
+            path: ListFolderPathErrorTag::NotFound,
+        } => ListFolderCallError::PathNotFound("".into()),
+        ListFolderErrorTag::Reset => ListFolderCallError::Reset,
+    })
+    .await
+}
+
+async fn int_get_metadata(
+    rth: &RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: GetMetadataParams,
+) -> Result<Meta, GetMetadataCallError> {
+    let token = { auth_info_holder.read().await.token.clone() };
+
+    let action = "dropbox.get_metadata";
+    let serialized = serde_json::to_string(&params).unwrap();
+    let path = params.path.clone();
+
+    let client = client_http2(rth);
+    let req = request_builder_http2()
+        .method("POST")
+        .header("Authorization", "Bearer ".to_owned() + &token)
+        .header("Content-Type", "application/json")
+        .uri("https://api.dropboxapi.com/2/files/get_metadata")
+        .body(Body::from(serialized))
+        .expect("request builder");
+
+    process_simple_request(client, req, action, |tr| match tr {
+        GetMetadataErrorTag::Path {
+            path: GetMetadataPathErrorTag::NotFound,
+        } => GetMetadataCallError::PathNotFound(path),
+    })
+    .await
+}
+
+async fn int_list_revisions(
+    rth: &RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: ListRevisionsParams,
+) -> Result<ListRevisionsCallResult, ListRevisionsCallError> {
+    let token = { auth_info_holder.read().await.token.clone() };
+
+    let action = "dropbox.list_revisions";
+    let serialized = serde_json::to_string(&params).unwrap();
+    let path = params.path.clone();
+
+    let client = client_http2(rth);
+    let req = request_builder_http2()
+        .method("POST")
+        .header("Authorization", "Bearer ".to_owned() + &token)
+        .header("Content-Type", "application/json")
+        .uri("https://api.dropboxapi.com/2/files/list_revisions")
+        .body(Body::from(serialized))
+        .expect("request builder");
+
+    process_simple_request(client, req, action, |tr| match tr {
+        ListRevisionsErrorTag::Path {
+            path: RevisionsLookupErrorTag::NotFound,
+        } => ListRevisionsCallError::PathNotFound(path),
+        ListRevisionsErrorTag::Path {
+            path: RevisionsLookupErrorTag::NotFile,
+        } => ListRevisionsCallError::NotFile(path),
+        ListRevisionsErrorTag::Path {
+            path: RevisionsLookupErrorTag::Other,
+        }
+        | ListRevisionsErrorTag::Other => ListRevisionsCallError::Other(path),
+    })
+    .await
+}
+
+async fn int_restore(
+    rth: &RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: RestoreParams,
+) -> Result<FileMeta, RestoreCallError> {
+    let token = { auth_info_holder.read().await.token.clone() };
+
+    let action = "dropbox.restore";
+    let serialized = serde_json::to_string(&params).unwrap();
+    let path = params.path.clone();
+
+    let client = client_http2(rth);
+    let req = request_builder_http2()
+        .method("POST")
+        .header("Authorization", "Bearer ".to_owned() + &token)
+        .header("Content-Type", "application/json")
+        .uri("https://api.dropboxapi.com/2/files/restore")
+        .body(Body::from(serialized))
+        .expect("request builder");
+
+    process_simple_request(client, req, action, |tr| match tr {
+        RestoreErrorTag::PathLookup {
+            path_lookup: RevisionsLookupErrorTag::NotFound,
+        } => RestoreCallError::PathNotFound(path),
+        RestoreErrorTag::PathLookup { .. } | RestoreErrorTag::Other => {
+            RestoreCallError::Other(path)
+        }
+        RestoreErrorTag::InvalidRevision => RestoreCallError::InvalidRevision,
+        RestoreErrorTag::InProgress => RestoreCallError::InProgress,
+    })
+    .await
+}
+
+/// Dropbox-API-Arg header value: json with non-ASCII characters escaped
+fn api_arg_header<P: Serialize>(params: &P) -> String {
+    let json = serde_json::to_string(params).unwrap();
+    let mut res = String::with_capacity(json.len());
+    for c in json.chars() {
+        if c.is_ascii() {
+            res.push(c);
+        } else {
+            for unit in c.encode_utf16(&mut [0; 2]) {
+                res.push_str(&format!("\\u{:04x}", unit));
+            }
+        }
+    }
+    res
+}
+
+fn upload_write_error(reason: WriteErrorTag, path: String) -> UploadCallError {
+    match reason {
+        WriteErrorTag::Conflict => UploadCallError::Conflict(path),
+        WriteErrorTag::InsufficientSpace => UploadCallError::InsufficientSpace,
+        WriteErrorTag::DisallowedName => UploadCallError::DisallowedName(path),
+        WriteErrorTag::Other => UploadCallError::Other(path),
+    }
+}
+
+/// files bigger than this are uploaded by upload session
+const UPLOAD_SINGLE_MAX_BYTES: u64 = 150 * 1024 * 1024;
+const UPLOAD_CHUNK_BYTES: u64 = 8 * 1024 * 1024;
+/// read buffer of file streamed in single upload request
+const UPLOAD_STREAM_BUFFER_BYTES: usize = 1024 * 1024;
+
+async fn int_upload_request<P, R>(
+    rth: &RuntimeHolder,
+    token: &str,
+    endpoint: &str,
+    arg: &P,
+    body: Body,
+    path: &str,
+) -> Result<R, UploadCallError>
+where
+    P: Serialize,
+    R: serde::de::DeserializeOwned,
+{
+    let action = "dropbox.".to_owned() + endpoint;
+    let client = client_http2(rth);
+    let req = request_builder_http2()
+        .method("POST")
+        .header("Authorization", "Bearer ".to_owned() + token)
+        .header("Dropbox-API-Arg", api_arg_header(arg))
+        .header("Content-Type", "application/octet-stream")
+        .uri("https://content.dropboxapi.com/2/files/".to_owned() + endpoint)
+        .body(body)
+        .expect("request builder");
+    let path = path.to_string();
+    if endpoint.eq("upload") {
+        process_simple_request(client, req, &action, |tr| match tr {
+            UploadErrorTag::Path { reason } => upload_write_error(reason, path),
+            UploadErrorTag::Other => UploadCallError::Other(path),
+        })
+        .await
+    } else {
+        process_simple_request(client, req, &action, |tr| match tr {
+            UploadSessionErrorTag::Path { path: reason } => upload_write_error(reason, path),
+            UploadSessionErrorTag::Other => UploadCallError::Other(path),
+        })
+        .await
+    }
+}
+
+async fn read_upload_chunk(file: &mut tokio::fs::File) -> std::io::Result<Vec<u8>> {
+    use tokio::io::AsyncReadExt;
+
+    let mut chunk = vec![0u8; UPLOAD_CHUNK_BYTES as usize];
+    let mut len = 0;
+    while len < chunk.len() {
+        let n = file.read(&mut chunk[len..]).await?;
+        if n == 0 {
+            break;
+        }
+        len += n;
+    }
+    chunk.truncate(len);
+    Ok(chunk)
+}
+
+/// streams the file into request body, stops quietly if the request is already finished
+async fn send_upload_file(
+    mut file: tokio::fs::File,
+    mut sender: hyper::body::Sender,
+) -> std::io::Result<()> {
+    use tokio::io::AsyncReadExt;
+
+    let mut buffer = vec![0u8; UPLOAD_STREAM_BUFFER_BYTES];
+    loop {
+        let n = match file.read(&mut buffer).await {
+            Ok(0) => return Ok(()),
+            Ok(n) => n,
+            Err(e) => {
+                sender.abort();
+                return Err(e);
+            }
+        };
+        if sender
+            .send_data(bytes::Bytes::copy_from_slice(&buffer[..n]))
+            .await
+            .is_err()
+        {
+            return Ok(());
+        }
+    }
+}
+
+/// uploads local file, by upload session if it is big
+async fn int_upload(
+    rth: &RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    local_path: &str,
+    commit: CommitInfo,
+) -> Result<FileMeta, UploadCallError> {
+    let token = { auth_info_holder.read().await.token.clone() };
+    let local_error = |e: std::io::Error| UploadCallError::LocalFile {
+        file: local_path.to_string(),
+        error: e.to_string(),
+    };
+    let size = tokio::fs::metadata(local_path)
+        .await
+        .map_err(local_error)?
+        .len();
+    if size <= UPLOAD_SINGLE_MAX_BYTES {
+        let file = tokio::fs::File::open(local_path)
+            .await
+            .map_err(local_error)?;
+        let (sender, body) = Body::channel();
+        let (res, sent) = futures::join!(
+            int_upload_request(rth, &token, "upload", &commit, body, &commit.path),
+            send_upload_file(file, sender)
+        );
+        sent.map_err(local_error)?;
+        return res;
+    }
+
+    let mut file = tokio::fs::File::open(local_path)
+        .await
+        .map_err(local_error)?;
+    let chunk = read_upload_chunk(&mut file).await.map_err(local_error)?;
+    let mut offset = chunk.len() as u64;
+    let started: UploadSessionStartResult = int_upload_request(
+        rth,
+        &token,
+        "upload_session/start",
+        &UploadSessionStartParams { close: false },
+        Body::from(chunk),
+        &commit.path,
+    )
+    .await?;
+    loop {
+        let chunk = read_upload_chunk(&mut file).await.map_err(local_error)?;
+        // file is shorter than at start, committing it would upload a truncated file
+        if chunk.is_empty() {
+            return Err(UploadCallError::LocalFile {
+                file: local_path.to_string(),
+                error: "file changed during upload".to_string(),
+            });
+        }
+        let cursor = UploadSessionCursor {
+            session_id: started.session_id.clone(),
+            offset,
+        };
+        if offset + chunk.len() as u64 >= size {
+            let path = commit.path.clone();
+            let params = UploadSessionFinishParams { cursor, commit };
+            return int_upload_request(
+                rth,
+                &token,
+                "upload_session/finish",
+                &params,
+                Body::from(chunk),
+                &path,
+            )
+            .await;
+        }
+        offset += chunk.len() as u64;
+        let _: serde_json::Value = int_upload_request(
+            rth,
+            &token,
+            "upload_session/append_v2",
+            &UploadSessionAppendParams {
+                cursor,
+                close: false,
+            },
+            Body::from(chunk),
+            &commit.path,
+        )
+        .await?;
+    }
+}
+
+/// common request for delete_v2 and move_v2
+async fn int_file_op<P>(
+    rth: &RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    endpoint: &str,
+    path: String,
+    params: P,
+) -> Result<MetadataResult, FileOpCallError>
+where
+    P: Serialize,
+{
+    let token = { auth_info_holder.read().await.token.clone() };
+
+    let action = "dropbox.".to_owned() + endpoint;
+    let serialized = serde_json::to_string(&params).unwrap();
+
+    let client = client_http2(rth);
+    let req = request_builder_http2()
+        .method("POST")
+        .header("Authorization", "Bearer ".to_owned() + &token)
+        .header("Content-Type", "application/json")
+        .uri("https://api.dropboxapi.com/2/files/".to_owned() + endpoint)
+        .body(Body::from(serialized))
+        .expect("request builder");
+
+    process_simple_request(client, req, &action, |tr| match tr {
+        FileOpErrorTag::PathLookup {
+            path_lookup: RevisionsLookupErrorTag::NotFound,
+        }
+        | FileOpErrorTag::FromLookup {
+            from_lookup: RevisionsLookupErrorTag::NotFound,
+        } => FileOpCallError::PathNotFound(path),
+        FileOpErrorTag::To {
+            to: WriteErrorTag::Conflict,
+        } => FileOpCallError::Conflict(path),
+        FileOpErrorTag::TooManyWriteOperations => FileOpCallError::TooManyWriteOperations,
+        _ => FileOpCallError::Other(path),
     })
     .await
 }
 
-async fn int_list_folder_continue(
-    rth: &RuntimeHolder,
+async fn int_create_folder(
+    rth: &RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: CreateFolderParams,
+) -> Result<CreateFolderResult, FileOpCallError> {
+    let token = { auth_info_holder.read().await.token.clone() };
+
+    let action = "dropbox.create_folder_v2";
+    let serialized = serde_json::to_string(&params).unwrap();
+    let path = params.path.clone();
+
+    let client = client_http2(rth);
+    let req = request_builder_http2()
+        .method("POST")
+        .header("Authorization", "Bearer ".to_owned() + &token)
+        .header("Content-Type", "application/json")
+        .uri("https://api.dropboxapi.com/2/files/create_folder_v2")
+        .body(Body::from(serialized))
+        .expect("request builder");
+
+    process_simple_request(client, req, action, |tr| match tr {
+        CreateFolderErrorTag::Path {
+            path: WriteErrorTag::Conflict,
+        } => FileOpCallError::Conflict(path),
+        _ => FileOpCallError::Other(path),
+    })
+    .await
+}
+
+/// common request for users/* calls without arguments
+async fn int_users_call<R>(
+    rth: &RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    endpoint: &str,
+) -> Result<R, AccountInfoCallError>
+where
+    R: serde::de::DeserializeOwned,
+{
+    let token = { auth_info_holder.read().await.token.clone() };
+
+    let action = "dropbox.".to_owned() + endpoint;
+
+    let client = client_http2(rth);
+    let req = request_builder_http2()
+        .method("POST")
+        .header("Authorization", "Bearer ".to_owned() + &token)
+        .header("Content-Type", "application/json")
+        .uri("https://api.dropboxapi.com/2/users/".to_owned() + endpoint)
+        .body(Body::from("null"))
+        .expect("request builder");
+
+    process_simple_request(client, req, &action, |tr| match tr {
+        AccountInfoErrorTag::Other => AccountInfoCallError::Other,
+    })
+    .await
+}
+
+async fn int_get_temporary_link(
+    rth: &RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: GetTemporaryLinkParams,
+) -> Result<GetTemporaryLinkCallResult, GetTemporaryLinkCallError> {
+    let token = { auth_info_holder.read().await.token.clone() };
+
+    let action = "dropbox.get_temporary_link";
+    let serialized = serde_json::to_string(&params).unwrap();
+    let path = params.path.clone();
+
+    let client = client_http2(rth);
+    let req = request_builder_http2()
+        .method("POST")
+        .header("Authorization", "Bearer ".to_owned() + &token)
+        .header("Content-Type", "application/json")
+        .uri("https://api.dropboxapi.com/2/files/get_temporary_link")
+        .body(Body::from(serialized))
+        .expect("request builder");
+
+    process_simple_request(client, req, action, |tr| match tr {
+        GetTemporaryLinkErrorTag::Path {
+            path: RevisionsLookupErrorTag::NotFound,
+        } => GetTemporaryLinkCallError::PathNotFound(path),
+        GetTemporaryLinkErrorTag::Path { .. } | GetTemporaryLinkErrorTag::Other => {
+            GetTemporaryLinkCallError::Other(path)
+        }
+        GetTemporaryLinkErrorTag::EmailNotVerified => GetTemporaryLinkCallError::EmailNotVerified,
+        GetTemporaryLinkErrorTag::UnsupportedFile => {
+            GetTemporaryLinkCallError::UnsupportedFile(path)
+        }
+        GetTemporaryLinkErrorTag::NotAllowed => GetTemporaryLinkCallError::NotAllowed,
+    })
+    .await
+}
+
+/// common request for sharing/* calls
+async fn int_shared_link_call<P, R>(
+    rth: &RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    endpoint: &str,
+    params: P,
+) -> Result<R, SharedLinkCallError>
+where
+    P: Serialize,
+    R: serde::de::DeserializeOwned,
+{
+    let token = { auth_info_holder.read().await.token.clone() };
+
+    let action = "dropbox.".to_owned() + endpoint;
+    let serialized = serde_json::to_string(&params).unwrap();
+
+    let client = client_http2(rth);
+    let req = request_builder_http2()
+        .method("POST")
+        .header("Authorization", "Bearer ".to_owned() + &token)
+        .header("Content-Type", "application/json")
+        .uri("https://api.dropboxapi.com/2/sharing/".to_owned() + endpoint)
+        .body(Body::from(serialized))
+        .expect("request builder");
+
+    process_simple_request(client, req, &action, |tr| match tr {
+        SharedLinkErrorTag::Path {
+            path: RevisionsLookupErrorTag::NotFound,
+        } => SharedLinkCallError::PathNotFound(endpoint.to_string()),
+        SharedLinkErrorTag::SharedLinkAlreadyExists => SharedLinkCallError::AlreadyExists,
+        SharedLinkErrorTag::SharedLinkNotFound => SharedLinkCallError::LinkNotFound,
+        SharedLinkErrorTag::EmailNotVerified => SharedLinkCallError::EmailNotVerified,
+        SharedLinkErrorTag::AccessDenied | SharedLinkErrorTag::SharedLinkAccessDenied => {
+            SharedLinkCallError::AccessDenied
+        }
+        SharedLinkErrorTag::SettingsError => SharedLinkCallError::SettingsError,
+        SharedLinkErrorTag::Path { .. } | SharedLinkErrorTag::Other => {
+            SharedLinkCallError::Other(endpoint.to_string())
+        }
+    })
+    .await
+}
+
+async fn int_get_thumbnail_batch(
+    rth: &RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: GetThumbnailBatchParams,
+) -> Result<GetThumbnailBatchCallResult, GetThumbnailBatchCallError> {
+    let token = { auth_info_holder.read().await.token.clone() };
+
+    let action = "dropbox.get_thumbnail_batch";
+    let serialized = serde_json::to_string(&params).unwrap();
+
+    let client = client_http2(rth);
+    let req = request_builder_http2()
+        .method("POST")
+        .header("Authorization", "Bearer ".to_owned() + &token)
+        .header("Content-Type", "application/json")
+        .uri("https://content.dropboxapi.com/2/files/get_thumbnail_batch")
+        .body(Body::from(serialized))
+        .expect("request builder");
+
+    process_simple_request(client, req, action, |tr| match tr {
+        GetThumbnailBatchErrorTag::TooManyFiles => GetThumbnailBatchCallError::TooManyFiles,
+    })
+    .await
+}
+
+async fn int_search(
+    rth: &RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: SearchParams,
+) -> Result<SearchCallResult, SearchCallError> {
+    let token = { auth_info_holder.read().await.token.clone() };
+
+    let action = "dropbox.search";
+    let serialized = serde_json::to_string(&params).unwrap();
+    let path = params
+        .options
+        .as_ref()
+        .and_then(|o| o.path.clone())
+        .unwrap_or_default();
+
+    let client = client_http2(rth);
+    let req = request_builder_http2()
+        .method("POST")
+        .header("Authorization", "Bearer ".to_owned() + &token)
+        .header("Content-Type", "application/json")
+        .uri("https://api.dropboxapi.com/2/files/search_v2")
+        .body(Body::from(serialized))
+        .expect("request builder");
+
+    process_simple_request(client, req, action, |tr| match tr {
+        SearchErrorTag::Path {
+            path: SearchPathErrorTag::NotFound,
+        } => SearchCallError::PathNotFound(path),
+        SearchErrorTag::InvalidArgument => SearchCallError::InvalidArgument,
+    })
+    .await
+}
+
+async fn int_search_continue(
+    rth: &RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: SearchContinueParams,
+) -> Result<SearchCallResult, SearchCallError> {
+    let token = { auth_info_holder.read().await.token.clone() };
+
+    let action = "dropbox.search_continue";
+    let serialized = serde_json::to_string(&params).unwrap();
+
+    let client = client_http2(rth);
+    let req = request_builder_http2()
+        .method("POST")
+        .header("Authorization", "Bearer ".to_owned() + &token)
+        .header("Content-Type", "application/json")
+        .uri("https://api.dropboxapi.com/2/files/search/continue_v2")
+        .body(Body::from(serialized))
+        .expect("request builder");
+
+    process_simple_request(client, req, action, |tr| match tr {
+        SearchErrorTag::Path {
+            path: SearchPathErrorTag::NotFound,
+        } => SearchCallError::PathNotFound("".into()),
+        SearchErrorTag::InvalidArgument => SearchCallError::InvalidArgument,
+    })
+    .await
+}
+
+/// doesn't need token, so there is no auto refresh wrapper
+pub async fn list_folder_longpoll(
+    rth: &RuntimeHolder,
+    params: ListFolderLongpollParams,
+) -> Result<ListFolderLongpollCallResult, ListFolderCallError> {
+    let action = "dropbox.list_folder_longpoll";
+    let serialized = serde_json::to_string(&params).unwrap();
+
+    let client = client_http2(rth);
+    let req = request_builder_http2()
+        .method("POST")
+        .header("Content-Type", "application/json")
+        .uri("https://notify.dropboxapi.com/2/files/list_folder/longpoll")
+        .body(Body::from(serialized))
+        .expect("request builder");
+
+    process_simple_request(client, req, action, |tr| match tr {
+        ListFolderLongpollErrorTag::Reset => ListFolderCallError::Reset,
+    })
+    .await
+}
+
+/// reports token refresh of a call as messages of kind `K`, e.g. `call_messages::Mirror`
+pub struct TokenMessages<K> {
+    call_id: u64,
+    kind: std::marker::PhantomData<fn() -> K>,
+}
+
+pub fn token_messages<K>(call_id: u64) -> TokenMessages<K> {
+    TokenMessages {
+        call_id,
+        kind: std::marker::PhantomData,
+    }
+}
+
+impl<K: call_messages::RefreshTokenMessages> WithRefreshToken<call_messages::Message>
+    for TokenMessages<K>
+{
+    fn refresh_token(&self) -> call_messages::Message {
+        call_messages::Message {
+            call_id: Some(self.call_id),
+            data: K::refresh_token().into(),
+        }
+    }
+
+    fn refresh_token_complete(&self) -> call_messages::Message {
+        call_messages::Message {
+            call_id: Some(self.call_id),
+            data: K::refresh_token_complete().into(),
+        }
+    }
+}
+
+pub async fn list_folder(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: ListFolderParams,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<ListFolderCallResult, ListFolderCallError> {
+    let rth_clone = rth.clone();
+    call_with_token_auto_refresh(
+        rth,
+        auth_info_holder.clone(),
+        messages,
+        message_creator,
+        || async { int_list_folder(&rth_clone, auth_info_holder.clone(), params.clone()).await },
+    )
+    .await
+}
+
+pub async fn list_folder_continue(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: ListFolderContinueParams,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<ListFolderCallResult, ListFolderCallError> {
+    let rth_clone = rth.clone();
+    call_with_token_auto_refresh(
+        rth,
+        auth_info_holder.clone(),
+        messages,
+        message_creator,
+        || async {
+            int_list_folder_continue(&rth_clone, auth_info_holder.clone(), params.clone()).await
+        },
+    )
+    .await
+}
+
+pub async fn get_metadata(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: GetMetadataParams,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<Meta, GetMetadataCallError> {
+    let rth_clone = rth.clone();
+    call_with_token_auto_refresh(
+        rth,
+        auth_info_holder.clone(),
+        messages,
+        message_creator,
+        || async { int_get_metadata(&rth_clone, auth_info_holder.clone(), params.clone()).await },
+    )
+    .await
+}
+
+/// download with DownloadFile progress messages
+pub async fn download_file(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: DownloadFileParams,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<DownloadFileCallResult, DownloadFileCallError> {
+    let rth_clone = rth.clone();
+    call_with_token_auto_refresh(
+        rth,
+        auth_info_holder.clone(),
+        messages.clone(),
+        token_messages::<call_messages::DownloadFile>(call_id),
+        || async {
+            int_download_file(
+                &rth_clone,
+                auth_info_holder.clone(),
+                params.clone(),
+                Some((call_id, messages.clone())),
+            )
+            .await
+        },
+    )
+    .await
+}
+
+/// download without progress messages, for batch transfers
+pub async fn download(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: DownloadFileParams,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<DownloadFileCallResult, DownloadFileCallError> {
+    let rth_clone = rth.clone();
+    call_with_token_auto_refresh(
+        rth,
+        auth_info_holder.clone(),
+        messages,
+        message_creator,
+        || async {
+            int_download_file(&rth_clone, auth_info_holder.clone(), params.clone(), None).await
+        },
+    )
+    .await
+}
+
+/// `range` is value of Range header, passed to the server as is
+pub async fn download_stream(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: DownloadFileParams,
+    range: Option<String>,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<DownloadStream, DownloadFileCallError> {
+    let rth_clone = rth.clone();
+    call_with_token_auto_refresh(
+        rth,
+        auth_info_holder.clone(),
+        messages,
+        message_creator,
+        || async {
+            int_download_stream(
+                &rth_clone,
+                auth_info_holder.clone(),
+                params.clone(),
+                range.clone(),
+            )
+            .await
+        },
+    )
+    .await
+}
+
+pub async fn download_head(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: DownloadFileParams,
+    max_bytes: u64,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<(DownloadFileCallResult, Vec<u8>), DownloadFileCallError> {
+    let rth_clone = rth.clone();
+    call_with_token_auto_refresh(
+        rth,
+        auth_info_holder.clone(),
+        messages,
+        message_creator,
+        || async {
+            int_download_head(
+                &rth_clone,
+                auth_info_holder.clone(),
+                params.clone(),
+                max_bytes,
+            )
+            .await
+        },
+    )
+    .await
+}
+
+pub async fn upload(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    local_path: String,
+    commit: CommitInfo,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<FileMeta, UploadCallError> {
+    let rth_clone = rth.clone();
+    call_with_token_auto_refresh(
+        rth,
+        auth_info_holder.clone(),
+        messages,
+        message_creator,
+        || async {
+            int_upload(
+                &rth_clone,
+                auth_info_holder.clone(),
+                &local_path,
+                commit.clone(),
+            )
+            .await
+        },
+    )
+    .await
+}
+
+pub async fn create_folder(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: CreateFolderParams,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<CreateFolderResult, FileOpCallError> {
+    let rth_clone = rth.clone();
+    call_with_token_auto_refresh(
+        rth,
+        auth_info_holder.clone(),
+        messages,
+        message_creator,
+        || async { int_create_folder(&rth_clone, auth_info_holder.clone(), params.clone()).await },
+    )
+    .await
+}
+
+pub async fn delete(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: DeleteParams,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<MetadataResult, FileOpCallError> {
+    let rth_clone = rth.clone();
+    call_with_token_auto_refresh(
+        rth,
+        auth_info_holder.clone(),
+        messages,
+        message_creator,
+        || async {
+            int_file_op(
+                &rth_clone,
+                auth_info_holder.clone(),
+                "delete_v2",
+                params.path.clone(),
+                params.clone(),
+            )
+            .await
+        },
+    )
+    .await
+}
+
+pub async fn move_file(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: MoveParams,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<MetadataResult, FileOpCallError> {
+    let rth_clone = rth.clone();
+    call_with_token_auto_refresh(
+        rth,
+        auth_info_holder.clone(),
+        messages,
+        message_creator,
+        || async {
+            int_file_op(
+                &rth_clone,
+                auth_info_holder.clone(),
+                "move_v2",
+                params.from_path.clone(),
+                params.clone(),
+            )
+            .await
+        },
+    )
+    .await
+}
+
+pub async fn list_revisions(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: ListRevisionsParams,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<ListRevisionsCallResult, ListRevisionsCallError> {
+    let rth_clone = rth.clone();
+    call_with_token_auto_refresh(
+        rth,
+        auth_info_holder.clone(),
+        messages,
+        message_creator,
+        || async { int_list_revisions(&rth_clone, auth_info_holder.clone(), params.clone()).await },
+    )
+    .await
+}
+
+pub async fn restore(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: RestoreParams,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<FileMeta, RestoreCallError> {
+    let rth_clone = rth.clone();
+    call_with_token_auto_refresh(
+        rth,
+        auth_info_holder.clone(),
+        messages,
+        message_creator,
+        || async { int_restore(&rth_clone, auth_info_holder.clone(), params.clone()).await },
+    )
+    .await
+}
+
+async fn shared_link_call<P, R>(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    endpoint: &str,
+    params: P,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<R, SharedLinkCallError>
+where
+    P: Serialize + Clone,
+    R: serde::de::DeserializeOwned,
+{
+    let rth_clone = rth.clone();
+    call_with_token_auto_refresh(
+        rth,
+        auth_info_holder.clone(),
+        messages,
+        message_creator,
+        || async {
+            int_shared_link_call(
+                &rth_clone,
+                auth_info_holder.clone(),
+                endpoint,
+                params.clone(),
+            )
+            .await
+        },
+    )
+    .await
+}
+
+pub async fn create_shared_link(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: CreateSharedLinkParams,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<SharedLinkMeta, SharedLinkCallError> {
+    shared_link_call(
+        rth,
+        auth_info_holder,
+        "create_shared_link_with_settings",
+        params,
+        messages,
+        message_creator,
+    )
+    .await
+}
+
+pub async fn list_shared_links(
+    rth: RuntimeHolder,
     auth_info_holder: clouds::AuthInfoHolder,
-    params: ListFolderContinueParams,
-    _call_id: u64,
-    _messages: MessagesSender<call_messages::Message>,
-) -> Result<ListFolderCallResult, ListFolderCallError> {
-    let token = { auth_info_holder.read().await.token.clone() };
-
-    let action = "dropbox.list_folder_continue";
-    let serialized = serde_json::to_string(&params).unwrap();
-
-    let client = client_http2(rth);
-    let req = request_builder_http2()
-        .method("POST")
-        .header("Authorization", "Bearer ".to_owned() + &token)
-        .header("Content-Type", "application/json")
-        .uri("https://api.dropboxapi.com/2/files/list_folder/continue")
-        .body(Body::from(serialized))
-        .expect("request builder");
-
-    process_simple_request(client, req, action, |tr| match tr {
-        ListFolderErrorTag::Path {
-            path: ListFolderPathErrorTag::NotFound,
-        } => ListFolderCallError::PathNotFound("".into()),
-    })
+    params: ListSharedLinksParams,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<ListSharedLinksCallResult, SharedLinkCallError> {
+    shared_link_call(
+        rth,
+        auth_info_holder,
+        "list_shared_links",
+        params,
+        messages,
+        message_creator,
+    )
     .await
 }
 
-struct ListFolderTokenMessageCreator {
-    call_id: u64,
+pub async fn modify_shared_link(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: ModifySharedLinkParams,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<SharedLinkMeta, SharedLinkCallError> {
+    shared_link_call(
+        rth,
+        auth_info_holder,
+        "modify_shared_link_settings",
+        params,
+        messages,
+        message_creator,
+    )
+    .await
 }
 
-impl WithRefreshToken<call_messages::Message> for ListFolderTokenMessageCreator {
-    fn refresh_token(&self) -> call_messages::Message {
-        call_messages::Message {
-            call_id: Some(self.call_id),
-            data: call_messages::Data::ListFolder(call_messages::ListFolder::RefreshToken),
-        }
-    }
+pub async fn revoke_shared_link(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: RevokeSharedLinkParams,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<(), SharedLinkCallError> {
+    shared_link_call(
+        rth,
+        auth_info_holder,
+        "revoke_shared_link",
+        params,
+        messages,
+        message_creator,
+    )
+    .await
+}
 
-    fn refresh_token_complete(&self) -> call_messages::Message {
-        call_messages::Message {
-            call_id: Some(self.call_id),
-            data: call_messages::Data::ListFolder(call_messages::ListFolder::RefreshTokenComplete),
-        }
-    }
+async fn users_call<R>(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    endpoint: &str,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<R, AccountInfoCallError>
+where
+    R: serde::de::DeserializeOwned,
+{
+    let rth_clone = rth.clone();
+    call_with_token_auto_refresh(
+        rth,
+        auth_info_holder.clone(),
+        messages,
+        message_creator,
+        || async { int_users_call(&rth_clone, auth_info_holder.clone(), endpoint).await },
+    )
+    .await
 }
 
-struct DownloadFileTokenMessageCreator {
-    call_id: u64,
+pub async fn get_current_account(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<FullAccount, AccountInfoCallError> {
+    users_call(
+        rth,
+        auth_info_holder,
+        "get_current_account",
+        messages,
+        message_creator,
+    )
+    .await
 }
 
-impl WithRefreshToken<call_messages::Message> for DownloadFileTokenMessageCreator {
-    fn refresh_token(&self) -> call_messages::Message {
-        call_messages::Message {
-            call_id: Some(self.call_id),
-            data: call_messages::Data::DownloadFile(call_messages::DownloadFile::RefreshToken),
-        }
-    }
+pub async fn get_space_usage(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<SpaceUsage, AccountInfoCallError> {
+    users_call(
+        rth,
+        auth_info_holder,
+        "get_space_usage",
+        messages,
+        message_creator,
+    )
+    .await
+}
 
-    fn refresh_token_complete(&self) -> call_messages::Message {
-        call_messages::Message {
-            call_id: Some(self.call_id),
-            data: call_messages::Data::DownloadFile(
-                call_messages::DownloadFile::RefreshTokenComplete,
-            ),
-        }
-    }
+pub async fn get_temporary_link(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: GetTemporaryLinkParams,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<GetTemporaryLinkCallResult, GetTemporaryLinkCallError> {
+    let rth_clone = rth.clone();
+    call_with_token_auto_refresh(
+        rth,
+        auth_info_holder.clone(),
+        messages,
+        message_creator,
+        || async {
+            int_get_temporary_link(&rth_clone, auth_info_holder.clone(), params.clone()).await
+        },
+    )
+    .await
 }
 
-pub async fn list_folder(
+pub async fn get_thumbnail_batch(
     rth: RuntimeHolder,
     auth_info_holder: clouds::AuthInfoHolder,
-    params: ListFolderParams,
-    call_id: u64,
+    params: GetThumbnailBatchParams,
     messages: MessagesSender<call_messages::Message>,
-) -> Result<ListFolderCallResult, ListFolderCallError> {
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<GetThumbnailBatchCallResult, GetThumbnailBatchCallError> {
     let rth_clone = rth.clone();
     call_with_token_auto_refresh(
         rth,
         auth_info_holder.clone(),
-        messages.clone(),
-        ListFolderTokenMessageCreator { call_id },
+        messages,
+        message_creator,
         || async {
-            int_list_folder(
-                &rth_clone,
-                auth_info_holder.clone(),
-                params.clone(),
-                call_id,
-                messages.clone(),
-            )
-            .await
+            int_get_thumbnail_batch(&rth_clone, auth_info_holder.clone(), params.clone()).await
         },
     )
     .await
 }
 
-pub async fn list_folder_continue(
+pub async fn search(
     rth: RuntimeHolder,
     auth_info_holder: clouds::AuthInfoHolder,
-    params: ListFolderContinueParams,
-    call_id: u64,
+    params: SearchParams,
     messages: MessagesSender<call_messages::Message>,
-) -> Result<ListFolderCallResult, ListFolderCallError> {
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<SearchCallResult, SearchCallError> {
     let rth_clone = rth.clone();
     call_with_token_auto_refresh(
         rth,
         auth_info_holder.clone(),
-        messages.clone(),
-        ListFolderTokenMessageCreator { call_id },
+        messages,
+        message_creator,
+        || async { int_search(&rth_clone, auth_info_holder.clone(), params.clone()).await },
+    )
+    .await
+}
+
+pub async fn search_continue(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: SearchContinueParams,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl WithRefreshToken<call_messages::Message>,
+) -> Result<SearchCallResult, SearchCallError> {
+    let rth_clone = rth.clone();
+    call_with_token_auto_refresh(
+        rth,
+        auth_info_holder.clone(),
+        messages,
+        message_creator,
         || async {
-            int_list_folder_continue(
-                &rth_clone,
-                auth_info_holder.clone(),
-                params.clone(),
-                call_id,
-                messages.clone(),
-            )
-            .await
+            int_search_continue(&rth_clone, auth_info_holder.clone(), params.clone()).await
         },
     )
     .await
 }
 
+/// DownloadFile progress messages are sent only if `progress` (call_id and messages) is set
 pub async fn int_download_file(
     rth: &RuntimeHolder,
     auth_info_holder: clouds::AuthInfoHolder,
     params: DownloadFileParams,
-    call_id: u64,
-    messages: MessagesSender<call_messages::Message>,
+    progress: Option<(u64, MessagesSender<call_messages::Message>)>,
 ) -> Result<DownloadFileCallResult, DownloadFileCallError> {
     use call_messages::Data::DownloadFile as MsgData;
     use call_messages::DownloadFile as msg;
@@ -715,12 +2679,14 @@ pub async fn int_download_file(
                         Ok(final_res) => {
                             use std::io::prelude::*;
                             let file_size = final_res.size;
-                            let _ = messages.send(Message {
-                                call_id: Some(call_id),
-                                data: MsgData(msg::SizeInfo {
-                                    size: Some(file_size),
-                                }),
-                            });
+                            if let Some((call_id, messages)) = &progress {
+                                let _ = messages.send(Message {
+                                    call_id: Some(*call_id),
+                                    data: MsgData(msg::SizeInfo {
+                                        size: Some(file_size),
+                                    }),
+                                });
+                            }
                             match std::fs::File::create(&params.save_to) {
                                 Ok(mut f) => {
                                     let mut tmp_res: Result<ResultType, ErrorType> = Ok(final_res);
@@ -730,12 +2696,14 @@ pub async fn int_download_file(
                                             Ok(chunk) => {
                                                 // log::info!("chunk size: {}", chunk.len().to_string());
                                                 bytes_received += chunk.len() as u64;
-                                                let _ = messages.send(Message {
-                                                    call_id: Some(call_id),
-                                                    data: MsgData(msg::Progress {
-                                                        value: bytes_received,
-                                                    }),
-                                                });
+                                                if let Some((call_id, messages)) = &progress {
+                                                    let _ = messages.send(Message {
+                                                        call_id: Some(*call_id),
+                                                        data: MsgData(msg::Progress {
+                                                            value: bytes_received,
+                                                        }),
+                                                    });
+                                                }
                                                 if let Err(e) = f.write_all(&chunk) {
                                                     tmp_res = Err(ErrorType::e_file_write(
                                                         action,
@@ -831,31 +2799,215 @@ pub async fn int_download_file(
     res
 }
 
-pub async fn download_file(
-    rth: RuntimeHolder,
+/// downloads first `max_bytes` bytes of file into memory (Range request)
+async fn int_download_head(
+    rth: &RuntimeHolder,
     auth_info_holder: clouds::AuthInfoHolder,
     params: DownloadFileParams,
-    call_id: u64,
-    messages: MessagesSender<call_messages::Message>,
-) -> Result<DownloadFileCallResult, DownloadFileCallError> {
-    let rth_clone = rth.clone();
-    call_with_token_auto_refresh(
-        rth,
-        auth_info_holder.clone(),
-        messages.clone(),
-        DownloadFileTokenMessageCreator { call_id },
-        || async {
-            int_download_file(
-                &rth_clone,
-                auth_info_holder.clone(),
-                params.clone(),
-                call_id,
-                messages.clone(),
-            )
-            .await
-        },
-    )
-    .await
+    max_bytes: u64,
+) -> Result<(DownloadFileCallResult, Vec<u8>), DownloadFileCallError> {
+    use http_body::Body; // for body.data()
+    type ErrorType = DownloadFileCallError;
+
+    let token = { auth_info_holder.read().await.token.clone() };
+
+    let action = "dropbox.download_head";
+    let serialized = serde_json::to_string(&params).unwrap();
+    let file_path = params.path.clone();
+
+    let client = client_http2(rth);
+    let req = request_builder_http2()
+        .method("POST")
+        .header("Authorization", "Bearer ".to_owned() + &token)
+        .header("Dropbox-API-Arg", serialized)
+        .header("Range", format!("bytes=0-{}", max_bytes.max(1) - 1))
+        .uri("https://content.dropboxapi.com/2/files/download")
+        .body(hyper::Body::empty())
+        .expect("request builder");
+
+    match client.request(req).await {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            if response.status().is_success() {
+                let header_name = "dropbox-api-result";
+                let meta = match response.headers().get(header_name) {
+                    Some(header_value) => {
+                        serde_json::from_slice::<DownloadFileCallResult>(header_value.as_bytes())
+                            .map_err(|e| {
+                                ErrorType::Base(clouds::BaseError::e_response_body_deserialization(
+                                    action, e,
+                                ))
+                            })?
+                    }
+                    None => {
+                        return Err(ErrorType::e_header_not_found(
+                            action,
+                            header_name,
+                            "",
+                            &params.path,
+                            status,
+                        ))
+                    }
+                };
+                let mut data: Vec<u8> = vec![];
+                let (_, mut body) = response.into_parts();
+                while let Some(next) = body.data().await {
+                    match next {
+                        Ok(chunk) => {
+                            data.extend_from_slice(&chunk);
+                            // server may ignore Range header
+                            if data.len() as u64 >= max_bytes {
+                                data.truncate(max_bytes as usize);
+                                break;
+                            }
+                        }
+                        Err(e) => {
+                            return Err(ErrorType::e_next_chunk(
+                                action,
+                                e,
+                                &params.path,
+                                data.len() as u64,
+                            ))
+                        }
+                    }
+                }
+                Ok((meta, data))
+            } else {
+                match hyper::body::to_bytes(response.into_body()).await {
+                    Ok(data) => Err(create_error_from_body_data(
+                        &data,
+                        action,
+                        status,
+                        |e| match e {
+                            DownloadFileErrorTag::Path {
+                                path: DownloadFilePathErrorTag::NotFound,
+                            } => ErrorType::NotFound(file_path),
+                        },
+                    )),
+                    Err(e) => Err(ErrorType::Base(clouds::BaseError::e_error_body_aggregate(
+                        action,
+                        e.into(),
+                    ))),
+                }
+            }
+        }
+        Err(e) => Err(ErrorType::Base(clouds::BaseError::e_response_wait(
+            action, &e,
+        ))),
+    }
+}
+
+/// files/download response with unread body, for passing the content through
+pub struct DownloadStream {
+    pub meta: DownloadFileCallResult,
+    /// 200 or 206 (partial content)
+    pub status: u16,
+    pub content_length: Option<u64>,
+    pub content_range: Option<String>,
+    pub body: hyper::Body,
+}
+
+impl std::fmt::Debug for DownloadStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DownloadStream")
+            .field("meta", &self.meta)
+            .field("status", &self.status)
+            .field("content_length", &self.content_length)
+            .field("content_range", &self.content_range)
+            .finish()
+    }
+}
+
+async fn int_download_stream(
+    rth: &RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    params: DownloadFileParams,
+    range: Option<String>,
+) -> Result<DownloadStream, DownloadFileCallError> {
+    type ErrorType = DownloadFileCallError;
+
+    let token = { auth_info_holder.read().await.token.clone() };
+
+    let action = "dropbox.download_stream";
+    let file_path = params.path.clone();
+
+    let client = client_http2(rth);
+    let mut builder = request_builder_http2()
+        .method("POST")
+        .header("Authorization", "Bearer ".to_owned() + &token)
+        .header("Dropbox-API-Arg", api_arg_header(&params))
+        .uri("https://content.dropboxapi.com/2/files/download");
+    if let Some(range) = &range {
+        builder = builder.header("Range", range.as_str());
+    }
+    let req = builder.body(hyper::Body::empty()).expect("request builder");
+
+    match client.request(req).await {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            if response.status().is_success() {
+                let header_name = "dropbox-api-result";
+                let meta = match response.headers().get(header_name) {
+                    Some(header_value) => {
+                        serde_json::from_slice::<DownloadFileCallResult>(header_value.as_bytes())
+                            .map_err(|e| {
+                                ErrorType::Base(clouds::BaseError::e_response_body_deserialization(
+                                    action, e,
+                                ))
+                            })?
+                    }
+                    None => {
+                        return Err(ErrorType::e_header_not_found(
+                            action,
+                            header_name,
+                            "",
+                            &params.path,
+                            status,
+                        ))
+                    }
+                };
+                let header = |name: http::header::HeaderName| {
+                    response
+                        .headers()
+                        .get(name)
+                        .and_then(|value| value.to_str().ok())
+                        .map(|value| value.to_string())
+                };
+                let content_length =
+                    header(http::header::CONTENT_LENGTH).and_then(|value| value.parse().ok());
+                let content_range = header(http::header::CONTENT_RANGE);
+                Ok(DownloadStream {
+                    meta,
+                    status,
+                    content_length,
+                    content_range,
+                    body: response.into_body(),
+                })
+            } else if status == 416 {
+                Err(ErrorType::RangeNotSatisfiable(range.unwrap_or_default()))
+            } else {
+                match hyper::body::to_bytes(response.into_body()).await {
+                    Ok(data) => Err(create_error_from_body_data(
+                        &data,
+                        action,
+                        status,
+                        |e| match e {
+                            DownloadFileErrorTag::Path {
+                                path: DownloadFilePathErrorTag::NotFound,
+                            } => ErrorType::NotFound(file_path),
+                        },
+                    )),
+                    Err(e) => Err(ErrorType::Base(clouds::BaseError::e_error_body_aggregate(
+                        action,
+                        e.into(),
+                    ))),
+                }
+            }
+        }
+        Err(e) => Err(ErrorType::Base(clouds::BaseError::e_response_wait(
+            action, &e,
+        ))),
+    }
 }
 
 async fn call_with_token_auto_refresh<ResType, ErrType, Fn, Fut, T>(
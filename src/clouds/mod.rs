@@ -1,3 +1,6 @@
+pub mod call_messages;
+pub mod dropbox;
+
 #[derive(Copy, Clone)]
 pub enum CloudId {
     Dropbox,
@@ -7,12 +10,29 @@ pub enum CloudId {
 pub struct AuthInfo {
     pub token: String,
     pub refresh_token: String,
+    /// account the tokens were issued for, empty if unknown
+    pub account_id: String,
     pub client_id: String,
     pub secret: String,
     pub redirect_addresses: Vec<String>,
     pub write_mutex: std::sync::Arc<tokio::sync::RwLock<u64>>,
 }
 
+impl AuthInfo {
+    /// tokens and api key of configured storage
+    pub fn from_config(storage_config: &crate::config::StorageConfig) -> Self {
+        Self {
+            client_id: storage_config.api_key.client_id.clone(),
+            secret: storage_config.api_key.secret.clone(),
+            refresh_token: storage_config.tokens.refresh_token.clone(),
+            token: storage_config.tokens.token.clone(),
+            account_id: storage_config.tokens.account_id.clone(),
+            write_mutex: std::sync::Arc::new(tokio::sync::RwLock::new(0)),
+            redirect_addresses: storage_config.redirect_addresses.clone(),
+        }
+    }
+}
+
 pub type AuthInfoHolder = std::sync::Arc<tokio::sync::RwLock<AuthInfo>>;
 
 #[derive(thiserror::Error, Debug, Clone)]
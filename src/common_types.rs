@@ -123,6 +123,12 @@ impl RuntimeHolder {
     }
 }
 
+impl Default for RuntimeHolder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> hyper::rt::Executor<T> for RuntimeHolder
 where
     T: std::future::Future + Send + 'static,
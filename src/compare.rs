@@ -0,0 +1,258 @@
+use crate::storage_models;
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+/// Dropbox content hash block size
+const HASH_BLOCK_BYTES: usize = 4 * 1024 * 1024;
+
+/// Dropbox content hash of local file:
+/// sha256 of concatenated sha256 digests of every 4 MiB block, lowercase hex
+pub fn content_hash(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut block = vec![0u8; HASH_BLOCK_BYTES];
+    let mut overall = Sha256::new();
+    loop {
+        let mut len = 0;
+        while len < block.len() {
+            let n = file.read(&mut block[len..])?;
+            if n == 0 {
+                break;
+            }
+            len += n;
+        }
+        if len == 0 {
+            break;
+        }
+        overall.update(Sha256::digest(&block[..len]));
+        if len < block.len() {
+            break;
+        }
+    }
+    Ok(overall
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+pub struct LocalFile {
+    /// relative to scanned folder, '/' separated
+    pub relative_path: String,
+    pub size: u64,
+    pub modified: Option<DateTime<Utc>>,
+}
+
+/// suffix of temporary files of unfinished downloads
+const DOWNLOAD_TEMP_SUFFIX: &str = ".clouds_viewer_download";
+
+/// temporary file next to `local` for download which replaces it when complete
+pub fn download_temp_path(local: &Path) -> std::path::PathBuf {
+    let mut name = std::ffi::OsString::from(".");
+    name.push(local.file_name().unwrap_or_default());
+    name.push(DOWNLOAD_TEMP_SUFFIX);
+    local.with_file_name(name)
+}
+
+/// all files of local folder (recursively)
+pub fn scan_local(root: &Path) -> std::io::Result<Vec<LocalFile>> {
+    let mut res = vec![];
+    let mut folders = vec![(root.to_path_buf(), "".to_string())];
+    while let Some((folder, relative)) = folders.pop() {
+        for entry in std::fs::read_dir(&folder)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let relative_path = if relative.is_empty() {
+                name
+            } else {
+                format!("{}/{}", relative, name)
+            };
+            if metadata.is_dir() {
+                folders.push((entry.path(), relative_path));
+            } else if metadata.is_file() && !relative_path.ends_with(DOWNLOAD_TEMP_SUFFIX) {
+                res.push(LocalFile {
+                    relative_path,
+                    size: metadata.len(),
+                    modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+                });
+            }
+        }
+    }
+    Ok(res)
+}
+
+/// all nested folders of local folder, relative to it, '/' separated
+pub fn scan_local_folders(root: &Path) -> std::io::Result<Vec<String>> {
+    let mut res = vec![];
+    let mut folders = vec![(root.to_path_buf(), "".to_string())];
+    while let Some((folder, relative)) = folders.pop() {
+        for entry in std::fs::read_dir(&folder)? {
+            let entry = entry?;
+            if !entry.metadata()?.is_dir() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            let relative_path = if relative.is_empty() {
+                name
+            } else {
+                format!("{}/{}", relative, name)
+            };
+            res.push(relative_path.clone());
+            folders.push((entry.path(), relative_path));
+        }
+    }
+    Ok(res)
+}
+
+/// local file exists and has the same size and content hash
+pub fn local_file_matches(path: &Path, size: u64, content_hash: &str) -> bool {
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.is_file() && metadata.len() == size => {
+            matches!(self::content_hash(path), Ok(hash) if hash.eq(content_hash))
+        }
+        _ => false,
+    }
+}
+
+/// local path of file with '/' separated `relative_path`
+pub fn local_file_path(local_root: &str, relative_path: &str) -> std::path::PathBuf {
+    let mut res = std::path::PathBuf::from(local_root);
+    res.extend(relative_path.split('/'));
+    res
+}
+
+/// remote path (without leading '/') of file with `relative_path`
+pub fn remote_file_path(remote_root: &str, relative_path: &str) -> String {
+    if remote_root.is_empty() {
+        relative_path.to_string()
+    } else {
+        format!("{}/{}", remote_root, relative_path)
+    }
+}
+
+/// remote files of recursive listing of `remote_root` keyed by lowercased relative path,
+/// with relative path as displayed
+pub fn remote_files_by_relative_path(
+    remote_root: &str,
+    remote_items: Vec<storage_models::Item>,
+) -> HashMap<String, (String, storage_models::Item)> {
+    let prefix = if remote_root.is_empty() {
+        "/".to_string()
+    } else {
+        format!("/{}/", remote_root.to_lowercase())
+    };
+    remote_items
+        .into_iter()
+        .filter(|item| !item.is_folder && !item.is_deleted)
+        .filter_map(|item| {
+            let relative = item.path_lower.strip_prefix(&prefix)?.to_string();
+            if relative.is_empty() {
+                return None;
+            }
+            // as many trailing components of path_display as the relative path has
+            let components = relative.split('/').count();
+            let mut display: Vec<&str> = item.path_display.rsplit('/').take(components).collect();
+            display.reverse();
+            Some((relative, (display.join("/"), item)))
+        })
+        .collect()
+}
+
+/// categorizes files of local folder and remote listing,
+/// calls `progress` with count of checked local files
+pub fn compare(
+    in_data: storage_models::compare_in_data,
+    progress: impl Fn(u64),
+) -> Result<storage_models::compare_out_data, storage_models::CompareError> {
+    let local_root = Path::new(&in_data.local_path);
+    let local_files =
+        scan_local(local_root).map_err(|e| storage_models::CompareError::Local(e.to_string()))?;
+    let mut remote = remote_files_by_relative_path(&in_data.remote_path, in_data.remote_items);
+
+    let mut entries = Vec::with_capacity(local_files.len() + remote.len());
+    for (i, local) in local_files.into_iter().enumerate() {
+        let remote_item = remote
+            .remove(&local.relative_path.to_lowercase())
+            .map(|(_, item)| item);
+        let status = match &remote_item {
+            None => storage_models::CompareStatus::OnlyLocal,
+            Some(item) if item.size != Some(local.size) => storage_models::CompareStatus::Changed,
+            Some(item) => {
+                let path = local_file_path(&in_data.local_path, &local.relative_path);
+                let hash = content_hash(&path).map_err(|e| {
+                    storage_models::CompareError::Local(format!("{}: {}", path.display(), e))
+                })?;
+                if hash.eq(&item.content_hash) {
+                    storage_models::CompareStatus::Identical
+                } else {
+                    storage_models::CompareStatus::Changed
+                }
+            }
+        };
+        entries.push(storage_models::CompareEntry {
+            relative_path: local.relative_path,
+            status,
+            local_size: Some(local.size),
+            local_modified: local.modified,
+            remote: remote_item,
+        });
+        progress(i as u64 + 1);
+    }
+    entries.extend(remote.into_values().map(|(relative_path, item)| {
+        storage_models::CompareEntry {
+            relative_path,
+            status: storage_models::CompareStatus::OnlyRemote,
+            local_size: None,
+            local_modified: None,
+            remote: Some(item),
+        }
+    }));
+    entries.sort_by_key(|entry| entry.relative_path.to_lowercase());
+    Ok(storage_models::compare_out_data { entries })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// hash of `content` written to a temporary file
+    fn hash_of(content: &[u8]) -> String {
+        let file =
+            std::env::temp_dir().join(format!("clouds_viewer_{:016x}", rand::random::<u64>()));
+        std::fs::write(&file, content).unwrap();
+        let hash = content_hash(&file).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        hash
+    }
+
+    // expected values are of the algorithm published by Dropbox (content-hash page),
+    // computed independently with Python hashlib
+    #[test]
+    fn content_hash_of_empty_file() {
+        assert_eq!(
+            hash_of(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn content_hash_of_one_block() {
+        assert_eq!(
+            hash_of(b"abc"),
+            "4f8b42c22dd3729b519ba6f68d2da7cc5b2d606d05daed5ad5128cc03e6c6358"
+        );
+    }
+
+    #[test]
+    fn content_hash_of_two_blocks() {
+        let mut content = vec![0u8; HASH_BLOCK_BYTES];
+        content.push(b'x');
+        assert_eq!(
+            hash_of(&content),
+            "5d090738d3950a5454a2f643223c44136d6c42662ef3cf8c62511faae3a059b5"
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::path::PathBuf;
@@ -6,6 +7,9 @@ use std::path::PathBuf;
 pub struct TokensConfig {
     pub token: String,
     pub refresh_token: String,
+    /// account the tokens were issued for
+    #[serde(default)]
+    pub account_id: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -14,6 +18,207 @@ pub struct ApiKeyConfig {
     pub secret: String,
 }
 
+/// stats of one run of mirror job
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MirrorRunStats {
+    pub started: DateTime<Utc>,
+    pub finished: DateTime<Utc>,
+    /// whole folder was listed (no cursor or cursor was reset)
+    pub full: bool,
+    pub downloaded: u64,
+    pub downloaded_bytes: u64,
+    pub deleted: u64,
+    /// files which failed to download or delete
+    pub failed: u64,
+    /// error which stopped the run
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// one-way (download) mirror of remote folder
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MirrorJobConfig {
+    pub name: String,
+    /// without leading '/', "" for root of storage
+    pub remote_path: String,
+    pub local_path: String,
+    /// delete local files and folders which are not in the remote folder
+    #[serde(default)]
+    pub delete_extraneous: bool,
+    /// run automatically every N minutes while app is running, 0 = on demand only
+    #[serde(default)]
+    pub interval_minutes: u64,
+    /// list_folder cursor of last successful run
+    #[serde(default)]
+    pub cursor: String,
+    /// last runs, newest last
+    #[serde(default)]
+    pub runs: Vec<MirrorRunStats>,
+}
+
+/// stats of one run of two-way sync job
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SyncRunStats {
+    pub started: DateTime<Utc>,
+    pub finished: DateTime<Utc>,
+    /// whole remote folder was listed (no cursor or cursor was reset)
+    pub full: bool,
+    pub uploaded: u64,
+    pub downloaded: u64,
+    pub deleted_local: u64,
+    pub deleted_remote: u64,
+    /// files changed on both sides, local version saved as "conflicted copy"
+    pub conflicts: u64,
+    pub failed: u64,
+    /// error which stopped the run
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// two-way sync of remote folder and local folder
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SyncJobConfig {
+    pub name: String,
+    /// without leading '/', "" for root of storage
+    pub remote_path: String,
+    pub local_path: String,
+    /// run automatically every N minutes while app is running, 0 = on demand only
+    #[serde(default)]
+    pub interval_minutes: u64,
+    /// run fails instead of deleting more remote files than this
+    #[serde(default = "default_sync_max_remote_deletes")]
+    pub max_remote_deletes: u64,
+    /// list_folder cursor of last successful run
+    #[serde(default)]
+    pub cursor: String,
+    /// last runs, newest last
+    #[serde(default)]
+    pub runs: Vec<SyncRunStats>,
+}
+
+/// stats of one run of backup set
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BackupRunStats {
+    pub started: DateTime<Utc>,
+    pub finished: DateTime<Utc>,
+    pub uploaded: u64,
+    pub uploaded_bytes: u64,
+    /// files with the same content hash in target folder
+    pub unchanged: u64,
+    /// excluded or unreadable files and folders
+    pub skipped: u64,
+    /// files which failed to upload
+    pub failed: u64,
+    /// manifest json of the run
+    #[serde(default)]
+    pub manifest: Option<PathBuf>,
+    /// error which stopped the run
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// upload of local folder to remote folder, replaced files stay in revisions history
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BackupSetConfig {
+    pub name: String,
+    pub local_path: String,
+    /// without leading '/', "" for root of storage
+    pub remote_path: String,
+    /// glob patterns ('*', '?', '**') of files to back up, empty = all files.
+    /// Patterns without '/' are matched against file name, others against relative path
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// glob patterns of files and folders to skip
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// run automatically every N minutes while app is running, 0 = on demand only
+    #[serde(default)]
+    pub interval_minutes: u64,
+    /// last runs, newest last
+    #[serde(default)]
+    pub runs: Vec<BackupRunStats>,
+}
+
+/// WebDAV server exposing the storage on 127.0.0.1
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WebDavConfig {
+    /// started with the app
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_webdav_port")]
+    pub port: u16,
+    /// basic auth, server is not started without it unless allow_anonymous
+    #[serde(default)]
+    pub user: String,
+    #[serde(default)]
+    pub password: String,
+    /// serve without authentication when user is empty
+    #[serde(default)]
+    pub allow_anonymous: bool,
+}
+
+fn default_webdav_port() -> u16 {
+    8090
+}
+
+impl Default for WebDavConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_webdav_port(),
+            user: "".to_string(),
+            password: "".to_string(),
+            allow_anonymous: false,
+        }
+    }
+}
+
+/// localhost proxy of file downloads for media players, started on first use
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StreamProxyConfig {
+    #[serde(default = "default_stream_proxy_port")]
+    pub port: u16,
+}
+
+fn default_stream_proxy_port() -> u16 {
+    8091
+}
+
+impl Default for StreamProxyConfig {
+    fn default() -> Self {
+        Self {
+            port: default_stream_proxy_port(),
+        }
+    }
+}
+
+/// localhost api for automation, requests carry `Authorization: Bearer <token>`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ControlConfig {
+    /// started with the app
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_control_port")]
+    pub port: u16,
+    /// generated on start if empty
+    #[serde(default)]
+    pub token: String,
+}
+
+fn default_control_port() -> u16 {
+    8092
+}
+
+impl Default for ControlConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_control_port(),
+            token: "".to_string(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct StorageConfig {
     pub caption: String,
@@ -25,11 +230,103 @@ pub struct StorageConfig {
     pub redirect_addresses: Vec<String>,
     #[serde(default)]
     pub download_to: String,
+    /// opened files changed locally are uploaded back without asking
+    #[serde(default)]
+    pub auto_upload_edited: bool,
+    #[serde(default)]
+    pub mirrors: Vec<MirrorJobConfig>,
+    #[serde(default)]
+    pub syncs: Vec<SyncJobConfig>,
+    #[serde(default)]
+    pub backups: Vec<BackupSetConfig>,
+    #[serde(default)]
+    pub webdav: WebDavConfig,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum SizeUnits {
+    #[serde(rename = "bytes")]
+    Bytes,
+    /// KiB, MiB, ... (1024 based)
+    #[serde(rename = "binary")]
+    Binary,
+    /// kB, MB, ... (1000 based)
+    #[serde(rename = "decimal")]
+    Decimal,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum FolderView {
+    #[serde(rename = "list")]
+    List,
+    #[serde(rename = "icons")]
+    Icons,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ViewConfig {
+    #[serde(default = "default_size_units")]
+    pub size_units: SizeUnits,
+    #[serde(default = "default_true")]
+    pub local_time: bool,
+    #[serde(default)]
+    pub relative_time: bool,
+    #[serde(default = "default_folder_view")]
+    pub folder_view: FolderView,
+    /// limit for on-disk thumbnails cache of every storage
+    #[serde(default = "default_thumbnails_cache_mbytes")]
+    pub thumbnails_cache_mbytes: u64,
+    /// max bytes fetched for quick-look preview
+    #[serde(default = "default_preview_max_kbytes")]
+    pub preview_max_kbytes: u64,
+}
+
+fn default_sync_max_remote_deletes() -> u64 {
+    100
+}
+
+fn default_preview_max_kbytes() -> u64 {
+    1024
+}
+
+fn default_folder_view() -> FolderView {
+    FolderView::List
+}
+
+fn default_thumbnails_cache_mbytes() -> u64 {
+    100
+}
+
+fn default_size_units() -> SizeUnits {
+    SizeUnits::Binary
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for ViewConfig {
+    fn default() -> Self {
+        Self {
+            size_units: default_size_units(),
+            local_time: true,
+            relative_time: false,
+            folder_view: default_folder_view(),
+            thumbnails_cache_mbytes: default_thumbnails_cache_mbytes(),
+            preview_max_kbytes: default_preview_max_kbytes(),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AppConfig {
     pub storages: Vec<StorageConfig>,
+    #[serde(default)]
+    pub view: ViewConfig,
+    #[serde(default)]
+    pub stream_proxy: StreamProxyConfig,
+    #[serde(default)]
+    pub control: ControlConfig,
 }
 
 impl AppConfig {
@@ -75,18 +372,25 @@ pub fn get_config_file_path(force_near_binary: bool) -> Result<(PathBuf, bool),
     }
 }
 
+/// folder for caches, placed near config file
+pub fn get_cache_dir() -> Result<PathBuf, Box<dyn Error>> {
+    let (mut path, _) = get_config_file_path(false)?;
+    path.set_extension("cache");
+    Ok(path)
+}
+
 pub fn load_config() -> Result<AppConfig, Box<dyn Error>> {
     let (file_path, checked) = get_config_file_path(false)?;
-    let file_path_str = file_path.to_str().unwrap().to_string();
+    let file_path_str = file_path.to_string_lossy().to_string();
     if checked || (file_path.exists() && file_path.is_file()) {
         log::debug!("config file: '{}'", file_path_str);
-        let data = std::fs::read(file_path)
-            .expect(&("Unable to read config file: ".to_owned() + &file_path_str));
+        let data = std::fs::read(&file_path)
+            .map_err(|e| format!("unable to read config file '{}': {}", file_path_str, e))?;
         let res: AppConfig = serde_json::from_slice(&data)
-            .expect(&("Unable to parse config file: ".to_owned() + &file_path_str));
+            .map_err(|e| format!("unable to parse config file '{}': {}", file_path_str, e))?;
         Ok(res)
     } else {
-        panic!("config file '{}' does not exists", &file_path_str)
+        Err(format!("config file '{}' does not exist", file_path_str).into())
     }
 }
 
@@ -97,6 +401,34 @@ pub fn save_config(app_config: &AppConfig) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// writes refreshed tokens of the storage into config file as it is now on disk,
+/// other settings possibly changed meanwhile by running app are kept
+pub fn save_storage_tokens(
+    storage_id: &str,
+    token: &str,
+    refresh_token: &str,
+) -> Result<(), Box<dyn Error>> {
+    let (file_path, _) = get_config_file_path(false)?;
+    let mut config: serde_json::Value = serde_json::from_slice(&std::fs::read(&file_path)?)?;
+    let tokens = config
+        .get_mut("storages")
+        .and_then(|storages| storages.as_array_mut())
+        .and_then(|storages| {
+            storages
+                .iter_mut()
+                .find(|storage| storage.get("id").and_then(|id| id.as_str()) == Some(storage_id))
+        })
+        .and_then(|storage| storage.get_mut("tokens"))
+        .and_then(|tokens| tokens.as_object_mut())
+        .ok_or_else(|| format!("storage '{}' not found in config file", storage_id))?;
+    tokens.insert("token".to_string(), token.into());
+    tokens.insert("refresh_token".to_string(), refresh_token.into());
+    let tmp = file_path.with_extension("config.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(&config)?)?;
+    std::fs::rename(&tmp, &file_path)?;
+    Ok(())
+}
+
 /*
 pub fn create_default_config() -> Result<(), Box<dyn Error>> {
     save_config(&AppConfig {
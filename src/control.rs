@@ -0,0 +1,196 @@
+//! Control api on 127.0.0.1 for automation: POST of `call_messages::ControlRequest` as JSON,
+//! with id of the storage, e.g. `{"storage": "id1", "method": "navigate", "path": "folder"}`.
+//! Requests are forwarded into messages of the storage and answered by the app on next frame,
+//! which is requested so a minimized window answers too. `call_messages::ControlResponse` is
+//! returned as JSON.
+use crate::clouds::call_messages;
+use crate::common_types::*;
+use crate::error::BindError;
+use crate::webdav::constant_time_eq;
+use eframe::epi;
+use hyper::{Body, Request, Response, StatusCode};
+use serde::Deserialize;
+
+const REPLY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// storage driven by the api
+#[derive(Clone)]
+pub struct Target {
+    pub id: String,
+    pub caption: String,
+    pub messages: MessagesSender<call_messages::Message>,
+}
+
+#[derive(Deserialize)]
+struct ControlCall {
+    #[serde(default)]
+    storage: String,
+    #[serde(flatten)]
+    request: call_messages::ControlRequest,
+}
+
+#[derive(Clone)]
+struct ServerData {
+    /// expected Authorization header
+    authorization: String,
+    targets: std::sync::Arc<Vec<Target>>,
+    repaint_signal: std::sync::Arc<dyn epi::backend::RepaintSignal>,
+}
+
+fn json_response(status: StatusCode, rsp: &call_messages::ControlResponse) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(rsp).unwrap_or_default()))
+        .unwrap()
+}
+
+fn error_response(status: StatusCode, message: String) -> Response<Body> {
+    log::error!("control: {}: {}", status, &message);
+    json_response(status, &call_messages::ControlResponse::Error { message })
+}
+
+impl ServerData {
+    fn is_authorized(&self, req: &Request<Body>) -> bool {
+        req.headers()
+            .get(hyper::header::AUTHORIZATION)
+            .map(|value| constant_time_eq(value.as_bytes(), self.authorization.as_bytes()))
+            .unwrap_or(false)
+    }
+
+    /// forwards request to the storage and waits for reply
+    async fn forward(&self, call: ControlCall) -> Response<Body> {
+        let target = match self
+            .targets
+            .iter()
+            .find(|target| target.id.eq(&call.storage))
+        {
+            Some(target) => target,
+            None => {
+                return error_response(
+                    StatusCode::NOT_FOUND,
+                    format!("unknown storage '{}'", call.storage),
+                )
+            }
+        };
+        let (reply, receiver) = tokio::sync::oneshot::channel();
+        if target
+            .messages
+            .send(call_messages::Message {
+                call_id: None,
+                data: call_messages::Data::Control(call_messages::Control::Request {
+                    request: call.request,
+                    reply,
+                }),
+            })
+            .is_err()
+        {
+            return error_response(StatusCode::SERVICE_UNAVAILABLE, "app is closed".to_string());
+        }
+        self.repaint_signal.request_repaint();
+        match tokio::time::timeout(REPLY_TIMEOUT, receiver).await {
+            Ok(Ok(rsp @ call_messages::ControlResponse::Error { .. })) => {
+                json_response(StatusCode::BAD_REQUEST, &rsp)
+            }
+            Ok(Ok(rsp)) => json_response(StatusCode::OK, &rsp),
+            Ok(Err(_)) => {
+                error_response(StatusCode::SERVICE_UNAVAILABLE, "app is closed".to_string())
+            }
+            Err(_) => error_response(StatusCode::GATEWAY_TIMEOUT, "no reply from app".to_string()),
+        }
+    }
+
+    async fn handle(self, req: Request<Body>) -> Response<Body> {
+        if !self.is_authorized(&req) {
+            return error_response(StatusCode::UNAUTHORIZED, "invalid token".to_string());
+        }
+        if req.method() != hyper::Method::POST {
+            return error_response(
+                StatusCode::METHOD_NOT_ALLOWED,
+                format!("method {} is not allowed", req.method()),
+            );
+        }
+        let body = match hyper::body::to_bytes(req.into_body()).await {
+            Ok(body) => body,
+            Err(e) => return error_response(StatusCode::BAD_REQUEST, e.to_string()),
+        };
+        let call = match serde_json::from_slice::<ControlCall>(&body) {
+            Ok(call) => call,
+            Err(e) => return error_response(StatusCode::BAD_REQUEST, e.to_string()),
+        };
+        log::debug!("control: {:?} for '{}'", &call.request, &call.storage);
+        match call.request {
+            call_messages::ControlRequest::Storages => json_response(
+                StatusCode::OK,
+                &call_messages::ControlResponse::Storages {
+                    storages: self
+                        .targets
+                        .iter()
+                        .map(|target| call_messages::ControlStorage {
+                            id: target.id.clone(),
+                            caption: target.caption.clone(),
+                        })
+                        .collect(),
+                },
+            ),
+            _ => self.forward(call).await,
+        }
+    }
+}
+
+impl tower::Service<Request<Body>> for ServerData {
+    type Response = Response<Body>;
+    type Error = std::convert::Infallible;
+    type Future = std::pin::Pin<
+        Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>,
+    >;
+
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let data = self.clone();
+        Box::pin(async move { Ok(data.handle(req).await) })
+    }
+}
+
+/// binds to 127.0.0.1:port and serves in background until async runtime is shut down
+pub fn start(
+    rth: RuntimeHolder,
+    port: u16,
+    token: &str,
+    targets: Vec<Target>,
+    repaint_signal: std::sync::Arc<dyn epi::backend::RepaintSignal>,
+) -> Result<std::net::SocketAddr, BindError> {
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+    let bind_error = |text: String| BindError::Error { addr, text };
+    // listener is registered in the runtime
+    let builder = rth
+        .block_on(async { hyper::Server::try_bind(&addr) })
+        .map_err(|e| bind_error(e.to_string()))?
+        .map_err(|e| bind_error(e.to_string()))?;
+
+    let data = ServerData {
+        authorization: "Bearer ".to_owned() + token,
+        targets: std::sync::Arc::new(targets),
+        repaint_signal,
+    };
+    let make_service = hyper::service::make_service_fn(move |_conn| {
+        let svc = data.clone();
+        async move { Ok::<_, std::convert::Infallible>(svc) }
+    });
+    let server = builder.executor(rth.clone()).serve(make_service);
+    rth.spawn(async move {
+        if let Err(e) = server.await {
+            log::error!("control server failed: {}", e);
+        }
+    })
+    .map_err(|e| bind_error(e.to_string()))?;
+
+    log::debug!("control server running at {}", &addr);
+    Ok(addr)
+}
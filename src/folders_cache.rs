@@ -0,0 +1,110 @@
+use crate::storage_models;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// folders above the limit are dropped, least recently saved first
+const MAX_FOLDERS: usize = 1000;
+/// listings not refreshed for this long are dropped
+const MAX_AGE_DAYS: i64 = 30;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CachedFolder {
+    /// list_folder cursor, used to fetch deltas via list_folder/continue
+    pub cursor: String,
+    pub saved: DateTime<Utc>,
+    pub items: Vec<storage_models::Item>,
+}
+
+/// folder listings of one storage, keyed by lowercased path, persisted as single json file
+#[derive(Clone, Debug)]
+pub struct FoldersCache {
+    file: PathBuf,
+    folders: Arc<Mutex<HashMap<String, CachedFolder>>>,
+}
+
+impl FoldersCache {
+    pub fn load(file: PathBuf) -> Self {
+        let mut folders = match std::fs::read_to_string(&file) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+                log::error!("folders cache '{}' parse error: {}", file.display(), e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        evict(&mut folders);
+        Self {
+            file,
+            folders: Arc::new(Mutex::new(folders)),
+        }
+    }
+
+    pub fn get(&self, path: &str) -> Option<CachedFolder> {
+        self.folders
+            .lock()
+            .unwrap()
+            .get(&path.to_lowercase())
+            .cloned()
+    }
+
+    /// file is replaced under the lock, so concurrent puts can't interleave
+    pub async fn put(&self, path: &str, folder: CachedFolder) {
+        let cache = self.clone();
+        let path = path.to_lowercase();
+        let res = tokio::task::spawn_blocking(move || {
+            let mut folders = cache.folders.lock().unwrap();
+            folders.insert(path, folder);
+            evict(&mut folders);
+            cache.write(&folders)
+        })
+        .await;
+        match res {
+            Ok(Err(e)) => log::error!("folders cache write error: {}", e),
+            Err(e) => log::error!("folders cache write error: {}", e),
+            Ok(Ok(())) => {}
+        }
+    }
+
+    /// writes temporary file and renames it over the cache file
+    fn write(&self, folders: &HashMap<String, CachedFolder>) -> std::io::Result<()> {
+        if let Some(dir) = self.file.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = self.file.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec(folders)?)?;
+        std::fs::rename(&tmp, &self.file)
+    }
+}
+
+/// drops too old listings and least recently saved ones above the limit
+fn evict(folders: &mut HashMap<String, CachedFolder>) {
+    let oldest = Utc::now() - chrono::Duration::days(MAX_AGE_DAYS);
+    folders.retain(|_, folder| folder.saved >= oldest);
+    if folders.len() > MAX_FOLDERS {
+        let mut saved: Vec<DateTime<Utc>> = folders.values().map(|folder| folder.saved).collect();
+        saved.sort_unstable();
+        let min_saved = saved[saved.len() - MAX_FOLDERS];
+        folders.retain(|_, folder| folder.saved >= min_saved);
+    }
+}
+
+/// applies list_folder/continue entries to cached items of the folder
+pub fn apply_deltas(items: &mut Vec<storage_models::Item>, deltas: Vec<storage_models::ItemDelta>) {
+    for delta in deltas {
+        match delta {
+            storage_models::ItemDelta::Deleted { name } => {
+                let name = name.to_lowercase();
+                items.retain(|item| item.name.to_lowercase() != name);
+            }
+            storage_models::ItemDelta::Changed(item) => {
+                let name = item.name.to_lowercase();
+                match items.iter_mut().find(|v| v.name.to_lowercase() == name) {
+                    Some(existing) => *existing = *item,
+                    None => items.push(*item),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use crate::config;
+use chrono::{DateTime, Local, Utc};
+
+pub fn format_size(size: u64, units: config::SizeUnits) -> String {
+    let (base, names): (f64, [&str; 6]) = match units {
+        config::SizeUnits::Bytes => return size.to_string(),
+        config::SizeUnits::Binary => (1024.0, ["B", "KiB", "MiB", "GiB", "TiB", "PiB"]),
+        config::SizeUnits::Decimal => (1000.0, ["B", "kB", "MB", "GB", "TB", "PB"]),
+    };
+    let mut value = size as f64;
+    let mut idx = 0;
+    while value >= base && idx < names.len() - 1 {
+        value /= base;
+        idx += 1;
+    }
+    if idx == 0 {
+        format!("{} {}", size, names[0])
+    } else {
+        format!("{:.1} {}", value, names[idx])
+    }
+}
+
+fn format_relative(time: &DateTime<Utc>, now: &DateTime<Utc>) -> String {
+    let secs = now.signed_duration_since(*time).num_seconds();
+    let (value, future) = if secs < 0 {
+        (-secs, true)
+    } else {
+        (secs, false)
+    };
+    let text = match value {
+        0..=59 => return "just now".to_string(),
+        60..=3599 => plural(value / 60, "minute"),
+        3600..=86399 => plural(value / 3600, "hour"),
+        86400..=2591999 => plural(value / 86400, "day"),
+        2592000..=31535999 => plural(value / 2592000, "month"),
+        _ => plural(value / 31536000, "year"),
+    };
+    if future {
+        format!("in {}", text)
+    } else {
+        format!("{} ago", text)
+    }
+}
+
+fn plural(value: i64, name: &str) -> String {
+    if value == 1 {
+        format!("1 {}", name)
+    } else {
+        format!("{} {}s", value, name)
+    }
+}
+
+pub fn format_time(time: &DateTime<Utc>, view: &config::ViewConfig) -> String {
+    if view.relative_time {
+        format_relative(time, &Utc::now())
+    } else if view.local_time {
+        time.with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    } else {
+        time.format("%Y-%m-%d %H:%M:%S UTC").to_string()
+    }
+}
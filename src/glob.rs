@@ -0,0 +1,62 @@
+//! glob patterns of backup sets and of the items filter
+
+/// case insensitive regex of glob: "**" matches across folders, "*" and "?" within one name
+pub fn glob_to_regex(glob: &str) -> String {
+    let mut res = "(?i)^".to_string();
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    // "a/**/b" matches "a/b" too
+                    chars.next();
+                    res.push_str("(.*/)?");
+                } else {
+                    res.push_str(".*");
+                }
+            }
+            '*' => res.push_str("[^/]*"),
+            '?' => res.push_str("[^/]"),
+            c => res.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    res.push('$');
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_match(glob: &str, path: &str) -> bool {
+        regex::Regex::new(&glob_to_regex(glob))
+            .unwrap()
+            .is_match(path)
+    }
+
+    #[test]
+    fn star_matches_within_one_name() {
+        assert!(is_match("*.jpg", "a.JPG"));
+        assert!(!is_match("*.jpg", "dir/a.jpg"));
+        assert!(is_match("dir/*.jpg", "dir/a.jpg"));
+        assert!(is_match("a?c", "abc"));
+        assert!(!is_match("a?c", "a/c"));
+    }
+
+    #[test]
+    fn double_star_matches_across_folders() {
+        assert!(is_match("**/*.jpg", "a.jpg"));
+        assert!(is_match("**/*.jpg", "dir/sub/a.jpg"));
+        assert!(is_match("dir/**/b", "dir/b"));
+        assert!(is_match("dir/**/b", "dir/x/y/b"));
+        assert!(is_match("dir/**", "dir/x/y"));
+        assert!(!is_match("dir/**/b", "other/b"));
+    }
+
+    #[test]
+    fn regex_chars_are_literal() {
+        assert!(is_match("a+b (1).txt", "a+b (1).txt"));
+        assert!(!is_match("a.txt", "abtxt"));
+    }
+}
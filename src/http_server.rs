@@ -1,8 +1,8 @@
-use crate::call_messages;
 use crate::clouds;
+use crate::clouds::call_messages;
+use crate::clouds::dropbox;
 use crate::clouds::AuthInfoHolder;
 use crate::common_types::*;
-use crate::dropbox;
 use crate::error::BindError;
 use crate::storage_models;
 use hyper::{Body, Request, Response};
@@ -81,6 +81,7 @@ async fn handle_request(
                                     let auth_info = &mut *auth_info_holder.write().await;
                                     auth_info.refresh_token = res.refresh_token;
                                     auth_info.token = res.access_token;
+                                    auth_info.account_id = res.account_id;
                                 }
                                 let _ = messages.send(call_messages::Message {
                                     call_id: Some(call_id),
@@ -252,7 +253,8 @@ pub async fn run(
             };
             match host_port_res {
                 Ok(host_port) => {
-                    let res: Vec<(String, Result<SocketAddr, BindError>)> = match host_port.to_socket_addrs()
+                    let res: Vec<(String, Result<SocketAddr, BindError>)> = match host_port
+                        .to_socket_addrs()
                     {
                         Ok(addrs) => addrs.map(|addr| (redirect_url.clone(), Ok(addr))).collect(),
                         Err(e) => std::iter::once((
@@ -1,7 +1,10 @@
+use crate::clouds;
+use crate::common_types::RuntimeHolder;
 use crate::config;
+use crate::folders_cache;
 use crate::storage_instance;
 use crate::storage_models;
-use crate::{clouds, RuntimeHolder};
+use crate::thumbnails;
 
 #[derive(thiserror::Error, Debug, Clone)]
 pub enum InitError {
@@ -81,7 +84,7 @@ fn init_log(module_name: String) -> Result<log4rs::Handle, Box<dyn std::error::E
     Ok(res)
 }
 
-pub fn init() -> Result<crate::AppState, Box<dyn std::error::Error>> {
+pub fn init() -> Result<crate::app::AppState, Box<dyn std::error::Error>> {
     if std::env::var("RUST_LOG").is_err() {
         if std::env::var("CARGO_MANIFEST_DIR").is_ok() {
             std::env::set_var("RUST_LOG", "clouds_viewer=debug");
@@ -103,6 +106,7 @@ pub fn init() -> Result<crate::AppState, Box<dyn std::error::Error>> {
 
     let rth = RuntimeHolder::new();
     let app_config = config::load_config()?;
+    let cache_dir = config::get_cache_dir()?;
     let mut storages: Vec<storage_instance::StorageInstance> = vec![];
     for config in &app_config.storages {
         let do_auth = !config.api_key.client_id.is_empty()
@@ -120,14 +124,16 @@ pub fn init() -> Result<crate::AppState, Box<dyn std::error::Error>> {
                 auth_needed: do_auth,
                 ..storage_instance::StorageVisualState::default()
             },
-            auth_info_holder: std::sync::Arc::new(tokio::sync::RwLock::new(clouds::AuthInfo {
-                client_id: config.api_key.client_id.clone(),
-                secret: config.api_key.secret.clone(),
-                refresh_token: config.tokens.refresh_token.clone(),
-                token: config.tokens.token.clone(),
-                write_mutex: std::sync::Arc::new(tokio::sync::RwLock::new(0)),
-                redirect_addresses: config.redirect_addresses.clone(),
-            })),
+            auth_info_holder: std::sync::Arc::new(tokio::sync::RwLock::new(
+                clouds::AuthInfo::from_config(config),
+            )),
+            thumbnail_cache: thumbnails::ThumbnailCache {
+                dir: cache_dir.join("thumbnails").join(&config.id),
+                max_bytes: app_config.view.thumbnails_cache_mbytes * 1024 * 1024,
+            },
+            folders_cache: folders_cache::FoldersCache::load(
+                cache_dir.join("folders").join(config.id.clone() + ".json"),
+            ),
             save_to_path: {
                 let mut path = config.download_to.clone();
                 if path.is_empty() {
@@ -146,13 +152,41 @@ pub fn init() -> Result<crate::AppState, Box<dyn std::error::Error>> {
                 log::debug!("download path for {}: {}", &config.caption, &path);
                 path
             },
+            mirrors: config
+                .mirrors
+                .iter()
+                .cloned()
+                .map(storage_instance::MirrorJobState::new)
+                .collect(),
+            syncs: config
+                .syncs
+                .iter()
+                .cloned()
+                .map(storage_instance::SyncJobState::new)
+                .collect(),
+            sync_state_dir: cache_dir.join("sync").join(&config.id),
+            backups: config
+                .backups
+                .iter()
+                .cloned()
+                .map(storage_instance::BackupJobState::new)
+                .collect(),
+            backup_dir: cache_dir.join("backup").join(&config.id),
+            edited_files: vec![],
+            edited_files_checked: std::time::Instant::now(),
+            auto_upload_edited: config.auto_upload_edited,
+            webdav: config.webdav.clone(),
+            webdav_call_id: None,
+            webdav_dir: cache_dir.join("webdav").join(&config.id),
+            config_changed: false,
         };
         storages.push(storage);
     }
 
-    Ok(crate::AppState {
+    Ok(crate::app::AppState {
         config: app_config,
         rth,
         storages,
+        stream_proxy: None,
     })
 }
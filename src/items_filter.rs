@@ -0,0 +1,107 @@
+use crate::glob;
+use crate::storage_models;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FilterMode {
+    Substring,
+    Glob,
+    Regex,
+}
+
+impl FilterMode {
+    pub fn caption(&self) -> &'static str {
+        match self {
+            Self::Substring => "substring",
+            Self::Glob => "glob",
+            Self::Regex => "regex",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FilterKind {
+    All,
+    OnlyFiles,
+    OnlyFolders,
+}
+
+enum Matcher {
+    Everything,
+    Substring(String),
+    Regex(regex::Regex),
+}
+
+pub struct ItemsFilter {
+    pub text: String,
+    pub mode: FilterMode,
+    pub kind: FilterKind,
+    matcher: Matcher,
+    prepared_for: Option<(String, FilterMode)>,
+    pub error: Option<String>,
+}
+
+impl Default for ItemsFilter {
+    fn default() -> Self {
+        Self {
+            text: "".into(),
+            mode: FilterMode::Substring,
+            kind: FilterKind::All,
+            matcher: Matcher::Everything,
+            prepared_for: None,
+            error: None,
+        }
+    }
+}
+
+impl ItemsFilter {
+    /// rebuilds matcher if text or mode were changed since last call
+    pub fn prepare(&mut self) {
+        let key = (self.text.clone(), self.mode);
+        if self.prepared_for.as_ref() == Some(&key) {
+            return;
+        }
+        self.error = None;
+        self.matcher = if self.text.is_empty() {
+            Matcher::Everything
+        } else {
+            match self.mode {
+                FilterMode::Substring => Matcher::Substring(self.text.to_lowercase()),
+                FilterMode::Glob | FilterMode::Regex => {
+                    let pattern = match self.mode {
+                        FilterMode::Glob => glob::glob_to_regex(&self.text),
+                        _ => self.text.clone(),
+                    };
+                    match regex::RegexBuilder::new(&pattern)
+                        .case_insensitive(true)
+                        .build()
+                    {
+                        Ok(re) => Matcher::Regex(re),
+                        Err(e) => {
+                            self.error = Some(e.to_string());
+                            Matcher::Everything
+                        }
+                    }
+                }
+            }
+        };
+        self.prepared_for = Some(key);
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.text.is_empty() || self.kind != FilterKind::All
+    }
+
+    pub fn matches(&self, item: &storage_models::Item) -> bool {
+        let kind_ok = match self.kind {
+            FilterKind::All => true,
+            FilterKind::OnlyFiles => !item.is_folder,
+            FilterKind::OnlyFolders => item.is_folder,
+        };
+        kind_ok
+            && match &self.matcher {
+                Matcher::Everything => true,
+                Matcher::Substring(text) => item.name.to_lowercase().contains(text),
+                Matcher::Regex(re) => re.is_match(&item.name),
+            }
+    }
+}
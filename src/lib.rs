@@ -0,0 +1,39 @@
+//! Dropbox client of clouds_viewer for other tools: `client::Client` calls the API with
+//! automatic token refresh, `auth` gets tokens, `models` are parameters and results of calls,
+//! `config` loads app config with configured storages.
+//! The egui app of the crate is started by `run_app`.
+
+pub mod auth;
+pub mod client;
+pub mod common_types;
+pub mod config;
+pub mod models;
+
+pub(crate) mod analyze;
+pub(crate) mod app;
+pub(crate) mod backup;
+pub(crate) mod call_states;
+pub(crate) mod cli;
+pub(crate) mod clouds;
+pub(crate) mod compare;
+pub(crate) mod control;
+pub(crate) mod error;
+pub(crate) mod folders_cache;
+pub(crate) mod formatting;
+pub(crate) mod glob;
+pub(crate) mod http_server;
+pub(crate) mod init;
+pub(crate) mod items_filter;
+pub(crate) mod preview;
+pub(crate) mod storage_instance;
+pub(crate) mod storage_models;
+pub(crate) mod storages;
+pub(crate) mod stream_proxy;
+pub(crate) mod sync_state;
+pub(crate) mod thumbnails;
+pub(crate) mod webdav;
+
+/// runs command of headless mode if arguments have one, otherwise the app
+pub fn run_app() -> Result<(), Box<dyn std::error::Error>> {
+    app::run()
+}
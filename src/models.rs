@@ -0,0 +1,12 @@
+//! parameters, results and errors of Dropbox API calls made by `client::Client`
+
+pub use crate::clouds::dropbox::{
+    AccountInfoCallError, AccountName, CommitInfo, CreateFolderParams, CreateFolderResult,
+    DeleteParams, DeletedMeta, Dimensions, DownloadFileCallError, DownloadFileCallResult,
+    DownloadFileParams, FileMeta, FileOpCallError, FolderMeta, FullAccount, GetMetadataCallError,
+    GetMetadataParams, GetTemporaryLinkCallError, GetTemporaryLinkCallResult,
+    GetTemporaryLinkParams, GpsCoordinates, ListFolderCallError, ListFolderCallResult,
+    ListFolderContinueParams, ListFolderParams, MediaInfo, MediaMetadata, Meta, MetadataResult,
+    MoveParams, SpaceAllocation, SpaceUsage, UploadCallError, WriteMode,
+};
+pub use crate::clouds::BaseError;
@@ -0,0 +1,84 @@
+use crate::thumbnails;
+
+const JSON_EXTENSIONS: [&str; 2] = ["json", "geojson"];
+const IMAGE_EXTENSIONS: [&str; 6] = ["jpg", "jpeg", "png", "gif", "bmp", "webp"];
+/// bytes shown in hex dump of binary files
+const HEX_DUMP_LIMIT: usize = 1024;
+
+#[derive(Debug)]
+pub enum PreviewData {
+    Text(String),
+    Json(String),
+    Image(thumbnails::DecodedImage),
+    Binary(String),
+}
+
+fn extension(name: &str) -> String {
+    match name.rsplit_once('.') {
+        Some((_, ext)) => ext.to_lowercase(),
+        None => "".to_string(),
+    }
+}
+
+fn looks_like_text(data: &[u8]) -> bool {
+    let text = String::from_utf8_lossy(data);
+    let total = text.chars().count();
+    if total == 0 {
+        return true;
+    }
+    let bad = text
+        .chars()
+        .filter(|c| *c == char::REPLACEMENT_CHARACTER || (c.is_control() && !c.is_whitespace()))
+        .count();
+    // last char may be cut in the middle by the range request
+    bad <= 1 + total / 100
+}
+
+fn hex_dump(data: &[u8]) -> String {
+    let mut res = String::new();
+    for (idx, line) in data[..data.len().min(HEX_DUMP_LIMIT)]
+        .chunks(16)
+        .enumerate()
+    {
+        let hex: Vec<String> = line.iter().map(|b| format!("{:02x}", b)).collect();
+        let ascii: String = line
+            .iter()
+            .map(|b| {
+                if b.is_ascii_graphic() || *b == b' ' {
+                    *b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        res += &format!("{:08x}  {:<48}  {}\n", idx * 16, hex.join(" "), ascii);
+    }
+    res
+}
+
+/// `truncated` - data contains only first part of the file
+pub fn make_preview(name: &str, data: &[u8], truncated: bool) -> Result<PreviewData, String> {
+    let ext = extension(name);
+    if IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+        if truncated {
+            return Err("image is too large for preview".to_string());
+        }
+        return thumbnails::decode(data).map(PreviewData::Image);
+    }
+    if JSON_EXTENSIONS.contains(&ext.as_str()) && !truncated {
+        if let Ok(value) = serde_json::from_slice::<serde_json::Value>(data) {
+            if let Ok(pretty) = serde_json::to_string_pretty(&value) {
+                return Ok(PreviewData::Json(pretty));
+            }
+        }
+    }
+    if looks_like_text(data) {
+        let mut text = String::from_utf8_lossy(data).to_string();
+        if truncated {
+            text += "\n...";
+        }
+        Ok(PreviewData::Text(text))
+    } else {
+        Ok(PreviewData::Binary(hex_dump(data)))
+    }
+}
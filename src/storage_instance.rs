@@ -1,25 +1,543 @@
-use crate::call_messages;
+use crate::analyze;
 use crate::call_states;
 use crate::clouds;
+use crate::clouds::call_messages;
 use crate::clouds::AuthInfoHolder;
 use crate::common_types::*;
+use crate::compare;
+use crate::config;
+use crate::control;
+use crate::folders_cache;
+use crate::items_filter;
+use crate::preview;
 use crate::storage_models;
 use crate::storages;
+use crate::stream_proxy;
+use crate::sync_state;
+use crate::thumbnails;
 use std::collections::HashMap;
 
 enum StorageAction {
-    To { path: String },
-    Forward { folder_name: String },
+    To {
+        path: String,
+    },
+    Forward {
+        folder_name: String,
+    },
     Backward,
-    DownloadFile { path: String, to_path: String },
-    CancelDownloadFile { call_id: u64 },
+    DownloadFile {
+        path: String,
+        to_path: String,
+        /// opened and watched for edits when finished
+        open: bool,
+    },
+    CancelDownloadFile {
+        call_id: u64,
+    },
+    GetMetadata {
+        path: String,
+    },
+    GetThumbnails {
+        entries: Vec<storage_models::ThumbnailRequest>,
+    },
+    Preview {
+        path: String,
+        max_bytes: u64,
+    },
+    Search {
+        in_data: storage_models::search_in_data,
+    },
+    WatchFolder {
+        path: String,
+        cursor: String,
+    },
+    Restore {
+        path: String,
+        rev: Option<String>,
+    },
+    ListRevisions {
+        path: String,
+    },
+    SharedLinks {
+        in_data: storage_models::shared_links_in_data,
+    },
+    TemporaryLink {
+        in_data: storage_models::temporary_link_in_data,
+    },
+    AccountInfo,
+    ListRecursive {
+        in_data: storage_models::list_recursive_in_data,
+    },
+    FileOps {
+        in_data: storage_models::file_ops_in_data,
+    },
+    Transfer {
+        in_data: storage_models::transfer_in_data,
+    },
+    Compare {
+        in_data: storage_models::compare_in_data,
+    },
+    Mirror {
+        in_data: storage_models::mirror_in_data,
+    },
+    Sync {
+        in_data: storage_models::sync_in_data,
+    },
+    Backup {
+        in_data: storage_models::backup_in_data,
+    },
+    WebDav {
+        in_data: storage_models::webdav_in_data,
+    },
     StartAuth,
     CancelAuth,
 }
 
+/// whether shown listing is confirmed by the server
+pub enum FolderFreshness {
+    Fresh,
+    /// shown from the folders cache, deltas are being requested
+    Refreshing {
+        saved: chrono::DateTime<chrono::Utc>,
+    },
+    /// shown from the folders cache, refresh failed (offline)
+    Stale {
+        saved: chrono::DateTime<chrono::Utc>,
+        error: String,
+    },
+}
+
 pub struct StorageActiveFolder {
     pub path: String,
     pub items: Vec<storage_models::Item>,
+    pub freshness: FolderFreshness,
+}
+
+pub struct PropertiesDialogState {
+    pub path: String,
+    pub item: Option<storage_models::Item>,
+    pub error: Option<String>,
+}
+
+pub struct RevisionsDialogState {
+    pub path: String,
+    pub is_deleted: bool,
+    /// newest first
+    pub entries: Option<Vec<storage_models::Item>>,
+    pub error: Option<String>,
+    /// rev of the last successful restore from the dialog
+    pub restored_rev: Option<String>,
+}
+
+pub struct SharedLinksDialogState {
+    pub path: String,
+    pub links: Option<Vec<storage_models::SharedLink>>,
+    pub error: Option<String>,
+    pub in_progress: bool,
+    /// settings for create/modify
+    pub audience: storage_models::LinkAudience,
+    /// 0 = never expires
+    pub expires_in_days: u32,
+    pub use_password: bool,
+    pub password: String,
+    /// url to put into clipboard on next frame
+    pub copy_to_clipboard: Option<String>,
+}
+
+impl SharedLinksDialogState {
+    fn settings(&self) -> storage_models::SharedLinkSettings {
+        storage_models::SharedLinkSettings {
+            audience: self.audience,
+            expires: if self.expires_in_days == 0 {
+                None
+            } else {
+                Some(chrono::Utc::now() + chrono::Duration::days(self.expires_in_days as i64))
+            },
+            password: if self.use_password {
+                Some(self.password.clone())
+            } else {
+                None
+            },
+        }
+    }
+}
+
+pub struct AnalyzeState {
+    pub path: String,
+    /// entries listed so far
+    pub count: u64,
+    pub root: Option<analyze::SizeNode>,
+    pub error: Option<String>,
+    /// drilled-into folder
+    pub current: String,
+    pub sort: analyze::SizeSort,
+}
+
+pub struct DuplicatesState {
+    pub path: String,
+    /// entries listed so far
+    pub count: u64,
+    pub groups: Option<Vec<analyze::DuplicateGroup>>,
+    pub error: Option<String>,
+    /// remote paths of copies selected for delete/move
+    pub selected: std::collections::HashSet<String>,
+    /// destination folder for move
+    pub move_to: String,
+    pub ops_in_progress: bool,
+    /// errors of last delete/move
+    pub ops_errors: Vec<String>,
+}
+
+impl DuplicatesState {
+    /// selects every copy except the oldest one of each group
+    pub fn select_all_but_oldest(&mut self) {
+        self.selected.clear();
+        if let Some(groups) = &self.groups {
+            for group in groups {
+                for item in group.items.iter().skip(1) {
+                    self.selected
+                        .insert(item.path_display.trim_start_matches('/').to_string());
+                }
+            }
+        }
+    }
+}
+
+pub struct CompareState {
+    pub local_path: String,
+    pub remote_path: String,
+    /// remote entries listed so far
+    pub listed: u64,
+    /// local files checked so far
+    pub checked: u64,
+    pub in_progress: bool,
+    pub entries: Option<Vec<storage_models::CompareEntry>>,
+    pub error: Option<String>,
+    pub show_identical: bool,
+    pub transfer_in_progress: bool,
+    /// errors of last download/upload
+    pub transfer_errors: Vec<String>,
+}
+
+impl CompareState {
+    pub fn count(&self, status: storage_models::CompareStatus) -> usize {
+        self.entries
+            .iter()
+            .flatten()
+            .filter(|entry| entry.status == status)
+            .count()
+    }
+
+    /// marks transferred files identical
+    fn apply_transfer_results(
+        &mut self,
+        results: Vec<(
+            storage_models::TransferOp,
+            Result<storage_models::Item, storage_models::TransferOpError>,
+        )>,
+    ) {
+        self.transfer_errors.clear();
+        for (op, res) in results {
+            let item = match res {
+                Ok(item) => item,
+                Err(e) => {
+                    self.transfer_errors
+                        .push(format!("{}: {}", op.remote_path(), e));
+                    continue;
+                }
+            };
+            let remote_path = op.remote_path().to_lowercase();
+            let entry = self.entries.iter_mut().flatten().find(|entry| {
+                compare::remote_file_path(&self.remote_path, &entry.relative_path)
+                    .to_lowercase()
+                    .eq(&remote_path)
+            });
+            if let Some(entry) = entry {
+                entry.status = storage_models::CompareStatus::Identical;
+                entry.local_size = item.size;
+                entry.local_modified = std::fs::metadata(op.local_path())
+                    .and_then(|m| m.modified())
+                    .ok()
+                    .map(chrono::DateTime::<chrono::Utc>::from);
+                entry.remote = Some(item);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditedFileStatus {
+    /// same as uploaded or downloaded revision
+    Unchanged,
+    /// changed locally, upload is offered
+    Modified,
+    /// `size` and `modified` of local file when upload started
+    Uploading {
+        size: u64,
+        modified: Option<chrono::DateTime<chrono::Utc>>,
+    },
+    /// remote file was changed after download
+    Conflict,
+    Failed(String),
+}
+
+/// opened file which can be uploaded back after local changes
+pub struct EditedFile {
+    /// without leading '/'
+    pub remote_path: String,
+    pub local_path: String,
+    /// remote revision the local file is based on
+    pub rev: String,
+    /// local file after download or last upload
+    pub size: u64,
+    pub modified: Option<chrono::DateTime<chrono::Utc>>,
+    pub status: EditedFileStatus,
+}
+
+/// how often opened files are checked for changes
+const EDITED_FILES_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+impl EditedFile {
+    fn local_state(&self) -> Option<(u64, Option<chrono::DateTime<chrono::Utc>>)> {
+        let metadata = std::fs::metadata(&self.local_path).ok()?;
+        Some((
+            metadata.len(),
+            metadata
+                .modified()
+                .ok()
+                .map(chrono::DateTime::<chrono::Utc>::from),
+        ))
+    }
+
+    fn apply_transfer_result(
+        &mut self,
+        res: Result<storage_models::Item, storage_models::TransferOpError>,
+    ) {
+        let (size, modified) = match &self.status {
+            EditedFileStatus::Uploading { size, modified } => (*size, *modified),
+            _ => return,
+        };
+        match res {
+            Ok(item) => {
+                log::info!("edited file uploaded: {}", &item.path_display);
+                self.remote_path = item.path_display.trim_start_matches('/').to_string();
+                self.rev = item.rev;
+                self.size = size;
+                self.modified = modified;
+                self.status = EditedFileStatus::Unchanged;
+            }
+            Err(storage_models::TransferOpError::Conflict(_)) => {
+                self.status = EditedFileStatus::Conflict;
+            }
+            Err(e) => self.status = EditedFileStatus::Failed(e.to_string()),
+        }
+    }
+}
+
+/// runs kept in config for every mirror, sync or backup job
+const JOB_RUNS_KEPT: usize = 20;
+
+pub struct MirrorJobState {
+    pub config: config::MirrorJobConfig,
+    /// start of current run
+    pub started: Option<chrono::DateTime<chrono::Utc>>,
+    pub listed: u64,
+    pub downloaded: u64,
+}
+
+impl MirrorJobState {
+    pub fn new(config: config::MirrorJobConfig) -> Self {
+        Self {
+            config,
+            started: None,
+            listed: 0,
+            downloaded: 0,
+        }
+    }
+
+    /// scheduled run is due
+    fn is_due(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        if self.started.is_some() || self.config.interval_minutes == 0 {
+            return false;
+        }
+        match self.config.runs.last() {
+            Some(run) => {
+                now - run.finished >= chrono::Duration::minutes(self.config.interval_minutes as i64)
+            }
+            None => true,
+        }
+    }
+
+    fn add_run(&mut self, stats: config::MirrorRunStats) {
+        self.started = None;
+        self.config.runs.push(stats);
+        if self.config.runs.len() > JOB_RUNS_KEPT {
+            let extra = self.config.runs.len() - JOB_RUNS_KEPT;
+            self.config.runs.drain(..extra);
+        }
+    }
+}
+
+pub struct SyncJobState {
+    pub config: config::SyncJobConfig,
+    /// start of current run
+    pub started: Option<chrono::DateTime<chrono::Utc>>,
+    pub done: u64,
+    pub total: u64,
+}
+
+impl SyncJobState {
+    pub fn new(config: config::SyncJobConfig) -> Self {
+        Self {
+            config,
+            started: None,
+            done: 0,
+            total: 0,
+        }
+    }
+
+    /// scheduled run is due
+    fn is_due(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        if self.started.is_some() || self.config.interval_minutes == 0 {
+            return false;
+        }
+        match self.config.runs.last() {
+            Some(run) => {
+                now - run.finished >= chrono::Duration::minutes(self.config.interval_minutes as i64)
+            }
+            None => true,
+        }
+    }
+
+    fn add_run(&mut self, stats: config::SyncRunStats) {
+        self.started = None;
+        self.config.runs.push(stats);
+        if self.config.runs.len() > JOB_RUNS_KEPT {
+            let extra = self.config.runs.len() - JOB_RUNS_KEPT;
+            self.config.runs.drain(..extra);
+        }
+    }
+}
+
+pub struct BackupJobState {
+    pub config: config::BackupSetConfig,
+    /// start of current run
+    pub started: Option<chrono::DateTime<chrono::Utc>>,
+    /// manifest of current run
+    pub manifest_file: Option<std::path::PathBuf>,
+    pub done: u64,
+    pub total: u64,
+    /// skipped files of last run in this session: relative path and reason
+    pub skipped: Vec<(String, String)>,
+    /// failed files of last run in this session: relative path and error
+    pub failed: Vec<(String, String)>,
+}
+
+impl BackupJobState {
+    pub fn new(config: config::BackupSetConfig) -> Self {
+        Self {
+            config,
+            started: None,
+            manifest_file: None,
+            done: 0,
+            total: 0,
+            skipped: vec![],
+            failed: vec![],
+        }
+    }
+
+    /// scheduled run is due
+    fn is_due(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        if self.started.is_some() || self.config.interval_minutes == 0 {
+            return false;
+        }
+        match self.config.runs.last() {
+            Some(run) => {
+                now - run.finished >= chrono::Duration::minutes(self.config.interval_minutes as i64)
+            }
+            None => true,
+        }
+    }
+
+    /// manifests of dropped runs are removed
+    fn add_run(&mut self, stats: config::BackupRunStats) {
+        self.started = None;
+        self.manifest_file = None;
+        self.config.runs.push(stats);
+        if self.config.runs.len() > JOB_RUNS_KEPT {
+            let extra = self.config.runs.len() - JOB_RUNS_KEPT;
+            for run in self.config.runs.drain(..extra) {
+                if let Some(manifest) = run.manifest {
+                    let _ = std::fs::remove_file(manifest);
+                }
+            }
+        }
+    }
+}
+
+/// file name for state or manifest of job with `name`
+fn job_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+pub struct PreviewState {
+    pub path: String,
+    pub size: Option<u64>,
+    pub data: Option<preview::PreviewData>,
+    pub error: Option<String>,
+    pub texture: Option<(eframe::egui::TextureId, [usize; 2])>,
+}
+
+/// search options as edited in the search bar
+pub struct SearchState {
+    pub query: String,
+    /// scope of the search, "" = whole storage
+    pub path: String,
+    pub filename_only: bool,
+    /// comma separated list, e.g. "jpg, png"
+    pub extensions: String,
+    pub categories: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl Default for SearchState {
+    fn default() -> Self {
+        Self {
+            query: "".into(),
+            path: "".into(),
+            filename_only: true,
+            extensions: "".into(),
+            categories: vec![],
+            error: None,
+        }
+    }
+}
+
+pub struct SearchResults {
+    pub query: String,
+    pub items: Vec<storage_models::Item>,
+    /// not all of the hits were fetched
+    pub limited: bool,
+}
+
+pub enum ThumbnailState {
+    Requested,
+    Decoded(thumbnails::DecodedImage),
+    Texture {
+        id: eframe::egui::TextureId,
+        size: [usize; 2],
+    },
+    Failed,
+}
+
+#[derive(Default)]
+pub struct FolderThumbnails {
+    /// folder the thumbnails were requested for
+    pub folder_path: String,
+    /// keyed by file rev
+    pub items: HashMap<String, ThumbnailState>,
 }
 
 pub struct AuthProcessState {
@@ -32,6 +550,31 @@ pub struct StorageVisualState {
     pub auth_needed: bool,
     pub auth_process_state: Option<AuthProcessState>,
     pub v_path: String,
+    pub filter: items_filter::ItemsFilter,
+    pub properties: Option<PropertiesDialogState>,
+    pub revisions: Option<RevisionsDialogState>,
+    pub shared_links: Option<SharedLinksDialogState>,
+    /// text to put into clipboard on next frame
+    pub copy_to_clipboard: Option<String>,
+    /// remote file to open in player through stream proxy on next frame
+    pub stream_in_player: Option<String>,
+    pub account: Option<storage_models::account_info_out_data>,
+    pub analyze: Option<AnalyzeState>,
+    pub duplicates: Option<DuplicatesState>,
+    pub compare: Option<CompareState>,
+    pub show_mirrors: bool,
+    pub show_syncs: bool,
+    pub show_backups: bool,
+    pub show_webdav: bool,
+    pub thumbnails: FolderThumbnails,
+    pub preview: Option<PreviewState>,
+    pub search: SearchState,
+    /// shown instead of the folder while present
+    pub search_results: Option<SearchResults>,
+    /// list folders with deleted entries
+    pub show_deleted: bool,
+    /// textures which are not used anymore, freed on next frame
+    pub textures_to_free: Vec<eframe::egui::TextureId>,
 }
 
 impl Default for StorageVisualState {
@@ -42,6 +585,26 @@ impl Default for StorageVisualState {
             auth_needed: false,
             auth_process_state: None,
             v_path: "".into(),
+            filter: items_filter::ItemsFilter::default(),
+            properties: None,
+            revisions: None,
+            shared_links: None,
+            copy_to_clipboard: None,
+            stream_in_player: None,
+            account: None,
+            analyze: None,
+            duplicates: None,
+            compare: None,
+            show_mirrors: false,
+            show_syncs: false,
+            show_backups: false,
+            show_webdav: false,
+            thumbnails: FolderThumbnails::default(),
+            preview: None,
+            search: SearchState::default(),
+            search_results: None,
+            show_deleted: false,
+            textures_to_free: vec![],
         }
     }
 }
@@ -72,6 +635,27 @@ pub struct StorageInstance {
     pub visual_state: StorageVisualState,
     pub auth_info_holder: clouds::AuthInfoHolder,
     pub save_to_path: String,
+    pub thumbnail_cache: thumbnails::ThumbnailCache,
+    pub folders_cache: folders_cache::FoldersCache,
+    pub mirrors: Vec<MirrorJobState>,
+    pub syncs: Vec<SyncJobState>,
+    /// folder for states of sync jobs
+    pub sync_state_dir: std::path::PathBuf,
+    pub backups: Vec<BackupJobState>,
+    /// folder for manifests of backup runs
+    pub backup_dir: std::path::PathBuf,
+    /// downloaded and opened files, watched for local changes
+    pub edited_files: Vec<EditedFile>,
+    pub edited_files_checked: std::time::Instant,
+    /// upload changed files without asking
+    pub auto_upload_edited: bool,
+    pub webdav: config::WebDavConfig,
+    /// call of running (or failed) WebDAV server
+    pub webdav_call_id: Option<u64>,
+    /// folder for files received by WebDAV server before upload
+    pub webdav_dir: std::path::PathBuf,
+    /// state of jobs or settings should be saved to config
+    pub config_changed: bool,
 }
 
 impl StorageInstance {
@@ -114,11 +698,12 @@ impl StorageInstance {
         _call_id: u64,
         msg: call_messages::ListFolder,
         state: &mut call_states::State,
+        messages: MessagesSender<call_messages::Message>,
         remove_call_info: &mut bool,
     ) {
         if let call_states::Data::ListFolder {
             data: ref mut state_data,
-            ..
+            ref path,
         } = state.data
         {
             match msg {
@@ -142,12 +727,34 @@ impl StorageInstance {
                     visual_state.folder = Some(StorageActiveFolder {
                         path: res.path.clone(),
                         items: res.items.take().unwrap_or(vec![]),
+                        freshness: FolderFreshness::Fresh,
                     });
                     visual_state.v_path = "/".to_owned() + &res.path;
+                    if !res.include_deleted {
+                        let _ = messages.send(call_messages::Message {
+                            call_id: None,
+                            data: call_messages::Data::WatchFolder(
+                                call_messages::WatchFolder::Start {
+                                    path: res.path,
+                                    cursor: res.cursor,
+                                },
+                            ),
+                        });
+                    }
                     *state_data = call_states::ListFolder::Ok;
                     *remove_call_info = true;
                 }
                 call_messages::ListFolder::Finished { result: Err(e) } => {
+                    if let Some(folder) = &mut visual_state.folder {
+                        if let FolderFreshness::Refreshing { saved } = folder.freshness {
+                            if folder.path.eq(path) {
+                                folder.freshness = FolderFreshness::Stale {
+                                    saved,
+                                    error: e.to_string(),
+                                };
+                            }
+                        }
+                    }
                     visual_state.list_folder_error = Some(e.to_string());
                     *state_data = call_states::ListFolder::Failed(e);
                 }
@@ -161,12 +768,23 @@ impl StorageInstance {
         match msg {
             call_messages::ListFolder::Started { handle, path } => {
                 self.call_states.retain(|_, v| match &v.data {
-                    call_states::Data::ListFolder { .. } => {
+                    call_states::Data::ListFolder { .. }
+                    | call_states::Data::WatchFolder { .. } => {
                         Self::abort_handles_and_senders(&mut v.handles, &mut v.cancellers);
                         false
                     }
                     _ => true,
                 });
+                if let Some(cached) = self.folders_cache.get(&path) {
+                    self.visual_state.folder = Some(StorageActiveFolder {
+                        path: path.clone(),
+                        items: cached.items,
+                        freshness: FolderFreshness::Refreshing {
+                            saved: cached.saved,
+                        },
+                    });
+                    self.visual_state.v_path = "/".to_owned() + &path;
+                }
                 self.call_states.insert(
                     call_id,
                     call_states::State {
@@ -187,6 +805,7 @@ impl StorageInstance {
     }
 
     fn process_download_file_msg(
+        edited_files: &mut Vec<EditedFile>,
         _call_id: u64,
         msg: call_messages::DownloadFile,
         state: &mut call_states::State,
@@ -194,10 +813,11 @@ impl StorageInstance {
     ) {
         if let call_states::Data::DownloadFile {
             data: ref mut state_data,
-            remote_path: _,
+            ref remote_path,
             ref local_path,
             size: ref mut total_size,
             ref mut downloaded,
+            open,
         } = state.data
         {
             match msg {
@@ -228,10 +848,29 @@ impl StorageInstance {
                     *downloaded = value;
                     *state_data = call_states::DownloadFile::InProgress { progress: value };
                 }
-                call_messages::DownloadFile::Finished { result: Ok(_res) } => {
+                call_messages::DownloadFile::Finished { result: Ok(res) } => {
                     *state_data = call_states::DownloadFile::Ok;
-                    log::debug!("open file: {}", &local_path);
-                    let _ = open::that_in_background(local_path);
+                    // old revisions are opened read-only
+                    if open && !remote_path.starts_with("rev:") {
+                        edited_files.retain(|file| !file.local_path.eq(local_path));
+                        let mut file = EditedFile {
+                            remote_path: res.path_display.trim_start_matches('/').to_string(),
+                            local_path: local_path.clone(),
+                            rev: res.rev,
+                            size: 0,
+                            modified: None,
+                            status: EditedFileStatus::Unchanged,
+                        };
+                        if let Some((size, modified)) = file.local_state() {
+                            file.size = size;
+                            file.modified = modified;
+                            edited_files.push(file);
+                        }
+                    }
+                    if open {
+                        log::debug!("open file: {}", &local_path);
+                        let _ = open::that_in_background(local_path);
+                    }
                     *remove_call_info = true;
                 }
                 call_messages::DownloadFile::Finished { result: Err(e) } => {
@@ -256,6 +895,7 @@ impl StorageInstance {
             handle,
             remote_path,
             local_path,
+            open,
         } = msg
         {
             self.call_states.insert(
@@ -269,6 +909,7 @@ impl StorageInstance {
                         local_path,
                         size: None,
                         downloaded: 0,
+                        open,
                     },
                 },
             );
@@ -277,123 +918,1555 @@ impl StorageInstance {
         }
     }
 
-    fn process_auth_msg(
+    fn process_get_metadata_msg(
         visual_state: &mut StorageVisualState,
-        call_id: u64,
-        msg: call_messages::Auth,
+        _call_id: u64,
+        msg: call_messages::GetMetadata,
         state: &mut call_states::State,
-        messages: MessagesSender<call_messages::Message>,
         remove_call_info: &mut bool,
-        rth: RuntimeHolder,
-        auth_info_holder: AuthInfoHolder,
     ) {
-        if let call_states::Data::Auth {
+        if let call_states::Data::GetMetadata {
             data: ref mut state_data,
+            ref path,
         } = state.data
         {
             match msg {
-                call_messages::Auth::Start => panic!("start"),
-                call_messages::Auth::Binded {
-                    redirect_url,
-                    addr: _,
-                    state_field,
-                } => {
-                    let _ = messages.send(call_messages::Message {
-                        call_id: Some(call_id),
-                        data: call_messages::Data::Auth(call_messages::Auth::CheckAvailability {
-                            redirect_url: redirect_url.clone(),
-                            state_field,
-                        }),
-                    });
-                    *state_data =
-                        call_states::Auth::InProgress(call_states::AuthProgress::Binded {
-                            redirect_url,
-                        });
+                call_messages::GetMetadata::Started { .. } => panic!("started"),
+                call_messages::GetMetadata::RefreshToken => {
+                    *state_data = call_states::GetMetadata::RefreshToken;
                 }
-                call_messages::Auth::LocalServerShutdowner {
-                    cancel_informer,
-                    cancel_awaiter,
-                } => {
-                    state.cancellers.push(call_states::Canceller {
-                        cancel_informer,
-                        cancel_awaiter,
-                    });
+                call_messages::GetMetadata::RefreshTokenComplete => {
+                    *state_data = call_states::GetMetadata::RefreshTokenComplete;
                 }
-                call_messages::Auth::CheckAvailability {
-                    redirect_url,
-                    state_field,
-                } => {
-                    let redirect_url_clone = redirect_url.clone();
-                    let (s, r) = tokio::sync::oneshot::channel::<()>();
-                    if let Ok(handle) = rth.clone().spawn(async move {
-                        let _ = r.await;
-                        let uri: http::Uri = redirect_url_clone.parse().unwrap();
-                        let mut parts = uri.into_parts();
-                        parts.path_and_query = Some(http::uri::PathAndQuery::from_static("/ping"));
-                        let ping_uri = http::Uri::from_parts(parts).expect("ping_uri");
-                        let client = Self::client_http_or_https(&rth);
-                        loop {
-                            let start = std::time::Instant::now();
-                            match client.get(ping_uri.clone()).await {
-                                Ok(_response) => {
-                                    let _ = messages.send(call_messages::Message {
-                                        call_id: Some(call_id),
-                                        data: call_messages::Data::Auth(
-                                            call_messages::Auth::ServerReady {
-                                                redirect_url: redirect_url_clone,
-                                                state_field,
-                                            },
-                                        ),
-                                    });
-                                    break;
-                                }
-                                Err(e) => {
-                                    log::debug!("ping failed for now: {}", e);
-                                    if start.elapsed() > std::time::Duration::from_secs(10) {
-                                        let _ = messages.send(call_messages::Message {
-                                            call_id: Some(call_id),
-                                            data: call_messages::Data::Auth(
-                                                call_messages::Auth::Finished {
-                                                    result: Err(
-                                                        storage_models::AuthError::LocalServerWaitForAvailabileFailed
-                                                    )
-                                                },
-                                            ),
-                                        });
-                                        break;
-                                    }
-                                }
-                            }
+                call_messages::GetMetadata::Finished { result: Ok(res) } => {
+                    if let Some(properties) = &mut visual_state.properties {
+                        if properties.path.eq(path) {
+                            properties.item = Some(*res.item);
                         }
-                    }) {
-                        state.handles.push(handle);
-                        *state_data = call_states::Auth::InProgress(
-                            call_states::AuthProgress::WaitingForLocalServerUp {
-                                redirect_url,
-                            },
-                        );
-                        let _ = s.send(());
                     }
+                    *state_data = call_states::GetMetadata::Ok;
+                    *remove_call_info = true;
                 }
-                call_messages::Auth::ServerReady {
-                    redirect_url,
-                    state_field,
-                } => {
-                    if let Ok(client_id) = rth.block_on(async move {
-                        let res = auth_info_holder.read().await;
-                        res.client_id.clone()
-                    }) {
-                        let params = format!(
-                            "client_id={}&response_type={}&state={}&token_access_type={}&redirect_uri={}",
-                            client_id,
-                            "code",
-                            state_field,
-                            "offline",
-                            redirect_url
-                        );
-                        let auth_url: String =
-                            "https://www.dropbox.com/oauth2/authorize?".to_string() + &params;
-                        log::debug!("auth url: {}", &auth_url);
+                call_messages::GetMetadata::Finished { result: Err(e) } => {
+                    if let Some(properties) = &mut visual_state.properties {
+                        if properties.path.eq(path) {
+                            properties.error = Some(e.to_string());
+                        }
+                    }
+                    *state_data = call_states::GetMetadata::Failed(e);
+                }
+            }
+        } else {
+            panic!("{:?}", state.data);
+        }
+    }
+
+    fn process_get_metadata_msg_new_state(
+        &mut self,
+        call_id: u64,
+        msg: call_messages::GetMetadata,
+    ) {
+        if let call_messages::GetMetadata::Started { handle, path } = msg {
+            self.call_states.insert(
+                call_id,
+                call_states::State {
+                    handles: vec![handle],
+                    cancellers: vec![],
+                    data: call_states::Data::GetMetadata {
+                        data: call_states::GetMetadata::InProgress,
+                        path,
+                    },
+                },
+            );
+        } else {
+            log::debug!("unknown call_id: {call_id}");
+        }
+    }
+
+    fn process_get_thumbnails_msg(
+        visual_state: &mut StorageVisualState,
+        _call_id: u64,
+        msg: call_messages::GetThumbnails,
+        state: &mut call_states::State,
+        remove_call_info: &mut bool,
+    ) {
+        if let call_states::Data::GetThumbnails {
+            data: ref mut state_data,
+            ref mut done,
+            ..
+        } = state.data
+        {
+            match msg {
+                call_messages::GetThumbnails::Started { .. } => panic!("started"),
+                call_messages::GetThumbnails::RefreshToken => {
+                    *state_data = call_states::GetThumbnails::RefreshToken;
+                }
+                call_messages::GetThumbnails::RefreshTokenComplete => {
+                    *state_data = call_states::GetThumbnails::RefreshTokenComplete;
+                }
+                call_messages::GetThumbnails::Thumbnail { rev, image } => {
+                    let thumbnail = match image {
+                        Ok(image) => ThumbnailState::Decoded(image),
+                        Err(e) => {
+                            log::debug!("thumbnail for rev '{}' failed: {}", rev, e);
+                            ThumbnailState::Failed
+                        }
+                    };
+                    visual_state.thumbnails.items.insert(rev, thumbnail);
+                    *done += 1;
+                }
+                call_messages::GetThumbnails::Finished { result: Ok(_) } => {
+                    *state_data = call_states::GetThumbnails::Ok;
+                    *remove_call_info = true;
+                }
+                call_messages::GetThumbnails::Finished { result: Err(e) } => {
+                    visual_state
+                        .thumbnails
+                        .items
+                        .retain(|_, v| !matches!(v, ThumbnailState::Requested));
+                    *state_data = call_states::GetThumbnails::Failed(e);
+                }
+            }
+        } else {
+            panic!("{:?}", state.data);
+        }
+    }
+
+    fn process_get_thumbnails_msg_new_state(
+        &mut self,
+        call_id: u64,
+        msg: call_messages::GetThumbnails,
+    ) {
+        if let call_messages::GetThumbnails::Started { handle, total } = msg {
+            self.call_states.insert(
+                call_id,
+                call_states::State {
+                    handles: vec![handle],
+                    cancellers: vec![],
+                    data: call_states::Data::GetThumbnails {
+                        data: call_states::GetThumbnails::InProgress,
+                        total,
+                        done: 0,
+                    },
+                },
+            );
+        } else {
+            log::debug!("unknown call_id: {call_id}");
+        }
+    }
+
+    fn process_preview_msg(
+        visual_state: &mut StorageVisualState,
+        _call_id: u64,
+        msg: call_messages::Preview,
+        state: &mut call_states::State,
+        remove_call_info: &mut bool,
+    ) {
+        if let call_states::Data::Preview {
+            data: ref mut state_data,
+            ref path,
+        } = state.data
+        {
+            match msg {
+                call_messages::Preview::Started { .. } => panic!("started"),
+                call_messages::Preview::RefreshToken => {
+                    *state_data = call_states::Preview::RefreshToken;
+                }
+                call_messages::Preview::RefreshTokenComplete => {
+                    *state_data = call_states::Preview::RefreshTokenComplete;
+                }
+                call_messages::Preview::Finished { result: Ok(res) } => {
+                    if let Some(preview) = &mut visual_state.preview {
+                        if preview.path.eq(path) {
+                            preview.size = Some(res.size);
+                            preview.data = Some(res.data);
+                        }
+                    }
+                    *state_data = call_states::Preview::Ok;
+                    *remove_call_info = true;
+                }
+                call_messages::Preview::Finished { result: Err(e) } => {
+                    if let Some(preview) = &mut visual_state.preview {
+                        if preview.path.eq(path) {
+                            preview.error = Some(e.to_string());
+                        }
+                    }
+                    *state_data = call_states::Preview::Failed(e);
+                    *remove_call_info = true;
+                }
+            }
+        } else {
+            panic!("{:?}", state.data);
+        }
+    }
+
+    fn process_preview_msg_new_state(&mut self, call_id: u64, msg: call_messages::Preview) {
+        if let call_messages::Preview::Started { handle, path } = msg {
+            self.call_states.retain(|_, v| match &v.data {
+                call_states::Data::Preview { .. } => {
+                    Self::abort_handles_and_senders(&mut v.handles, &mut v.cancellers);
+                    false
+                }
+                _ => true,
+            });
+            self.call_states.insert(
+                call_id,
+                call_states::State {
+                    handles: vec![handle],
+                    cancellers: vec![],
+                    data: call_states::Data::Preview {
+                        data: call_states::Preview::InProgress,
+                        path,
+                    },
+                },
+            );
+        } else {
+            log::debug!("unknown call_id: {call_id}");
+        }
+    }
+
+    fn process_search_msg(
+        visual_state: &mut StorageVisualState,
+        _call_id: u64,
+        msg: call_messages::Search,
+        state: &mut call_states::State,
+        remove_call_info: &mut bool,
+    ) {
+        if let call_states::Data::Search {
+            data: ref mut state_data,
+            ..
+        } = state.data
+        {
+            match msg {
+                call_messages::Search::Started { .. } => panic!("started"),
+                call_messages::Search::Progress { count } => {
+                    *state_data = call_states::Search::InProgress { count };
+                }
+                call_messages::Search::RefreshToken => {
+                    *state_data = call_states::Search::RefreshToken;
+                }
+                call_messages::Search::RefreshTokenComplete => {
+                    *state_data = call_states::Search::RefreshTokenComplete;
+                }
+                call_messages::Search::Finished { result: Ok(res) } => {
+                    visual_state.search.error = None;
+                    visual_state.search_results = Some(SearchResults {
+                        query: res.query,
+                        items: res.items,
+                        limited: res.limited,
+                    });
+                    *state_data = call_states::Search::Ok;
+                    *remove_call_info = true;
+                }
+                call_messages::Search::Finished { result: Err(e) } => {
+                    visual_state.search.error = Some(e.to_string());
+                    *state_data = call_states::Search::Failed(e);
+                    *remove_call_info = true;
+                }
+            }
+        } else {
+            panic!("{:?}", state.data);
+        }
+    }
+
+    fn process_search_msg_new_state(&mut self, call_id: u64, msg: call_messages::Search) {
+        if let call_messages::Search::Started { handle, query } = msg {
+            self.call_states.retain(|_, v| match &v.data {
+                call_states::Data::Search { .. } => {
+                    Self::abort_handles_and_senders(&mut v.handles, &mut v.cancellers);
+                    false
+                }
+                _ => true,
+            });
+            self.call_states.insert(
+                call_id,
+                call_states::State {
+                    handles: vec![handle],
+                    cancellers: vec![],
+                    data: call_states::Data::Search {
+                        data: call_states::Search::InProgress { count: 0 },
+                        query,
+                    },
+                },
+            );
+        } else {
+            log::debug!("unknown call_id: {call_id}");
+        }
+    }
+
+    fn process_watch_folder_msg(
+        visual_state: &mut StorageVisualState,
+        _call_id: u64,
+        msg: call_messages::WatchFolder,
+        state: &mut call_states::State,
+        remove_call_info: &mut bool,
+    ) {
+        if let call_states::Data::WatchFolder {
+            data: ref mut state_data,
+            ref path,
+        } = state.data
+        {
+            match msg {
+                call_messages::WatchFolder::Start { .. } => panic!("start"),
+                call_messages::WatchFolder::Started { .. } => panic!("started"),
+                call_messages::WatchFolder::RefreshToken => {
+                    *state_data = call_states::WatchFolder::RefreshToken;
+                }
+                call_messages::WatchFolder::RefreshTokenComplete => {
+                    *state_data = call_states::WatchFolder::Watching;
+                }
+                call_messages::WatchFolder::Changes { deltas } => {
+                    if let Some(folder) = &mut visual_state.folder {
+                        if folder.path.eq(path) {
+                            folders_cache::apply_deltas(&mut folder.items, deltas);
+                        }
+                    }
+                }
+                call_messages::WatchFolder::Relisted { items } => {
+                    if let Some(folder) = &mut visual_state.folder {
+                        if folder.path.eq(path) {
+                            folder.items = items;
+                        }
+                    }
+                }
+                call_messages::WatchFolder::Finished { result: Ok(()) } => {
+                    *remove_call_info = true;
+                }
+                call_messages::WatchFolder::Finished { result: Err(e) } => {
+                    log::error!("watch folder '{}' failed: {}", path, e);
+                    *state_data = call_states::WatchFolder::Failed(e);
+                }
+            }
+        } else {
+            panic!("{:?}", state.data);
+        }
+    }
+
+    fn process_watch_folder_msg_new_state(
+        &mut self,
+        call_id: u64,
+        msg: call_messages::WatchFolder,
+    ) {
+        if let call_messages::WatchFolder::Started { handle, path } = msg {
+            self.call_states.retain(|_, v| match &v.data {
+                call_states::Data::WatchFolder { .. } => {
+                    Self::abort_handles_and_senders(&mut v.handles, &mut v.cancellers);
+                    false
+                }
+                _ => true,
+            });
+            self.call_states.insert(
+                call_id,
+                call_states::State {
+                    handles: vec![handle],
+                    cancellers: vec![],
+                    data: call_states::Data::WatchFolder {
+                        data: call_states::WatchFolder::Watching,
+                        path,
+                    },
+                },
+            );
+        } else {
+            log::debug!("unknown call_id: {call_id}");
+        }
+    }
+
+    fn process_list_revisions_msg(
+        visual_state: &mut StorageVisualState,
+        _call_id: u64,
+        msg: call_messages::ListRevisions,
+        state: &mut call_states::State,
+        remove_call_info: &mut bool,
+    ) {
+        if let call_states::Data::ListRevisions {
+            data: ref mut state_data,
+            ref path,
+        } = state.data
+        {
+            match msg {
+                call_messages::ListRevisions::Started { .. } => panic!("started"),
+                call_messages::ListRevisions::RefreshToken => {
+                    *state_data = call_states::ListRevisions::RefreshToken;
+                }
+                call_messages::ListRevisions::RefreshTokenComplete => {
+                    *state_data = call_states::ListRevisions::RefreshTokenComplete;
+                }
+                call_messages::ListRevisions::Finished { result: Ok(res) } => {
+                    if let Some(revisions) = &mut visual_state.revisions {
+                        if revisions.path.eq(path) {
+                            revisions.is_deleted = res.is_deleted;
+                            revisions.entries = Some(res.entries);
+                        }
+                    }
+                    *state_data = call_states::ListRevisions::Ok;
+                    *remove_call_info = true;
+                }
+                call_messages::ListRevisions::Finished { result: Err(e) } => {
+                    if let Some(revisions) = &mut visual_state.revisions {
+                        if revisions.path.eq(path) {
+                            revisions.error = Some(e.to_string());
+                        }
+                    }
+                    *state_data = call_states::ListRevisions::Failed(e);
+                    *remove_call_info = true;
+                }
+            }
+        } else {
+            panic!("{:?}", state.data);
+        }
+    }
+
+    fn process_list_revisions_msg_new_state(
+        &mut self,
+        call_id: u64,
+        msg: call_messages::ListRevisions,
+    ) {
+        if let call_messages::ListRevisions::Started { handle, path } = msg {
+            self.call_states.insert(
+                call_id,
+                call_states::State {
+                    handles: vec![handle],
+                    cancellers: vec![],
+                    data: call_states::Data::ListRevisions {
+                        data: call_states::ListRevisions::InProgress,
+                        path,
+                    },
+                },
+            );
+        } else {
+            log::debug!("unknown call_id: {call_id}");
+        }
+    }
+
+    fn process_shared_links_msg(
+        visual_state: &mut StorageVisualState,
+        _call_id: u64,
+        msg: call_messages::SharedLinks,
+        state: &mut call_states::State,
+        remove_call_info: &mut bool,
+    ) {
+        if let call_states::Data::SharedLinks {
+            data: ref mut state_data,
+            ref path,
+        } = state.data
+        {
+            let dialog = visual_state
+                .shared_links
+                .as_mut()
+                .filter(|dialog| dialog.path.eq(path));
+            match msg {
+                call_messages::SharedLinks::Started { .. } => panic!("started"),
+                call_messages::SharedLinks::RefreshToken => {
+                    *state_data = call_states::SharedLinks::RefreshToken;
+                }
+                call_messages::SharedLinks::RefreshTokenComplete => {
+                    *state_data = call_states::SharedLinks::RefreshTokenComplete;
+                }
+                call_messages::SharedLinks::Finished { result: Ok(res) } => {
+                    if let Some(dialog) = dialog {
+                        dialog.in_progress = false;
+                        dialog.links = Some(res.links);
+                        dialog.copy_to_clipboard = res.created;
+                    }
+                    *state_data = call_states::SharedLinks::Ok;
+                    *remove_call_info = true;
+                }
+                call_messages::SharedLinks::Finished { result: Err(e) } => {
+                    if let Some(dialog) = dialog {
+                        dialog.in_progress = false;
+                        dialog.error = Some(e.to_string());
+                    }
+                    *state_data = call_states::SharedLinks::Failed(e);
+                    *remove_call_info = true;
+                }
+            }
+        } else {
+            panic!("{:?}", state.data);
+        }
+    }
+
+    fn process_shared_links_msg_new_state(
+        &mut self,
+        call_id: u64,
+        msg: call_messages::SharedLinks,
+    ) {
+        if let call_messages::SharedLinks::Started { handle, path } = msg {
+            self.call_states.insert(
+                call_id,
+                call_states::State {
+                    handles: vec![handle],
+                    cancellers: vec![],
+                    data: call_states::Data::SharedLinks {
+                        data: call_states::SharedLinks::InProgress,
+                        path,
+                    },
+                },
+            );
+        } else {
+            log::debug!("unknown call_id: {call_id}");
+        }
+    }
+
+    fn process_file_ops_msg(
+        visual_state: &mut StorageVisualState,
+        _call_id: u64,
+        msg: call_messages::FileOps,
+        state: &mut call_states::State,
+        remove_call_info: &mut bool,
+    ) {
+        if let call_states::Data::FileOps {
+            data: ref mut state_data,
+            ref mut done,
+            ..
+        } = state.data
+        {
+            match msg {
+                call_messages::FileOps::Started { .. } => panic!("started"),
+                call_messages::FileOps::RefreshToken => {
+                    *state_data = call_states::FileOps::RefreshToken;
+                }
+                call_messages::FileOps::RefreshTokenComplete => {
+                    *state_data = call_states::FileOps::RefreshTokenComplete;
+                }
+                call_messages::FileOps::Progress { done: value } => {
+                    *done = value;
+                    *state_data = call_states::FileOps::InProgress;
+                }
+                call_messages::FileOps::Finished { result: Ok(res) } => {
+                    if let Some(duplicates) = &mut visual_state.duplicates {
+                        duplicates.ops_in_progress = false;
+                        duplicates.ops_errors.clear();
+                        let mut processed = std::collections::HashSet::new();
+                        for (op, res) in res.results {
+                            match res {
+                                Ok(()) => {
+                                    duplicates.selected.remove(op.path());
+                                    processed.insert(op.path().to_lowercase());
+                                }
+                                Err(e) => duplicates.ops_errors.push(e),
+                            }
+                        }
+                        if let Some(groups) = &mut duplicates.groups {
+                            for group in groups.iter_mut() {
+                                group.items.retain(|item| {
+                                    !processed.contains(
+                                        &item.path_display.trim_start_matches('/').to_lowercase(),
+                                    )
+                                });
+                            }
+                            groups.retain(|group| group.items.len() > 1);
+                        }
+                    }
+                    *state_data = call_states::FileOps::Ok;
+                    *remove_call_info = true;
+                }
+                call_messages::FileOps::Finished { result: Err(e) } => {
+                    if let Some(duplicates) = &mut visual_state.duplicates {
+                        duplicates.ops_in_progress = false;
+                        duplicates.ops_errors = vec![e.to_string()];
+                    }
+                    *state_data = call_states::FileOps::Failed(e);
+                }
+            }
+        } else {
+            panic!("{:?}", state.data);
+        }
+    }
+
+    fn process_file_ops_msg_new_state(&mut self, call_id: u64, msg: call_messages::FileOps) {
+        if let call_messages::FileOps::Started { handle, total } = msg {
+            self.call_states.insert(
+                call_id,
+                call_states::State {
+                    handles: vec![handle],
+                    cancellers: vec![],
+                    data: call_states::Data::FileOps {
+                        data: call_states::FileOps::InProgress,
+                        total,
+                        done: 0,
+                    },
+                },
+            );
+        } else {
+            log::debug!("unknown call_id: {call_id}");
+        }
+    }
+
+    fn process_transfer_msg(
+        visual_state: &mut StorageVisualState,
+        edited_files: &mut [EditedFile],
+        _call_id: u64,
+        msg: call_messages::Transfer,
+        state: &mut call_states::State,
+        remove_call_info: &mut bool,
+    ) {
+        if let call_states::Data::Transfer {
+            data: ref mut state_data,
+            ref mut done,
+            purpose,
+            ..
+        } = state.data
+        {
+            let compare = match purpose {
+                storage_models::TransferPurpose::Compare => visual_state.compare.as_mut(),
+                storage_models::TransferPurpose::Edit => None,
+            };
+            match msg {
+                call_messages::Transfer::Started { .. } => panic!("started"),
+                call_messages::Transfer::RefreshToken => {
+                    *state_data = call_states::Transfer::RefreshToken;
+                }
+                call_messages::Transfer::RefreshTokenComplete => {
+                    *state_data = call_states::Transfer::RefreshTokenComplete;
+                }
+                call_messages::Transfer::Progress { done: value } => {
+                    *done = value;
+                    *state_data = call_states::Transfer::InProgress;
+                }
+                call_messages::Transfer::Finished { result: Ok(res) } => {
+                    if purpose == storage_models::TransferPurpose::Edit {
+                        for (op, res) in res.results {
+                            let file = edited_files
+                                .iter_mut()
+                                .find(|file| file.local_path.eq(op.local_path()));
+                            if let Some(file) = file {
+                                file.apply_transfer_result(res);
+                            }
+                        }
+                    } else if let Some(compare) = compare {
+                        compare.transfer_in_progress = false;
+                        compare.apply_transfer_results(res.results);
+                    }
+                    *state_data = call_states::Transfer::Ok;
+                    *remove_call_info = true;
+                }
+                call_messages::Transfer::Finished { result: Err(e) } => {
+                    if purpose == storage_models::TransferPurpose::Edit {
+                        for file in edited_files.iter_mut() {
+                            if let EditedFileStatus::Uploading { .. } = file.status {
+                                file.status = EditedFileStatus::Failed(e.to_string());
+                            }
+                        }
+                    } else if let Some(compare) = compare {
+                        compare.transfer_in_progress = false;
+                        compare.transfer_errors = vec![e.to_string()];
+                    }
+                    *state_data = call_states::Transfer::Failed(e);
+                }
+            }
+        } else {
+            panic!("{:?}", state.data);
+        }
+    }
+
+    fn process_transfer_msg_new_state(&mut self, call_id: u64, msg: call_messages::Transfer) {
+        if let call_messages::Transfer::Started {
+            handle,
+            total,
+            purpose,
+        } = msg
+        {
+            self.call_states.insert(
+                call_id,
+                call_states::State {
+                    handles: vec![handle],
+                    cancellers: vec![],
+                    data: call_states::Data::Transfer {
+                        data: call_states::Transfer::InProgress,
+                        total,
+                        done: 0,
+                        purpose,
+                    },
+                },
+            );
+        } else {
+            log::debug!("unknown call_id: {call_id}");
+        }
+    }
+
+    fn process_mirror_msg(
+        mirrors: &mut [MirrorJobState],
+        config_changed: &mut bool,
+        _call_id: u64,
+        msg: call_messages::Mirror,
+        state: &mut call_states::State,
+        remove_call_info: &mut bool,
+    ) {
+        if let call_states::Data::Mirror {
+            data: ref mut state_data,
+            ref name,
+        } = state.data
+        {
+            let job = mirrors.iter_mut().find(|job| job.config.name.eq(name));
+            match msg {
+                call_messages::Mirror::Started { .. } => panic!("started"),
+                call_messages::Mirror::RefreshToken => {
+                    *state_data = call_states::Mirror::RefreshToken;
+                }
+                call_messages::Mirror::RefreshTokenComplete => {
+                    *state_data = call_states::Mirror::RefreshTokenComplete;
+                }
+                call_messages::Mirror::Progress { listed, downloaded } => {
+                    if let Some(job) = job {
+                        job.listed = listed;
+                        job.downloaded = downloaded;
+                    }
+                    *state_data = call_states::Mirror::InProgress { listed, downloaded };
+                }
+                call_messages::Mirror::Finished { result: Ok(res) } => {
+                    if let Some(job) = job {
+                        log::info!(
+                            "mirror '{}': downloaded {}, deleted {}, failed {}",
+                            name,
+                            res.stats.downloaded,
+                            res.stats.deleted,
+                            res.stats.failed
+                        );
+                        job.config.cursor = res.cursor;
+                        job.add_run(res.stats);
+                        *config_changed = true;
+                    }
+                    *state_data = call_states::Mirror::Ok;
+                    *remove_call_info = true;
+                }
+                call_messages::Mirror::Finished { result: Err(e) } => {
+                    if let Some(job) = job {
+                        let now = chrono::Utc::now();
+                        job.add_run(config::MirrorRunStats {
+                            started: job.started.unwrap_or(now),
+                            finished: now,
+                            full: job.config.cursor.is_empty(),
+                            downloaded: 0,
+                            downloaded_bytes: 0,
+                            deleted: 0,
+                            failed: 0,
+                            error: Some(e.to_string()),
+                        });
+                        *config_changed = true;
+                    }
+                    *state_data = call_states::Mirror::Failed(e);
+                    *remove_call_info = true;
+                }
+            }
+        } else {
+            panic!("{:?}", state.data);
+        }
+    }
+
+    fn process_mirror_msg_new_state(&mut self, call_id: u64, msg: call_messages::Mirror) {
+        if let call_messages::Mirror::Started { handle, name } = msg {
+            self.call_states.insert(
+                call_id,
+                call_states::State {
+                    handles: vec![handle],
+                    cancellers: vec![],
+                    data: call_states::Data::Mirror {
+                        data: call_states::Mirror::InProgress {
+                            listed: 0,
+                            downloaded: 0,
+                        },
+                        name,
+                    },
+                },
+            );
+        } else {
+            log::debug!("unknown call_id: {call_id}");
+        }
+    }
+
+    fn process_sync_msg(
+        syncs: &mut [SyncJobState],
+        config_changed: &mut bool,
+        _call_id: u64,
+        msg: call_messages::Sync,
+        state: &mut call_states::State,
+        remove_call_info: &mut bool,
+    ) {
+        if let call_states::Data::Sync {
+            data: ref mut state_data,
+            ref name,
+        } = state.data
+        {
+            let job = syncs.iter_mut().find(|job| job.config.name.eq(name));
+            match msg {
+                call_messages::Sync::Started { .. } => panic!("started"),
+                call_messages::Sync::RefreshToken => {
+                    *state_data = call_states::Sync::RefreshToken;
+                }
+                call_messages::Sync::RefreshTokenComplete => {
+                    *state_data = call_states::Sync::RefreshTokenComplete;
+                }
+                call_messages::Sync::Progress { done, total } => {
+                    if let Some(job) = job {
+                        job.done = done;
+                        job.total = total;
+                    }
+                    *state_data = call_states::Sync::InProgress { done, total };
+                }
+                call_messages::Sync::Finished { result: Ok(res) } => {
+                    if let Some(job) = job {
+                        log::info!(
+                            "sync '{}': uploaded {}, downloaded {}, conflicts {}, failed {}",
+                            name,
+                            res.stats.uploaded,
+                            res.stats.downloaded,
+                            res.stats.conflicts,
+                            res.stats.failed
+                        );
+                        job.config.cursor = res.cursor;
+                        job.add_run(res.stats);
+                        *config_changed = true;
+                    }
+                    *state_data = call_states::Sync::Ok;
+                    *remove_call_info = true;
+                }
+                call_messages::Sync::Finished { result: Err(e) } => {
+                    if let Some(job) = job {
+                        let now = chrono::Utc::now();
+                        job.add_run(config::SyncRunStats {
+                            started: job.started.unwrap_or(now),
+                            finished: now,
+                            full: job.config.cursor.is_empty(),
+                            uploaded: 0,
+                            downloaded: 0,
+                            deleted_local: 0,
+                            deleted_remote: 0,
+                            conflicts: 0,
+                            failed: 0,
+                            error: Some(e.to_string()),
+                        });
+                        *config_changed = true;
+                    }
+                    *state_data = call_states::Sync::Failed(e);
+                    *remove_call_info = true;
+                }
+            }
+        } else {
+            panic!("{:?}", state.data);
+        }
+    }
+
+    fn process_sync_msg_new_state(&mut self, call_id: u64, msg: call_messages::Sync) {
+        if let call_messages::Sync::Started { handle, name } = msg {
+            self.call_states.insert(
+                call_id,
+                call_states::State {
+                    handles: vec![handle],
+                    cancellers: vec![],
+                    data: call_states::Data::Sync {
+                        data: call_states::Sync::InProgress { done: 0, total: 0 },
+                        name,
+                    },
+                },
+            );
+        } else {
+            log::debug!("unknown call_id: {call_id}");
+        }
+    }
+
+    fn process_backup_msg(
+        backups: &mut [BackupJobState],
+        config_changed: &mut bool,
+        _call_id: u64,
+        msg: call_messages::Backup,
+        state: &mut call_states::State,
+        remove_call_info: &mut bool,
+    ) {
+        if let call_states::Data::Backup {
+            data: ref mut state_data,
+            ref name,
+        } = state.data
+        {
+            let job = backups.iter_mut().find(|job| job.config.name.eq(name));
+            match msg {
+                call_messages::Backup::Started { .. } => panic!("started"),
+                call_messages::Backup::RefreshToken => {
+                    *state_data = call_states::Backup::RefreshToken;
+                }
+                call_messages::Backup::RefreshTokenComplete => {
+                    *state_data = call_states::Backup::RefreshTokenComplete;
+                }
+                call_messages::Backup::Progress { done, total } => {
+                    if let Some(job) = job {
+                        job.done = done;
+                        job.total = total;
+                    }
+                    *state_data = call_states::Backup::InProgress { done, total };
+                }
+                call_messages::Backup::Finished { result: Ok(res) } => {
+                    if let Some(job) = job {
+                        log::info!(
+                            "backup '{}': uploaded {}, unchanged {}, skipped {}, failed {}",
+                            name,
+                            res.stats.uploaded,
+                            res.stats.unchanged,
+                            res.stats.skipped,
+                            res.stats.failed
+                        );
+                        job.skipped = res.skipped;
+                        job.failed = res.failed;
+                        job.add_run(res.stats);
+                        *config_changed = true;
+                    }
+                    *state_data = call_states::Backup::Ok;
+                    *remove_call_info = true;
+                }
+                call_messages::Backup::Finished { result: Err(e) } => {
+                    if let Some(job) = job {
+                        let now = chrono::Utc::now();
+                        job.skipped.clear();
+                        job.failed.clear();
+                        // partial manifest is written when the run was stopped by error
+                        let manifest = job.manifest_file.take().filter(|file| file.exists());
+                        job.add_run(config::BackupRunStats {
+                            started: job.started.unwrap_or(now),
+                            finished: now,
+                            uploaded: 0,
+                            uploaded_bytes: 0,
+                            unchanged: 0,
+                            skipped: 0,
+                            failed: 0,
+                            manifest,
+                            error: Some(e.to_string()),
+                        });
+                        *config_changed = true;
+                    }
+                    *state_data = call_states::Backup::Failed(e);
+                    *remove_call_info = true;
+                }
+            }
+        } else {
+            panic!("{:?}", state.data);
+        }
+    }
+
+    fn process_backup_msg_new_state(&mut self, call_id: u64, msg: call_messages::Backup) {
+        if let call_messages::Backup::Started { handle, name } = msg {
+            self.call_states.insert(
+                call_id,
+                call_states::State {
+                    handles: vec![handle],
+                    cancellers: vec![],
+                    data: call_states::Data::Backup {
+                        data: call_states::Backup::InProgress { done: 0, total: 0 },
+                        name,
+                    },
+                },
+            );
+        } else {
+            log::debug!("unknown call_id: {call_id}");
+        }
+    }
+
+    fn process_compare_msg(
+        visual_state: &mut StorageVisualState,
+        _call_id: u64,
+        msg: call_messages::Compare,
+        state: &mut call_states::State,
+        remove_call_info: &mut bool,
+    ) {
+        if let call_states::Data::Compare {
+            data: ref mut state_data,
+            ref local_path,
+        } = state.data
+        {
+            let compare = visual_state
+                .compare
+                .as_mut()
+                .filter(|compare| compare.local_path.eq(local_path));
+            match msg {
+                call_messages::Compare::Start { .. } => panic!("start"),
+                call_messages::Compare::Started { .. } => panic!("started"),
+                call_messages::Compare::Progress { count } => {
+                    if let Some(compare) = compare {
+                        compare.checked = count;
+                    }
+                    *state_data = call_states::Compare::InProgress { count };
+                }
+                call_messages::Compare::Finished { result: Ok(res) } => {
+                    if let Some(compare) = compare {
+                        compare.in_progress = false;
+                        compare.entries = Some(res.entries);
+                    }
+                    *state_data = call_states::Compare::Ok;
+                    *remove_call_info = true;
+                }
+                call_messages::Compare::Finished { result: Err(e) } => {
+                    if let Some(compare) = compare {
+                        compare.in_progress = false;
+                        compare.error = Some(e.to_string());
+                    }
+                    *state_data = call_states::Compare::Failed(e);
+                }
+            }
+        } else {
+            panic!("{:?}", state.data);
+        }
+    }
+
+    fn process_compare_msg_new_state(&mut self, call_id: u64, msg: call_messages::Compare) {
+        if let call_messages::Compare::Started { handle, local_path } = msg {
+            self.call_states.insert(
+                call_id,
+                call_states::State {
+                    handles: vec![handle],
+                    cancellers: vec![],
+                    data: call_states::Data::Compare {
+                        data: call_states::Compare::InProgress { count: 0 },
+                        local_path,
+                    },
+                },
+            );
+        } else {
+            log::debug!("unknown call_id: {call_id}");
+        }
+    }
+
+    fn process_list_recursive_msg(
+        visual_state: &mut StorageVisualState,
+        _call_id: u64,
+        msg: call_messages::ListRecursive,
+        state: &mut call_states::State,
+        messages: MessagesSender<call_messages::Message>,
+        remove_call_info: &mut bool,
+    ) {
+        if let call_states::Data::ListRecursive {
+            data: ref mut state_data,
+            ref path,
+            purpose,
+        } = state.data
+        {
+            let analyze = match purpose {
+                storage_models::ListRecursivePurpose::Analyze => visual_state
+                    .analyze
+                    .as_mut()
+                    .filter(|analyze| analyze.path.eq(path)),
+                _ => None,
+            };
+            let duplicates = match purpose {
+                storage_models::ListRecursivePurpose::Duplicates => visual_state
+                    .duplicates
+                    .as_mut()
+                    .filter(|duplicates| duplicates.path.eq(path)),
+                _ => None,
+            };
+            let compare = match purpose {
+                storage_models::ListRecursivePurpose::Compare => visual_state
+                    .compare
+                    .as_mut()
+                    .filter(|compare| compare.remote_path.eq(path)),
+                _ => None,
+            };
+            match msg {
+                call_messages::ListRecursive::Started { .. } => panic!("started"),
+                call_messages::ListRecursive::RefreshToken => {
+                    *state_data = call_states::ListRecursive::RefreshToken;
+                }
+                call_messages::ListRecursive::RefreshTokenComplete => {
+                    *state_data = call_states::ListRecursive::RefreshTokenComplete;
+                }
+                call_messages::ListRecursive::Progress { count } => {
+                    if let Some(analyze) = analyze {
+                        analyze.count = count;
+                    }
+                    if let Some(duplicates) = duplicates {
+                        duplicates.count = count;
+                    }
+                    if let Some(compare) = compare {
+                        compare.listed = count;
+                    }
+                    *state_data = call_states::ListRecursive::InProgress { count };
+                }
+                call_messages::ListRecursive::Finished {
+                    result: Ok(mut res),
+                } => {
+                    if let Some(analyze) = analyze {
+                        analyze.count = res.items.len() as u64;
+                        let mut root = analyze::build_tree(&res.path, &res.items);
+                        if analyze.sort != analyze::SizeSort::Size {
+                            root.sort(analyze.sort);
+                        }
+                        analyze.root = Some(root);
+                    }
+                    if let Some(duplicates) = duplicates {
+                        duplicates.count = res.items.len() as u64;
+                        duplicates.groups =
+                            Some(analyze::find_duplicates(std::mem::take(&mut res.items)));
+                    }
+                    if let Some(compare) = compare {
+                        compare.listed = res.items.len() as u64;
+                        let _ = messages.send(call_messages::Message {
+                            call_id: None,
+                            data: call_messages::Data::Compare(call_messages::Compare::Start {
+                                local_path: compare.local_path.clone(),
+                                remote_path: res.path.clone(),
+                                remote_items: std::mem::take(&mut res.items),
+                            }),
+                        });
+                    }
+                    *state_data = call_states::ListRecursive::Ok;
+                    *remove_call_info = true;
+                }
+                call_messages::ListRecursive::Finished { result: Err(e) } => {
+                    if let Some(analyze) = analyze {
+                        analyze.error = Some(e.to_string());
+                    }
+                    if let Some(duplicates) = duplicates {
+                        duplicates.error = Some(e.to_string());
+                    }
+                    if let Some(compare) = compare {
+                        compare.in_progress = false;
+                        compare.error = Some(e.to_string());
+                    }
+                    *state_data = call_states::ListRecursive::Failed(e);
+                }
+            }
+        } else {
+            panic!("{:?}", state.data);
+        }
+    }
+
+    fn process_list_recursive_msg_new_state(
+        &mut self,
+        call_id: u64,
+        msg: call_messages::ListRecursive,
+    ) {
+        if let call_messages::ListRecursive::Started {
+            handle,
+            path,
+            purpose,
+        } = msg
+        {
+            self.call_states.insert(
+                call_id,
+                call_states::State {
+                    handles: vec![handle],
+                    cancellers: vec![],
+                    data: call_states::Data::ListRecursive {
+                        data: call_states::ListRecursive::InProgress { count: 0 },
+                        path,
+                        purpose,
+                    },
+                },
+            );
+        } else {
+            log::debug!("unknown call_id: {call_id}");
+        }
+    }
+
+    fn process_account_info_msg(
+        visual_state: &mut StorageVisualState,
+        _call_id: u64,
+        msg: call_messages::AccountInfo,
+        state: &mut call_states::State,
+        remove_call_info: &mut bool,
+    ) {
+        if let call_states::Data::AccountInfo {
+            data: ref mut state_data,
+        } = state.data
+        {
+            match msg {
+                call_messages::AccountInfo::Start => panic!("start"),
+                call_messages::AccountInfo::Started { .. } => panic!("started"),
+                call_messages::AccountInfo::RefreshToken => {
+                    *state_data = call_states::AccountInfo::RefreshToken;
+                }
+                call_messages::AccountInfo::RefreshTokenComplete => {
+                    *state_data = call_states::AccountInfo::RefreshTokenComplete;
+                }
+                call_messages::AccountInfo::Finished { result: Ok(res) } => {
+                    if let Some(expected) = &res.expected_account_id {
+                        log::warn!(
+                            "tokens belong to account '{}', expected '{}'",
+                            &res.account_id,
+                            expected
+                        );
+                    }
+                    visual_state.account = Some(res);
+                    *state_data = call_states::AccountInfo::Ok;
+                    *remove_call_info = true;
+                }
+                call_messages::AccountInfo::Finished { result: Err(e) } => {
+                    *state_data = call_states::AccountInfo::Failed(e);
+                }
+            }
+        } else {
+            panic!("{:?}", state.data);
+        }
+    }
+
+    fn process_account_info_msg_new_state(
+        &mut self,
+        call_id: u64,
+        msg: call_messages::AccountInfo,
+    ) {
+        if let call_messages::AccountInfo::Started { handle } = msg {
+            self.call_states.insert(
+                call_id,
+                call_states::State {
+                    handles: vec![handle],
+                    cancellers: vec![],
+                    data: call_states::Data::AccountInfo {
+                        data: call_states::AccountInfo::InProgress,
+                    },
+                },
+            );
+        } else {
+            log::debug!("unknown call_id: {call_id}");
+        }
+    }
+
+    fn process_temporary_link_msg(
+        visual_state: &mut StorageVisualState,
+        _call_id: u64,
+        msg: call_messages::TemporaryLink,
+        state: &mut call_states::State,
+        remove_call_info: &mut bool,
+    ) {
+        if let call_states::Data::TemporaryLink {
+            data: ref mut state_data,
+            ..
+        } = state.data
+        {
+            match msg {
+                call_messages::TemporaryLink::Started { .. } => panic!("started"),
+                call_messages::TemporaryLink::RefreshToken => {
+                    *state_data = call_states::TemporaryLink::RefreshToken;
+                }
+                call_messages::TemporaryLink::RefreshTokenComplete => {
+                    *state_data = call_states::TemporaryLink::RefreshTokenComplete;
+                }
+                call_messages::TemporaryLink::Finished { result: Ok(res) } => {
+                    match res.purpose {
+                        storage_models::TemporaryLinkPurpose::CopyToClipboard => {
+                            visual_state.copy_to_clipboard = Some(res.link);
+                        }
+                        storage_models::TemporaryLinkPurpose::OpenExternally => {
+                            log::debug!("open link: {}", &res.link);
+                            let _ = open::that_in_background(res.link);
+                        }
+                    }
+                    *state_data = call_states::TemporaryLink::Ok;
+                    *remove_call_info = true;
+                }
+                call_messages::TemporaryLink::Finished { result: Err(e) } => {
+                    *state_data = call_states::TemporaryLink::Failed(e);
+                }
+            }
+        } else {
+            panic!("{:?}", state.data);
+        }
+    }
+
+    fn process_temporary_link_msg_new_state(
+        &mut self,
+        call_id: u64,
+        msg: call_messages::TemporaryLink,
+    ) {
+        if let call_messages::TemporaryLink::Started { handle, path } = msg {
+            self.call_states.insert(
+                call_id,
+                call_states::State {
+                    handles: vec![handle],
+                    cancellers: vec![],
+                    data: call_states::Data::TemporaryLink {
+                        data: call_states::TemporaryLink::InProgress,
+                        path,
+                    },
+                },
+            );
+        } else {
+            log::debug!("unknown call_id: {call_id}");
+        }
+    }
+
+    fn process_restore_msg(
+        visual_state: &mut StorageVisualState,
+        _call_id: u64,
+        msg: call_messages::Restore,
+        state: &mut call_states::State,
+        remove_call_info: &mut bool,
+    ) {
+        if let call_states::Data::Restore {
+            data: ref mut state_data,
+            ref path,
+        } = state.data
+        {
+            match msg {
+                call_messages::Restore::Started { .. } => panic!("started"),
+                call_messages::Restore::RefreshToken => {
+                    *state_data = call_states::Restore::RefreshToken;
+                }
+                call_messages::Restore::RefreshTokenComplete => {
+                    *state_data = call_states::Restore::RefreshTokenComplete;
+                }
+                call_messages::Restore::Finished { result: Ok(res) } => {
+                    if let Some(revisions) = &mut visual_state.revisions {
+                        if revisions.path.eq(path) {
+                            revisions.is_deleted = false;
+                            revisions.restored_rev = Some(res.item.rev.clone());
+                        }
+                    }
+                    if let Some(folder) = &mut visual_state.folder {
+                        if folder.path.eq(&Self::parent_path(path.clone())) {
+                            folders_cache::apply_deltas(
+                                &mut folder.items,
+                                vec![storage_models::ItemDelta::Changed(res.item)],
+                            );
+                        }
+                    }
+                    *state_data = call_states::Restore::Ok;
+                    *remove_call_info = true;
+                }
+                call_messages::Restore::Finished { result: Err(e) } => {
+                    if let Some(revisions) = &mut visual_state.revisions {
+                        if revisions.path.eq(path) {
+                            revisions.error = Some(e.to_string());
+                        }
+                    }
+                    *state_data = call_states::Restore::Failed(e);
+                }
+            }
+        } else {
+            panic!("{:?}", state.data);
+        }
+    }
+
+    fn process_restore_msg_new_state(&mut self, call_id: u64, msg: call_messages::Restore) {
+        if let call_messages::Restore::Started { handle, path } = msg {
+            self.call_states.insert(
+                call_id,
+                call_states::State {
+                    handles: vec![handle],
+                    cancellers: vec![],
+                    data: call_states::Data::Restore {
+                        data: call_states::Restore::InProgress,
+                        path,
+                    },
+                },
+            );
+        } else {
+            log::debug!("unknown call_id: {call_id}");
+        }
+    }
+
+    fn process_webdav_msg(
+        _call_id: u64,
+        msg: call_messages::WebDav,
+        state: &mut call_states::State,
+        remove_call_info: &mut bool,
+    ) {
+        if let call_states::Data::WebDav {
+            data: ref mut state_data,
+            ref mut addr,
+            ref mut served,
+            ref mut last_request,
+            ..
+        } = state.data
+        {
+            match msg {
+                call_messages::WebDav::Started { .. } => panic!("started"),
+                call_messages::WebDav::Binded { addr: value } => {
+                    *addr = Some(value);
+                    *state_data = call_states::WebDav::Serving;
+                }
+                call_messages::WebDav::LocalServerShutdowner {
+                    cancel_informer,
+                    cancel_awaiter,
+                } => {
+                    state.cancellers.push(call_states::Canceller {
+                        cancel_informer,
+                        cancel_awaiter,
+                    });
+                }
+                call_messages::WebDav::Served { request } => {
+                    *served += 1;
+                    *last_request = Some(request);
+                }
+                call_messages::WebDav::RefreshToken => {
+                    *state_data = call_states::WebDav::RefreshToken;
+                }
+                call_messages::WebDav::RefreshTokenComplete => {
+                    *state_data = call_states::WebDav::Serving;
+                }
+                call_messages::WebDav::Finished { result: Ok(()) } => {
+                    *remove_call_info = true;
+                }
+                call_messages::WebDav::Finished { result: Err(e) } => {
+                    log::error!("WebDAV server failed: {}", e);
+                    *state_data = call_states::WebDav::Failed(e);
+                }
+            }
+        } else {
+            panic!("{:?}", state.data);
+        }
+    }
+
+    fn process_webdav_msg_new_state(&mut self, call_id: u64, msg: call_messages::WebDav) {
+        if let call_messages::WebDav::Started { handle, port } = msg {
+            // stopped before start
+            if self.webdav_call_id != Some(call_id) {
+                handle.abort();
+                return;
+            }
+            self.call_states.insert(
+                call_id,
+                call_states::State {
+                    handles: vec![handle],
+                    cancellers: vec![],
+                    data: call_states::Data::WebDav {
+                        data: call_states::WebDav::Starting,
+                        port,
+                        addr: None,
+                        served: 0,
+                        last_request: None,
+                    },
+                },
+            );
+        } else {
+            log::debug!("unknown call_id: {call_id}");
+        }
+    }
+
+    fn process_stream_msg(
+        _call_id: u64,
+        msg: call_messages::Stream,
+        state: &mut call_states::State,
+    ) {
+        if let call_states::Data::Stream {
+            data: ref mut state_data,
+        } = state.data
+        {
+            match msg {
+                call_messages::Stream::RefreshToken => {
+                    *state_data = call_states::Stream::RefreshToken;
+                }
+                call_messages::Stream::RefreshTokenComplete => {
+                    *state_data = call_states::Stream::Serving;
+                }
+            }
+        } else {
+            panic!("{:?}", state.data);
+        }
+    }
+
+    fn process_auth_msg(
+        visual_state: &mut StorageVisualState,
+        call_id: u64,
+        msg: call_messages::Auth,
+        state: &mut call_states::State,
+        messages: MessagesSender<call_messages::Message>,
+        remove_call_info: &mut bool,
+        rth: RuntimeHolder,
+        auth_info_holder: AuthInfoHolder,
+    ) {
+        if let call_states::Data::Auth {
+            data: ref mut state_data,
+        } = state.data
+        {
+            match msg {
+                call_messages::Auth::Start => panic!("start"),
+                call_messages::Auth::Binded {
+                    redirect_url,
+                    addr: _,
+                    state_field,
+                } => {
+                    let _ = messages.send(call_messages::Message {
+                        call_id: Some(call_id),
+                        data: call_messages::Data::Auth(call_messages::Auth::CheckAvailability {
+                            redirect_url: redirect_url.clone(),
+                            state_field,
+                        }),
+                    });
+                    *state_data =
+                        call_states::Auth::InProgress(call_states::AuthProgress::Binded {
+                            redirect_url,
+                        });
+                }
+                call_messages::Auth::LocalServerShutdowner {
+                    cancel_informer,
+                    cancel_awaiter,
+                } => {
+                    state.cancellers.push(call_states::Canceller {
+                        cancel_informer,
+                        cancel_awaiter,
+                    });
+                }
+                call_messages::Auth::CheckAvailability {
+                    redirect_url,
+                    state_field,
+                } => {
+                    let redirect_url_clone = redirect_url.clone();
+                    let (s, r) = tokio::sync::oneshot::channel::<()>();
+                    if let Ok(handle) = rth.clone().spawn(async move {
+                        let _ = r.await;
+                        let uri: http::Uri = redirect_url_clone.parse().unwrap();
+                        let mut parts = uri.into_parts();
+                        parts.path_and_query = Some(http::uri::PathAndQuery::from_static("/ping"));
+                        let ping_uri = http::Uri::from_parts(parts).expect("ping_uri");
+                        let client = Self::client_http_or_https(&rth);
+                        loop {
+                            let start = std::time::Instant::now();
+                            match client.get(ping_uri.clone()).await {
+                                Ok(_response) => {
+                                    let _ = messages.send(call_messages::Message {
+                                        call_id: Some(call_id),
+                                        data: call_messages::Data::Auth(
+                                            call_messages::Auth::ServerReady {
+                                                redirect_url: redirect_url_clone,
+                                                state_field,
+                                            },
+                                        ),
+                                    });
+                                    break;
+                                }
+                                Err(e) => {
+                                    log::debug!("ping failed for now: {}", e);
+                                    if start.elapsed() > std::time::Duration::from_secs(10) {
+                                        let _ = messages.send(call_messages::Message {
+                                            call_id: Some(call_id),
+                                            data: call_messages::Data::Auth(
+                                                call_messages::Auth::Finished {
+                                                    result: Err(
+                                                        storage_models::AuthError::LocalServerWaitForAvailabileFailed
+                                                    )
+                                                },
+                                            ),
+                                        });
+                                        break;
+                                    }
+                                }
+                            }
+                        }
+                    }) {
+                        state.handles.push(handle);
+                        *state_data = call_states::Auth::InProgress(
+                            call_states::AuthProgress::WaitingForLocalServerUp {
+                                redirect_url,
+                            },
+                        );
+                        let _ = s.send(());
+                    }
+                }
+                call_messages::Auth::ServerReady {
+                    redirect_url,
+                    state_field,
+                } => {
+                    if let Ok(client_id) = rth.block_on(async move {
+                        let res = auth_info_holder.read().await;
+                        res.client_id.clone()
+                    }) {
+                        let params = format!(
+                            "client_id={}&response_type={}&state={}&token_access_type={}&redirect_uri={}",
+                            client_id,
+                            "code",
+                            state_field,
+                            "offline",
+                            redirect_url
+                        );
+                        let auth_url: String =
+                            "https://www.dropbox.com/oauth2/authorize?".to_string() + &params;
+                        log::debug!("auth url: {}", &auth_url);
                         *state_data = call_states::Auth::InProgress(
                             call_states::AuthProgress::TryOpenBrowser {
                                 auth_url: auth_url.clone(),
@@ -437,6 +2510,10 @@ impl StorageInstance {
                             path: "".to_string(),
                         }),
                     });
+                    let _ = messages.send(call_messages::Message {
+                        call_id: None,
+                        data: call_messages::Data::AccountInfo(call_messages::AccountInfo::Start),
+                    });
 
                     *remove_call_info = true;
                 }
@@ -491,9 +2568,153 @@ impl StorageInstance {
                             call_id,
                             data,
                             state,
+                            self.messages.sender.clone(),
                             &mut remove_call_info,
                         ),
                         call_messages::Data::DownloadFile(data) => Self::process_download_file_msg(
+                            &mut self.edited_files,
+                            call_id,
+                            data,
+                            state,
+                            &mut remove_call_info,
+                        ),
+                        call_messages::Data::GetMetadata(data) => Self::process_get_metadata_msg(
+                            &mut self.visual_state,
+                            call_id,
+                            data,
+                            state,
+                            &mut remove_call_info,
+                        ),
+                        call_messages::Data::GetThumbnails(data) => {
+                            Self::process_get_thumbnails_msg(
+                                &mut self.visual_state,
+                                call_id,
+                                data,
+                                state,
+                                &mut remove_call_info,
+                            )
+                        }
+                        call_messages::Data::Preview(data) => Self::process_preview_msg(
+                            &mut self.visual_state,
+                            call_id,
+                            data,
+                            state,
+                            &mut remove_call_info,
+                        ),
+                        call_messages::Data::Search(data) => Self::process_search_msg(
+                            &mut self.visual_state,
+                            call_id,
+                            data,
+                            state,
+                            &mut remove_call_info,
+                        ),
+                        call_messages::Data::WatchFolder(data) => Self::process_watch_folder_msg(
+                            &mut self.visual_state,
+                            call_id,
+                            data,
+                            state,
+                            &mut remove_call_info,
+                        ),
+                        call_messages::Data::ListRevisions(data) => {
+                            Self::process_list_revisions_msg(
+                                &mut self.visual_state,
+                                call_id,
+                                data,
+                                state,
+                                &mut remove_call_info,
+                            )
+                        }
+                        call_messages::Data::SharedLinks(data) => Self::process_shared_links_msg(
+                            &mut self.visual_state,
+                            call_id,
+                            data,
+                            state,
+                            &mut remove_call_info,
+                        ),
+                        call_messages::Data::TemporaryLink(data) => {
+                            Self::process_temporary_link_msg(
+                                &mut self.visual_state,
+                                call_id,
+                                data,
+                                state,
+                                &mut remove_call_info,
+                            )
+                        }
+                        call_messages::Data::AccountInfo(data) => Self::process_account_info_msg(
+                            &mut self.visual_state,
+                            call_id,
+                            data,
+                            state,
+                            &mut remove_call_info,
+                        ),
+                        call_messages::Data::ListRecursive(data) => {
+                            Self::process_list_recursive_msg(
+                                &mut self.visual_state,
+                                call_id,
+                                data,
+                                state,
+                                self.messages.sender.clone(),
+                                &mut remove_call_info,
+                            )
+                        }
+                        call_messages::Data::FileOps(data) => Self::process_file_ops_msg(
+                            &mut self.visual_state,
+                            call_id,
+                            data,
+                            state,
+                            &mut remove_call_info,
+                        ),
+                        call_messages::Data::Transfer(data) => Self::process_transfer_msg(
+                            &mut self.visual_state,
+                            &mut self.edited_files,
+                            call_id,
+                            data,
+                            state,
+                            &mut remove_call_info,
+                        ),
+                        call_messages::Data::Compare(data) => Self::process_compare_msg(
+                            &mut self.visual_state,
+                            call_id,
+                            data,
+                            state,
+                            &mut remove_call_info,
+                        ),
+                        call_messages::Data::Mirror(data) => Self::process_mirror_msg(
+                            &mut self.mirrors,
+                            &mut self.config_changed,
+                            call_id,
+                            data,
+                            state,
+                            &mut remove_call_info,
+                        ),
+                        call_messages::Data::Sync(data) => Self::process_sync_msg(
+                            &mut self.syncs,
+                            &mut self.config_changed,
+                            call_id,
+                            data,
+                            state,
+                            &mut remove_call_info,
+                        ),
+                        call_messages::Data::Backup(data) => Self::process_backup_msg(
+                            &mut self.backups,
+                            &mut self.config_changed,
+                            call_id,
+                            data,
+                            state,
+                            &mut remove_call_info,
+                        ),
+                        call_messages::Data::WebDav(data) => {
+                            Self::process_webdav_msg(call_id, data, state, &mut remove_call_info)
+                        }
+                        call_messages::Data::Stream(data) => {
+                            Self::process_stream_msg(call_id, data, state)
+                        }
+                        // sent without call_id
+                        call_messages::Data::Control(_) => {
+                            log::error!("control message with call_id: {call_id}");
+                        }
+                        call_messages::Data::Restore(data) => Self::process_restore_msg(
+                            &mut self.visual_state,
                             call_id,
                             data,
                             state,
@@ -518,6 +2739,63 @@ impl StorageInstance {
                         call_messages::Data::DownloadFile(data) => {
                             self.process_download_file_msg_new_state(call_id, data)
                         }
+                        call_messages::Data::GetMetadata(data) => {
+                            self.process_get_metadata_msg_new_state(call_id, data)
+                        }
+                        call_messages::Data::GetThumbnails(data) => {
+                            self.process_get_thumbnails_msg_new_state(call_id, data)
+                        }
+                        call_messages::Data::Preview(data) => {
+                            self.process_preview_msg_new_state(call_id, data)
+                        }
+                        call_messages::Data::Search(data) => {
+                            self.process_search_msg_new_state(call_id, data)
+                        }
+                        call_messages::Data::WatchFolder(data) => {
+                            self.process_watch_folder_msg_new_state(call_id, data)
+                        }
+                        call_messages::Data::ListRevisions(data) => {
+                            self.process_list_revisions_msg_new_state(call_id, data)
+                        }
+                        call_messages::Data::Restore(data) => {
+                            self.process_restore_msg_new_state(call_id, data)
+                        }
+                        call_messages::Data::SharedLinks(data) => {
+                            self.process_shared_links_msg_new_state(call_id, data)
+                        }
+                        call_messages::Data::TemporaryLink(data) => {
+                            self.process_temporary_link_msg_new_state(call_id, data)
+                        }
+                        call_messages::Data::AccountInfo(data) => {
+                            self.process_account_info_msg_new_state(call_id, data)
+                        }
+                        call_messages::Data::ListRecursive(data) => {
+                            self.process_list_recursive_msg_new_state(call_id, data)
+                        }
+                        call_messages::Data::FileOps(data) => {
+                            self.process_file_ops_msg_new_state(call_id, data)
+                        }
+                        call_messages::Data::Transfer(data) => {
+                            self.process_transfer_msg_new_state(call_id, data)
+                        }
+                        call_messages::Data::Compare(data) => {
+                            self.process_compare_msg_new_state(call_id, data)
+                        }
+                        call_messages::Data::Mirror(data) => {
+                            self.process_mirror_msg_new_state(call_id, data)
+                        }
+                        call_messages::Data::Sync(data) => {
+                            self.process_sync_msg_new_state(call_id, data)
+                        }
+                        call_messages::Data::Backup(data) => {
+                            self.process_backup_msg_new_state(call_id, data)
+                        }
+                        call_messages::Data::WebDav(data) => {
+                            self.process_webdav_msg_new_state(call_id, data)
+                        }
+                        call_messages::Data::Stream(_) | call_messages::Data::Control(_) => {
+                            log::debug!("unknown call_id: {call_id}");
+                        }
                         call_messages::Data::Auth(data) => {
                             self.process_auth_msg_new_state(call_id, data)
                         }
@@ -535,14 +2813,223 @@ impl StorageInstance {
                         call_messages::ListFolder::Start { path } => self.nav_to(path),
                         _ => panic!(),
                     },
+                    call_messages::Data::WatchFolder(call_messages::WatchFolder::Start {
+                        path,
+                        cursor,
+                    }) => self.action(StorageAction::WatchFolder { path, cursor }, None),
+                    call_messages::Data::AccountInfo(call_messages::AccountInfo::Start) => {
+                        self.load_account_info()
+                    }
+                    call_messages::Data::Compare(call_messages::Compare::Start {
+                        local_path,
+                        remote_path,
+                        remote_items,
+                    }) => {
+                        let in_data = storage_models::compare_in_data {
+                            local_path,
+                            remote_path,
+                            remote_items,
+                        };
+                        self.action(StorageAction::Compare { in_data }, None)
+                    }
+                    call_messages::Data::Control(call_messages::Control::Request {
+                        request,
+                        reply,
+                    }) => {
+                        let _ = reply.send(self.control(request));
+                    }
                     _ => panic!(),
                 }
             }
         }
     }
 
+    /// request of control api, calls are started with call_id returned to the client
+    fn control(
+        &mut self,
+        request: call_messages::ControlRequest,
+    ) -> call_messages::ControlResponse {
+        match request {
+            call_messages::ControlRequest::Storages => call_messages::ControlResponse::Error {
+                message: "storages are listed by control server".to_string(),
+            },
+            call_messages::ControlRequest::Folder => match &self.visual_state.folder {
+                Some(folder) => call_messages::ControlResponse::Folder {
+                    path: folder.path.clone(),
+                    items: folder.items.clone(),
+                },
+                None => call_messages::ControlResponse::Error {
+                    message: "folder is not loaded".to_string(),
+                },
+            },
+            call_messages::ControlRequest::Navigate { path } => {
+                let call_id = self.gen_call_id();
+                self.action(
+                    StorageAction::To {
+                        path: path.trim_matches('/').to_string(),
+                    },
+                    Some(call_id),
+                );
+                call_messages::ControlResponse::Started { call_id }
+            }
+            call_messages::ControlRequest::Download { path, to_path } => {
+                let path = path.trim_matches('/').to_string();
+                let name = path.rsplit('/').next().unwrap_or_default();
+                if name.is_empty() {
+                    return call_messages::ControlResponse::Error {
+                        message: "path of file is required".to_string(),
+                    };
+                }
+                // only into download folder of the storage
+                let to_path = match to_path {
+                    Some(to_path)
+                        if !to_path.is_empty()
+                            && std::path::Path::new(&to_path)
+                                .components()
+                                .all(|c| matches!(c, std::path::Component::Normal(_))) =>
+                    {
+                        self.save_to_path.clone() + &to_path
+                    }
+                    Some(to_path) => {
+                        return call_messages::ControlResponse::Error {
+                            message: format!(
+                                "to_path '{}' has to be relative to download folder, without '..'",
+                                to_path
+                            ),
+                        }
+                    }
+                    None => self.save_to_path.clone() + name,
+                };
+                let call_id = self.gen_call_id();
+                // files of api clients are not opened, nor uploaded back when changed
+                self.action(
+                    StorageAction::DownloadFile {
+                        path,
+                        to_path,
+                        open: false,
+                    },
+                    Some(call_id),
+                );
+                call_messages::ControlResponse::Started { call_id }
+            }
+            call_messages::ControlRequest::Transfers => {
+                let mut transfers: Vec<call_messages::ControlTransfer> = self
+                    .call_states
+                    .iter()
+                    .filter_map(|(call_id, state)| match &state.data {
+                        call_states::Data::DownloadFile {
+                            data,
+                            remote_path,
+                            local_path,
+                            size,
+                            downloaded,
+                            ..
+                        } => {
+                            let (state, error) = match data {
+                                call_states::DownloadFile::Ok => ("ok", None),
+                                call_states::DownloadFile::Failed(e) => {
+                                    ("failed", Some(e.to_string()))
+                                }
+                                call_states::DownloadFile::Started
+                                | call_states::DownloadFile::SizeInfo { .. } => ("started", None),
+                                call_states::DownloadFile::InProgress { .. }
+                                | call_states::DownloadFile::RefreshTokenComplete => {
+                                    ("in_progress", None)
+                                }
+                                call_states::DownloadFile::RefreshToken => ("refresh_token", None),
+                            };
+                            Some(call_messages::ControlTransfer {
+                                call_id: *call_id,
+                                remote_path: remote_path.clone(),
+                                local_path: local_path.clone(),
+                                size: *size,
+                                downloaded: *downloaded,
+                                state: state.to_string(),
+                                error,
+                            })
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                transfers.sort_by_key(|transfer| transfer.call_id);
+                call_messages::ControlResponse::Transfers { transfers }
+            }
+        }
+    }
+
+    /// storage driven by control api
+    pub fn control_target(&self) -> control::Target {
+        control::Target {
+            id: self.id.clone(),
+            caption: self.caption.clone(),
+            messages: self.messages.sender.clone(),
+        }
+    }
+
     pub fn prepare_visual_state(&mut self) {
         self.process_messages();
+        self.run_due_jobs();
+        self.check_edited_files();
+    }
+
+    /// marks opened files changed since download or last upload, uploads them if enabled
+    fn check_edited_files(&mut self) {
+        if self.edited_files_checked.elapsed() < EDITED_FILES_CHECK_INTERVAL {
+            return;
+        }
+        self.edited_files_checked = std::time::Instant::now();
+        // removed or moved away
+        self.edited_files
+            .retain(|file| std::path::Path::new(&file.local_path).is_file());
+        let mut modified = vec![];
+        for (idx, file) in self.edited_files.iter_mut().enumerate() {
+            if file.status != EditedFileStatus::Unchanged {
+                continue;
+            }
+            if let Some(state) = file.local_state() {
+                if state != (file.size, file.modified) {
+                    log::debug!("edited file changed: {}", &file.local_path);
+                    file.status = EditedFileStatus::Modified;
+                    modified.push(idx);
+                }
+            }
+        }
+        if self.auto_upload_edited
+            && !self.visual_state.auth_needed
+            && self.visual_state.auth_process_state.is_none()
+        {
+            for idx in modified {
+                self.upload_edited(idx);
+            }
+        }
+    }
+
+    fn run_due_jobs(&mut self) {
+        if self.visual_state.auth_needed || self.visual_state.auth_process_state.is_some() {
+            return;
+        }
+        let now = chrono::Utc::now();
+        let due: Vec<usize> = (0..self.mirrors.len())
+            .filter(|idx| self.mirrors[*idx].is_due(now))
+            .collect();
+        for idx in due {
+            self.run_mirror(idx);
+        }
+        let due: Vec<usize> = (0..self.syncs.len())
+            .filter(|idx| self.syncs[*idx].is_due(now))
+            .collect();
+        for idx in due {
+            self.run_sync(idx);
+        }
+        let due: Vec<usize> = (0..self.backups.len())
+            .filter(|idx| self.backups[*idx].is_due(now))
+            .collect();
+        for idx in due {
+            self.run_backup(idx);
+        }
+        if self.webdav.enabled && self.webdav_call_id.is_none() {
+            self.start_webdav();
+        }
     }
 
     fn random_string(n: usize) -> String {
@@ -600,6 +3087,49 @@ impl StorageInstance {
     }
 
     async fn int_nav_to(
+        rth: RuntimeHolder,
+        messages: MessagesSender<call_messages::Message>,
+        auth_info_holder: clouds::AuthInfoHolder,
+        storage_type: storage_models::StorageType,
+        in_data: storage_models::list_folder_in_data,
+        call_id: u64,
+    ) -> Result<(), AsyncRuntimeError> {
+        let call_in_data = storage_models::CallInData::list_folder(in_data);
+
+        storages::storage_call(
+            rth,
+            messages,
+            auth_info_holder,
+            storage_type,
+            call_in_data,
+            call_id,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn int_download_file(
+        rth: RuntimeHolder,
+        messages: MessagesSender<call_messages::Message>,
+        auth_info_holder: clouds::AuthInfoHolder,
+        storage_type: storage_models::StorageType,
+        in_data: storage_models::download_file_in_data,
+        call_id: u64,
+    ) -> Result<(), AsyncRuntimeError> {
+        let call_in_data = storage_models::CallInData::download_file(in_data);
+        storages::storage_call(
+            rth,
+            messages,
+            auth_info_holder,
+            storage_type,
+            call_in_data,
+            call_id,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn int_get_metadata(
         rth: RuntimeHolder,
         messages: MessagesSender<call_messages::Message>,
         auth_info_holder: clouds::AuthInfoHolder,
@@ -608,8 +3138,33 @@ impl StorageInstance {
         call_id: u64,
     ) -> Result<(), AsyncRuntimeError> {
         let call_in_data =
-            storage_models::CallInData::list_folder(storage_models::list_folder_in_data { path });
+            storage_models::CallInData::get_metadata(storage_models::get_metadata_in_data { path });
+        storages::storage_call(
+            rth,
+            messages,
+            auth_info_holder,
+            storage_type,
+            call_in_data,
+            call_id,
+        )
+        .await?;
+        Ok(())
+    }
 
+    async fn int_get_thumbnails(
+        rth: RuntimeHolder,
+        messages: MessagesSender<call_messages::Message>,
+        auth_info_holder: clouds::AuthInfoHolder,
+        storage_type: storage_models::StorageType,
+        entries: Vec<storage_models::ThumbnailRequest>,
+        cache: thumbnails::ThumbnailCache,
+        call_id: u64,
+    ) -> Result<(), AsyncRuntimeError> {
+        let call_in_data =
+            storage_models::CallInData::get_thumbnails(storage_models::get_thumbnails_in_data {
+                entries,
+                cache,
+            });
         storages::storage_call(
             rth,
             messages,
@@ -622,19 +3177,107 @@ impl StorageInstance {
         Ok(())
     }
 
-    async fn int_download_file(
+    async fn int_preview(
+        rth: RuntimeHolder,
+        messages: MessagesSender<call_messages::Message>,
+        auth_info_holder: clouds::AuthInfoHolder,
+        storage_type: storage_models::StorageType,
+        path: String,
+        max_bytes: u64,
+        call_id: u64,
+    ) -> Result<(), AsyncRuntimeError> {
+        let call_in_data = storage_models::CallInData::preview(storage_models::preview_in_data {
+            path,
+            max_bytes,
+        });
+        storages::storage_call(
+            rth,
+            messages,
+            auth_info_holder,
+            storage_type,
+            call_in_data,
+            call_id,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn int_search(
+        rth: RuntimeHolder,
+        messages: MessagesSender<call_messages::Message>,
+        auth_info_holder: clouds::AuthInfoHolder,
+        storage_type: storage_models::StorageType,
+        in_data: storage_models::search_in_data,
+        call_id: u64,
+    ) -> Result<(), AsyncRuntimeError> {
+        let call_in_data = storage_models::CallInData::search(in_data);
+        storages::storage_call(
+            rth,
+            messages,
+            auth_info_holder,
+            storage_type,
+            call_in_data,
+            call_id,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn int_watch_folder(
+        rth: RuntimeHolder,
+        messages: MessagesSender<call_messages::Message>,
+        auth_info_holder: clouds::AuthInfoHolder,
+        storage_type: storage_models::StorageType,
+        in_data: storage_models::watch_folder_in_data,
+        call_id: u64,
+    ) -> Result<(), AsyncRuntimeError> {
+        let call_in_data = storage_models::CallInData::watch_folder(in_data);
+        storages::storage_call(
+            rth,
+            messages,
+            auth_info_holder,
+            storage_type,
+            call_in_data,
+            call_id,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn int_restore(
+        rth: RuntimeHolder,
+        messages: MessagesSender<call_messages::Message>,
+        auth_info_holder: clouds::AuthInfoHolder,
+        storage_type: storage_models::StorageType,
+        path: String,
+        rev: Option<String>,
+        call_id: u64,
+    ) -> Result<(), AsyncRuntimeError> {
+        let call_in_data =
+            storage_models::CallInData::restore(storage_models::restore_in_data { path, rev });
+        storages::storage_call(
+            rth,
+            messages,
+            auth_info_holder,
+            storage_type,
+            call_in_data,
+            call_id,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn int_list_revisions(
         rth: RuntimeHolder,
         messages: MessagesSender<call_messages::Message>,
         auth_info_holder: clouds::AuthInfoHolder,
         storage_type: storage_models::StorageType,
         path: String,
-        save_to: String,
         call_id: u64,
     ) -> Result<(), AsyncRuntimeError> {
         let call_in_data =
-            storage_models::CallInData::download_file(storage_models::download_file_in_data {
-                remote_path: path,
-                local_path: save_to,
+            storage_models::CallInData::list_revisions(storage_models::list_revisions_in_data {
+                path,
             });
         storages::storage_call(
             rth,
@@ -648,6 +3291,48 @@ impl StorageInstance {
         Ok(())
     }
 
+    async fn int_shared_links(
+        rth: RuntimeHolder,
+        messages: MessagesSender<call_messages::Message>,
+        auth_info_holder: clouds::AuthInfoHolder,
+        storage_type: storage_models::StorageType,
+        in_data: storage_models::shared_links_in_data,
+        call_id: u64,
+    ) -> Result<(), AsyncRuntimeError> {
+        let call_in_data = storage_models::CallInData::shared_links(in_data);
+        storages::storage_call(
+            rth,
+            messages,
+            auth_info_holder,
+            storage_type,
+            call_in_data,
+            call_id,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn int_temporary_link(
+        rth: RuntimeHolder,
+        messages: MessagesSender<call_messages::Message>,
+        auth_info_holder: clouds::AuthInfoHolder,
+        storage_type: storage_models::StorageType,
+        in_data: storage_models::temporary_link_in_data,
+        call_id: u64,
+    ) -> Result<(), AsyncRuntimeError> {
+        let call_in_data = storage_models::CallInData::temporary_link(in_data);
+        storages::storage_call(
+            rth,
+            messages,
+            auth_info_holder,
+            storage_type,
+            call_in_data,
+            call_id,
+        )
+        .await?;
+        Ok(())
+    }
+
     fn gen_call_id(&self) -> u64 {
         let mut last_call_id_g = self.last_call_id.write().unwrap();
         let call_id = (*last_call_id_g) + 1;
@@ -660,6 +3345,9 @@ impl StorageInstance {
         let messages_sender = self.messages.sender.clone();
         let auth_info_holder = self.auth_info_holder.clone();
         let storage_type = self.storage_type;
+        let thumbnail_cache = self.thumbnail_cache.clone();
+        let folders_cache = self.folders_cache.clone();
+        let include_deleted = self.visual_state.show_deleted;
         let current_path = match &self.visual_state.folder {
             Some(folder) => folder.path.clone(),
             None => "".to_string(),
@@ -671,56 +3359,280 @@ impl StorageInstance {
             match action {
                 StorageAction::To { path } => {
                     let _ = Self::int_nav_to(
+                        rth,
+                        messages_sender,
+                        auth_info_holder,
+                        storage_type,
+                        storage_models::list_folder_in_data {
+                            path,
+                            include_deleted,
+                            cache: folders_cache,
+                        },
+                        call_id,
+                    )
+                    .await;
+                }
+                StorageAction::Backward => {
+                    let _ = Self::int_nav_to(
+                        rth,
+                        messages_sender,
+                        auth_info_holder,
+                        storage_type,
+                        storage_models::list_folder_in_data {
+                            path: Self::parent_path(current_path),
+                            include_deleted,
+                            cache: folders_cache,
+                        },
+                        call_id,
+                    )
+                    .await;
+                }
+                StorageAction::Forward { folder_name } => {
+                    let _ = Self::int_nav_to(
+                        rth,
+                        messages_sender,
+                        auth_info_holder,
+                        storage_type,
+                        storage_models::list_folder_in_data {
+                            path: Self::append_path(current_path, folder_name),
+                            include_deleted,
+                            cache: folders_cache,
+                        },
+                        call_id,
+                    )
+                    .await;
+                }
+                StorageAction::DownloadFile {
+                    path,
+                    to_path,
+                    open,
+                } => {
+                    let _ = Self::int_download_file(
+                        rth,
+                        messages_sender,
+                        auth_info_holder,
+                        storage_type,
+                        storage_models::download_file_in_data {
+                            remote_path: path,
+                            local_path: to_path,
+                            open,
+                        },
+                        call_id,
+                    )
+                    .await;
+                }
+                StorageAction::CancelDownloadFile { call_id } => {
+                    let _ = messages_sender.send(call_messages::Message {
+                        call_id: Some(call_id),
+                        data: call_messages::Data::DownloadFile(
+                            call_messages::DownloadFile::Cancelled,
+                        ),
+                    });
+                }
+                StorageAction::GetMetadata { path } => {
+                    let _ = Self::int_get_metadata(
+                        rth,
+                        messages_sender,
+                        auth_info_holder,
+                        storage_type,
+                        path,
+                        call_id,
+                    )
+                    .await;
+                }
+                StorageAction::GetThumbnails { entries } => {
+                    let _ = Self::int_get_thumbnails(
+                        rth,
+                        messages_sender,
+                        auth_info_holder,
+                        storage_type,
+                        entries,
+                        thumbnail_cache,
+                        call_id,
+                    )
+                    .await;
+                }
+                StorageAction::Preview { path, max_bytes } => {
+                    let _ = Self::int_preview(
+                        rth,
+                        messages_sender,
+                        auth_info_holder,
+                        storage_type,
+                        path,
+                        max_bytes,
+                        call_id,
+                    )
+                    .await;
+                }
+                StorageAction::Search { in_data } => {
+                    let _ = Self::int_search(
+                        rth,
+                        messages_sender,
+                        auth_info_holder,
+                        storage_type,
+                        in_data,
+                        call_id,
+                    )
+                    .await;
+                }
+                StorageAction::WatchFolder { path, cursor } => {
+                    let _ = Self::int_watch_folder(
+                        rth,
+                        messages_sender,
+                        auth_info_holder,
+                        storage_type,
+                        storage_models::watch_folder_in_data {
+                            path,
+                            cursor,
+                            cache: folders_cache,
+                        },
+                        call_id,
+                    )
+                    .await;
+                }
+                StorageAction::Restore { path, rev } => {
+                    let _ = Self::int_restore(
                         rth,
                         messages_sender,
                         auth_info_holder,
                         storage_type,
                         path,
+                        rev,
                         call_id,
                     )
                     .await;
                 }
-                StorageAction::Backward => {
-                    let _ = Self::int_nav_to(
+                StorageAction::ListRevisions { path } => {
+                    let _ = Self::int_list_revisions(
                         rth,
                         messages_sender,
                         auth_info_holder,
                         storage_type,
-                        Self::parent_path(current_path),
+                        path,
                         call_id,
                     )
                     .await;
                 }
-                StorageAction::Forward { folder_name } => {
-                    let _ = Self::int_nav_to(
+                StorageAction::SharedLinks { in_data } => {
+                    let _ = Self::int_shared_links(
                         rth,
                         messages_sender,
                         auth_info_holder,
                         storage_type,
-                        Self::append_path(current_path, folder_name),
+                        in_data,
                         call_id,
                     )
                     .await;
                 }
-                StorageAction::DownloadFile { path, to_path } => {
-                    let _ = Self::int_download_file(
+                StorageAction::TemporaryLink { in_data } => {
+                    let _ = Self::int_temporary_link(
                         rth,
                         messages_sender,
                         auth_info_holder,
                         storage_type,
-                        path,
-                        to_path,
+                        in_data,
                         call_id,
                     )
                     .await;
                 }
-                StorageAction::CancelDownloadFile { call_id } => {
-                    let _ = messages_sender.send(call_messages::Message {
-                        call_id: Some(call_id),
-                        data: call_messages::Data::DownloadFile(
-                            call_messages::DownloadFile::Cancelled,
-                        ),
-                    });
+                StorageAction::AccountInfo => {
+                    let _ = storages::storage_call(
+                        rth,
+                        messages_sender,
+                        auth_info_holder,
+                        storage_type,
+                        storage_models::CallInData::account_info,
+                        call_id,
+                    )
+                    .await;
+                }
+                StorageAction::ListRecursive { in_data } => {
+                    let _ = storages::storage_call(
+                        rth,
+                        messages_sender,
+                        auth_info_holder,
+                        storage_type,
+                        storage_models::CallInData::list_recursive(in_data),
+                        call_id,
+                    )
+                    .await;
+                }
+                StorageAction::FileOps { in_data } => {
+                    let _ = storages::storage_call(
+                        rth,
+                        messages_sender,
+                        auth_info_holder,
+                        storage_type,
+                        storage_models::CallInData::file_ops(in_data),
+                        call_id,
+                    )
+                    .await;
+                }
+                StorageAction::Transfer { in_data } => {
+                    let _ = storages::storage_call(
+                        rth,
+                        messages_sender,
+                        auth_info_holder,
+                        storage_type,
+                        storage_models::CallInData::transfer(in_data),
+                        call_id,
+                    )
+                    .await;
+                }
+                StorageAction::Compare { in_data } => {
+                    let _ = storages::storage_call(
+                        rth,
+                        messages_sender,
+                        auth_info_holder,
+                        storage_type,
+                        storage_models::CallInData::compare(in_data),
+                        call_id,
+                    )
+                    .await;
+                }
+                StorageAction::Mirror { in_data } => {
+                    let _ = storages::storage_call(
+                        rth,
+                        messages_sender,
+                        auth_info_holder,
+                        storage_type,
+                        storage_models::CallInData::mirror(in_data),
+                        call_id,
+                    )
+                    .await;
+                }
+                StorageAction::Sync { in_data } => {
+                    let _ = storages::storage_call(
+                        rth,
+                        messages_sender,
+                        auth_info_holder,
+                        storage_type,
+                        storage_models::CallInData::sync(in_data),
+                        call_id,
+                    )
+                    .await;
+                }
+                StorageAction::Backup { in_data } => {
+                    let _ = storages::storage_call(
+                        rth,
+                        messages_sender,
+                        auth_info_holder,
+                        storage_type,
+                        storage_models::CallInData::backup(in_data),
+                        call_id,
+                    )
+                    .await;
+                }
+                StorageAction::WebDav { in_data } => {
+                    let _ = storages::storage_call(
+                        rth,
+                        messages_sender,
+                        auth_info_holder,
+                        storage_type,
+                        storage_models::CallInData::webdav(in_data),
+                        call_id,
+                    )
+                    .await;
                 }
                 StorageAction::StartAuth => {
                     let _ = messages_sender.send(call_messages::Message {
@@ -749,12 +3661,612 @@ impl StorageInstance {
     }
 
     pub fn download_file(&self, path: String, to_path: String) {
-        self.action(StorageAction::DownloadFile { path, to_path }, None);
+        self.action(
+            StorageAction::DownloadFile {
+                path,
+                to_path,
+                open: true,
+            },
+            None,
+        );
     }
     pub fn cancel_download_file(&self, call_id: u64) {
         self.action(StorageAction::CancelDownloadFile { call_id }, None);
     }
 
+    pub fn show_properties(&mut self, path: String) {
+        self.visual_state.properties = Some(PropertiesDialogState {
+            path: path.clone(),
+            item: None,
+            error: None,
+        });
+        self.action(StorageAction::GetMetadata { path }, None);
+    }
+
+    /// requests thumbnails for images of current folder which were not requested yet
+    pub fn request_thumbnails(&mut self) {
+        let folder = match &self.visual_state.folder {
+            Some(folder) => folder,
+            None => return,
+        };
+        let thumbnails = &mut self.visual_state.thumbnails;
+        let mut entries: Vec<storage_models::ThumbnailRequest> = vec![];
+        for item in &folder.items {
+            if item.is_folder
+                || item.rev.is_empty()
+                || !thumbnails::is_thumbnail_supported(&item.name)
+                || thumbnails.items.contains_key(&item.rev)
+            {
+                continue;
+            }
+            thumbnails
+                .items
+                .insert(item.rev.clone(), ThumbnailState::Requested);
+            entries.push(storage_models::ThumbnailRequest {
+                path: if folder.path.is_empty() {
+                    item.name.clone()
+                } else {
+                    folder.path.clone() + "/" + &item.name
+                },
+                rev: item.rev.clone(),
+            });
+        }
+        if !entries.is_empty() {
+            self.action(StorageAction::GetThumbnails { entries }, None);
+        }
+    }
+
+    pub fn show_preview(&mut self, path: String, max_bytes: u64) {
+        self.close_preview();
+        self.visual_state.preview = Some(PreviewState {
+            path: path.clone(),
+            size: None,
+            data: None,
+            error: None,
+            texture: None,
+        });
+        self.action(StorageAction::Preview { path, max_bytes }, None);
+    }
+
+    pub fn close_preview(&mut self) {
+        if let Some(preview) = self.visual_state.preview.take() {
+            if let Some((texture_id, _)) = preview.texture {
+                self.visual_state.textures_to_free.push(texture_id);
+            }
+        }
+    }
+
+    /// starts search with options from the search bar
+    pub fn start_search(&mut self) {
+        let search = &mut self.visual_state.search;
+        search.error = None;
+        let in_data = storage_models::search_in_data {
+            query: search.query.trim().to_string(),
+            path: search.path.trim_matches('/').to_string(),
+            filename_only: search.filename_only,
+            extensions: search
+                .extensions
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .map(|v| v.trim_start_matches('.').to_string())
+                .filter(|v| !v.is_empty())
+                .collect(),
+            categories: search.categories.clone(),
+        };
+        if in_data.query.is_empty() {
+            search.error = Some("query is empty".to_string());
+            return;
+        }
+        self.action(StorageAction::Search { in_data }, None);
+    }
+
+    pub fn clear_search(&mut self) {
+        self.call_states.retain(|_, v| match &v.data {
+            call_states::Data::Search { .. } => {
+                Self::abort_handles_and_senders(&mut v.handles, &mut v.cancellers);
+                false
+            }
+            _ => true,
+        });
+        self.visual_state.search_results = None;
+        self.visual_state.search.error = None;
+    }
+
+    /// reloads current folder with or without deleted entries
+    pub fn set_show_deleted(&mut self, show_deleted: bool) {
+        self.visual_state.show_deleted = show_deleted;
+        if let Some(folder) = &self.visual_state.folder {
+            self.nav_to(folder.path.clone());
+        }
+    }
+
+    /// restores deleted file to its latest revision
+    pub fn restore_deleted(&self, path: String) {
+        self.action(StorageAction::Restore { path, rev: None }, None);
+    }
+
+    pub fn show_revisions(&mut self, path: String) {
+        self.visual_state.revisions = Some(RevisionsDialogState {
+            path: path.clone(),
+            is_deleted: false,
+            entries: None,
+            error: None,
+            restored_rev: None,
+        });
+        self.action(StorageAction::ListRevisions { path }, None);
+    }
+
+    pub fn restore_revision(&mut self, path: String, rev: String) {
+        if let Some(revisions) = &mut self.visual_state.revisions {
+            revisions.error = None;
+            revisions.restored_rev = None;
+        }
+        self.action(
+            StorageAction::Restore {
+                path,
+                rev: Some(rev),
+            },
+            None,
+        );
+    }
+
+    /// downloads specific revision of the file as "name (rev).ext"
+    pub fn download_revision(&self, name: &str, rev: &str) {
+        let local_name = match name.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() => format!("{} ({}).{}", stem, rev, ext),
+            _ => format!("{} ({})", name, rev),
+        };
+        self.download_file(
+            "rev:".to_owned() + rev,
+            self.save_to_path.clone() + &local_name,
+        );
+    }
+
+    pub fn show_shared_links(&mut self, path: String) {
+        self.visual_state.shared_links = Some(SharedLinksDialogState {
+            path,
+            links: None,
+            error: None,
+            in_progress: false,
+            audience: storage_models::LinkAudience::Public,
+            expires_in_days: 0,
+            use_password: false,
+            password: "".into(),
+            copy_to_clipboard: None,
+        });
+        self.shared_links_op(storage_models::SharedLinksOp::List);
+    }
+
+    /// runs operation for the path of shared links dialog
+    fn shared_links_op(&mut self, op: storage_models::SharedLinksOp) {
+        if let Some(dialog) = &mut self.visual_state.shared_links {
+            dialog.error = None;
+            dialog.in_progress = true;
+            let in_data = storage_models::shared_links_in_data {
+                path: dialog.path.clone(),
+                op,
+            };
+            self.action(StorageAction::SharedLinks { in_data }, None);
+        }
+    }
+
+    /// creates link with settings from the dialog
+    pub fn create_shared_link(&mut self) {
+        if let Some(dialog) = &self.visual_state.shared_links {
+            let settings = dialog.settings();
+            self.shared_links_op(storage_models::SharedLinksOp::Create(settings));
+        }
+    }
+
+    /// applies settings from the dialog to the link
+    pub fn modify_shared_link(&mut self, url: String) {
+        if let Some(dialog) = &self.visual_state.shared_links {
+            let settings = dialog.settings();
+            self.shared_links_op(storage_models::SharedLinksOp::Modify { url, settings });
+        }
+    }
+
+    pub fn revoke_shared_link(&mut self, url: String) {
+        self.shared_links_op(storage_models::SharedLinksOp::Revoke { url });
+    }
+
+    /// sizes of all nested folders of the path
+    pub fn analyze_folder(&mut self, path: String) {
+        self.visual_state.analyze = Some(AnalyzeState {
+            path: path.clone(),
+            count: 0,
+            root: None,
+            error: None,
+            current: path.clone(),
+            sort: analyze::SizeSort::Size,
+        });
+        let in_data = storage_models::list_recursive_in_data {
+            path,
+            purpose: storage_models::ListRecursivePurpose::Analyze,
+        };
+        self.action(StorageAction::ListRecursive { in_data }, None);
+    }
+
+    /// groups of equal files (by size and content hash) under the path
+    pub fn find_duplicates(&mut self, path: String) {
+        self.visual_state.duplicates = Some(DuplicatesState {
+            path: path.clone(),
+            count: 0,
+            groups: None,
+            error: None,
+            selected: std::collections::HashSet::new(),
+            move_to: "".into(),
+            ops_in_progress: false,
+            ops_errors: vec![],
+        });
+        let in_data = storage_models::list_recursive_in_data {
+            path,
+            purpose: storage_models::ListRecursivePurpose::Duplicates,
+        };
+        self.action(StorageAction::ListRecursive { in_data }, None);
+    }
+
+    fn duplicates_ops(&mut self, op: impl Fn(String) -> storage_models::FileOp) {
+        if let Some(duplicates) = &mut self.visual_state.duplicates {
+            if duplicates.selected.is_empty() {
+                return;
+            }
+            duplicates.ops_in_progress = true;
+            let mut paths: Vec<String> = duplicates.selected.iter().cloned().collect();
+            paths.sort();
+            let in_data = storage_models::file_ops_in_data {
+                ops: paths.into_iter().map(op).collect(),
+            };
+            self.action(StorageAction::FileOps { in_data }, None);
+        }
+    }
+
+    pub fn delete_selected_duplicates(&mut self) {
+        self.duplicates_ops(|path| storage_models::FileOp::Delete { path });
+    }
+
+    pub fn move_selected_duplicates(&mut self) {
+        let to = match &self.visual_state.duplicates {
+            Some(duplicates) => duplicates.move_to.trim_matches('/').to_string(),
+            None => return,
+        };
+        self.duplicates_ops(|path| storage_models::FileOp::Move {
+            path,
+            to: to.clone(),
+        });
+    }
+
+    /// opens compare dialog for remote folder, local folder defaults to the downloads folder
+    pub fn show_compare(&mut self, remote_path: String) {
+        let name = remote_path.rsplit('/').next().unwrap_or_default();
+        self.visual_state.compare = Some(CompareState {
+            local_path: self.save_to_path.clone() + name,
+            remote_path,
+            listed: 0,
+            checked: 0,
+            in_progress: false,
+            entries: None,
+            error: None,
+            show_identical: false,
+            transfer_in_progress: false,
+            transfer_errors: vec![],
+        });
+    }
+
+    /// lists remote folder, then hashes and compares local files
+    pub fn compare_folders(&mut self) {
+        if let Some(compare) = &mut self.visual_state.compare {
+            compare.remote_path = compare.remote_path.trim_matches('/').to_string();
+            compare.listed = 0;
+            compare.checked = 0;
+            compare.in_progress = true;
+            compare.entries = None;
+            compare.error = None;
+            compare.transfer_errors.clear();
+            let in_data = storage_models::list_recursive_in_data {
+                path: compare.remote_path.clone(),
+                purpose: storage_models::ListRecursivePurpose::Compare,
+            };
+            self.action(StorageAction::ListRecursive { in_data }, None);
+        }
+    }
+
+    fn compare_transfer(
+        &mut self,
+        op: impl Fn(&CompareState, &storage_models::CompareEntry) -> Option<storage_models::TransferOp>,
+    ) {
+        if let Some(compare) = &mut self.visual_state.compare {
+            let ops: Vec<storage_models::TransferOp> = compare
+                .entries
+                .iter()
+                .flatten()
+                .filter_map(|entry| op(compare, entry))
+                .collect();
+            if ops.is_empty() {
+                return;
+            }
+            compare.transfer_in_progress = true;
+            let in_data = storage_models::transfer_in_data {
+                ops,
+                purpose: storage_models::TransferPurpose::Compare,
+            };
+            self.action(StorageAction::Transfer { in_data }, None);
+        }
+    }
+
+    /// downloads files which are only in the remote folder
+    pub fn download_missing(&mut self) {
+        self.compare_transfer(|compare, entry| match entry.status {
+            storage_models::CompareStatus::OnlyRemote => {
+                Some(storage_models::TransferOp::Download {
+                    remote_path: compare::remote_file_path(
+                        &compare.remote_path,
+                        &entry.relative_path,
+                    ),
+                    local_path: compare::local_file_path(&compare.local_path, &entry.relative_path)
+                        .to_string_lossy()
+                        .to_string(),
+                })
+            }
+            _ => None,
+        });
+    }
+
+    /// uploads changed and local only files,
+    /// changed files are replaced only if remote revision is still the compared one
+    pub fn upload_changed(&mut self) {
+        self.compare_transfer(|compare, entry| {
+            let mode = match (entry.status, &entry.remote) {
+                (storage_models::CompareStatus::OnlyLocal, _) => storage_models::UploadMode::Add,
+                (storage_models::CompareStatus::Changed, Some(remote)) => {
+                    storage_models::UploadMode::Update {
+                        rev: remote.rev.clone(),
+                    }
+                }
+                _ => return None,
+            };
+            Some(storage_models::TransferOp::Upload {
+                local_path: compare::local_file_path(&compare.local_path, &entry.relative_path)
+                    .to_string_lossy()
+                    .to_string(),
+                remote_path: compare::remote_file_path(&compare.remote_path, &entry.relative_path),
+                mode,
+            })
+        });
+    }
+
+    /// starts mirror job unless it is running already
+    pub fn run_mirror(&mut self, idx: usize) {
+        let job = match self.mirrors.get_mut(idx) {
+            Some(job) if job.started.is_none() => job,
+            _ => return,
+        };
+        job.started = Some(chrono::Utc::now());
+        job.listed = 0;
+        job.downloaded = 0;
+        let in_data = storage_models::mirror_in_data {
+            name: job.config.name.clone(),
+            remote_path: job.config.remote_path.trim_matches('/').to_string(),
+            local_path: job.config.local_path.clone(),
+            delete_extraneous: job.config.delete_extraneous,
+            cursor: job.config.cursor.clone(),
+        };
+        self.action(StorageAction::Mirror { in_data }, None);
+    }
+
+    /// next run lists whole remote folder
+    pub fn reset_mirror(&mut self, idx: usize) {
+        if let Some(job) = self.mirrors.get_mut(idx) {
+            job.config.cursor.clear();
+            self.config_changed = true;
+        }
+    }
+
+    /// starts two-way sync job unless it is running already
+    pub fn run_sync(&mut self, idx: usize) {
+        let job = match self.syncs.get_mut(idx) {
+            Some(job) if job.started.is_none() => job,
+            _ => return,
+        };
+        job.started = Some(chrono::Utc::now());
+        job.done = 0;
+        job.total = 0;
+        let in_data = storage_models::sync_in_data {
+            name: job.config.name.clone(),
+            remote_path: job.config.remote_path.trim_matches('/').to_string(),
+            local_path: job.config.local_path.clone(),
+            cursor: job.config.cursor.clone(),
+            state_file: self
+                .sync_state_dir
+                .join(job_file_name(&job.config.name) + ".json"),
+            max_remote_deletes: job.config.max_remote_deletes,
+        };
+        self.action(StorageAction::Sync { in_data }, None);
+    }
+
+    /// uploads changed opened file over the revision it was downloaded as,
+    /// remote changes made meanwhile are reported as conflict
+    pub fn upload_edited(&mut self, idx: usize) {
+        let file = match self.edited_files.get(idx) {
+            Some(file) => file,
+            None => return,
+        };
+        let op = storage_models::TransferOp::Upload {
+            local_path: file.local_path.clone(),
+            remote_path: file.remote_path.clone(),
+            mode: storage_models::UploadMode::Update {
+                rev: file.rev.clone(),
+            },
+        };
+        self.start_edited_upload(idx, op);
+    }
+
+    /// uploads opened file next to the remote one as "conflicted copy", later changes go to the copy
+    pub fn upload_edited_as_copy(&mut self, idx: usize) {
+        let file = match self.edited_files.get(idx) {
+            Some(file) => file,
+            None => return,
+        };
+        let op = storage_models::TransferOp::Upload {
+            local_path: file.local_path.clone(),
+            remote_path: sync_state::conflicted_copy_path(&file.remote_path, chrono::Utc::now()),
+            mode: storage_models::UploadMode::Add,
+        };
+        self.start_edited_upload(idx, op);
+    }
+
+    fn start_edited_upload(&mut self, idx: usize, op: storage_models::TransferOp) {
+        let file = &mut self.edited_files[idx];
+        let (size, modified) = match file.local_state() {
+            Some(state) => state,
+            None => return,
+        };
+        file.status = EditedFileStatus::Uploading { size, modified };
+        self.action(
+            StorageAction::Transfer {
+                in_data: storage_models::transfer_in_data {
+                    ops: vec![op],
+                    purpose: storage_models::TransferPurpose::Edit,
+                },
+            },
+            None,
+        );
+    }
+
+    /// stops watching opened file
+    pub fn forget_edited(&mut self, idx: usize) {
+        if idx < self.edited_files.len() {
+            self.edited_files.remove(idx);
+        }
+    }
+
+    /// starts backup set unless it is running already
+    pub fn run_backup(&mut self, idx: usize) {
+        let job = match self.backups.get_mut(idx) {
+            Some(job) if job.started.is_none() => job,
+            _ => return,
+        };
+        let now = chrono::Utc::now();
+        job.started = Some(now);
+        job.done = 0;
+        job.total = 0;
+        let manifest_file = self
+            .backup_dir
+            .join(job_file_name(&job.config.name))
+            .join(now.format("%Y%m%d-%H%M%S.json").to_string());
+        job.manifest_file = Some(manifest_file.clone());
+        let in_data = storage_models::backup_in_data {
+            name: job.config.name.clone(),
+            local_path: job.config.local_path.clone(),
+            remote_path: job.config.remote_path.trim_matches('/').to_string(),
+            include: job.config.include.clone(),
+            exclude: job.config.exclude.clone(),
+            manifest_file,
+            previous_manifest: job
+                .config
+                .runs
+                .iter()
+                .rev()
+                .find_map(|run| run.manifest.clone()),
+        };
+        self.action(StorageAction::Backup { in_data }, None);
+    }
+
+    /// (re)starts WebDAV server with current settings
+    pub fn start_webdav(&mut self) {
+        self.stop_webdav();
+        let call_id = self.gen_call_id();
+        self.webdav_call_id = Some(call_id);
+        let in_data = storage_models::webdav_in_data {
+            port: self.webdav.port,
+            user: self.webdav.user.clone(),
+            password: self.webdav.password.clone(),
+            allow_anonymous: self.webdav.allow_anonymous,
+            temp_dir: self.webdav_dir.clone(),
+        };
+        self.action(StorageAction::WebDav { in_data }, Some(call_id));
+    }
+
+    pub fn stop_webdav(&mut self) {
+        if let Some(call_id) = self.webdav_call_id.take() {
+            if let Some(mut state) = self.call_states.remove(&call_id) {
+                Self::abort_handles_and_senders(&mut state.handles, &mut state.cancellers);
+            }
+        }
+    }
+
+    /// state, address and served requests count of WebDAV server
+    pub fn webdav_state(
+        &self,
+    ) -> Option<(
+        &call_states::WebDav,
+        Option<std::net::SocketAddr>,
+        u64,
+        Option<&String>,
+    )> {
+        let state = self.call_states.get(&self.webdav_call_id?)?;
+        match &state.data {
+            call_states::Data::WebDav {
+                data,
+                addr,
+                served,
+                last_request,
+                ..
+            } => Some((data, *addr, *served, last_request.as_ref())),
+            _ => None,
+        }
+    }
+
+    /// registers call for requests of stream proxy to this storage
+    pub fn stream_target(&mut self) -> stream_proxy::Target {
+        let call_id = self.gen_call_id();
+        self.call_states.insert(
+            call_id,
+            call_states::State {
+                handles: vec![],
+                cancellers: vec![],
+                data: call_states::Data::Stream {
+                    data: call_states::Stream::Serving,
+                },
+            },
+        );
+        stream_proxy::Target {
+            auth_info_holder: self.auth_info_holder.clone(),
+            call_id,
+            messages: self.messages.sender.clone(),
+        }
+    }
+
+    /// next run lists whole remote folder
+    pub fn reset_sync(&mut self, idx: usize) {
+        if let Some(job) = self.syncs.get_mut(idx) {
+            job.config.cursor.clear();
+            self.config_changed = true;
+        }
+    }
+
+    /// name, email and space usage of logged in account
+    pub fn load_account_info(&self) {
+        self.action(StorageAction::AccountInfo, None);
+    }
+
+    /// copies direct download url (valid for 4 hours) to clipboard
+    pub fn copy_temporary_link(&self, path: String) {
+        let in_data = storage_models::temporary_link_in_data {
+            path,
+            purpose: storage_models::TemporaryLinkPurpose::CopyToClipboard,
+        };
+        self.action(StorageAction::TemporaryLink { in_data }, None);
+    }
+
+    /// opens direct download url by default handler (player) without downloading
+    pub fn open_externally(&self, path: String) {
+        let in_data = storage_models::temporary_link_in_data {
+            path,
+            purpose: storage_models::TemporaryLinkPurpose::OpenExternally,
+        };
+        self.action(StorageAction::TemporaryLink { in_data }, None);
+    }
+
     pub fn start_auth(&self) {
         self.action(StorageAction::StartAuth, None);
     }
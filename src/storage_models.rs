@@ -1,6 +1,11 @@
 use crate::clouds;
+use crate::config;
 use crate::error;
+use crate::folders_cache;
+use crate::preview;
+use crate::thumbnails;
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
 #[derive(Copy, Clone)]
 pub enum StorageType {
@@ -8,7 +13,17 @@ pub enum StorageType {
     //    FileSystem,
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MediaInfo {
+    pub kind: String,
+    pub dimensions: Option<(u64, u64)>,
+    pub location: Option<(f64, f64)>,
+    pub time_taken: Option<DateTime<Utc>>,
+    /// milliseconds
+    pub duration: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Item {
     pub name: String,
     pub id: String,
@@ -16,12 +31,32 @@ pub struct Item {
     pub modified: Option<DateTime<Utc>>,
     pub size: Option<u64>,
     pub items: Option<Vec<Item>>,
+    pub path_display: String,
+    /// empty in folders cache written before it was added
+    #[serde(default)]
+    pub path_lower: String,
+    pub rev: String,
+    pub client_modified: Option<DateTime<Utc>>,
+    pub is_downloadable: bool,
+    pub content_hash: String,
+    pub media_info: Option<MediaInfo>,
+    #[serde(default)]
+    pub is_deleted: bool,
 }
 
 #[allow(non_camel_case_types)]
 pub struct list_folder_in_data {
     pub path: String,
     //    pub recursive: bool,
+    pub include_deleted: bool,
+    pub cache: folders_cache::FoldersCache,
+}
+
+/// change of folder entry reported by list_folder/continue
+#[derive(Debug, Clone)]
+pub enum ItemDelta {
+    Changed(Box<Item>),
+    Deleted { name: String },
 }
 
 #[allow(non_camel_case_types)]
@@ -30,18 +65,443 @@ pub struct list_folder_out_data {
     //    pub path_id: String,
     pub path: String,
     pub items: Option<Vec<Item>>,
+    pub cursor: String,
+    pub include_deleted: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LinkAudience {
+    Public,
+    Team,
+    NoOne,
+    Other,
+}
+
+impl LinkAudience {
+    pub fn caption(&self) -> &'static str {
+        match self {
+            Self::Public => "anyone with link",
+            Self::Team => "team members",
+            Self::NoOne => "no one (only invited)",
+            Self::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SharedLinkSettings {
+    pub audience: LinkAudience,
+    pub expires: Option<DateTime<Utc>>,
+    /// link is protected with password if present
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SharedLink {
+    pub url: String,
+    pub name: String,
+    pub expires: Option<DateTime<Utc>>,
+    pub audience: Option<LinkAudience>,
+    pub password_protected: bool,
+    pub can_revoke: bool,
+}
+
+pub enum SharedLinksOp {
+    List,
+    Create(SharedLinkSettings),
+    Modify {
+        url: String,
+        settings: SharedLinkSettings,
+    },
+    Revoke {
+        url: String,
+    },
+}
+
+#[allow(non_camel_case_types)]
+pub struct shared_links_in_data {
+    pub path: String,
+    pub op: SharedLinksOp,
+}
+
+/// links of the path after the operation
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct shared_links_out_data {
+    pub links: Vec<SharedLink>,
+    /// url of created (or already existing) link
+    pub created: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum FileOp {
+    Delete {
+        path: String,
+    },
+    /// `to` is destination folder, name is kept (or autorenamed on conflict)
+    Move {
+        path: String,
+        to: String,
+    },
+}
+
+impl FileOp {
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Delete { path } | Self::Move { path, .. } => path,
+        }
+    }
+}
+
+#[allow(non_camel_case_types)]
+pub struct file_ops_in_data {
+    pub ops: Vec<FileOp>,
+}
+
+/// result of every operation in order of in_data
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct file_ops_out_data {
+    pub results: Vec<(FileOp, Result<(), String>)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UploadMode {
+    /// autorename on conflict
+    Add,
+    /// overwrite only if remote file has revision `rev`
+    Update { rev: String },
+}
+
+/// paths of remote files are without leading '/'
+#[derive(Debug, Clone)]
+pub enum TransferOp {
+    Download {
+        remote_path: String,
+        local_path: String,
+    },
+    Upload {
+        local_path: String,
+        remote_path: String,
+        mode: UploadMode,
+    },
+}
+
+impl TransferOp {
+    pub fn remote_path(&self) -> &str {
+        match self {
+            Self::Download { remote_path, .. } | Self::Upload { remote_path, .. } => remote_path,
+        }
+    }
+
+    pub fn local_path(&self) -> &str {
+        match self {
+            Self::Download { local_path, .. } | Self::Upload { local_path, .. } => local_path,
+        }
+    }
+}
+
+/// consumer of batch transfer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransferPurpose {
+    Compare,
+    /// upload of opened files changed locally
+    Edit,
+}
+
+#[allow(non_camel_case_types)]
+pub struct transfer_in_data {
+    pub ops: Vec<TransferOp>,
+    pub purpose: TransferPurpose,
+}
+
+/// result of every operation in order of in_data, with remote metadata after transfer
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct transfer_out_data {
+    pub results: Vec<(TransferOp, Result<Item, TransferOpError>)>,
+}
+
+/// consumer of recursive listing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListRecursivePurpose {
+    Analyze,
+    Duplicates,
+    Compare,
+}
+
+#[allow(non_camel_case_types)]
+pub struct list_recursive_in_data {
+    pub path: String,
+    pub purpose: ListRecursivePurpose,
+}
+
+/// all files and folders of subtree (with path_display)
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct list_recursive_out_data {
+    pub path: String,
+    pub items: Vec<Item>,
+}
+
+#[allow(non_camel_case_types)]
+pub struct mirror_in_data {
+    pub name: String,
+    pub remote_path: String,
+    pub local_path: String,
+    pub delete_extraneous: bool,
+    /// "" = list whole folder
+    pub cursor: String,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct mirror_out_data {
+    pub cursor: String,
+    pub stats: config::MirrorRunStats,
+}
+
+#[allow(non_camel_case_types)]
+pub struct sync_in_data {
+    pub name: String,
+    pub remote_path: String,
+    pub local_path: String,
+    /// "" = list whole folder
+    pub cursor: String,
+    /// state of files after last sync
+    pub state_file: std::path::PathBuf,
+    pub max_remote_deletes: u64,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct sync_out_data {
+    pub cursor: String,
+    pub stats: config::SyncRunStats,
+}
+
+#[allow(non_camel_case_types)]
+pub struct backup_in_data {
+    pub name: String,
+    pub local_path: String,
+    pub remote_path: String,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    /// manifest of the run is written there
+    pub manifest_file: std::path::PathBuf,
+    /// manifest of the previous run, its hashes are reused for files with same size and mtime
+    pub previous_manifest: Option<std::path::PathBuf>,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct backup_out_data {
+    pub stats: config::BackupRunStats,
+    /// relative path and reason
+    pub skipped: Vec<(String, String)>,
+    /// relative path and error
+    pub failed: Vec<(String, String)>,
+}
+
+#[allow(non_camel_case_types)]
+pub struct webdav_in_data {
+    pub port: u16,
+    /// basic auth, required unless allow_anonymous
+    pub user: String,
+    pub password: String,
+    pub allow_anonymous: bool,
+    /// uploaded files are received there before upload
+    pub temp_dir: std::path::PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompareStatus {
+    OnlyLocal,
+    OnlyRemote,
+    Identical,
+    Changed,
+}
+
+/// file of local folder and/or remote folder
+#[derive(Debug, Clone)]
+pub struct CompareEntry {
+    /// relative to compared folders, '/' separated
+    pub relative_path: String,
+    pub status: CompareStatus,
+    pub local_size: Option<u64>,
+    pub local_modified: Option<DateTime<Utc>>,
+    pub remote: Option<Item>,
+}
+
+#[allow(non_camel_case_types)]
+pub struct compare_in_data {
+    pub local_path: String,
+    pub remote_path: String,
+    /// recursive listing of remote_path
+    pub remote_items: Vec<Item>,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct compare_out_data {
+    pub entries: Vec<CompareEntry>,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct account_info_out_data {
+    pub account_id: String,
+    pub name: String,
+    pub email: String,
+    pub used: u64,
+    /// None if allocation is unknown
+    pub allocated: Option<u64>,
+    /// account id from config if tokens belong to another account
+    pub expected_account_id: Option<String>,
+}
+
+/// what to do with received temporary link
+#[derive(Debug, Clone, Copy)]
+pub enum TemporaryLinkPurpose {
+    CopyToClipboard,
+    OpenExternally,
+}
+
+#[allow(non_camel_case_types)]
+pub struct temporary_link_in_data {
+    pub path: String,
+    pub purpose: TemporaryLinkPurpose,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct temporary_link_out_data {
+    pub link: String,
+    pub purpose: TemporaryLinkPurpose,
+}
+
+#[allow(non_camel_case_types)]
+pub struct list_revisions_in_data {
+    pub path: String,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct list_revisions_out_data {
+    pub is_deleted: bool,
+    /// newest first
+    pub entries: Vec<Item>,
+}
+
+#[allow(non_camel_case_types)]
+pub struct restore_in_data {
+    pub path: String,
+    /// latest revision of deleted file if None
+    pub rev: Option<String>,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct restore_out_data {
+    pub item: Box<Item>,
+}
+
+#[allow(non_camel_case_types)]
+pub struct watch_folder_in_data {
+    pub path: String,
+    pub cursor: String,
+    pub cache: folders_cache::FoldersCache,
 }
 
 #[allow(non_camel_case_types)]
 pub struct download_file_in_data {
     pub remote_path: String,
     pub local_path: String,
+    /// opened and watched for edits when finished
+    pub open: bool,
 }
 
 #[allow(non_camel_case_types)]
 #[derive(Debug)]
 pub struct download_file_out_data {
     pub name: String,
+    pub rev: String,
+    pub path_display: String,
+}
+
+#[allow(non_camel_case_types)]
+pub struct get_metadata_in_data {
+    pub path: String,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct get_metadata_out_data {
+    pub item: Box<Item>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ThumbnailRequest {
+    pub path: String,
+    pub rev: String,
+}
+
+#[allow(non_camel_case_types)]
+pub struct get_thumbnails_in_data {
+    pub entries: Vec<ThumbnailRequest>,
+    pub cache: thumbnails::ThumbnailCache,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct get_thumbnails_out_data {
+    pub loaded: u64,
+    pub failed: u64,
+}
+
+#[allow(non_camel_case_types)]
+pub struct preview_in_data {
+    pub path: String,
+    pub max_bytes: u64,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct preview_out_data {
+    pub size: u64,
+    pub data: preview::PreviewData,
+}
+
+/// file categories accepted by search
+pub const SEARCH_CATEGORIES: &[&str] = &[
+    "image",
+    "document",
+    "pdf",
+    "spreadsheet",
+    "presentation",
+    "audio",
+    "video",
+    "folder",
+    "paper",
+    "others",
+];
+
+#[allow(non_camel_case_types)]
+pub struct search_in_data {
+    pub query: String,
+    pub path: String,
+    pub filename_only: bool,
+    pub extensions: Vec<String>,
+    pub categories: Vec<String>,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct search_out_data {
+    pub query: String,
+    pub items: Vec<Item>,
+    /// more results exist than were fetched
+    pub limited: bool,
 }
 
 pub enum CallInData {
@@ -49,6 +509,42 @@ pub enum CallInData {
     list_folder(list_folder_in_data),
     #[allow(non_camel_case_types)]
     download_file(download_file_in_data),
+    #[allow(non_camel_case_types)]
+    get_metadata(get_metadata_in_data),
+    #[allow(non_camel_case_types)]
+    get_thumbnails(get_thumbnails_in_data),
+    #[allow(non_camel_case_types)]
+    preview(preview_in_data),
+    #[allow(non_camel_case_types)]
+    search(search_in_data),
+    #[allow(non_camel_case_types)]
+    watch_folder(watch_folder_in_data),
+    #[allow(non_camel_case_types)]
+    restore(restore_in_data),
+    #[allow(non_camel_case_types)]
+    list_revisions(list_revisions_in_data),
+    #[allow(non_camel_case_types)]
+    shared_links(shared_links_in_data),
+    #[allow(non_camel_case_types)]
+    temporary_link(temporary_link_in_data),
+    #[allow(non_camel_case_types)]
+    account_info,
+    #[allow(non_camel_case_types)]
+    list_recursive(list_recursive_in_data),
+    #[allow(non_camel_case_types)]
+    file_ops(file_ops_in_data),
+    #[allow(non_camel_case_types)]
+    transfer(transfer_in_data),
+    #[allow(non_camel_case_types)]
+    compare(compare_in_data),
+    #[allow(non_camel_case_types)]
+    mirror(mirror_in_data),
+    #[allow(non_camel_case_types)]
+    sync(sync_in_data),
+    #[allow(non_camel_case_types)]
+    backup(backup_in_data),
+    #[allow(non_camel_case_types)]
+    webdav(webdav_in_data),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -71,6 +567,191 @@ pub enum DownloadFileError {
     Other(String),
 }
 
+#[derive(thiserror::Error, Debug)]
+pub enum GetMetadataError {
+    #[error("path not found: '{0}'")]
+    PathNotFound(String),
+    #[error("token error: '{0}'")]
+    Token(String),
+    #[error("other error: '{0}'")]
+    Other(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GetThumbnailsError {
+    #[error("token error: '{0}'")]
+    Token(String),
+    #[error("other error: '{0}'")]
+    Other(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum PreviewError {
+    #[error("remote path not found: '{0}'")]
+    RemotePathNotFound(String),
+    #[error("token error: '{0}'")]
+    Token(String),
+    #[error("can't preview: '{0}'")]
+    Unsupported(String),
+    #[error("other error: '{0}'")]
+    Other(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SharedLinksError {
+    #[error("path not found: '{0}'")]
+    PathNotFound(String),
+    #[error("token error: '{0}'")]
+    Token(String),
+    #[error("other error: '{0}'")]
+    Other(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum FileOpsError {
+    #[error("token error: '{0}'")]
+    Token(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum TransferError {
+    #[error("token error: '{0}'")]
+    Token(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum MirrorError {
+    #[error("path not found: '{0}'")]
+    PathNotFound(String),
+    #[error("local folder error: '{0}'")]
+    Local(String),
+    #[error("token error: '{0}'")]
+    Token(String),
+    #[error("other error: '{0}'")]
+    Other(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SyncError {
+    #[error("path not found: '{0}'")]
+    PathNotFound(String),
+    #[error("local folder error: '{0}'")]
+    Local(String),
+    #[error("token error: '{0}'")]
+    Token(String),
+    #[error("other error: '{0}'")]
+    Other(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum TransferOpError {
+    /// remote file has other revision than the replaced one
+    #[error("conflict: '{0}'")]
+    Conflict(String),
+    #[error("{0}")]
+    Other(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum BackupError {
+    #[error("local folder error: '{0}'")]
+    Local(String),
+    #[error("token error: '{0}'")]
+    Token(String),
+    #[error("other error: '{0}'")]
+    Other(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum WebDavError {
+    #[error("user is not set, set user and password or allow anonymous access")]
+    NoCredentials,
+    #[error(transparent)]
+    Bind(error::BindError),
+    #[error("local server error: {0}")]
+    LocalServerError(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum CompareError {
+    #[error("local folder error: '{0}'")]
+    Local(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ListRecursiveError {
+    #[error("path not found: '{0}'")]
+    PathNotFound(String),
+    #[error("token error: '{0}'")]
+    Token(String),
+    #[error("other error: '{0}'")]
+    Other(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum AccountInfoError {
+    #[error("token error: '{0}'")]
+    Token(String),
+    #[error("other error: '{0}'")]
+    Other(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum TemporaryLinkError {
+    #[error("path not found: '{0}'")]
+    PathNotFound(String),
+    #[error("token error: '{0}'")]
+    Token(String),
+    #[error("other error: '{0}'")]
+    Other(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ListRevisionsError {
+    #[error("path not found: '{0}'")]
+    PathNotFound(String),
+    #[error("revisions are available only for files: '{0}'")]
+    NotFile(String),
+    #[error("token error: '{0}'")]
+    Token(String),
+    #[error("other error: '{0}'")]
+    Other(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum RestoreError {
+    #[error("path not found: '{0}'")]
+    PathNotFound(String),
+    #[error("only files can be restored: '{0}'")]
+    NotFile(String),
+    #[error("no revisions to restore: '{0}'")]
+    NoRevisions(String),
+    #[error("token error: '{0}'")]
+    Token(String),
+    #[error("other error: '{0}'")]
+    Other(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum WatchFolderError {
+    #[error("cursor was reset")]
+    Reset,
+    #[error("token error: '{0}'")]
+    Token(String),
+    #[error("other error: '{0}'")]
+    Other(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SearchError {
+    #[error("path not found: '{0}'")]
+    PathNotFound(String),
+    #[error("token error: '{0}'")]
+    Token(String),
+    #[error("other error: '{0}'")]
+    Other(String),
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum AuthError {
     #[error("bind errors: '{}'", get_bind_errors_text(errors.clone()))]
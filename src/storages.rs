@@ -1,8 +1,15 @@
-use crate::call_messages;
+use crate::backup;
 use crate::clouds;
+use crate::clouds::call_messages;
+use crate::clouds::dropbox;
 use crate::common_types::*;
-use crate::dropbox;
+use crate::compare;
+use crate::config;
+use crate::folders_cache;
+use crate::preview;
 use crate::storage_models;
+use crate::sync_state;
+use crate::thumbnails;
 
 pub async fn storage_call(
     rth: RuntimeHolder,
@@ -18,7 +25,6 @@ pub async fn storage_call(
             storage_models::CallInData::list_folder(in_data),
         ) => {
             let path = in_data.path.clone();
-            let path_clone = path.clone();
             let messages_clone = messages.clone();
             let rth_clone = rth.clone();
             let (s, r) = tokio::sync::oneshot::channel::<()>();
@@ -27,12 +33,13 @@ pub async fn storage_call(
                 list_folder_dropbox(
                     rth_clone,
                     auth_info_holder,
-                    path_clone,
+                    in_data,
                     call_id,
                     messages_clone,
                 )
                 .await
             })?;
+            log::debug!("storage_call.list_folder: {}", &path);
             let _ = messages.send(call_messages::Message {
                 call_id: Some(call_id),
                 data: call_messages::Data::ListFolder(call_messages::ListFolder::Started {
@@ -41,7 +48,6 @@ pub async fn storage_call(
                 }),
             });
             let _ = s.send(());
-            log::debug!("storage_call.list_folder: {}", in_data.path);
             Ok(())
         }
         (
@@ -73,6 +79,7 @@ pub async fn storage_call(
                     handle,
                     local_path,
                     remote_path,
+                    open: in_data.open,
                 }),
             });
             let _ = s.send(());
@@ -83,238 +90,3328 @@ pub async fn storage_call(
             );
             Ok(())
         }
-    }
-}
-
-async fn list_folder_dropbox_impl(
-    rth: RuntimeHolder,
-    auth_info_holder: clouds::AuthInfoHolder,
-    path: String,
-    call_id: u64,
-    messages: MessagesSender<call_messages::Message>,
-) -> Result<storage_models::list_folder_out_data, storage_models::ListFolderError> {
-    fn add_to_result(
-        res: dropbox::ListFolderCallResult,
-        result: &mut Vec<storage_models::Item>,
-    ) -> u64 {
-        let mut cnt: u64 = 0;
-        for entry in res.entries {
-            let item: storage_models::Item;
-            match entry {
-                dropbox::Meta::File(file) => {
-                    item = storage_models::Item {
-                        name: file.name.clone(),
-                        id: file.id,
-                        is_folder: false,
-                        modified: Some(file.server_modified),
-                        size: Some(file.size),
-                        items: Some(vec![]),
-                    }
-                }
-                dropbox::Meta::Folder(folder) => {
-                    item = storage_models::Item {
-                        name: folder.name,
-                        id: folder.id,
-                        is_folder: true,
-                        modified: None,
-                        size: None,
-                        items: None,
-                    }
-                }
-            };
-            result.push(item);
-            cnt += 1;
+        (
+            storage_models::StorageType::Cloud(clouds::CloudId::Dropbox),
+            storage_models::CallInData::get_metadata(in_data),
+        ) => {
+            let path = in_data.path.clone();
+            let path_clone = path.clone();
+            let messages_clone = messages.clone();
+            let rth_clone = rth.clone();
+            let (s, r) = tokio::sync::oneshot::channel::<()>();
+            let handle = rth.spawn(async move {
+                let _ = r.await;
+                get_metadata_dropbox(
+                    rth_clone,
+                    auth_info_holder,
+                    path_clone,
+                    call_id,
+                    messages_clone,
+                )
+                .await
+            })?;
+            let _ = messages.send(call_messages::Message {
+                call_id: Some(call_id),
+                data: call_messages::Data::GetMetadata(call_messages::GetMetadata::Started {
+                    handle,
+                    path,
+                }),
+            });
+            let _ = s.send(());
+            log::debug!("storage_call.get_metadata: {}", in_data.path);
+            Ok(())
         }
-        cnt
-    }
-
-    let mut result: Vec<storage_models::Item> = vec![];
-    let mut params = dropbox::ListFolderParams {
-        path: path.to_owned(),
-        recursive: Some(false),
-        include_deleted: Some(false),
-        include_has_explicit_shared_members: None,
-        include_mounted_folders: None, //  = true
-        limit: Some(1000),             //  = 1000
-        include_non_downloadable_files: None,
-    };
-
-    params.path = path.clone();
-    if params.path.eq("/") {
-        panic!("path = '/'");
-    }
-    if !params.path.is_empty() {
-        params.path = "/".to_owned() + &params.path;
-    }
-
-    let res = dropbox::list_folder(
-        rth.clone(),
-        auth_info_holder.clone(),
-        params,
-        call_id,
-        messages.clone(),
-    )
-    .await;
-    match res {
-        Ok(res) => {
-            let mut has_more = res.has_more;
-            let mut cursor = res.cursor.clone();
-            let mut count: u64 = res.entries.len() as u64;
-            add_to_result(res, &mut result);
-            while has_more {
-                let _ = messages.send(call_messages::Message {
-                    call_id: Some(call_id),
-                    data: call_messages::Data::ListFolder(call_messages::ListFolder::Progress {
-                        value: count,
-                        value_str: None,
-                    }),
-                });
-
-                let continue_params = dropbox::ListFolderContinueParams {
-                    cursor: cursor.clone(),
-                };
-                let res = dropbox::list_folder_continue(
-                    rth.clone(),
-                    auth_info_holder.clone(),
-                    continue_params,
+        (
+            storage_models::StorageType::Cloud(clouds::CloudId::Dropbox),
+            storage_models::CallInData::get_thumbnails(in_data),
+        ) => {
+            let total = in_data.entries.len() as u64;
+            let messages_clone = messages.clone();
+            let rth_clone = rth.clone();
+            let (s, r) = tokio::sync::oneshot::channel::<()>();
+            let handle = rth.spawn(async move {
+                let _ = r.await;
+                get_thumbnails_dropbox(
+                    rth_clone,
+                    auth_info_holder,
+                    in_data,
                     call_id,
-                    messages.clone(),
+                    messages_clone,
                 )
-                .await;
-                match res {
-                    Ok(res) => {
-                        has_more = res.has_more;
-                        cursor = res.cursor.clone();
-                        count += res.entries.len() as u64;
-                        let _ = messages.send(call_messages::Message {
-                            call_id: Some(call_id),
-                            data: call_messages::Data::ListFolder(
-                                call_messages::ListFolder::Progress {
-                                    value: count,
-                                    value_str: None,
-                                },
-                            ),
-                        });
-                        add_to_result(res, &mut result);
-                    }
-                    Err(e) => {
-                        let err = match e {
-                            dropbox::ListFolderCallError::Base(base) => {
-                                if base.is_token_error() {
-                                    storage_models::ListFolderError::Token(base.to_string())
-                                } else {
-                                    storage_models::ListFolderError::Other(base.to_string())
-                                }
-                            }
-                            dropbox::ListFolderCallError::PathNotFound(p) => {
-                                storage_models::ListFolderError::PathNotFound(p)
-                            }
-                        };
-                        return Err(err);
-                    }
-                }
-            }
-            Ok(storage_models::list_folder_out_data {
-                path,
-                items: Some(result),
-            })
+                .await
+            })?;
+            let _ = messages.send(call_messages::Message {
+                call_id: Some(call_id),
+                data: call_messages::Data::GetThumbnails(call_messages::GetThumbnails::Started {
+                    handle,
+                    total,
+                }),
+            });
+            let _ = s.send(());
+            log::debug!("storage_call.get_thumbnails: {} entries", total);
+            Ok(())
         }
-        Err(e) => {
-            let err = match e {
-                dropbox::ListFolderCallError::Base(base) => {
-                    if base.is_token_error() {
-                        storage_models::ListFolderError::Token(base.to_string())
-                    } else {
-                        storage_models::ListFolderError::Other(base.to_string())
-                    }
-                }
-                dropbox::ListFolderCallError::PathNotFound(p) => {
-                    storage_models::ListFolderError::PathNotFound(p)
-                }
-            };
-            Err(err)
+        (
+            storage_models::StorageType::Cloud(clouds::CloudId::Dropbox),
+            storage_models::CallInData::preview(in_data),
+        ) => {
+            let path = in_data.path.clone();
+            let messages_clone = messages.clone();
+            let rth_clone = rth.clone();
+            let (s, r) = tokio::sync::oneshot::channel::<()>();
+            let handle = rth.spawn(async move {
+                let _ = r.await;
+                preview_dropbox(
+                    rth_clone,
+                    auth_info_holder,
+                    in_data,
+                    call_id,
+                    messages_clone,
+                )
+                .await
+            })?;
+            log::debug!("storage_call.preview: {}", &path);
+            let _ = messages.send(call_messages::Message {
+                call_id: Some(call_id),
+                data: call_messages::Data::Preview(call_messages::Preview::Started {
+                    handle,
+                    path,
+                }),
+            });
+            let _ = s.send(());
+            Ok(())
         }
-    }
-}
-
-async fn list_folder_dropbox(
-    rth: RuntimeHolder,
-    auth_info_holder: clouds::AuthInfoHolder,
-    path: String,
-    call_id: u64,
-    messages: MessagesSender<call_messages::Message>,
-) {
-    let res =
-        list_folder_dropbox_impl(rth, auth_info_holder, path, call_id, messages.clone()).await;
-    let _ = messages.send(call_messages::Message {
-        call_id: Some(call_id),
-        data: call_messages::Data::ListFolder(call_messages::ListFolder::Finished { result: res }),
-    });
-}
-
-async fn download_file_dropbox_impl(
-    rth: RuntimeHolder,
-    auth_info_holder: clouds::AuthInfoHolder,
-    remote_path: String,
-    local_path: String,
-    call_id: u64,
-    messages: MessagesSender<call_messages::Message>,
-) -> Result<storage_models::download_file_out_data, storage_models::DownloadFileError> {
-    let mut params = dropbox::DownloadFileParams {
-        path: remote_path.to_owned(),
-        save_to: local_path.to_owned(),
-    };
-
-    params.path = remote_path.clone();
-    if params.path.eq(r"/") {
-        panic!("path = '/'");
-    }
-    if !params.path.is_empty() {
-        params.path = "/".to_owned() + &params.path;
-    }
-
-    match dropbox::download_file(rth, auth_info_holder.clone(), params, call_id, messages).await {
-        Ok(res) => Ok(storage_models::download_file_out_data { name: res.name }),
-        Err(e) => {
-            let err = match e {
-                dropbox::DownloadFileCallError::Base(base) => {
-                    if base.is_token_error() {
-                        storage_models::DownloadFileError::Token(base.to_string())
-                    } else {
-                        storage_models::DownloadFileError::Other(base.to_string())
-                    }
-                }
-                dropbox::DownloadFileCallError::NotFound(path) => {
-                    storage_models::DownloadFileError::RemotePathNotFound(path)
-                }
-                _ => storage_models::DownloadFileError::Other(e.to_string()),
-            };
-            Err(err)
+        (
+            storage_models::StorageType::Cloud(clouds::CloudId::Dropbox),
+            storage_models::CallInData::search(in_data),
+        ) => {
+            let query = in_data.query.clone();
+            let messages_clone = messages.clone();
+            let rth_clone = rth.clone();
+            let (s, r) = tokio::sync::oneshot::channel::<()>();
+            let handle = rth.spawn(async move {
+                let _ = r.await;
+                search_dropbox(
+                    rth_clone,
+                    auth_info_holder,
+                    in_data,
+                    call_id,
+                    messages_clone,
+                )
+                .await
+            })?;
+            log::debug!("storage_call.search: {}", &query);
+            let _ = messages.send(call_messages::Message {
+                call_id: Some(call_id),
+                data: call_messages::Data::Search(call_messages::Search::Started { handle, query }),
+            });
+            let _ = s.send(());
+            Ok(())
         }
-    }
-}
-
-async fn download_file_dropbox(
-    rth: RuntimeHolder,
-    auth_info_holder: clouds::AuthInfoHolder,
-    remote_path: String,
-    local_path: String,
-    call_id: u64,
-    messages: MessagesSender<call_messages::Message>,
-) {
-    let res = download_file_dropbox_impl(
-        rth,
-        auth_info_holder,
-        remote_path,
-        local_path,
-        call_id,
-        messages.clone(),
-    )
-    .await;
-    let _ = messages.send(call_messages::Message {
-        call_id: Some(call_id),
-        data: call_messages::Data::DownloadFile(call_messages::DownloadFile::Finished {
-            result: res,
-        }),
+        (
+            storage_models::StorageType::Cloud(clouds::CloudId::Dropbox),
+            storage_models::CallInData::watch_folder(in_data),
+        ) => {
+            let path = in_data.path.clone();
+            let messages_clone = messages.clone();
+            let rth_clone = rth.clone();
+            let (s, r) = tokio::sync::oneshot::channel::<()>();
+            let handle = rth.spawn(async move {
+                let _ = r.await;
+                watch_folder_dropbox(
+                    rth_clone,
+                    auth_info_holder,
+                    in_data,
+                    call_id,
+                    messages_clone,
+                )
+                .await
+            })?;
+            log::debug!("storage_call.watch_folder: {}", &path);
+            let _ = messages.send(call_messages::Message {
+                call_id: Some(call_id),
+                data: call_messages::Data::WatchFolder(call_messages::WatchFolder::Started {
+                    handle,
+                    path,
+                }),
+            });
+            let _ = s.send(());
+            Ok(())
+        }
+        (
+            storage_models::StorageType::Cloud(clouds::CloudId::Dropbox),
+            storage_models::CallInData::restore(in_data),
+        ) => {
+            let path = in_data.path.clone();
+            let messages_clone = messages.clone();
+            let rth_clone = rth.clone();
+            let (s, r) = tokio::sync::oneshot::channel::<()>();
+            let handle = rth.spawn(async move {
+                let _ = r.await;
+                restore_dropbox(
+                    rth_clone,
+                    auth_info_holder,
+                    in_data,
+                    call_id,
+                    messages_clone,
+                )
+                .await
+            })?;
+            log::debug!("storage_call.restore: {}", &path);
+            let _ = messages.send(call_messages::Message {
+                call_id: Some(call_id),
+                data: call_messages::Data::Restore(call_messages::Restore::Started {
+                    handle,
+                    path,
+                }),
+            });
+            let _ = s.send(());
+            Ok(())
+        }
+        (
+            storage_models::StorageType::Cloud(clouds::CloudId::Dropbox),
+            storage_models::CallInData::list_revisions(in_data),
+        ) => {
+            let path = in_data.path.clone();
+            let messages_clone = messages.clone();
+            let rth_clone = rth.clone();
+            let (s, r) = tokio::sync::oneshot::channel::<()>();
+            let handle = rth.spawn(async move {
+                let _ = r.await;
+                list_revisions_dropbox(
+                    rth_clone,
+                    auth_info_holder,
+                    in_data,
+                    call_id,
+                    messages_clone,
+                )
+                .await
+            })?;
+            log::debug!("storage_call.list_revisions: {}", &path);
+            let _ = messages.send(call_messages::Message {
+                call_id: Some(call_id),
+                data: call_messages::Data::ListRevisions(call_messages::ListRevisions::Started {
+                    handle,
+                    path,
+                }),
+            });
+            let _ = s.send(());
+            Ok(())
+        }
+        (
+            storage_models::StorageType::Cloud(clouds::CloudId::Dropbox),
+            storage_models::CallInData::shared_links(in_data),
+        ) => {
+            let path = in_data.path.clone();
+            let messages_clone = messages.clone();
+            let rth_clone = rth.clone();
+            let (s, r) = tokio::sync::oneshot::channel::<()>();
+            let handle = rth.spawn(async move {
+                let _ = r.await;
+                shared_links_dropbox(
+                    rth_clone,
+                    auth_info_holder,
+                    in_data,
+                    call_id,
+                    messages_clone,
+                )
+                .await
+            })?;
+            log::debug!("storage_call.shared_links: {}", &path);
+            let _ = messages.send(call_messages::Message {
+                call_id: Some(call_id),
+                data: call_messages::Data::SharedLinks(call_messages::SharedLinks::Started {
+                    handle,
+                    path,
+                }),
+            });
+            let _ = s.send(());
+            Ok(())
+        }
+        (
+            storage_models::StorageType::Cloud(clouds::CloudId::Dropbox),
+            storage_models::CallInData::temporary_link(in_data),
+        ) => {
+            let path = in_data.path.clone();
+            let messages_clone = messages.clone();
+            let rth_clone = rth.clone();
+            let (s, r) = tokio::sync::oneshot::channel::<()>();
+            let handle = rth.spawn(async move {
+                let _ = r.await;
+                temporary_link_dropbox(
+                    rth_clone,
+                    auth_info_holder,
+                    in_data,
+                    call_id,
+                    messages_clone,
+                )
+                .await
+            })?;
+            log::debug!("storage_call.temporary_link: {}", &path);
+            let _ = messages.send(call_messages::Message {
+                call_id: Some(call_id),
+                data: call_messages::Data::TemporaryLink(call_messages::TemporaryLink::Started {
+                    handle,
+                    path,
+                }),
+            });
+            let _ = s.send(());
+            Ok(())
+        }
+        (
+            storage_models::StorageType::Cloud(clouds::CloudId::Dropbox),
+            storage_models::CallInData::account_info,
+        ) => {
+            let messages_clone = messages.clone();
+            let rth_clone = rth.clone();
+            let (s, r) = tokio::sync::oneshot::channel::<()>();
+            let handle = rth.spawn(async move {
+                let _ = r.await;
+                account_info_dropbox(rth_clone, auth_info_holder, call_id, messages_clone).await
+            })?;
+            log::debug!("storage_call.account_info");
+            let _ = messages.send(call_messages::Message {
+                call_id: Some(call_id),
+                data: call_messages::Data::AccountInfo(call_messages::AccountInfo::Started {
+                    handle,
+                }),
+            });
+            let _ = s.send(());
+            Ok(())
+        }
+        (
+            storage_models::StorageType::Cloud(clouds::CloudId::Dropbox),
+            storage_models::CallInData::list_recursive(in_data),
+        ) => {
+            let path = in_data.path.clone();
+            let purpose = in_data.purpose;
+            let messages_clone = messages.clone();
+            let rth_clone = rth.clone();
+            let (s, r) = tokio::sync::oneshot::channel::<()>();
+            let handle = rth.spawn(async move {
+                let _ = r.await;
+                list_recursive_dropbox(
+                    rth_clone,
+                    auth_info_holder,
+                    in_data,
+                    call_id,
+                    messages_clone,
+                )
+                .await
+            })?;
+            log::debug!("storage_call.list_recursive: {}", &path);
+            let _ = messages.send(call_messages::Message {
+                call_id: Some(call_id),
+                data: call_messages::Data::ListRecursive(call_messages::ListRecursive::Started {
+                    handle,
+                    path,
+                    purpose,
+                }),
+            });
+            let _ = s.send(());
+            Ok(())
+        }
+        (
+            storage_models::StorageType::Cloud(clouds::CloudId::Dropbox),
+            storage_models::CallInData::file_ops(in_data),
+        ) => {
+            let total = in_data.ops.len() as u64;
+            let messages_clone = messages.clone();
+            let rth_clone = rth.clone();
+            let (s, r) = tokio::sync::oneshot::channel::<()>();
+            let handle = rth.spawn(async move {
+                let _ = r.await;
+                file_ops_dropbox(
+                    rth_clone,
+                    auth_info_holder,
+                    in_data,
+                    call_id,
+                    messages_clone,
+                )
+                .await
+            })?;
+            log::debug!("storage_call.file_ops: {}", total);
+            let _ = messages.send(call_messages::Message {
+                call_id: Some(call_id),
+                data: call_messages::Data::FileOps(call_messages::FileOps::Started {
+                    handle,
+                    total,
+                }),
+            });
+            let _ = s.send(());
+            Ok(())
+        }
+        (
+            storage_models::StorageType::Cloud(clouds::CloudId::Dropbox),
+            storage_models::CallInData::transfer(in_data),
+        ) => {
+            let total = in_data.ops.len() as u64;
+            let purpose = in_data.purpose;
+            let messages_clone = messages.clone();
+            let rth_clone = rth.clone();
+            let (s, r) = tokio::sync::oneshot::channel::<()>();
+            let handle = rth.spawn(async move {
+                let _ = r.await;
+                transfer_dropbox(
+                    rth_clone,
+                    auth_info_holder,
+                    in_data,
+                    call_id,
+                    messages_clone,
+                )
+                .await
+            })?;
+            log::debug!("storage_call.transfer: {} ({:?})", total, purpose);
+            let _ = messages.send(call_messages::Message {
+                call_id: Some(call_id),
+                data: call_messages::Data::Transfer(call_messages::Transfer::Started {
+                    handle,
+                    total,
+                    purpose,
+                }),
+            });
+            let _ = s.send(());
+            Ok(())
+        }
+        (
+            storage_models::StorageType::Cloud(clouds::CloudId::Dropbox),
+            storage_models::CallInData::mirror(in_data),
+        ) => {
+            let name = in_data.name.clone();
+            let messages_clone = messages.clone();
+            let rth_clone = rth.clone();
+            let (s, r) = tokio::sync::oneshot::channel::<()>();
+            let handle = rth.spawn(async move {
+                let _ = r.await;
+                mirror_dropbox(
+                    rth_clone,
+                    auth_info_holder,
+                    in_data,
+                    call_id,
+                    messages_clone,
+                )
+                .await
+            })?;
+            log::debug!("storage_call.mirror: {}", &name);
+            let _ = messages.send(call_messages::Message {
+                call_id: Some(call_id),
+                data: call_messages::Data::Mirror(call_messages::Mirror::Started { handle, name }),
+            });
+            let _ = s.send(());
+            Ok(())
+        }
+        (
+            storage_models::StorageType::Cloud(clouds::CloudId::Dropbox),
+            storage_models::CallInData::sync(in_data),
+        ) => {
+            let name = in_data.name.clone();
+            let messages_clone = messages.clone();
+            let rth_clone = rth.clone();
+            let (s, r) = tokio::sync::oneshot::channel::<()>();
+            let handle = rth.spawn(async move {
+                let _ = r.await;
+                sync_dropbox(
+                    rth_clone,
+                    auth_info_holder,
+                    in_data,
+                    call_id,
+                    messages_clone,
+                )
+                .await
+            })?;
+            log::debug!("storage_call.sync: {}", &name);
+            let _ = messages.send(call_messages::Message {
+                call_id: Some(call_id),
+                data: call_messages::Data::Sync(call_messages::Sync::Started { handle, name }),
+            });
+            let _ = s.send(());
+            Ok(())
+        }
+        (
+            storage_models::StorageType::Cloud(clouds::CloudId::Dropbox),
+            storage_models::CallInData::backup(in_data),
+        ) => {
+            let name = in_data.name.clone();
+            let messages_clone = messages.clone();
+            let rth_clone = rth.clone();
+            let (s, r) = tokio::sync::oneshot::channel::<()>();
+            let handle = rth.spawn(async move {
+                let _ = r.await;
+                backup_dropbox(
+                    rth_clone,
+                    auth_info_holder,
+                    in_data,
+                    call_id,
+                    messages_clone,
+                )
+                .await
+            })?;
+            log::debug!("storage_call.backup: {}", &name);
+            let _ = messages.send(call_messages::Message {
+                call_id: Some(call_id),
+                data: call_messages::Data::Backup(call_messages::Backup::Started { handle, name }),
+            });
+            let _ = s.send(());
+            Ok(())
+        }
+        (
+            storage_models::StorageType::Cloud(clouds::CloudId::Dropbox),
+            storage_models::CallInData::webdav(in_data),
+        ) => {
+            let port = in_data.port;
+            let messages_clone = messages.clone();
+            let rth_clone = rth.clone();
+            let (s, r) = tokio::sync::oneshot::channel::<()>();
+            let handle = rth.spawn(async move {
+                let _ = r.await;
+                webdav_dropbox(
+                    rth_clone,
+                    auth_info_holder,
+                    in_data,
+                    call_id,
+                    messages_clone,
+                )
+                .await
+            })?;
+            log::debug!("storage_call.webdav: {}", port);
+            let _ = messages.send(call_messages::Message {
+                call_id: Some(call_id),
+                data: call_messages::Data::WebDav(call_messages::WebDav::Started { handle, port }),
+            });
+            let _ = s.send(());
+            Ok(())
+        }
+        // local folder only, storage independent
+        (_, storage_models::CallInData::compare(in_data)) => {
+            let local_path = in_data.local_path.clone();
+            let messages_clone = messages.clone();
+            let (s, r) = tokio::sync::oneshot::channel::<()>();
+            let handle = rth.spawn(async move {
+                let _ = r.await;
+                compare_local(in_data, call_id, messages_clone).await
+            })?;
+            log::debug!("storage_call.compare: {}", &local_path);
+            let _ = messages.send(call_messages::Message {
+                call_id: Some(call_id),
+                data: call_messages::Data::Compare(call_messages::Compare::Started {
+                    handle,
+                    local_path,
+                }),
+            });
+            let _ = s.send(());
+            Ok(())
+        }
+    }
+}
+
+fn media_info_from_dropbox(info: dropbox::MediaInfo) -> Option<storage_models::MediaInfo> {
+    match info {
+        dropbox::MediaInfo::Pending => None,
+        dropbox::MediaInfo::Metadata { metadata } => {
+            let (kind, dimensions, location, time_taken, duration) = match metadata {
+                dropbox::MediaMetadata::Photo {
+                    dimensions,
+                    location,
+                    time_taken,
+                } => ("photo", dimensions, location, time_taken, None),
+                dropbox::MediaMetadata::Video {
+                    dimensions,
+                    location,
+                    time_taken,
+                    duration,
+                } => ("video", dimensions, location, time_taken, duration),
+            };
+            Some(storage_models::MediaInfo {
+                kind: kind.to_string(),
+                dimensions: dimensions.map(|d| (d.width, d.height)),
+                location: location.map(|l| (l.latitude, l.longitude)),
+                time_taken,
+                duration,
+            })
+        }
+    }
+}
+
+/// None for deleted entries
+pub fn item_from_meta(meta: dropbox::Meta) -> Option<storage_models::Item> {
+    match meta {
+        dropbox::Meta::File(file) => Some(storage_models::Item {
+            name: file.name,
+            id: file.id,
+            is_folder: false,
+            modified: Some(file.server_modified),
+            size: Some(file.size),
+            items: Some(vec![]),
+            path_display: file.path_display,
+            path_lower: file.path_lower,
+            rev: file.rev,
+            client_modified: Some(file.client_modified),
+            is_downloadable: file.is_downloadable,
+            content_hash: file.content_hash,
+            media_info: file.media_info.and_then(media_info_from_dropbox),
+            is_deleted: false,
+        }),
+        dropbox::Meta::Folder(folder) => Some(storage_models::Item {
+            name: folder.name,
+            id: folder.id,
+            is_folder: true,
+            modified: None,
+            size: None,
+            items: None,
+            path_display: folder.path_display,
+            path_lower: folder.path_lower,
+            rev: "".to_string(),
+            client_modified: None,
+            is_downloadable: false,
+            content_hash: "".to_string(),
+            media_info: None,
+            is_deleted: false,
+        }),
+        dropbox::Meta::Deleted(_) => None,
+    }
+}
+
+pub fn item_from_download_result(res: dropbox::DownloadFileCallResult) -> storage_models::Item {
+    storage_models::Item {
+        name: res.name,
+        id: res.id,
+        is_folder: false,
+        modified: Some(res.server_modified),
+        size: Some(res.size),
+        items: Some(vec![]),
+        path_display: res.path_display,
+        path_lower: res.path_lower,
+        rev: res.rev,
+        client_modified: Some(res.client_modified),
+        is_downloadable: true,
+        content_hash: res.content_hash,
+        media_info: None,
+        is_deleted: false,
+    }
+}
+
+/// deleted entries do not tell whether they were files or folders, `is_folder` is always false
+fn deleted_item_from_meta(meta: dropbox::DeletedMeta) -> storage_models::Item {
+    storage_models::Item {
+        name: meta.name,
+        id: "".to_string(),
+        is_folder: false,
+        modified: None,
+        size: None,
+        items: None,
+        path_display: meta.path_display,
+        path_lower: meta.path_lower,
+        rev: "".to_string(),
+        client_modified: None,
+        is_downloadable: false,
+        content_hash: "".to_string(),
+        media_info: None,
+        is_deleted: true,
+    }
+}
+
+fn list_folder_error(e: dropbox::ListFolderCallError) -> storage_models::ListFolderError {
+    match e {
+        dropbox::ListFolderCallError::Base(base) => {
+            if base.is_token_error() {
+                storage_models::ListFolderError::Token(base.to_string())
+            } else {
+                storage_models::ListFolderError::Other(base.to_string())
+            }
+        }
+        dropbox::ListFolderCallError::PathNotFound(p) => {
+            storage_models::ListFolderError::PathNotFound(p)
+        }
+        e => storage_models::ListFolderError::Other(e.to_string()),
+    }
+}
+
+fn item_delta_from_meta(meta: dropbox::Meta) -> storage_models::ItemDelta {
+    match meta {
+        dropbox::Meta::Deleted(deleted) => {
+            storage_models::ItemDelta::Deleted { name: deleted.name }
+        }
+        meta => storage_models::ItemDelta::Changed(Box::new(item_from_meta(meta).unwrap())),
+    }
+}
+
+/// calls list_folder/continue until has_more is false, returns entries and last cursor
+pub async fn list_folder_continue_dropbox(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    cursor: String,
+    mut count: u64,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<(Vec<dropbox::Meta>, String), dropbox::ListFolderCallError> {
+    let mut entries: Vec<dropbox::Meta> = vec![];
+    let mut cursor = cursor;
+    loop {
+        let res = dropbox::list_folder_continue(
+            rth.clone(),
+            auth_info_holder.clone(),
+            dropbox::ListFolderContinueParams { cursor },
+            messages.clone(),
+            dropbox::token_messages::<call_messages::ListFolder>(call_id),
+        )
+        .await?;
+        count += res.entries.len() as u64;
+        cursor = res.cursor;
+        entries.extend(res.entries);
+        if !res.has_more {
+            return Ok((entries, cursor));
+        }
+        let _ = messages.send(call_messages::Message {
+            call_id: Some(call_id),
+            data: call_messages::Data::ListFolder(call_messages::ListFolder::Progress {
+                value: count,
+                value_str: None,
+            }),
+        });
+    }
+}
+
+async fn list_folder_dropbox_impl(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::list_folder_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<storage_models::list_folder_out_data, storage_models::ListFolderError> {
+    let path = in_data.path;
+    if path.eq("/") {
+        panic!("path = '/'");
+    }
+    let include_deleted = in_data.include_deleted;
+
+    // listings with deleted entries are not cached
+    let cached = if include_deleted {
+        None
+    } else {
+        in_data.cache.get(&path)
+    };
+    if let Some(mut cached) = cached {
+        match list_folder_continue_dropbox(
+            rth.clone(),
+            auth_info_holder.clone(),
+            cached.cursor.clone(),
+            0,
+            call_id,
+            messages.clone(),
+        )
+        .await
+        {
+            Ok((entries, cursor)) => {
+                log::debug!("list_folder.deltas: {}: {}", &path, entries.len());
+                folders_cache::apply_deltas(
+                    &mut cached.items,
+                    entries.into_iter().map(item_delta_from_meta).collect(),
+                );
+                cached.cursor = cursor;
+                cached.saved = chrono::Utc::now();
+                in_data.cache.put(&path, cached.clone()).await;
+                return Ok(storage_models::list_folder_out_data {
+                    path,
+                    items: Some(cached.items),
+                    cursor: cached.cursor,
+                    include_deleted,
+                });
+            }
+            Err(dropbox::ListFolderCallError::Reset) => {
+                log::debug!("list_folder.reset: {}", &path);
+            }
+            Err(e) => return Err(list_folder_error(e)),
+        }
+    }
+
+    let params = dropbox::ListFolderParams {
+        path: if path.is_empty() {
+            "".to_string()
+        } else {
+            "/".to_owned() + &path
+        },
+        recursive: Some(false),
+        include_deleted: Some(include_deleted),
+        include_has_explicit_shared_members: None,
+        include_mounted_folders: None, //  = true
+        limit: Some(1000),             //  = 1000
+        include_non_downloadable_files: None,
+    };
+
+    let res = dropbox::list_folder(
+        rth.clone(),
+        auth_info_holder.clone(),
+        params,
+        messages.clone(),
+        dropbox::token_messages::<call_messages::ListFolder>(call_id),
+    )
+    .await
+    .map_err(list_folder_error)?;
+    let mut cursor = res.cursor;
+    let mut entries = res.entries;
+    if res.has_more {
+        let count = entries.len() as u64;
+        let _ = messages.send(call_messages::Message {
+            call_id: Some(call_id),
+            data: call_messages::Data::ListFolder(call_messages::ListFolder::Progress {
+                value: count,
+                value_str: None,
+            }),
+        });
+        let (more, last_cursor) =
+            list_folder_continue_dropbox(rth, auth_info_holder, cursor, count, call_id, messages)
+                .await
+                .map_err(list_folder_error)?;
+        entries.extend(more);
+        cursor = last_cursor;
+    }
+    let items: Vec<storage_models::Item> = entries
+        .into_iter()
+        .filter_map(|entry| match entry {
+            dropbox::Meta::Deleted(deleted) => Some(deleted_item_from_meta(deleted)),
+            entry => item_from_meta(entry),
+        })
+        .collect();
+    if !include_deleted {
+        in_data
+            .cache
+            .put(
+                &path,
+                folders_cache::CachedFolder {
+                    cursor: cursor.clone(),
+                    saved: chrono::Utc::now(),
+                    items: items.clone(),
+                },
+            )
+            .await;
+    }
+    Ok(storage_models::list_folder_out_data {
+        path,
+        items: Some(items),
+        cursor,
+        include_deleted,
+    })
+}
+
+async fn list_folder_dropbox(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::list_folder_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) {
+    let res =
+        list_folder_dropbox_impl(rth, auth_info_holder, in_data, call_id, messages.clone()).await;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::ListFolder(call_messages::ListFolder::Finished { result: res }),
+    });
+}
+
+async fn download_file_dropbox_impl(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    remote_path: String,
+    local_path: String,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<storage_models::download_file_out_data, storage_models::DownloadFileError> {
+    let mut params = dropbox::DownloadFileParams {
+        path: remote_path.to_owned(),
+        save_to: local_path.to_owned(),
+    };
+
+    params.path = remote_path.clone();
+    if params.path.eq(r"/") {
+        panic!("path = '/'");
+    }
+    // "rev:..." paths are passed as is
+    if !params.path.is_empty() && !params.path.starts_with("rev:") {
+        params.path = "/".to_owned() + &params.path;
+    }
+
+    match dropbox::download_file(rth, auth_info_holder.clone(), params, call_id, messages).await {
+        Ok(res) => Ok(storage_models::download_file_out_data {
+            name: res.name,
+            rev: res.rev,
+            path_display: res.path_display,
+        }),
+        Err(e) => {
+            let err = match e {
+                dropbox::DownloadFileCallError::Base(base) => {
+                    if base.is_token_error() {
+                        storage_models::DownloadFileError::Token(base.to_string())
+                    } else {
+                        storage_models::DownloadFileError::Other(base.to_string())
+                    }
+                }
+                dropbox::DownloadFileCallError::NotFound(path) => {
+                    storage_models::DownloadFileError::RemotePathNotFound(path)
+                }
+                _ => storage_models::DownloadFileError::Other(e.to_string()),
+            };
+            Err(err)
+        }
+    }
+}
+
+async fn download_file_dropbox(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    remote_path: String,
+    local_path: String,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) {
+    let res = download_file_dropbox_impl(
+        rth,
+        auth_info_holder,
+        remote_path,
+        local_path,
+        call_id,
+        messages.clone(),
+    )
+    .await;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::DownloadFile(call_messages::DownloadFile::Finished {
+            result: res,
+        }),
+    });
+}
+
+async fn get_metadata_dropbox_impl(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    path: String,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<storage_models::get_metadata_out_data, storage_models::GetMetadataError> {
+    let mut params = dropbox::GetMetadataParams {
+        path: path.to_owned(),
+        include_media_info: Some(true),
+        include_deleted: Some(false),
+        include_has_explicit_shared_members: None,
+    };
+    if params.path.eq("/") || params.path.is_empty() {
+        return Err(storage_models::GetMetadataError::Other(
+            "metadata for root folder is not supported".to_string(),
+        ));
+    }
+    params.path = "/".to_owned() + &params.path;
+
+    match dropbox::get_metadata(
+        rth,
+        auth_info_holder,
+        params,
+        messages,
+        dropbox::token_messages::<call_messages::GetMetadata>(call_id),
+    )
+    .await
+    {
+        Ok(meta) => match item_from_meta(meta) {
+            Some(item) => Ok(storage_models::get_metadata_out_data {
+                item: Box::new(item),
+            }),
+            None => Err(storage_models::GetMetadataError::PathNotFound(path)),
+        },
+        Err(e) => Err(match e {
+            dropbox::GetMetadataCallError::Base(base) => {
+                if base.is_token_error() {
+                    storage_models::GetMetadataError::Token(base.to_string())
+                } else {
+                    storage_models::GetMetadataError::Other(base.to_string())
+                }
+            }
+            dropbox::GetMetadataCallError::PathNotFound(p) => {
+                storage_models::GetMetadataError::PathNotFound(p)
+            }
+        }),
+    }
+}
+
+async fn get_metadata_dropbox(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    path: String,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) {
+    let res =
+        get_metadata_dropbox_impl(rth, auth_info_holder, path, call_id, messages.clone()).await;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::GetMetadata(call_messages::GetMetadata::Finished {
+            result: res,
+        }),
+    });
+}
+
+async fn get_thumbnails_dropbox_impl(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::get_thumbnails_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<storage_models::get_thumbnails_out_data, storage_models::GetThumbnailsError> {
+    let send_thumbnail = |rev: String, data: &[u8]| {
+        let image = thumbnails::decode(data);
+        let _ = messages.send(call_messages::Message {
+            call_id: Some(call_id),
+            data: call_messages::Data::GetThumbnails(call_messages::GetThumbnails::Thumbnail {
+                rev,
+                image,
+            }),
+        });
+    };
+
+    let mut res = storage_models::get_thumbnails_out_data {
+        loaded: 0,
+        failed: 0,
+    };
+    let mut to_fetch: Vec<storage_models::ThumbnailRequest> = vec![];
+    for entry in in_data.entries {
+        match in_data.cache.get(&entry.rev) {
+            Some(data) => {
+                send_thumbnail(entry.rev, &data);
+                res.loaded += 1;
+            }
+            None => to_fetch.push(entry),
+        }
+    }
+
+    for chunk in to_fetch.chunks(dropbox::THUMBNAIL_BATCH_LIMIT) {
+        let params = dropbox::GetThumbnailBatchParams {
+            entries: chunk
+                .iter()
+                .map(|entry| dropbox::ThumbnailArg {
+                    path: "/".to_owned() + &entry.path,
+                    format: "jpeg".to_string(),
+                    size: "w128h128".to_string(),
+                    mode: "bestfit".to_string(),
+                })
+                .collect(),
+        };
+        match dropbox::get_thumbnail_batch(
+            rth.clone(),
+            auth_info_holder.clone(),
+            params,
+            messages.clone(),
+            dropbox::token_messages::<call_messages::GetThumbnails>(call_id),
+        )
+        .await
+        {
+            Ok(batch) => {
+                for (entry, result) in chunk.iter().zip(batch.entries) {
+                    match result {
+                        dropbox::GetThumbnailBatchResultEntry::Success {
+                            metadata,
+                            thumbnail,
+                        } => match base64::decode(&thumbnail) {
+                            Ok(data) => {
+                                in_data.cache.put(&metadata.rev, &data);
+                                send_thumbnail(metadata.rev, &data);
+                                res.loaded += 1;
+                            }
+                            Err(e) => {
+                                log::error!("thumbnail decode error for '{}': {}", entry.path, e);
+                                res.failed += 1;
+                            }
+                        },
+                        dropbox::GetThumbnailBatchResultEntry::Failure { failure } => {
+                            log::debug!("thumbnail failure for '{}': {}", entry.path, failure);
+                            let _ = messages.send(call_messages::Message {
+                                call_id: Some(call_id),
+                                data: call_messages::Data::GetThumbnails(
+                                    call_messages::GetThumbnails::Thumbnail {
+                                        rev: entry.rev.clone(),
+                                        image: Err(failure.to_string()),
+                                    },
+                                ),
+                            });
+                            res.failed += 1;
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                return Err(match e {
+                    dropbox::GetThumbnailBatchCallError::Base(base) => {
+                        if base.is_token_error() {
+                            storage_models::GetThumbnailsError::Token(base.to_string())
+                        } else {
+                            storage_models::GetThumbnailsError::Other(base.to_string())
+                        }
+                    }
+                    e => storage_models::GetThumbnailsError::Other(e.to_string()),
+                });
+            }
+        }
+    }
+    Ok(res)
+}
+
+async fn get_thumbnails_dropbox(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::get_thumbnails_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) {
+    let res =
+        get_thumbnails_dropbox_impl(rth, auth_info_holder, in_data, call_id, messages.clone())
+            .await;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::GetThumbnails(call_messages::GetThumbnails::Finished {
+            result: res,
+        }),
+    });
+}
+
+async fn preview_dropbox_impl(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::preview_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<storage_models::preview_out_data, storage_models::PreviewError> {
+    if in_data.path.is_empty() || in_data.path.eq("/") {
+        panic!("path = '{}'", in_data.path);
+    }
+    let params = dropbox::DownloadFileParams {
+        path: "/".to_owned() + &in_data.path,
+        save_to: "".to_string(),
+    };
+    match dropbox::download_head(
+        rth,
+        auth_info_holder,
+        params,
+        in_data.max_bytes,
+        messages,
+        dropbox::token_messages::<call_messages::Preview>(call_id),
+    )
+    .await
+    {
+        Ok((meta, data)) => {
+            let truncated = (data.len() as u64) < meta.size;
+            match preview::make_preview(&meta.name, &data, truncated) {
+                Ok(data) => Ok(storage_models::preview_out_data {
+                    size: meta.size,
+                    data,
+                }),
+                Err(e) => Err(storage_models::PreviewError::Unsupported(e)),
+            }
+        }
+        Err(e) => Err(match e {
+            dropbox::DownloadFileCallError::Base(base) => {
+                if base.is_token_error() {
+                    storage_models::PreviewError::Token(base.to_string())
+                } else {
+                    storage_models::PreviewError::Other(base.to_string())
+                }
+            }
+            dropbox::DownloadFileCallError::NotFound(path) => {
+                storage_models::PreviewError::RemotePathNotFound(path)
+            }
+            _ => storage_models::PreviewError::Other(e.to_string()),
+        }),
+    }
+}
+
+async fn preview_dropbox(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::preview_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) {
+    let res = preview_dropbox_impl(rth, auth_info_holder, in_data, call_id, messages.clone()).await;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::Preview(call_messages::Preview::Finished { result: res }),
+    });
+}
+
+/// upper bound for the number of search hits collected via search/continue_v2
+const SEARCH_MAX_ITEMS: usize = 1000;
+
+async fn search_dropbox_impl(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::search_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<storage_models::search_out_data, storage_models::SearchError> {
+    let to_search_error = |e: dropbox::SearchCallError| match e {
+        dropbox::SearchCallError::Base(base) => {
+            if base.is_token_error() {
+                storage_models::SearchError::Token(base.to_string())
+            } else {
+                storage_models::SearchError::Other(base.to_string())
+            }
+        }
+        dropbox::SearchCallError::PathNotFound(path) => {
+            storage_models::SearchError::PathNotFound(path)
+        }
+        e => storage_models::SearchError::Other(e.to_string()),
+    };
+    let non_empty = |v: Vec<String>| if v.is_empty() { None } else { Some(v) };
+
+    let params = dropbox::SearchParams {
+        query: in_data.query.clone(),
+        options: Some(dropbox::SearchOptions {
+            path: if in_data.path.is_empty() {
+                None
+            } else {
+                Some("/".to_owned() + &in_data.path)
+            },
+            max_results: Some(1000),
+            file_status: None,
+            filename_only: Some(in_data.filename_only),
+            file_extensions: non_empty(in_data.extensions),
+            file_categories: non_empty(in_data.categories),
+        }),
+    };
+
+    let mut items: Vec<storage_models::Item> = vec![];
+    let mut res = dropbox::search(
+        rth.clone(),
+        auth_info_holder.clone(),
+        params,
+        messages.clone(),
+        dropbox::token_messages::<call_messages::Search>(call_id),
+    )
+    .await
+    .map_err(to_search_error)?;
+    loop {
+        items.extend(res.matches.into_iter().filter_map(|m| match m.metadata {
+            dropbox::SearchMatchMetadata::Metadata { metadata } => item_from_meta(*metadata),
+            dropbox::SearchMatchMetadata::Other => None,
+        }));
+        let _ = messages.send(call_messages::Message {
+            call_id: Some(call_id),
+            data: call_messages::Data::Search(call_messages::Search::Progress {
+                count: items.len() as u64,
+            }),
+        });
+        match (res.has_more, res.cursor) {
+            (true, Some(cursor)) if items.len() < SEARCH_MAX_ITEMS => {
+                res = dropbox::search_continue(
+                    rth.clone(),
+                    auth_info_holder.clone(),
+                    dropbox::SearchContinueParams { cursor },
+                    messages.clone(),
+                    dropbox::token_messages::<call_messages::Search>(call_id),
+                )
+                .await
+                .map_err(to_search_error)?;
+            }
+            (has_more, _) => {
+                return Ok(storage_models::search_out_data {
+                    query: in_data.query,
+                    items,
+                    limited: has_more,
+                });
+            }
+        }
+    }
+}
+
+async fn search_dropbox(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::search_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) {
+    let res = search_dropbox_impl(rth, auth_info_holder, in_data, call_id, messages.clone()).await;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::Search(call_messages::Search::Finished { result: res }),
+    });
+}
+
+/// longpoll timeout, seconds
+const WATCH_FOLDER_TIMEOUT: u64 = 120;
+/// pause after network error, doubled on each failure in a row up to the max, seconds
+const WATCH_FOLDER_RETRY_DELAY: u64 = 30;
+const WATCH_FOLDER_MAX_RETRY_DELAY: u64 = 600;
+
+/// lists the watched folder again after its cursor was reset, replaces the listing in the
+/// cache and in the app, returns new cursor
+async fn watch_folder_relist(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: &storage_models::watch_folder_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<String, dropbox::ListFolderCallError> {
+    log::debug!("watch_folder.reset: {}", &in_data.path);
+    let params = dropbox::ListFolderParams {
+        path: if in_data.path.is_empty() {
+            "".to_string()
+        } else {
+            "/".to_owned() + &in_data.path
+        },
+        recursive: Some(false),
+        include_deleted: Some(false),
+        include_has_explicit_shared_members: None,
+        include_mounted_folders: None,
+        limit: Some(1000),
+        include_non_downloadable_files: None,
+    };
+    let mut res = dropbox::list_folder(
+        rth.clone(),
+        auth_info_holder.clone(),
+        params,
+        messages.clone(),
+        dropbox::token_messages::<call_messages::WatchFolder>(call_id),
+    )
+    .await?;
+    let mut entries = res.entries;
+    while res.has_more {
+        res = dropbox::list_folder_continue(
+            rth.clone(),
+            auth_info_holder.clone(),
+            dropbox::ListFolderContinueParams { cursor: res.cursor },
+            messages.clone(),
+            dropbox::token_messages::<call_messages::WatchFolder>(call_id),
+        )
+        .await?;
+        entries.extend(res.entries);
+    }
+    let items: Vec<storage_models::Item> = entries.into_iter().filter_map(item_from_meta).collect();
+    in_data
+        .cache
+        .put(
+            &in_data.path,
+            folders_cache::CachedFolder {
+                cursor: res.cursor.clone(),
+                saved: chrono::Utc::now(),
+                items: items.clone(),
+            },
+        )
+        .await;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::WatchFolder(call_messages::WatchFolder::Relisted { items }),
+    });
+    Ok(res.cursor)
+}
+
+/// fetches changes since `cursor` and reports them as deltas, returns new cursor
+async fn watch_folder_changes(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: &storage_models::watch_folder_in_data,
+    mut cursor: String,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<String, dropbox::ListFolderCallError> {
+    let mut entries: Vec<dropbox::Meta> = vec![];
+    loop {
+        let res = match dropbox::list_folder_continue(
+            rth.clone(),
+            auth_info_holder.clone(),
+            dropbox::ListFolderContinueParams { cursor },
+            messages.clone(),
+            dropbox::token_messages::<call_messages::WatchFolder>(call_id),
+        )
+        .await
+        {
+            Ok(res) => res,
+            Err(dropbox::ListFolderCallError::Reset) => {
+                return watch_folder_relist(rth, auth_info_holder, in_data, call_id, messages)
+                    .await;
+            }
+            Err(e) => return Err(e),
+        };
+        cursor = res.cursor;
+        entries.extend(res.entries);
+        if !res.has_more {
+            break;
+        }
+    }
+    log::debug!("watch_folder.changes: {}: {}", &in_data.path, entries.len());
+    let deltas: Vec<storage_models::ItemDelta> =
+        entries.into_iter().map(item_delta_from_meta).collect();
+    if let Some(mut cached) = in_data.cache.get(&in_data.path) {
+        folders_cache::apply_deltas(&mut cached.items, deltas.clone());
+        cached.cursor = cursor.clone();
+        cached.saved = chrono::Utc::now();
+        in_data.cache.put(&in_data.path, cached).await;
+    }
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::WatchFolder(call_messages::WatchFolder::Changes { deltas }),
+    });
+    Ok(cursor)
+}
+
+async fn watch_folder_dropbox_impl(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::watch_folder_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<(), storage_models::WatchFolderError> {
+    let to_watch_error = |e: dropbox::ListFolderCallError| match e {
+        dropbox::ListFolderCallError::Base(base) => {
+            if base.is_token_error() {
+                storage_models::WatchFolderError::Token(base.to_string())
+            } else {
+                storage_models::WatchFolderError::Other(base.to_string())
+            }
+        }
+        dropbox::ListFolderCallError::Reset => storage_models::WatchFolderError::Reset,
+        e => storage_models::WatchFolderError::Other(e.to_string()),
+    };
+
+    let mut cursor = in_data.cursor.clone();
+    let mut retry_delay = WATCH_FOLDER_RETRY_DELAY;
+    loop {
+        let params = dropbox::ListFolderLongpollParams {
+            cursor: cursor.clone(),
+            timeout: Some(WATCH_FOLDER_TIMEOUT),
+        };
+        let res = match dropbox::list_folder_longpoll(&rth, params).await {
+            Ok(res) if res.changes => watch_folder_changes(
+                rth.clone(),
+                auth_info_holder.clone(),
+                &in_data,
+                cursor.clone(),
+                call_id,
+                messages.clone(),
+            )
+            .await
+            .map(|cursor| (cursor, res.backoff)),
+            Ok(res) => Ok((cursor.clone(), res.backoff)),
+            Err(dropbox::ListFolderCallError::Reset) => watch_folder_relist(
+                rth.clone(),
+                auth_info_holder.clone(),
+                &in_data,
+                call_id,
+                messages.clone(),
+            )
+            .await
+            .map(|cursor| (cursor, None)),
+            Err(e) => Err(e),
+        };
+        let backoff = match res {
+            Ok((new_cursor, backoff)) => {
+                cursor = new_cursor;
+                retry_delay = WATCH_FOLDER_RETRY_DELAY;
+                backoff
+            }
+            Err(dropbox::ListFolderCallError::Base(base)) if !base.is_token_error() => {
+                log::debug!("watch_folder: {}: {}", &in_data.path, base);
+                let delay = retry_delay;
+                retry_delay = (retry_delay * 2).min(WATCH_FOLDER_MAX_RETRY_DELAY);
+                Some(delay)
+            }
+            Err(e) => return Err(to_watch_error(e)),
+        };
+
+        if let Some(backoff) = backoff {
+            tokio::time::sleep(std::time::Duration::from_secs(backoff)).await;
+        }
+    }
+}
+
+async fn watch_folder_dropbox(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::watch_folder_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) {
+    let res =
+        watch_folder_dropbox_impl(rth, auth_info_holder, in_data, call_id, messages.clone()).await;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::WatchFolder(call_messages::WatchFolder::Finished {
+            result: res,
+        }),
+    });
+}
+
+async fn restore_dropbox_impl(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::restore_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<storage_models::restore_out_data, storage_models::RestoreError> {
+    let path = "/".to_owned() + &in_data.path;
+    let rev = match in_data.rev {
+        Some(rev) => rev,
+        None => {
+            let params = dropbox::ListRevisionsParams {
+                path: path.clone(),
+                mode: None,
+                limit: Some(1),
+            };
+            match dropbox::list_revisions(
+                rth.clone(),
+                auth_info_holder.clone(),
+                params,
+                messages.clone(),
+                dropbox::token_messages::<call_messages::Restore>(call_id),
+            )
+            .await
+            {
+                Ok(res) if !res.is_deleted => {
+                    return Err(storage_models::RestoreError::Other(format!(
+                        "file is not deleted: '{}'",
+                        in_data.path
+                    )))
+                }
+                Ok(res) => match res.entries.into_iter().next() {
+                    Some(entry) => {
+                        log::debug!(
+                            "restore: '{}' deleted at {:?}, latest rev: {}",
+                            &path,
+                            res.server_deleted,
+                            &entry.rev
+                        );
+                        entry.rev
+                    }
+                    None => return Err(storage_models::RestoreError::NoRevisions(in_data.path)),
+                },
+                Err(e) => {
+                    return Err(match e {
+                        dropbox::ListRevisionsCallError::Base(base) => {
+                            if base.is_token_error() {
+                                storage_models::RestoreError::Token(base.to_string())
+                            } else {
+                                storage_models::RestoreError::Other(base.to_string())
+                            }
+                        }
+                        dropbox::ListRevisionsCallError::PathNotFound(p) => {
+                            storage_models::RestoreError::PathNotFound(p)
+                        }
+                        dropbox::ListRevisionsCallError::NotFile(p) => {
+                            storage_models::RestoreError::NotFile(p)
+                        }
+                        e => storage_models::RestoreError::Other(e.to_string()),
+                    })
+                }
+            }
+        }
+    };
+    let params = dropbox::RestoreParams { path, rev };
+    match dropbox::restore(
+        rth,
+        auth_info_holder,
+        params,
+        messages,
+        dropbox::token_messages::<call_messages::Restore>(call_id),
+    )
+    .await
+    {
+        Ok(file) => Ok(storage_models::restore_out_data {
+            item: Box::new(item_from_meta(dropbox::Meta::File(file)).unwrap()),
+        }),
+        Err(e) => Err(match e {
+            dropbox::RestoreCallError::Base(base) => {
+                if base.is_token_error() {
+                    storage_models::RestoreError::Token(base.to_string())
+                } else {
+                    storage_models::RestoreError::Other(base.to_string())
+                }
+            }
+            dropbox::RestoreCallError::PathNotFound(p) => {
+                storage_models::RestoreError::PathNotFound(p)
+            }
+            e => storage_models::RestoreError::Other(e.to_string()),
+        }),
+    }
+}
+
+async fn restore_dropbox(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::restore_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) {
+    let res = restore_dropbox_impl(rth, auth_info_holder, in_data, call_id, messages.clone()).await;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::Restore(call_messages::Restore::Finished { result: res }),
+    });
+}
+
+async fn list_revisions_dropbox_impl(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::list_revisions_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<storage_models::list_revisions_out_data, storage_models::ListRevisionsError> {
+    let params = dropbox::ListRevisionsParams {
+        path: "/".to_owned() + &in_data.path,
+        mode: None,
+        limit: Some(100),
+    };
+    match dropbox::list_revisions(
+        rth,
+        auth_info_holder,
+        params,
+        messages,
+        dropbox::token_messages::<call_messages::ListRevisions>(call_id),
+    )
+    .await
+    {
+        Ok(res) => Ok(storage_models::list_revisions_out_data {
+            is_deleted: res.is_deleted,
+            entries: res
+                .entries
+                .into_iter()
+                .filter_map(|file| item_from_meta(dropbox::Meta::File(file)))
+                .collect(),
+        }),
+        Err(e) => Err(match e {
+            dropbox::ListRevisionsCallError::Base(base) => {
+                if base.is_token_error() {
+                    storage_models::ListRevisionsError::Token(base.to_string())
+                } else {
+                    storage_models::ListRevisionsError::Other(base.to_string())
+                }
+            }
+            dropbox::ListRevisionsCallError::PathNotFound(p) => {
+                storage_models::ListRevisionsError::PathNotFound(p)
+            }
+            dropbox::ListRevisionsCallError::NotFile(p) => {
+                storage_models::ListRevisionsError::NotFile(p)
+            }
+            e => storage_models::ListRevisionsError::Other(e.to_string()),
+        }),
+    }
+}
+
+async fn list_revisions_dropbox(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::list_revisions_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) {
+    let res =
+        list_revisions_dropbox_impl(rth, auth_info_holder, in_data, call_id, messages.clone())
+            .await;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::ListRevisions(call_messages::ListRevisions::Finished {
+            result: res,
+        }),
+    });
+}
+
+fn shared_link_settings_to_dropbox(
+    settings: storage_models::SharedLinkSettings,
+) -> dropbox::SharedLinkSettings {
+    dropbox::SharedLinkSettings {
+        audience: Some(match settings.audience {
+            storage_models::LinkAudience::Public => dropbox::LinkAudience::Public,
+            storage_models::LinkAudience::Team => dropbox::LinkAudience::Team,
+            storage_models::LinkAudience::NoOne | storage_models::LinkAudience::Other => {
+                dropbox::LinkAudience::NoOne
+            }
+        }),
+        expires: settings
+            .expires
+            .map(|time| time.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+        require_password: Some(settings.password.is_some()),
+        link_password: settings.password,
+    }
+}
+
+fn shared_link_from_dropbox(meta: dropbox::SharedLinkMeta) -> storage_models::SharedLink {
+    storage_models::SharedLink {
+        url: meta.url,
+        name: meta.name,
+        expires: meta.expires,
+        audience: meta
+            .link_permissions
+            .effective_audience
+            .map(|audience| match audience {
+                dropbox::LinkAudience::Public => storage_models::LinkAudience::Public,
+                dropbox::LinkAudience::Team => storage_models::LinkAudience::Team,
+                dropbox::LinkAudience::NoOne => storage_models::LinkAudience::NoOne,
+                _ => storage_models::LinkAudience::Other,
+            }),
+        password_protected: meta.link_permissions.require_password.unwrap_or(false),
+        can_revoke: meta.link_permissions.can_revoke,
+    }
+}
+
+async fn shared_links_dropbox_impl(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::shared_links_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<storage_models::shared_links_out_data, storage_models::SharedLinksError> {
+    let to_shared_links_error = |e: dropbox::SharedLinkCallError| match e {
+        dropbox::SharedLinkCallError::Base(base) => {
+            if base.is_token_error() {
+                storage_models::SharedLinksError::Token(base.to_string())
+            } else {
+                storage_models::SharedLinksError::Other(base.to_string())
+            }
+        }
+        dropbox::SharedLinkCallError::PathNotFound(_) => {
+            storage_models::SharedLinksError::PathNotFound(in_data.path.clone())
+        }
+        e => storage_models::SharedLinksError::Other(e.to_string()),
+    };
+    let path = "/".to_owned() + &in_data.path;
+    let list = || async {
+        let mut links = vec![];
+        let mut cursor: Option<String> = None;
+        loop {
+            let res = dropbox::list_shared_links(
+                rth.clone(),
+                auth_info_holder.clone(),
+                dropbox::ListSharedLinksParams {
+                    path: Some(path.clone()),
+                    cursor: cursor.clone(),
+                    direct_only: Some(true),
+                },
+                messages.clone(),
+                dropbox::token_messages::<call_messages::SharedLinks>(call_id),
+            )
+            .await?;
+            links.extend(res.links);
+            match res.cursor {
+                Some(next) if res.has_more => cursor = Some(next),
+                _ => break,
+            }
+        }
+        Ok::<_, dropbox::SharedLinkCallError>(links)
+    };
+
+    let mut created: Option<String> = None;
+    match in_data.op {
+        storage_models::SharedLinksOp::List => {}
+        storage_models::SharedLinksOp::Create(settings) => {
+            let settings = shared_link_settings_to_dropbox(settings);
+            match dropbox::create_shared_link(
+                rth.clone(),
+                auth_info_holder.clone(),
+                dropbox::CreateSharedLinkParams {
+                    path: path.clone(),
+                    settings: settings.clone(),
+                },
+                messages.clone(),
+                dropbox::token_messages::<call_messages::SharedLinks>(call_id),
+            )
+            .await
+            {
+                Ok(link) => created = Some(link.url),
+                Err(dropbox::SharedLinkCallError::AlreadyExists) => {
+                    // apply requested settings to the existing link
+                    let existing = list().await.map_err(to_shared_links_error)?;
+                    if let Some(link) = existing.into_iter().next() {
+                        let remove_expiration = settings.expires.is_none();
+                        let link = dropbox::modify_shared_link(
+                            rth.clone(),
+                            auth_info_holder.clone(),
+                            dropbox::ModifySharedLinkParams {
+                                url: link.url,
+                                settings,
+                                remove_expiration,
+                            },
+                            messages.clone(),
+                            dropbox::token_messages::<call_messages::SharedLinks>(call_id),
+                        )
+                        .await
+                        .map_err(to_shared_links_error)?;
+                        created = Some(link.url);
+                    }
+                }
+                Err(e) => return Err(to_shared_links_error(e)),
+            }
+        }
+        storage_models::SharedLinksOp::Modify { url, settings } => {
+            let settings = shared_link_settings_to_dropbox(settings);
+            let remove_expiration = settings.expires.is_none();
+            dropbox::modify_shared_link(
+                rth.clone(),
+                auth_info_holder.clone(),
+                dropbox::ModifySharedLinkParams {
+                    url,
+                    settings,
+                    remove_expiration,
+                },
+                messages.clone(),
+                dropbox::token_messages::<call_messages::SharedLinks>(call_id),
+            )
+            .await
+            .map_err(to_shared_links_error)?;
+        }
+        storage_models::SharedLinksOp::Revoke { url } => {
+            dropbox::revoke_shared_link(
+                rth.clone(),
+                auth_info_holder.clone(),
+                dropbox::RevokeSharedLinkParams { url },
+                messages.clone(),
+                dropbox::token_messages::<call_messages::SharedLinks>(call_id),
+            )
+            .await
+            .map_err(to_shared_links_error)?;
+        }
+    }
+
+    let links = list().await.map_err(to_shared_links_error)?;
+    Ok(storage_models::shared_links_out_data {
+        links: links.into_iter().map(shared_link_from_dropbox).collect(),
+        created,
+    })
+}
+
+async fn shared_links_dropbox(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::shared_links_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) {
+    let res =
+        shared_links_dropbox_impl(rth, auth_info_holder, in_data, call_id, messages.clone()).await;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::SharedLinks(call_messages::SharedLinks::Finished {
+            result: res,
+        }),
+    });
+}
+
+async fn temporary_link_dropbox_impl(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::temporary_link_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<storage_models::temporary_link_out_data, storage_models::TemporaryLinkError> {
+    let params = dropbox::GetTemporaryLinkParams {
+        path: "/".to_owned() + &in_data.path,
+    };
+    match dropbox::get_temporary_link(
+        rth,
+        auth_info_holder,
+        params,
+        messages,
+        dropbox::token_messages::<call_messages::TemporaryLink>(call_id),
+    )
+    .await
+    {
+        Ok(res) => Ok(storage_models::temporary_link_out_data {
+            link: res.link,
+            purpose: in_data.purpose,
+        }),
+        Err(e) => Err(match e {
+            dropbox::GetTemporaryLinkCallError::Base(base) => {
+                if base.is_token_error() {
+                    storage_models::TemporaryLinkError::Token(base.to_string())
+                } else {
+                    storage_models::TemporaryLinkError::Other(base.to_string())
+                }
+            }
+            dropbox::GetTemporaryLinkCallError::PathNotFound(_) => {
+                storage_models::TemporaryLinkError::PathNotFound(in_data.path)
+            }
+            e => storage_models::TemporaryLinkError::Other(e.to_string()),
+        }),
+    }
+}
+
+async fn temporary_link_dropbox(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::temporary_link_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) {
+    let res =
+        temporary_link_dropbox_impl(rth, auth_info_holder, in_data, call_id, messages.clone())
+            .await;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::TemporaryLink(call_messages::TemporaryLink::Finished {
+            result: res,
+        }),
+    });
+}
+
+async fn account_info_dropbox_impl(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<storage_models::account_info_out_data, storage_models::AccountInfoError> {
+    let to_account_info_error = |e: dropbox::AccountInfoCallError| match e {
+        dropbox::AccountInfoCallError::Base(base) => {
+            if base.is_token_error() {
+                storage_models::AccountInfoError::Token(base.to_string())
+            } else {
+                storage_models::AccountInfoError::Other(base.to_string())
+            }
+        }
+        e => storage_models::AccountInfoError::Other(e.to_string()),
+    };
+    let account = dropbox::get_current_account(
+        rth.clone(),
+        auth_info_holder.clone(),
+        messages.clone(),
+        dropbox::token_messages::<call_messages::AccountInfo>(call_id),
+    )
+    .await
+    .map_err(to_account_info_error)?;
+    let usage = dropbox::get_space_usage(
+        rth,
+        auth_info_holder.clone(),
+        messages,
+        dropbox::token_messages::<call_messages::AccountInfo>(call_id),
+    )
+    .await
+    .map_err(to_account_info_error)?;
+
+    let expected_account_id = {
+        let auth_info = &mut *auth_info_holder.write().await;
+        if auth_info.account_id.is_empty() {
+            // tokens from config written before account id was stored
+            auth_info.account_id = account.account_id.clone();
+            None
+        } else if auth_info.account_id.ne(&account.account_id) {
+            Some(auth_info.account_id.clone())
+        } else {
+            None
+        }
+    };
+    let (used, allocated) = match usage.allocation {
+        dropbox::SpaceAllocation::Individual { allocated } => (usage.used, Some(allocated)),
+        dropbox::SpaceAllocation::Team { used, allocated } => (used, Some(allocated)),
+        dropbox::SpaceAllocation::Other => (usage.used, None),
+    };
+    Ok(storage_models::account_info_out_data {
+        account_id: account.account_id,
+        name: account.name.display_name,
+        email: account.email,
+        used,
+        allocated,
+        expected_account_id,
+    })
+}
+
+async fn account_info_dropbox(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) {
+    let res = account_info_dropbox_impl(rth, auth_info_holder, call_id, messages.clone()).await;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::AccountInfo(call_messages::AccountInfo::Finished {
+            result: res,
+        }),
+    });
+}
+
+async fn list_recursive_dropbox_impl(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::list_recursive_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<storage_models::list_recursive_out_data, storage_models::ListRecursiveError> {
+    let to_list_recursive_error = |e: dropbox::ListFolderCallError| match e {
+        dropbox::ListFolderCallError::Base(base) => {
+            if base.is_token_error() {
+                storage_models::ListRecursiveError::Token(base.to_string())
+            } else {
+                storage_models::ListRecursiveError::Other(base.to_string())
+            }
+        }
+        dropbox::ListFolderCallError::PathNotFound(_) => {
+            storage_models::ListRecursiveError::PathNotFound(in_data.path.clone())
+        }
+        e => storage_models::ListRecursiveError::Other(e.to_string()),
+    };
+    let params = dropbox::ListFolderParams {
+        path: if in_data.path.is_empty() {
+            "".to_string()
+        } else {
+            "/".to_owned() + &in_data.path
+        },
+        recursive: Some(true),
+        include_deleted: Some(false),
+        include_has_explicit_shared_members: None,
+        include_mounted_folders: None,
+        limit: Some(2000),
+        include_non_downloadable_files: None,
+    };
+    let mut res = dropbox::list_folder(
+        rth.clone(),
+        auth_info_holder.clone(),
+        params,
+        messages.clone(),
+        dropbox::token_messages::<call_messages::ListRecursive>(call_id),
+    )
+    .await
+    .map_err(to_list_recursive_error)?;
+    let mut items: Vec<storage_models::Item> = vec![];
+    loop {
+        items.extend(res.entries.into_iter().filter_map(item_from_meta));
+        if !res.has_more {
+            break;
+        }
+        let _ = messages.send(call_messages::Message {
+            call_id: Some(call_id),
+            data: call_messages::Data::ListRecursive(call_messages::ListRecursive::Progress {
+                count: items.len() as u64,
+            }),
+        });
+        res = dropbox::list_folder_continue(
+            rth.clone(),
+            auth_info_holder.clone(),
+            dropbox::ListFolderContinueParams { cursor: res.cursor },
+            messages.clone(),
+            dropbox::token_messages::<call_messages::ListRecursive>(call_id),
+        )
+        .await
+        .map_err(to_list_recursive_error)?;
+    }
+    Ok(storage_models::list_recursive_out_data {
+        path: in_data.path,
+        items,
+    })
+}
+
+async fn list_recursive_dropbox(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::list_recursive_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) {
+    let res =
+        list_recursive_dropbox_impl(rth, auth_info_holder, in_data, call_id, messages.clone())
+            .await;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::ListRecursive(call_messages::ListRecursive::Finished {
+            result: res,
+        }),
+    });
+}
+
+async fn file_ops_dropbox_impl(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::file_ops_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<storage_models::file_ops_out_data, storage_models::FileOpsError> {
+    let mut results = vec![];
+    for op in in_data.ops {
+        let res = match &op {
+            storage_models::FileOp::Delete { path } => {
+                dropbox::delete(
+                    rth.clone(),
+                    auth_info_holder.clone(),
+                    dropbox::DeleteParams {
+                        path: "/".to_owned() + path,
+                        parent_rev: None,
+                    },
+                    messages.clone(),
+                    dropbox::token_messages::<call_messages::FileOps>(call_id),
+                )
+                .await
+            }
+            storage_models::FileOp::Move { path, to } => {
+                let name = path.rsplit('/').next().unwrap_or_default();
+                dropbox::move_file(
+                    rth.clone(),
+                    auth_info_holder.clone(),
+                    dropbox::MoveParams {
+                        from_path: "/".to_owned() + path,
+                        to_path: if to.is_empty() {
+                            "/".to_owned() + name
+                        } else {
+                            format!("/{}/{}", to, name)
+                        },
+                        autorename: true,
+                    },
+                    messages.clone(),
+                    dropbox::token_messages::<call_messages::FileOps>(call_id),
+                )
+                .await
+            }
+        };
+        match res {
+            Ok(res) => {
+                if let Some(item) = item_from_meta(res.metadata) {
+                    log::debug!("file_ops.done: {} -> {}", op.path(), &item.path_display);
+                }
+                results.push((op, Ok(())));
+            }
+            Err(dropbox::FileOpCallError::Base(base)) if base.is_token_error() => {
+                return Err(storage_models::FileOpsError::Token(base.to_string()));
+            }
+            Err(e) => results.push((op, Err(e.to_string()))),
+        }
+        let _ = messages.send(call_messages::Message {
+            call_id: Some(call_id),
+            data: call_messages::Data::FileOps(call_messages::FileOps::Progress {
+                done: results.len() as u64,
+            }),
+        });
+    }
+    Ok(storage_models::file_ops_out_data { results })
+}
+
+async fn file_ops_dropbox(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::file_ops_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) {
+    let res =
+        file_ops_dropbox_impl(rth, auth_info_holder, in_data, call_id, messages.clone()).await;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::FileOps(call_messages::FileOps::Finished { result: res }),
+    });
+}
+
+async fn transfer_dropbox_impl(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::transfer_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<storage_models::transfer_out_data, storage_models::TransferError> {
+    let mut results = vec![];
+    for op in in_data.ops {
+        let res = match &op {
+            storage_models::TransferOp::Download {
+                remote_path,
+                local_path,
+            } => {
+                let parent = std::path::Path::new(local_path).parent();
+                if let Some(Err(e)) = parent.map(std::fs::create_dir_all) {
+                    results.push((
+                        op,
+                        Err(storage_models::TransferOpError::Other(e.to_string())),
+                    ));
+                    continue;
+                }
+                match dropbox::download(
+                    rth.clone(),
+                    auth_info_holder.clone(),
+                    dropbox::DownloadFileParams {
+                        path: "/".to_owned() + remote_path,
+                        save_to: local_path.clone(),
+                    },
+                    messages.clone(),
+                    dropbox::token_messages::<call_messages::Transfer>(call_id),
+                )
+                .await
+                {
+                    Ok(res) => Ok(item_from_download_result(res)),
+                    Err(dropbox::DownloadFileCallError::Base(base)) if base.is_token_error() => {
+                        return Err(storage_models::TransferError::Token(base.to_string()));
+                    }
+                    Err(e) => Err(storage_models::TransferOpError::Other(e.to_string())),
+                }
+            }
+            storage_models::TransferOp::Upload {
+                local_path,
+                remote_path,
+                mode,
+            } => {
+                let client_modified = std::fs::metadata(local_path)
+                    .and_then(|m| m.modified())
+                    .ok()
+                    .map(|t| {
+                        chrono::DateTime::<chrono::Utc>::from(t)
+                            .format("%Y-%m-%dT%H:%M:%SZ")
+                            .to_string()
+                    });
+                let commit = dropbox::CommitInfo {
+                    path: "/".to_owned() + remote_path,
+                    mode: match mode {
+                        storage_models::UploadMode::Add => dropbox::WriteMode::Add,
+                        storage_models::UploadMode::Update { rev } => dropbox::WriteMode::Update {
+                            update: rev.clone(),
+                        },
+                    },
+                    autorename: matches!(mode, storage_models::UploadMode::Add),
+                    client_modified,
+                    mute: false,
+                };
+                match dropbox::upload(
+                    rth.clone(),
+                    auth_info_holder.clone(),
+                    local_path.clone(),
+                    commit,
+                    messages.clone(),
+                    dropbox::token_messages::<call_messages::Transfer>(call_id),
+                )
+                .await
+                {
+                    Ok(meta) => {
+                        Ok(item_from_meta(dropbox::Meta::File(meta))
+                            .expect("file meta is converted"))
+                    }
+                    Err(dropbox::UploadCallError::Base(base)) if base.is_token_error() => {
+                        return Err(storage_models::TransferError::Token(base.to_string()));
+                    }
+                    Err(dropbox::UploadCallError::Conflict(path)) => {
+                        Err(storage_models::TransferOpError::Conflict(path))
+                    }
+                    Err(e) => Err(storage_models::TransferOpError::Other(e.to_string())),
+                }
+            }
+        };
+        if let Ok(item) = &res {
+            log::debug!(
+                "transfer.done: {} <-> {}",
+                op.local_path(),
+                &item.path_display
+            );
+        }
+        results.push((op, res));
+        let _ = messages.send(call_messages::Message {
+            call_id: Some(call_id),
+            data: call_messages::Data::Transfer(call_messages::Transfer::Progress {
+                done: results.len() as u64,
+            }),
+        });
+    }
+    Ok(storage_models::transfer_out_data { results })
+}
+
+async fn transfer_dropbox(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::transfer_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) {
+    let res =
+        transfer_dropbox_impl(rth, auth_info_holder, in_data, call_id, messages.clone()).await;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::Transfer(call_messages::Transfer::Finished { result: res }),
+    });
+}
+
+async fn compare_local(
+    in_data: storage_models::compare_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) {
+    let messages_clone = messages.clone();
+    let res = tokio::task::spawn_blocking(move || {
+        compare::compare(in_data, |count| {
+            let _ = messages_clone.send(call_messages::Message {
+                call_id: Some(call_id),
+                data: call_messages::Data::Compare(call_messages::Compare::Progress { count }),
+            });
+        })
+    })
+    .await
+    .unwrap_or_else(|e| Err(storage_models::CompareError::Local(e.to_string())));
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::Compare(call_messages::Compare::Finished { result: res }),
+    });
+}
+
+/// downloads to a temporary file next to `local` and renames it over `local` when complete,
+/// so an interrupted download never replaces the previous copy
+async fn download_replacing(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    remote_path: String,
+    local: &std::path::Path,
+    messages: MessagesSender<call_messages::Message>,
+    message_creator: impl dropbox::WithRefreshToken<call_messages::Message>,
+) -> Result<dropbox::DownloadFileCallResult, dropbox::DownloadFileCallError> {
+    let temp = compare::download_temp_path(local);
+    let res = dropbox::download(
+        rth,
+        auth_info_holder,
+        dropbox::DownloadFileParams {
+            path: remote_path.clone(),
+            save_to: temp.to_string_lossy().to_string(),
+        },
+        messages,
+        message_creator,
+    )
+    .await
+    .and_then(|res| {
+        std::fs::rename(&temp, local).map(|_| res).map_err(|e| {
+            dropbox::DownloadFileCallError::FileCreate {
+                cloud_file: remote_path,
+                file: local.to_string_lossy().to_string(),
+                error: e.to_string(),
+            }
+        })
+    });
+    if res.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    res
+}
+
+async fn mirror_dropbox_impl(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::mirror_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<storage_models::mirror_out_data, storage_models::MirrorError> {
+    let started = chrono::Utc::now();
+    let to_mirror_error = |e: dropbox::ListFolderCallError| match e {
+        dropbox::ListFolderCallError::Base(base) => {
+            if base.is_token_error() {
+                storage_models::MirrorError::Token(base.to_string())
+            } else {
+                storage_models::MirrorError::Other(base.to_string())
+            }
+        }
+        dropbox::ListFolderCallError::PathNotFound(_) => {
+            storage_models::MirrorError::PathNotFound(in_data.remote_path.clone())
+        }
+        e => storage_models::MirrorError::Other(e.to_string()),
+    };
+    let local_error = |e: std::io::Error| storage_models::MirrorError::Local(e.to_string());
+    let progress = |listed: u64, downloaded: u64| {
+        let _ = messages.send(call_messages::Message {
+            call_id: Some(call_id),
+            data: call_messages::Data::Mirror(call_messages::Mirror::Progress {
+                listed,
+                downloaded,
+            }),
+        });
+    };
+    std::fs::create_dir_all(&in_data.local_path).map_err(local_error)?;
+
+    // changes since last run, whole folder if there is no cursor or it was reset
+    let mut res = None;
+    if !in_data.cursor.is_empty() {
+        match dropbox::list_folder_continue(
+            rth.clone(),
+            auth_info_holder.clone(),
+            dropbox::ListFolderContinueParams {
+                cursor: in_data.cursor.clone(),
+            },
+            messages.clone(),
+            dropbox::token_messages::<call_messages::Mirror>(call_id),
+        )
+        .await
+        {
+            Ok(changes) => res = Some(changes),
+            Err(dropbox::ListFolderCallError::Reset) => {
+                log::debug!("mirror.cursor_reset: {}", &in_data.name);
+            }
+            Err(e) => return Err(to_mirror_error(e)),
+        }
+    }
+    let full = res.is_none();
+    let mut res = match res {
+        Some(res) => res,
+        None => dropbox::list_folder(
+            rth.clone(),
+            auth_info_holder.clone(),
+            dropbox::ListFolderParams {
+                path: if in_data.remote_path.is_empty() {
+                    "".to_string()
+                } else {
+                    "/".to_owned() + &in_data.remote_path
+                },
+                recursive: Some(true),
+                include_deleted: Some(false),
+                include_has_explicit_shared_members: None,
+                include_mounted_folders: None,
+                limit: Some(2000),
+                include_non_downloadable_files: Some(false),
+            },
+            messages.clone(),
+            dropbox::token_messages::<call_messages::Mirror>(call_id),
+        )
+        .await
+        .map_err(to_mirror_error)?,
+    };
+    let mut entries = vec![];
+    loop {
+        entries.extend(res.entries);
+        progress(entries.len() as u64, 0);
+        if !res.has_more {
+            break;
+        }
+        res = dropbox::list_folder_continue(
+            rth.clone(),
+            auth_info_holder.clone(),
+            dropbox::ListFolderContinueParams { cursor: res.cursor },
+            messages.clone(),
+            dropbox::token_messages::<call_messages::Mirror>(call_id),
+        )
+        .await
+        .map_err(to_mirror_error)?;
+    }
+    let listed = entries.len() as u64;
+
+    let prefix = if in_data.remote_path.is_empty() {
+        "/".to_string()
+    } else {
+        format!("/{}/", in_data.remote_path.to_lowercase())
+    };
+    // path relative to mirrored folder, None for the folder itself and entries outside of it
+    let relative = |path_lower: &str, path_display: &str| -> Option<String> {
+        path_lower.strip_prefix(&prefix)?;
+        path_display
+            .get(prefix.len()..)
+            .filter(|relative| !relative.is_empty())
+            .map(|relative| relative.to_string())
+    };
+    let mut stats = config::MirrorRunStats {
+        started,
+        finished: started,
+        full,
+        downloaded: 0,
+        downloaded_bytes: 0,
+        deleted: 0,
+        failed: 0,
+        error: None,
+    };
+    let mut remote_paths = std::collections::HashSet::new();
+    for meta in entries {
+        match meta {
+            dropbox::Meta::Folder(folder) => {
+                if let Some(relative) = relative(&folder.path_lower, &folder.path_display) {
+                    let local = compare::local_file_path(&in_data.local_path, &relative);
+                    if let Err(e) = std::fs::create_dir_all(&local) {
+                        log::warn!("mirror.create_dir: {}: {}", local.display(), e);
+                        stats.failed += 1;
+                    }
+                    remote_paths.insert(relative.to_lowercase());
+                }
+            }
+            dropbox::Meta::File(file) => {
+                let relative = match relative(&file.path_lower, &file.path_display) {
+                    Some(relative) => relative,
+                    None => continue,
+                };
+                remote_paths.insert(relative.to_lowercase());
+                let local = compare::local_file_path(&in_data.local_path, &relative);
+                let (local_clone, size, content_hash) =
+                    (local.clone(), file.size, file.content_hash.clone());
+                let unchanged = tokio::task::spawn_blocking(move || {
+                    compare::local_file_matches(&local_clone, size, &content_hash)
+                })
+                .await
+                .unwrap_or(false);
+                if unchanged {
+                    continue;
+                }
+                if let Some(parent) = local.parent() {
+                    let _ = std::fs::create_dir_all(parent);
+                }
+                match download_replacing(
+                    rth.clone(),
+                    auth_info_holder.clone(),
+                    file.path_display.clone(),
+                    &local,
+                    messages.clone(),
+                    dropbox::token_messages::<call_messages::Mirror>(call_id),
+                )
+                .await
+                {
+                    Ok(_) => {
+                        stats.downloaded += 1;
+                        stats.downloaded_bytes += file.size;
+                    }
+                    Err(dropbox::DownloadFileCallError::Base(base)) if base.is_token_error() => {
+                        return Err(storage_models::MirrorError::Token(base.to_string()));
+                    }
+                    Err(e) => {
+                        log::warn!("mirror.download: {}: {}", &file.path_display, e);
+                        stats.failed += 1;
+                    }
+                }
+                progress(listed, stats.downloaded);
+            }
+            dropbox::Meta::Deleted(deleted) => {
+                if !in_data.delete_extraneous {
+                    continue;
+                }
+                if let Some(relative) = relative(&deleted.path_lower, &deleted.path_display) {
+                    let local = compare::local_file_path(&in_data.local_path, &relative);
+                    let res = match std::fs::metadata(&local) {
+                        Ok(metadata) if metadata.is_dir() => std::fs::remove_dir_all(&local),
+                        Ok(_) => std::fs::remove_file(&local),
+                        Err(_) => continue,
+                    };
+                    match res {
+                        Ok(()) => stats.deleted += 1,
+                        Err(e) => {
+                            log::warn!("mirror.delete: {}: {}", local.display(), e);
+                            stats.failed += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // deletions are not reported by full listing, compare with local folder instead
+    if full && in_data.delete_extraneous {
+        let local_root = std::path::Path::new(&in_data.local_path);
+        for file in compare::scan_local(local_root).map_err(local_error)? {
+            if remote_paths.contains(&file.relative_path.to_lowercase()) {
+                continue;
+            }
+            let local = compare::local_file_path(&in_data.local_path, &file.relative_path);
+            match std::fs::remove_file(&local) {
+                Ok(()) => stats.deleted += 1,
+                Err(e) => {
+                    log::warn!("mirror.delete: {}: {}", local.display(), e);
+                    stats.failed += 1;
+                }
+            }
+        }
+        let mut folders = compare::scan_local_folders(local_root).map_err(local_error)?;
+        // nested folders last, they can be removed with their parents
+        folders.sort();
+        for folder in folders {
+            let local = compare::local_file_path(&in_data.local_path, &folder);
+            if remote_paths.contains(&folder.to_lowercase()) || !local.exists() {
+                continue;
+            }
+            match std::fs::remove_dir_all(&local) {
+                Ok(()) => stats.deleted += 1,
+                Err(e) => {
+                    log::warn!("mirror.delete: {}: {}", local.display(), e);
+                    stats.failed += 1;
+                }
+            }
+        }
+    }
+    stats.finished = chrono::Utc::now();
+    Ok(storage_models::mirror_out_data {
+        // failed files are retried by the next run
+        cursor: if stats.failed == 0 {
+            res.cursor
+        } else {
+            in_data.cursor
+        },
+        stats,
+    })
+}
+
+async fn mirror_dropbox(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::mirror_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) {
+    let res = mirror_dropbox_impl(rth, auth_info_holder, in_data, call_id, messages.clone()).await;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::Mirror(call_messages::Mirror::Finished { result: res }),
+    });
+}
+
+fn local_modified(path: &std::path::Path) -> Option<chrono::DateTime<chrono::Utc>> {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(chrono::DateTime::<chrono::Utc>::from)
+}
+
+/// uploads to exact path: replaces revision `rev` or adds new file
+async fn sync_upload_file(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    local: &std::path::Path,
+    remote_path: String,
+    rev: Option<String>,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<dropbox::FileMeta, dropbox::UploadCallError> {
+    let commit = dropbox::CommitInfo {
+        path: "/".to_owned() + &remote_path,
+        mode: match rev {
+            Some(rev) => dropbox::WriteMode::Update { update: rev },
+            None => dropbox::WriteMode::Add,
+        },
+        autorename: false,
+        client_modified: local_modified(local).map(|t| t.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+        mute: false,
+    };
+    dropbox::upload(
+        rth,
+        auth_info_holder,
+        local.to_string_lossy().to_string(),
+        commit,
+        messages,
+        dropbox::token_messages::<call_messages::Sync>(call_id),
+    )
+    .await
+}
+
+async fn sync_dropbox_impl(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::sync_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<storage_models::sync_out_data, storage_models::SyncError> {
+    let started = chrono::Utc::now();
+    let to_sync_error = |e: dropbox::ListFolderCallError| match e {
+        dropbox::ListFolderCallError::Base(base) => {
+            if base.is_token_error() {
+                storage_models::SyncError::Token(base.to_string())
+            } else {
+                storage_models::SyncError::Other(base.to_string())
+            }
+        }
+        dropbox::ListFolderCallError::PathNotFound(_) => {
+            storage_models::SyncError::PathNotFound(in_data.remote_path.clone())
+        }
+        e => storage_models::SyncError::Other(e.to_string()),
+    };
+    let local_error = |e: std::io::Error| storage_models::SyncError::Local(e.to_string());
+    // missing folder with synced files is unmounted or renamed, its files are not deleted
+    if !std::path::Path::new(&in_data.local_path).is_dir() {
+        let state_file = in_data.state_file.clone();
+        let synced = tokio::task::spawn_blocking(move || {
+            sync_state::SyncState::load(state_file).files.len()
+        })
+        .await
+        .map_err(|e| storage_models::SyncError::Other(e.to_string()))?;
+        if synced > 0 {
+            return Err(storage_models::SyncError::Local(format!(
+                "'{}' does not exist, {} files were synced there",
+                &in_data.local_path, synced
+            )));
+        }
+        std::fs::create_dir_all(&in_data.local_path).map_err(local_error)?;
+    }
+
+    // remote changes since last sync, whole folder if there is no cursor or it was reset
+    let mut res = None;
+    if !in_data.cursor.is_empty() {
+        match dropbox::list_folder_continue(
+            rth.clone(),
+            auth_info_holder.clone(),
+            dropbox::ListFolderContinueParams {
+                cursor: in_data.cursor.clone(),
+            },
+            messages.clone(),
+            dropbox::token_messages::<call_messages::Sync>(call_id),
+        )
+        .await
+        {
+            Ok(changes) => res = Some(changes),
+            Err(dropbox::ListFolderCallError::Reset) => {
+                log::debug!("sync.cursor_reset: {}", &in_data.name);
+            }
+            Err(e) => return Err(to_sync_error(e)),
+        }
+    }
+    let full = res.is_none();
+    let mut res = match res {
+        Some(res) => res,
+        None => dropbox::list_folder(
+            rth.clone(),
+            auth_info_holder.clone(),
+            dropbox::ListFolderParams {
+                path: if in_data.remote_path.is_empty() {
+                    "".to_string()
+                } else {
+                    "/".to_owned() + &in_data.remote_path
+                },
+                recursive: Some(true),
+                include_deleted: Some(false),
+                include_has_explicit_shared_members: None,
+                include_mounted_folders: None,
+                limit: Some(2000),
+                include_non_downloadable_files: Some(false),
+            },
+            messages.clone(),
+            dropbox::token_messages::<call_messages::Sync>(call_id),
+        )
+        .await
+        .map_err(to_sync_error)?,
+    };
+    let mut entries = vec![];
+    loop {
+        entries.extend(res.entries);
+        if !res.has_more {
+            break;
+        }
+        res = dropbox::list_folder_continue(
+            rth.clone(),
+            auth_info_holder.clone(),
+            dropbox::ListFolderContinueParams { cursor: res.cursor },
+            messages.clone(),
+            dropbox::token_messages::<call_messages::Sync>(call_id),
+        )
+        .await
+        .map_err(to_sync_error)?;
+    }
+
+    let prefix = if in_data.remote_path.is_empty() {
+        "/".to_string()
+    } else {
+        format!("/{}/", in_data.remote_path.to_lowercase())
+    };
+    let relative = |path_lower: &str, path_display: &str| -> Option<String> {
+        path_lower.strip_prefix(&prefix)?;
+        path_display
+            .get(prefix.len()..)
+            .filter(|relative| !relative.is_empty())
+            .map(|relative| relative.to_string())
+    };
+    let mut remote = vec![];
+    let mut deleted_paths = vec![];
+    for meta in entries {
+        match meta {
+            dropbox::Meta::File(file) => {
+                if let Some(path) = relative(&file.path_lower, &file.path_display) {
+                    remote.push(sync_state::RemoteEntry::File(sync_state::RemoteFile {
+                        path,
+                        rev: file.rev,
+                        content_hash: file.content_hash,
+                        size: file.size,
+                    }));
+                }
+            }
+            dropbox::Meta::Folder(folder) => {
+                if let Some(path) = relative(&folder.path_lower, &folder.path_display) {
+                    let _ = std::fs::create_dir_all(compare::local_file_path(
+                        &in_data.local_path,
+                        &path,
+                    ));
+                }
+            }
+            dropbox::Meta::Deleted(deleted) => {
+                if let Some(path) = relative(&deleted.path_lower, &deleted.path_display) {
+                    deleted_paths.push(path.clone());
+                    remote.push(sync_state::RemoteEntry::Deleted { path });
+                }
+            }
+        }
+    }
+
+    // local scan and hashing
+    let state_file = in_data.state_file.clone();
+    let local_path = in_data.local_path.clone();
+    let (mut state, actions) = tokio::task::spawn_blocking(move || {
+        let mut state = sync_state::SyncState::load(state_file);
+        let local_files = compare::scan_local(std::path::Path::new(&local_path))?;
+        if local_files.is_empty() && !state.files.is_empty() {
+            return Err(std::io::Error::other(format!(
+                "'{}' is empty, {} files were synced there",
+                &local_path,
+                state.files.len()
+            )));
+        }
+        let actions = sync_state::plan(&mut state, &local_path, local_files, remote, full);
+        Ok((state, actions))
+    })
+    .await
+    .map_err(|e| storage_models::SyncError::Other(e.to_string()))?
+    .map_err(local_error)?;
+    let remote_deletes = actions
+        .iter()
+        .filter(|action| matches!(action, sync_state::SyncAction::DeleteRemote { .. }))
+        .count() as u64;
+    if remote_deletes > in_data.max_remote_deletes {
+        return Err(storage_models::SyncError::Local(format!(
+            "{} remote files would be deleted, more than max_remote_deletes ({})",
+            remote_deletes, in_data.max_remote_deletes
+        )));
+    }
+
+    let mut stats = config::SyncRunStats {
+        started,
+        finished: started,
+        full,
+        uploaded: 0,
+        downloaded: 0,
+        deleted_local: 0,
+        deleted_remote: 0,
+        conflicts: 0,
+        failed: 0,
+        error: None,
+    };
+    let total = actions.len() as u64;
+    let progress = |done: u64| {
+        let _ = messages.send(call_messages::Message {
+            call_id: Some(call_id),
+            data: call_messages::Data::Sync(call_messages::Sync::Progress { done, total }),
+        });
+    };
+    progress(0);
+    let remote_path = |path: &str| compare::remote_file_path(&in_data.remote_path, path);
+    let local_file = |path: &str| compare::local_file_path(&in_data.local_path, path);
+    let synced_file =
+        |path: &str, rev: String, content_hash: String, size: u64| sync_state::SyncedFile {
+            path: path.to_string(),
+            rev,
+            content_hash,
+            size,
+            local_modified: local_modified(&local_file(path)),
+        };
+    let mut token_error = None;
+    for (done, action) in actions.into_iter().enumerate() {
+        log::debug!("sync.action: {:?}", &action);
+        let (path, res) = match action {
+            sync_state::SyncAction::Upload { path, rev } => {
+                match sync_upload_file(
+                    rth.clone(),
+                    auth_info_holder.clone(),
+                    &local_file(&path),
+                    remote_path(&path),
+                    rev,
+                    call_id,
+                    messages.clone(),
+                )
+                .await
+                {
+                    Ok(meta) => {
+                        state.put(synced_file(&path, meta.rev, meta.content_hash, meta.size));
+                        stats.uploaded += 1;
+                        (path, Ok(()))
+                    }
+                    // remote file was changed after listing
+                    Err(dropbox::UploadCallError::Conflict(_)) => {
+                        let res = sync_conflict(
+                            rth.clone(),
+                            auth_info_holder.clone(),
+                            &mut state,
+                            &in_data,
+                            &path,
+                            call_id,
+                            messages.clone(),
+                        )
+                        .await;
+                        if res.is_ok() {
+                            stats.conflicts += 1;
+                        }
+                        (path, res)
+                    }
+                    Err(dropbox::UploadCallError::Base(base)) if base.is_token_error() => {
+                        token_error = Some(base.to_string());
+                        break;
+                    }
+                    Err(e) => (path, Err(e.to_string())),
+                }
+            }
+            sync_state::SyncAction::Download { path } => {
+                let local = local_file(&path);
+                if let Some(parent) = local.parent() {
+                    let _ = std::fs::create_dir_all(parent);
+                }
+                match download_replacing(
+                    rth.clone(),
+                    auth_info_holder.clone(),
+                    "/".to_owned() + &remote_path(&path),
+                    &local,
+                    messages.clone(),
+                    dropbox::token_messages::<call_messages::Sync>(call_id),
+                )
+                .await
+                {
+                    Ok(res) => {
+                        state.put(synced_file(&path, res.rev, res.content_hash, res.size));
+                        stats.downloaded += 1;
+                        (path, Ok(()))
+                    }
+                    Err(dropbox::DownloadFileCallError::Base(base)) if base.is_token_error() => {
+                        token_error = Some(base.to_string());
+                        break;
+                    }
+                    Err(e) => (path, Err(e.to_string())),
+                }
+            }
+            sync_state::SyncAction::DeleteLocal { path } => {
+                match std::fs::remove_file(local_file(&path)) {
+                    Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                        (path, Err(e.to_string()))
+                    }
+                    _ => {
+                        state.remove(&path);
+                        stats.deleted_local += 1;
+                        (path, Ok(()))
+                    }
+                }
+            }
+            sync_state::SyncAction::DeleteRemote { path, rev } => {
+                match dropbox::delete(
+                    rth.clone(),
+                    auth_info_holder.clone(),
+                    dropbox::DeleteParams {
+                        path: "/".to_owned() + &remote_path(&path),
+                        parent_rev: Some(rev),
+                    },
+                    messages.clone(),
+                    dropbox::token_messages::<call_messages::Sync>(call_id),
+                )
+                .await
+                {
+                    Ok(_) => {
+                        state.remove(&path);
+                        stats.deleted_remote += 1;
+                        (path, Ok(()))
+                    }
+                    Err(dropbox::FileOpCallError::PathNotFound(_)) => {
+                        state.remove(&path);
+                        (path, Ok(()))
+                    }
+                    Err(dropbox::FileOpCallError::Base(base)) if base.is_token_error() => {
+                        token_error = Some(base.to_string());
+                        break;
+                    }
+                    Err(e) => (path, Err(e.to_string())),
+                }
+            }
+            sync_state::SyncAction::Conflict { path } => {
+                let res = sync_conflict(
+                    rth.clone(),
+                    auth_info_holder.clone(),
+                    &mut state,
+                    &in_data,
+                    &path,
+                    call_id,
+                    messages.clone(),
+                )
+                .await;
+                if res.is_ok() {
+                    stats.conflicts += 1;
+                }
+                (path, res)
+            }
+            sync_state::SyncAction::Record { file } => {
+                state.put(synced_file(
+                    &file.path,
+                    file.rev,
+                    file.content_hash,
+                    file.size,
+                ));
+                (file.path, Ok(()))
+            }
+            sync_state::SyncAction::Forget { path } => {
+                state.remove(&path);
+                (path, Ok(()))
+            }
+        };
+        if let Err(e) = res {
+            log::warn!("sync '{}': {}: {}", &in_data.name, path, e);
+            stats.failed += 1;
+        }
+        progress(done as u64 + 1);
+    }
+    // deleted entries do not tell folders from files, files are already deleted above
+    for path in deleted_paths {
+        remove_empty_dirs(&local_file(&path));
+    }
+    state.save().map_err(local_error)?;
+    if let Some(e) = token_error {
+        return Err(storage_models::SyncError::Token(e));
+    }
+    stats.finished = chrono::Utc::now();
+    Ok(storage_models::sync_out_data {
+        // failed files are retried by the next run
+        cursor: if stats.failed == 0 {
+            res.cursor
+        } else {
+            in_data.cursor
+        },
+        stats,
+    })
+}
+
+/// removes folder with its subfolders if there are no files in them, returns true if removed
+fn remove_empty_dirs(dir: &std::path::Path) -> bool {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return false,
+    };
+    let mut empty = true;
+    for entry in entries.flatten() {
+        let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+        if !(is_dir && remove_empty_dirs(&entry.path())) {
+            empty = false;
+        }
+    }
+    empty && std::fs::remove_dir(dir).is_ok()
+}
+
+/// both sides changed: local file is renamed to "conflicted copy" and uploaded,
+/// remote version is downloaded to original path
+async fn sync_conflict(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    state: &mut sync_state::SyncState,
+    in_data: &storage_models::sync_in_data,
+    path: &str,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<(), String> {
+    let conflicted = sync_state::conflicted_copy_path(path, chrono::Utc::now());
+    let local = compare::local_file_path(&in_data.local_path, path);
+    let conflicted_local = compare::local_file_path(&in_data.local_path, &conflicted);
+    std::fs::rename(&local, &conflicted_local).map_err(|e| e.to_string())?;
+    log::info!(
+        "sync '{}': conflict: {} -> {}",
+        &in_data.name,
+        path,
+        &conflicted
+    );
+
+    let meta = sync_upload_file(
+        rth.clone(),
+        auth_info_holder.clone(),
+        &conflicted_local,
+        compare::remote_file_path(&in_data.remote_path, &conflicted),
+        None,
+        call_id,
+        messages.clone(),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    state.put(sync_state::SyncedFile {
+        path: conflicted.clone(),
+        rev: meta.rev,
+        content_hash: meta.content_hash,
+        size: meta.size,
+        local_modified: local_modified(&conflicted_local),
+    });
+
+    let res = download_replacing(
+        rth,
+        auth_info_holder,
+        "/".to_owned() + &compare::remote_file_path(&in_data.remote_path, path),
+        &local,
+        messages,
+        dropbox::token_messages::<call_messages::Sync>(call_id),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    state.put(sync_state::SyncedFile {
+        path: path.to_string(),
+        rev: res.rev,
+        content_hash: res.content_hash,
+        size: res.size,
+        local_modified: local_modified(&local),
+    });
+    Ok(())
+}
+
+async fn sync_dropbox(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::sync_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) {
+    let res = sync_dropbox_impl(rth, auth_info_holder, in_data, call_id, messages.clone()).await;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::Sync(call_messages::Sync::Finished { result: res }),
+    });
+}
+
+async fn backup_dropbox_impl(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::backup_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<storage_models::backup_out_data, storage_models::BackupError> {
+    let started = chrono::Utc::now();
+    let to_backup_error = |e: dropbox::ListFolderCallError| match e {
+        dropbox::ListFolderCallError::Base(base) if base.is_token_error() => {
+            storage_models::BackupError::Token(base.to_string())
+        }
+        e => storage_models::BackupError::Other(e.to_string()),
+    };
+    let progress = |done: u64, total: u64| {
+        let _ = messages.send(call_messages::Message {
+            call_id: Some(call_id),
+            data: call_messages::Data::Backup(call_messages::Backup::Progress { done, total }),
+        });
+    };
+
+    let filter = backup::Filter::new(&in_data.include, &in_data.exclude)
+        .map_err(storage_models::BackupError::Other)?;
+    let local_root = std::path::PathBuf::from(&in_data.local_path);
+    let previous_manifest = in_data.previous_manifest.clone();
+    let (scanned, previous) = tokio::task::spawn_blocking(move || {
+        // hashes of files backed up by previous run keyed by relative path
+        let previous: std::collections::HashMap<String, backup::ManifestFile> = previous_manifest
+            .and_then(|file| backup::Manifest::load(&file))
+            .map(|manifest| manifest.files)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|file| file.content_hash.is_some())
+            .map(|file| (file.path.clone(), file))
+            .collect();
+        backup::scan(&local_root, &filter).map(|scanned| (scanned, previous))
+    })
+    .await
+    .map_err(|e| storage_models::BackupError::Other(e.to_string()))?
+    .map_err(|e| storage_models::BackupError::Local(e.to_string()))?;
+    let total = scanned.files.len() as u64;
+    progress(0, total);
+
+    // files already in target folder keyed by lowercased relative path,
+    // missing folder is created by the first upload
+    let prefix = if in_data.remote_path.is_empty() {
+        "/".to_string()
+    } else {
+        format!("/{}/", in_data.remote_path.to_lowercase())
+    };
+    let mut remote = std::collections::HashMap::new();
+    let listed = dropbox::list_folder(
+        rth.clone(),
+        auth_info_holder.clone(),
+        dropbox::ListFolderParams {
+            path: if in_data.remote_path.is_empty() {
+                "".to_string()
+            } else {
+                "/".to_owned() + &in_data.remote_path
+            },
+            recursive: Some(true),
+            include_deleted: Some(false),
+            include_has_explicit_shared_members: None,
+            include_mounted_folders: None,
+            limit: Some(2000),
+            include_non_downloadable_files: Some(false),
+        },
+        messages.clone(),
+        dropbox::token_messages::<call_messages::Backup>(call_id),
+    )
+    .await;
+    match listed {
+        Ok(mut res) => loop {
+            for meta in std::mem::take(&mut res.entries) {
+                if let dropbox::Meta::File(file) = meta {
+                    if let Some(relative) = file.path_lower.strip_prefix(&prefix) {
+                        remote.insert(relative.to_string(), file);
+                    }
+                }
+            }
+            if !res.has_more {
+                break;
+            }
+            res = dropbox::list_folder_continue(
+                rth.clone(),
+                auth_info_holder.clone(),
+                dropbox::ListFolderContinueParams { cursor: res.cursor },
+                messages.clone(),
+                dropbox::token_messages::<call_messages::Backup>(call_id),
+            )
+            .await
+            .map_err(to_backup_error)?;
+        },
+        Err(dropbox::ListFolderCallError::PathNotFound(_)) => {}
+        Err(e) => return Err(to_backup_error(e)),
+    }
+
+    let mut stats = config::BackupRunStats {
+        started,
+        finished: started,
+        uploaded: 0,
+        uploaded_bytes: 0,
+        unchanged: 0,
+        skipped: scanned.skipped.len() as u64,
+        failed: 0,
+        manifest: None,
+        error: None,
+    };
+    let mut manifest = backup::Manifest {
+        name: in_data.name.clone(),
+        local_path: in_data.local_path.clone(),
+        remote_path: in_data.remote_path.clone(),
+        started,
+        finished: started,
+        files: vec![],
+    };
+    manifest.files.extend(
+        scanned
+            .skipped
+            .iter()
+            .map(|(path, reason)| backup::ManifestFile {
+                path: path.clone(),
+                size: None,
+                modified: None,
+                content_hash: None,
+                rev: None,
+                status: backup::ManifestStatus::Skipped {
+                    reason: reason.clone(),
+                },
+            }),
+    );
+    let mut skipped = scanned.skipped;
+    let mut failed = vec![];
+    let mut token_error = None;
+    for (i, file) in scanned.files.into_iter().enumerate() {
+        let local = compare::local_file_path(&in_data.local_path, &file.relative_path);
+        let unchanged_hash = previous
+            .get(&file.relative_path)
+            .filter(|previous| {
+                previous.size == Some(file.size)
+                    && file.modified.is_some()
+                    && previous.modified == file.modified
+            })
+            .and_then(|previous| previous.content_hash.clone());
+        let hash = match unchanged_hash {
+            Some(hash) => Ok(hash),
+            None => {
+                let local_clone = local.clone();
+                tokio::task::spawn_blocking(move || compare::content_hash(&local_clone))
+                    .await
+                    .map_err(|e| storage_models::BackupError::Other(e.to_string()))?
+            }
+        };
+        let mut entry = backup::ManifestFile {
+            path: file.relative_path.clone(),
+            size: Some(file.size),
+            modified: file.modified,
+            content_hash: None,
+            rev: None,
+            status: backup::ManifestStatus::Unchanged,
+        };
+        let hash = match hash {
+            Ok(hash) => hash,
+            Err(e) => {
+                log::warn!("backup.hash: {}: {}", local.display(), e);
+                stats.skipped += 1;
+                skipped.push((file.relative_path, e.to_string()));
+                entry.status = backup::ManifestStatus::Skipped {
+                    reason: e.to_string(),
+                };
+                manifest.files.push(entry);
+                progress(i as u64 + 1, total);
+                continue;
+            }
+        };
+        let remote_file = remote.get(&file.relative_path.to_lowercase());
+        match remote_file {
+            Some(remote_file) if remote_file.content_hash.eq(&hash) => {
+                stats.unchanged += 1;
+                entry.rev = Some(remote_file.rev.clone());
+            }
+            _ => {
+                // replaced content stays in revisions history of the remote file
+                let commit = dropbox::CommitInfo {
+                    path: "/".to_owned()
+                        + &compare::remote_file_path(&in_data.remote_path, &file.relative_path),
+                    mode: match remote_file {
+                        Some(remote_file) => dropbox::WriteMode::Update {
+                            update: remote_file.rev.clone(),
+                        },
+                        None => dropbox::WriteMode::Add,
+                    },
+                    autorename: false,
+                    client_modified: file
+                        .modified
+                        .map(|t| t.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+                    mute: true,
+                };
+                match dropbox::upload(
+                    rth.clone(),
+                    auth_info_holder.clone(),
+                    local.to_string_lossy().to_string(),
+                    commit,
+                    messages.clone(),
+                    dropbox::token_messages::<call_messages::Backup>(call_id),
+                )
+                .await
+                {
+                    Ok(meta) => {
+                        stats.uploaded += 1;
+                        stats.uploaded_bytes += meta.size;
+                        entry.rev = Some(meta.rev);
+                        entry.status = backup::ManifestStatus::Uploaded;
+                    }
+                    Err(dropbox::UploadCallError::Base(base)) if base.is_token_error() => {
+                        token_error = Some(base.to_string());
+                        break;
+                    }
+                    Err(e) => {
+                        log::warn!("backup.upload: {}: {}", &file.relative_path, e);
+                        stats.failed += 1;
+                        failed.push((file.relative_path.clone(), e.to_string()));
+                        entry.status = backup::ManifestStatus::Failed {
+                            error: e.to_string(),
+                        };
+                    }
+                }
+            }
+        }
+        entry.content_hash = Some(hash);
+        manifest.files.push(entry);
+        progress(i as u64 + 1, total);
+    }
+    stats.finished = chrono::Utc::now();
+    manifest.finished = stats.finished;
+    match manifest.save(&in_data.manifest_file) {
+        Ok(()) => stats.manifest = Some(in_data.manifest_file),
+        Err(e) => log::warn!(
+            "backup.manifest: {}: {}",
+            in_data.manifest_file.display(),
+            e
+        ),
+    }
+    // manifest of files processed before the error is kept
+    if let Some(e) = token_error {
+        return Err(storage_models::BackupError::Token(e));
+    }
+    Ok(storage_models::backup_out_data {
+        stats,
+        skipped,
+        failed,
+    })
+}
+
+async fn backup_dropbox(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::backup_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) {
+    let res = backup_dropbox_impl(rth, auth_info_holder, in_data, call_id, messages.clone()).await;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::Backup(call_messages::Backup::Finished { result: res }),
+    });
+}
+
+async fn webdav_dropbox(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::webdav_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) {
+    let res = crate::webdav::run(rth, auth_info_holder, in_data, call_id, messages.clone()).await;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::WebDav(call_messages::WebDav::Finished { result: res }),
     });
 }
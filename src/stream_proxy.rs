@@ -0,0 +1,226 @@
+//! HTTP proxy of file downloads on 127.0.0.1 for media players.
+//! `http://127.0.0.1:port/<token>/<path>` is streamed from the storage with Range passed
+//! through, so players can seek. Only files opened in a player are served, each under its own
+//! random token. Token refreshes are reported to the call of the storage.
+use crate::clouds;
+use crate::clouds::{call_messages, dropbox};
+use crate::common_types::*;
+use crate::error::BindError;
+use crate::storages;
+use crate::webdav::{
+    decode_path, download_response, encode_path, head_response, is_local_host,
+    remote_error_response, status_response,
+};
+use hyper::{Body, Request, Response, StatusCode};
+use std::collections::HashMap;
+
+/// storage served by the proxy
+#[derive(Clone)]
+pub struct Target {
+    pub auth_info_holder: clouds::AuthInfoHolder,
+    pub call_id: u64,
+    pub messages: MessagesSender<call_messages::Message>,
+}
+
+type Targets = std::sync::Arc<std::sync::RwLock<HashMap<String, Target>>>;
+
+/// file opened in a player
+#[derive(Clone, PartialEq)]
+struct Stream {
+    storage_id: String,
+    path: String,
+}
+
+/// streamed files by url token
+type Streams = std::sync::Arc<std::sync::RwLock<HashMap<String, Stream>>>;
+
+#[derive(Clone)]
+struct ServerData {
+    rth: RuntimeHolder,
+    port: u16,
+    targets: Targets,
+    streams: Streams,
+}
+
+impl ServerData {
+    async fn get(&self, target: Target, path: &str, range: Option<String>) -> Response<Body> {
+        download_response(
+            dropbox::download_stream(
+                self.rth.clone(),
+                target.auth_info_holder,
+                dropbox::DownloadFileParams {
+                    path: "/".to_owned() + path,
+                    save_to: "".to_string(),
+                },
+                range,
+                target.messages,
+                dropbox::token_messages::<call_messages::Stream>(target.call_id),
+            )
+            .await,
+        )
+    }
+
+    async fn head(&self, target: Target, path: &str) -> Response<Body> {
+        match dropbox::get_metadata(
+            self.rth.clone(),
+            target.auth_info_holder,
+            dropbox::GetMetadataParams {
+                path: "/".to_owned() + path,
+                include_media_info: None,
+                include_deleted: None,
+                include_has_explicit_shared_members: None,
+            },
+            target.messages,
+            dropbox::token_messages::<call_messages::Stream>(target.call_id),
+        )
+        .await
+        {
+            Ok(meta) => match storages::item_from_meta(meta) {
+                Some(item) => head_response(&item),
+                None => status_response(StatusCode::NOT_FOUND),
+            },
+            Err(dropbox::GetMetadataCallError::PathNotFound(_)) => {
+                status_response(StatusCode::NOT_FOUND)
+            }
+            Err(e) => remote_error_response(e),
+        }
+    }
+
+    async fn handle(self, req: Request<Body>) -> Response<Body> {
+        let method = req.method().as_str().to_string();
+        let path = decode_path(req.uri().path()).unwrap_or_default();
+        let range = req
+            .headers()
+            .get(hyper::header::RANGE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+        // the path has to be the one the token was given for
+        let target = path.split_once('/').and_then(|(token, path)| {
+            let stream = self.streams.read().unwrap().get(token).cloned()?;
+            if stream.path != path {
+                return None;
+            }
+            let targets = self.targets.read().unwrap();
+            targets
+                .get(&stream.storage_id)
+                .map(|target| (target.clone(), stream.path))
+        });
+        let rsp = match (method.as_str(), target) {
+            _ if !is_local_host(&req, self.port) => status_response(StatusCode::FORBIDDEN),
+            ("GET", Some((target, path))) => self.get(target, &path, range).await,
+            ("HEAD", Some((target, path))) => self.head(target, &path).await,
+            ("GET" | "HEAD", None) => status_response(StatusCode::NOT_FOUND),
+            _ => Response::builder()
+                .status(StatusCode::METHOD_NOT_ALLOWED)
+                .header(hyper::header::ALLOW, "GET, HEAD")
+                .body(Body::empty())
+                .unwrap(),
+        };
+        log::debug!(
+            "stream proxy: {} /{} {}",
+            method,
+            path,
+            rsp.status().as_u16()
+        );
+        rsp
+    }
+}
+
+impl tower::Service<Request<Body>> for ServerData {
+    type Response = Response<Body>;
+    type Error = std::convert::Infallible;
+    type Future = std::pin::Pin<
+        Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>,
+    >;
+
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let data = self.clone();
+        Box::pin(async move { Ok(data.handle(req).await) })
+    }
+}
+
+/// running proxy, serves until async runtime is shut down
+pub struct StreamProxy {
+    pub addr: std::net::SocketAddr,
+    targets: Targets,
+    streams: Streams,
+}
+
+impl StreamProxy {
+    /// binds to 127.0.0.1:port and serves in background
+    pub fn start(rth: RuntimeHolder, port: u16) -> Result<Self, BindError> {
+        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+        let bind_error = |text: String| BindError::Error { addr, text };
+        // listener is registered in the runtime
+        let builder = rth
+            .block_on(async { hyper::Server::try_bind(&addr) })
+            .map_err(|e| bind_error(e.to_string()))?
+            .map_err(|e| bind_error(e.to_string()))?;
+
+        let targets = Targets::default();
+        let streams = Streams::default();
+        let data = ServerData {
+            rth: rth.clone(),
+            port,
+            targets: targets.clone(),
+            streams: streams.clone(),
+        };
+        let make_service = hyper::service::make_service_fn(move |_conn| {
+            let svc = data.clone();
+            async move { Ok::<_, std::convert::Infallible>(svc) }
+        });
+        let server = builder.executor(rth.clone()).serve(make_service);
+        rth.spawn(async move {
+            if let Err(e) = server.await {
+                log::error!("stream proxy failed: {}", e);
+            }
+        })
+        .map_err(|e| bind_error(e.to_string()))?;
+
+        log::debug!("stream proxy running at {}", &addr);
+        Ok(Self {
+            addr,
+            targets,
+            streams,
+        })
+    }
+
+    pub fn has_target(&self, storage_id: &str) -> bool {
+        self.targets.read().unwrap().contains_key(storage_id)
+    }
+
+    pub fn add_target(&self, storage_id: String, target: Target) {
+        self.targets.write().unwrap().insert(storage_id, target);
+    }
+
+    /// url of remote file for players, `path` is without leading '/'.
+    /// The file is served from now on, under the same token for repeated calls
+    pub fn url(&self, storage_id: &str, path: &str) -> String {
+        use rand::Rng;
+        let stream = Stream {
+            storage_id: storage_id.to_string(),
+            path: path.to_string(),
+        };
+        let mut streams = self.streams.write().unwrap();
+        let token = match streams.iter().find(|(_, s)| **s == stream) {
+            Some((token, _)) => token.clone(),
+            None => {
+                let token: String = rand::thread_rng()
+                    .sample_iter(rand::distributions::Alphanumeric)
+                    .map(char::from)
+                    .take(32)
+                    .collect();
+                streams.insert(token.clone(), stream);
+                token
+            }
+        };
+        format!("http://{}/{}/{}", self.addr, token, encode_path(path))
+    }
+}
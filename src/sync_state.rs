@@ -0,0 +1,523 @@
+use crate::compare;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/// state of a file after last sync of both sides
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SyncedFile {
+    /// relative to synced folders, '/' separated
+    pub path: String,
+    pub rev: String,
+    pub content_hash: String,
+    pub size: u64,
+    /// local modification time after sync, to skip hashing of unchanged files
+    pub local_modified: Option<DateTime<Utc>>,
+}
+
+/// synced files of one sync job keyed by lowercased relative path, persisted as json file
+pub struct SyncState {
+    file: PathBuf,
+    pub files: HashMap<String, SyncedFile>,
+}
+
+impl SyncState {
+    pub fn load(file: PathBuf) -> Self {
+        let files = match std::fs::read_to_string(&file) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+                log::error!("sync state '{}' parse error: {}", file.display(), e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        Self { file, files }
+    }
+
+    /// writes temporary file and renames it over the state file
+    pub fn save(&self) -> std::io::Result<()> {
+        if let Some(dir) = self.file.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = self.file.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string(&self.files).unwrap())?;
+        std::fs::rename(&tmp, &self.file)
+    }
+
+    pub fn put(&mut self, file: SyncedFile) {
+        self.files.insert(file.path.to_lowercase(), file);
+    }
+
+    pub fn remove(&mut self, path: &str) {
+        self.files.remove(&path.to_lowercase());
+    }
+}
+
+/// remote file as listed
+#[derive(Debug, Clone)]
+pub struct RemoteFile {
+    pub path: String,
+    pub rev: String,
+    pub content_hash: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone)]
+pub enum RemoteEntry {
+    File(RemoteFile),
+    Deleted { path: String },
+}
+
+#[derive(Debug, Clone)]
+pub enum SyncAction {
+    /// local file is new or changed, `rev` is the synced remote revision
+    Upload {
+        path: String,
+        rev: Option<String>,
+    },
+    Download {
+        path: String,
+    },
+    DeleteLocal {
+        path: String,
+    },
+    /// local file was deleted, `rev` is the synced remote revision
+    DeleteRemote {
+        path: String,
+        rev: String,
+    },
+    /// both sides changed, local copy is kept as a "conflicted copy"
+    Conflict {
+        path: String,
+    },
+    /// both sides are equal, only state is updated
+    Record {
+        file: RemoteFile,
+    },
+    /// deleted on both sides
+    Forget {
+        path: String,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+enum Change {
+    Absent,
+    Unchanged,
+    New,
+    Changed,
+    Deleted,
+}
+
+/// decides what to do with every file, `full` = `remote` is listing of whole folder,
+/// otherwise it is list of changes since last sync.
+/// Local files with changed mtime or size are hashed to confirm the change.
+pub fn plan(
+    state: &mut SyncState,
+    local_root: &str,
+    local_files: Vec<compare::LocalFile>,
+    remote: Vec<RemoteEntry>,
+    full: bool,
+) -> Vec<SyncAction> {
+    let mut local: HashMap<String, compare::LocalFile> = local_files
+        .into_iter()
+        .map(|file| (file.relative_path.to_lowercase(), file))
+        .collect();
+    let mut remote_files: HashMap<String, RemoteFile> = HashMap::new();
+    let mut remote_deleted: Vec<String> = vec![];
+    for entry in remote {
+        match entry {
+            RemoteEntry::File(file) => {
+                remote_files.insert(file.path.to_lowercase(), file);
+            }
+            RemoteEntry::Deleted { path } => remote_deleted.push(path.to_lowercase()),
+        }
+    }
+    // deleted folder removes every synced file inside it
+    let is_remote_deleted = |key: &str| {
+        remote_deleted.iter().any(|deleted| {
+            key.eq(deleted)
+                || (key.starts_with(deleted.as_str()) && key[deleted.len()..].starts_with('/'))
+        })
+    };
+
+    let mut keys: HashSet<String> = state.files.keys().cloned().collect();
+    keys.extend(local.keys().cloned());
+    keys.extend(remote_files.keys().cloned());
+    let mut keys: Vec<String> = keys.into_iter().collect();
+    keys.sort();
+
+    let mut actions = vec![];
+    for key in keys {
+        let synced = state.files.get(&key).cloned();
+        let local_file = local.remove(&key);
+        let remote_file = remote_files.remove(&key);
+
+        let mut local_hash = None;
+        let local_change = match (&local_file, &synced) {
+            (None, None) => Change::Absent,
+            (None, Some(_)) => Change::Deleted,
+            (Some(_), None) => Change::New,
+            (Some(file), Some(synced)) => {
+                if file.size == synced.size && file.modified == synced.local_modified {
+                    Change::Unchanged
+                } else {
+                    let path = compare::local_file_path(local_root, &file.relative_path);
+                    match compare::content_hash(&path) {
+                        Ok(hash) if hash.eq(&synced.content_hash) => {
+                            // only touched, remember new mtime
+                            state.put(SyncedFile {
+                                local_modified: file.modified,
+                                ..synced.clone()
+                            });
+                            Change::Unchanged
+                        }
+                        Ok(hash) => {
+                            local_hash = Some(hash);
+                            Change::Changed
+                        }
+                        Err(e) => {
+                            log::warn!("sync.hash: {}: {}", path.display(), e);
+                            continue;
+                        }
+                    }
+                }
+            }
+        };
+        let remote_change = match (&remote_file, &synced) {
+            (Some(_), None) => Change::New,
+            (Some(file), Some(synced)) if file.rev.eq(&synced.rev) => Change::Unchanged,
+            (Some(_), Some(_)) => Change::Changed,
+            (None, Some(_)) if full || is_remote_deleted(&key) => Change::Deleted,
+            (None, Some(_)) => Change::Unchanged,
+            (None, None) => Change::Absent,
+        };
+
+        let path = local_file
+            .as_ref()
+            .map(|file| file.relative_path.clone())
+            .or_else(|| remote_file.as_ref().map(|file| file.path.clone()))
+            .or_else(|| synced.as_ref().map(|file| file.path.clone()))
+            .unwrap_or(key);
+        let action = match (local_change, remote_change) {
+            (Change::Unchanged, Change::Unchanged) => None,
+            (Change::Unchanged, Change::Deleted) => Some(SyncAction::DeleteLocal { path }),
+            (
+                Change::Unchanged | Change::Absent | Change::Deleted,
+                Change::New | Change::Changed,
+            ) => Some(SyncAction::Download { path }),
+            (Change::Changed, Change::Unchanged) => Some(SyncAction::Upload {
+                path,
+                rev: synced.map(|synced| synced.rev),
+            }),
+            (Change::New | Change::Changed, Change::Absent | Change::Deleted) => {
+                Some(SyncAction::Upload { path, rev: None })
+            }
+            (Change::Deleted, Change::Unchanged) => Some(SyncAction::DeleteRemote {
+                path,
+                rev: synced.map(|synced| synced.rev).unwrap_or_default(),
+            }),
+            (Change::Deleted, Change::Deleted | Change::Absent) => {
+                Some(SyncAction::Forget { path })
+            }
+            (Change::New | Change::Changed, Change::New | Change::Changed) => {
+                let remote_file = remote_file.expect("remote file is listed");
+                let hash = match local_hash {
+                    Some(hash) => Ok(hash),
+                    None => compare::content_hash(&compare::local_file_path(local_root, &path)),
+                };
+                match hash {
+                    Ok(hash) if hash.eq(&remote_file.content_hash) => Some(SyncAction::Record {
+                        file: RemoteFile {
+                            path,
+                            ..remote_file
+                        },
+                    }),
+                    _ => Some(SyncAction::Conflict { path }),
+                }
+            }
+            // nothing on both sides, or inconsistent with state (e.g. new local file with synced remote)
+            _ => None,
+        };
+        actions.extend(action);
+    }
+    actions
+}
+
+/// "dir/name (conflicted copy 2022-03-01 101502).ext"
+pub fn conflicted_copy_path(path: &str, now: DateTime<Utc>) -> String {
+    let (dir, name) = match path.rsplit_once('/') {
+        Some((dir, name)) => (Some(dir), name),
+        None => (None, path),
+    };
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, Some(ext)),
+        _ => (name, None),
+    };
+    let mut res = format!(
+        "{} (conflicted copy {})",
+        stem,
+        now.format("%Y-%m-%d %H%M%S")
+    );
+    if let Some(ext) = ext {
+        res = res + "." + ext;
+    }
+    match dir {
+        Some(dir) => format!("{}/{}", dir, res),
+        None => res,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn modified() -> Option<DateTime<Utc>> {
+        Some(Utc.ymd(2022, 3, 1).and_hms(10, 15, 2))
+    }
+
+    /// state without file, nothing is saved by `plan`
+    fn state(files: &[(&str, &str)]) -> SyncState {
+        let mut state = SyncState {
+            file: PathBuf::new(),
+            files: HashMap::new(),
+        };
+        for (path, rev) in files {
+            state.put(SyncedFile {
+                path: path.to_string(),
+                rev: rev.to_string(),
+                content_hash: "hash".to_string(),
+                size: 3,
+                local_modified: modified(),
+            });
+        }
+        state
+    }
+
+    /// unchanged since sync
+    fn local(path: &str) -> compare::LocalFile {
+        compare::LocalFile {
+            relative_path: path.to_string(),
+            size: 3,
+            modified: modified(),
+        }
+    }
+
+    fn remote(path: &str, rev: &str, content_hash: &str) -> RemoteEntry {
+        RemoteEntry::File(RemoteFile {
+            path: path.to_string(),
+            rev: rev.to_string(),
+            content_hash: content_hash.to_string(),
+            size: 3,
+        })
+    }
+
+    fn deleted(path: &str) -> RemoteEntry {
+        RemoteEntry::Deleted {
+            path: path.to_string(),
+        }
+    }
+
+    fn plan_str(
+        state: &mut SyncState,
+        local_root: &str,
+        local_files: Vec<compare::LocalFile>,
+        remote: Vec<RemoteEntry>,
+        full: bool,
+    ) -> String {
+        format!("{:?}", plan(state, local_root, local_files, remote, full))
+    }
+
+    /// folder with files of given content, removed by caller
+    fn local_root(files: &[(&str, &[u8])]) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("clouds_viewer_{:016x}", rand::random::<u64>()));
+        for (path, content) in files {
+            let file = root.join(path);
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(file, content).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn unchanged_files_have_no_actions() {
+        let mut state = state(&[("a.txt", "r1")]);
+        let actions = plan_str(&mut state, "", vec![local("a.txt")], vec![], false);
+        assert_eq!(actions, "[]");
+        let actions = plan_str(
+            &mut state,
+            "",
+            vec![local("a.txt")],
+            vec![remote("a.txt", "r1", "hash")],
+            true,
+        );
+        assert_eq!(actions, "[]");
+    }
+
+    #[test]
+    fn file_missing_from_full_listing_is_deleted_locally() {
+        let mut state = state(&[("a.txt", "r1")]);
+        let actions = plan_str(&mut state, "", vec![local("a.txt")], vec![], true);
+        assert_eq!(actions, r#"[DeleteLocal { path: "a.txt" }]"#);
+    }
+
+    #[test]
+    fn file_missing_from_incremental_changes_is_kept() {
+        let mut state = state(&[("a.txt", "r1")]);
+        let actions = plan_str(
+            &mut state,
+            "",
+            vec![local("a.txt")],
+            vec![remote("b.txt", "r2", "hash")],
+            false,
+        );
+        assert_eq!(actions, r#"[Download { path: "b.txt" }]"#);
+    }
+
+    #[test]
+    fn deleted_remote_folder_deletes_its_files_only() {
+        let mut state = state(&[
+            ("Dir/a.txt", "r1"),
+            ("dir/b.txt", "r2"),
+            ("dirx/c.txt", "r3"),
+        ]);
+        let actions = plan_str(
+            &mut state,
+            "",
+            vec![local("Dir/a.txt"), local("dir/b.txt"), local("dirx/c.txt")],
+            vec![deleted("dir")],
+            false,
+        );
+        assert_eq!(
+            actions,
+            r#"[DeleteLocal { path: "Dir/a.txt" }, DeleteLocal { path: "dir/b.txt" }]"#
+        );
+    }
+
+    #[test]
+    fn local_deletion_deletes_synced_revision() {
+        let mut state = state(&[("a.txt", "r1")]);
+        let actions = plan_str(&mut state, "", vec![], vec![], false);
+        assert_eq!(actions, r#"[DeleteRemote { path: "a.txt", rev: "r1" }]"#);
+        // remote was changed meanwhile
+        let actions = plan_str(
+            &mut state,
+            "",
+            vec![],
+            vec![remote("a.txt", "r2", "h2")],
+            false,
+        );
+        assert_eq!(actions, r#"[Download { path: "a.txt" }]"#);
+    }
+
+    #[test]
+    fn deleted_on_both_sides_is_forgotten() {
+        let mut state = state(&[("a.txt", "r1")]);
+        let actions = plan_str(&mut state, "", vec![], vec![deleted("a.txt")], false);
+        assert_eq!(actions, r#"[Forget { path: "a.txt" }]"#);
+        let actions = plan_str(&mut state, "", vec![], vec![], true);
+        assert_eq!(actions, r#"[Forget { path: "a.txt" }]"#);
+    }
+
+    #[test]
+    fn remote_change_is_downloaded() {
+        let mut state = state(&[("a.txt", "r1")]);
+        let actions = plan_str(
+            &mut state,
+            "",
+            vec![local("a.txt")],
+            vec![remote("a.txt", "r2", "h2")],
+            false,
+        );
+        assert_eq!(actions, r#"[Download { path: "a.txt" }]"#);
+    }
+
+    #[test]
+    fn local_changes_are_uploaded() {
+        let root = local_root(&[("a.txt", b"new content")]);
+        let root_str = root.to_string_lossy().to_string();
+        let mut state = state(&[("a.txt", "r1")]);
+        let changed = compare::LocalFile {
+            size: 11,
+            ..local("a.txt")
+        };
+        let actions = plan_str(
+            &mut state,
+            &root_str,
+            vec![changed, local("b.txt")],
+            vec![],
+            false,
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            actions,
+            r#"[Upload { path: "a.txt", rev: Some("r1") }, Upload { path: "b.txt", rev: None }]"#
+        );
+    }
+
+    #[test]
+    fn touched_local_file_is_not_uploaded() {
+        let root = local_root(&[("a.txt", b"abc")]);
+        let root_str = root.to_string_lossy().to_string();
+        let mut state = state(&[("a.txt", "r1")]);
+        state.files.get_mut("a.txt").unwrap().content_hash =
+            compare::content_hash(&root.join("a.txt")).unwrap();
+        let touched = compare::LocalFile {
+            modified: None,
+            ..local("a.txt")
+        };
+        let actions = plan_str(&mut state, &root_str, vec![touched], vec![], false);
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(actions, "[]");
+        assert_eq!(state.files["a.txt"].local_modified, None);
+    }
+
+    #[test]
+    fn changes_on_both_sides_conflict_unless_equal() {
+        let root = local_root(&[("same.txt", b"abc"), ("other.txt", b"abc")]);
+        let root_str = root.to_string_lossy().to_string();
+        let hash = compare::content_hash(&root.join("same.txt")).unwrap();
+        let mut state = state(&[]);
+        let actions = plan_str(
+            &mut state,
+            &root_str,
+            vec![local("same.txt"), local("other.txt")],
+            vec![
+                remote("same.txt", "r1", &hash),
+                remote("other.txt", "r2", "h2"),
+            ],
+            true,
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            actions,
+            format!(
+                r#"[Conflict {{ path: "other.txt" }}, Record {{ file: RemoteFile {{ path: "same.txt", rev: "r1", content_hash: "{}", size: 3 }} }}]"#,
+                hash
+            )
+        );
+    }
+
+    #[test]
+    fn conflicted_copy_keeps_folder_and_extension() {
+        let now = Utc.ymd(2022, 3, 1).and_hms(10, 15, 2);
+        assert_eq!(
+            conflicted_copy_path("dir/name.ext", now),
+            "dir/name (conflicted copy 2022-03-01 101502).ext"
+        );
+        assert_eq!(
+            conflicted_copy_path("name", now),
+            "name (conflicted copy 2022-03-01 101502)"
+        );
+        assert_eq!(
+            conflicted_copy_path("a.b/.hidden", now),
+            "a.b/.hidden (conflicted copy 2022-03-01 101502)"
+        );
+        assert_eq!(
+            conflicted_copy_path("a.tar.gz", now),
+            "a.tar (conflicted copy 2022-03-01 101502).gz"
+        );
+    }
+}
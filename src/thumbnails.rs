@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+/// file extensions dropbox can make thumbnails for
+const THUMBNAIL_EXTENSIONS: [&str; 10] = [
+    "jpg", "jpeg", "png", "tiff", "tif", "gif", "webp", "ppm", "bmp", "heic",
+];
+
+pub fn is_thumbnail_supported(name: &str) -> bool {
+    match name.rsplit_once('.') {
+        Some((_, ext)) => THUMBNAIL_EXTENSIONS.contains(&ext.to_lowercase().as_str()),
+        None => false,
+    }
+}
+
+pub struct DecodedImage {
+    pub size: [usize; 2],
+    pub rgba: Vec<u8>,
+}
+
+impl std::fmt::Debug for DecodedImage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DecodedImage({}x{})", self.size[0], self.size[1])
+    }
+}
+
+pub fn decode(data: &[u8]) -> Result<DecodedImage, String> {
+    let image = image::load_from_memory(data).map_err(|e| e.to_string())?;
+    let rgba = image.to_rgba8();
+    Ok(DecodedImage {
+        size: [rgba.width() as usize, rgba.height() as usize],
+        rgba: rgba.into_raw(),
+    })
+}
+
+/// on-disk LRU cache of thumbnails keyed by file `rev`.
+/// recency is tracked by file modification time.
+#[derive(Clone, Debug)]
+pub struct ThumbnailCache {
+    pub dir: PathBuf,
+    pub max_bytes: u64,
+}
+
+impl ThumbnailCache {
+    fn file_path(&self, rev: &str) -> PathBuf {
+        self.dir.join(rev.to_string() + ".jpg")
+    }
+
+    pub fn get(&self, rev: &str) -> Option<Vec<u8>> {
+        if rev.is_empty() {
+            return None;
+        }
+        let path = self.file_path(rev);
+        let data = std::fs::read(&path).ok()?;
+        if let Ok(f) = std::fs::File::options().write(true).open(&path) {
+            let _ = f.set_modified(std::time::SystemTime::now());
+        }
+        Some(data)
+    }
+
+    pub fn put(&self, rev: &str, data: &[u8]) {
+        if rev.is_empty() {
+            return;
+        }
+        if let Err(e) = std::fs::create_dir_all(&self.dir) {
+            log::error!("thumbnails cache dir create error: {}", e);
+            return;
+        }
+        if let Err(e) = std::fs::write(self.file_path(rev), data) {
+            log::error!("thumbnails cache write error: {}", e);
+            return;
+        }
+        self.evict();
+    }
+
+    fn evict(&self) {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        let mut files: Vec<(std::time::SystemTime, u64, PathBuf)> = entries
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let meta = entry.metadata().ok()?;
+                if !meta.is_file() {
+                    return None;
+                }
+                Some((meta.modified().ok()?, meta.len(), entry.path()))
+            })
+            .collect();
+        let mut total: u64 = files.iter().map(|v| v.1).sum();
+        if total <= self.max_bytes {
+            return;
+        }
+        files.sort_by_key(|v| v.0);
+        for (_, len, path) in files {
+            if total <= self.max_bytes {
+                break;
+            }
+            if std::fs::remove_file(&path).is_ok() {
+                total -= len;
+            }
+        }
+    }
+}
@@ -0,0 +1,793 @@
+//! WebDAV server exposing a storage on 127.0.0.1, for file managers, davfs2 and office apps.
+//! Locks are not enforced, LOCK only hands out a token so clients requiring class 2 can write.
+use crate::clouds;
+use crate::clouds::{call_messages, dropbox};
+use crate::common_types::*;
+use crate::error::BindError;
+use crate::storage_models;
+use crate::storages;
+use hyper::{Body, Request, Response, StatusCode};
+
+const ALLOWED_METHODS: &str =
+    "OPTIONS, PROPFIND, GET, HEAD, PUT, MKCOL, MOVE, DELETE, LOCK, UNLOCK";
+/// methods of folders, content of a folder is read by PROPFIND
+const FOLDER_ALLOWED_METHODS: &str = "OPTIONS, PROPFIND, HEAD, MKCOL, MOVE, DELETE, LOCK, UNLOCK";
+
+#[derive(Clone)]
+struct ServerData {
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    /// expected Authorization header, None = no auth
+    authorization: Option<String>,
+    port: u16,
+    temp_dir: std::path::PathBuf,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+}
+
+/// path for api calls: "" for root, "/path" otherwise
+fn api_path(path: &str) -> String {
+    if path.is_empty() {
+        "".to_string()
+    } else {
+        "/".to_owned() + path
+    }
+}
+
+/// percent-decoded request path without leading and trailing '/'
+pub(crate) fn decode_path(path: &str) -> Option<String> {
+    let bytes = path.as_bytes();
+    let mut res = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(idx + 1..idx + 3)?).ok()?;
+            res.push(u8::from_str_radix(hex, 16).ok()?);
+            idx += 3;
+        } else {
+            res.push(bytes[idx]);
+            idx += 1;
+        }
+    }
+    String::from_utf8(res)
+        .ok()
+        .map(|path| path.trim_matches('/').to_string())
+}
+
+/// percent-encoded path, '/' is kept
+pub(crate) fn encode_path(path: &str) -> String {
+    let mut res = String::with_capacity(path.len());
+    for b in path.bytes() {
+        if b.is_ascii_alphanumeric() || b"/-_.~".contains(&b) {
+            res.push(b as char);
+        } else {
+            res.push_str(&format!("%{:02X}", b));
+        }
+    }
+    res
+}
+
+/// percent-encoded href of remote path, folders end with '/'
+fn href(path: &str, is_folder: bool) -> String {
+    let mut res = "/".to_owned() + &encode_path(path);
+    if is_folder && !path.is_empty() {
+        res.push('/');
+    }
+    res
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn http_date(date: chrono::DateTime<chrono::Utc>) -> String {
+    date.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// content type by file extension, for players and browsers
+pub fn content_type(name: &str) -> &'static str {
+    let ext = match name.rsplit_once('.') {
+        Some((_, ext)) => ext.to_lowercase(),
+        None => return "application/octet-stream",
+    };
+    match ext.as_str() {
+        "mp4" | "m4v" => "video/mp4",
+        "mkv" => "video/x-matroska",
+        "webm" => "video/webm",
+        "avi" => "video/x-msvideo",
+        "mov" => "video/quicktime",
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "flac" => "audio/flac",
+        "ogg" | "oga" => "audio/ogg",
+        "wav" => "audio/wav",
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "pdf" => "application/pdf",
+        "txt" | "log" | "md" => "text/plain; charset=utf-8",
+        "html" | "htm" => "text/html; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+/// compares secrets without returning early on the first different byte
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Host header names this server on loopback, requests of pages from other hosts resolved
+/// to 127.0.0.1 (DNS rebinding) are rejected
+pub(crate) fn is_local_host(req: &Request<Body>, port: u16) -> bool {
+    req.headers()
+        .get(hyper::header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .map(|host| {
+            host.eq_ignore_ascii_case(&format!("127.0.0.1:{}", port))
+                || host.eq_ignore_ascii_case(&format!("localhost:{}", port))
+        })
+        .unwrap_or(false)
+}
+
+pub(crate) fn status_response(status: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn error_response(status: StatusCode, text: String) -> Response<Body> {
+    log::error!("local server: {}: {}", status, &text);
+    Response::builder()
+        .status(status)
+        .header(hyper::header::CONTENT_TYPE, "text/plain")
+        .body(Body::from(text))
+        .unwrap()
+}
+
+/// remote errors; token errors can't be fixed by client
+pub(crate) fn remote_error_response<E: clouds::BaseErrorAccess + std::fmt::Display>(
+    e: E,
+) -> Response<Body> {
+    match e.get_base_error() {
+        Some(base) if base.is_token_error() => {
+            error_response(StatusCode::SERVICE_UNAVAILABLE, e.to_string())
+        }
+        _ => error_response(StatusCode::BAD_GATEWAY, e.to_string()),
+    }
+}
+
+/// streamed content with headers for seeking
+pub(crate) fn download_response(
+    res: Result<dropbox::DownloadStream, dropbox::DownloadFileCallError>,
+) -> Response<Body> {
+    match res {
+        Ok(stream) => {
+            let mut builder = Response::builder()
+                .status(stream.status)
+                .header(hyper::header::ACCEPT_RANGES, "bytes")
+                .header(hyper::header::CONTENT_TYPE, content_type(&stream.meta.name))
+                .header(hyper::header::ETAG, format!("\"{}\"", stream.meta.rev))
+                .header(
+                    hyper::header::LAST_MODIFIED,
+                    http_date(stream.meta.client_modified),
+                );
+            if let Some(content_length) = stream.content_length {
+                builder = builder.header(hyper::header::CONTENT_LENGTH, content_length);
+            }
+            if let Some(content_range) = stream.content_range {
+                builder = builder.header(hyper::header::CONTENT_RANGE, content_range);
+            }
+            builder.body(stream.body).unwrap()
+        }
+        Err(dropbox::DownloadFileCallError::NotFound(_)) => status_response(StatusCode::NOT_FOUND),
+        Err(dropbox::DownloadFileCallError::RangeNotSatisfiable(_)) => {
+            status_response(StatusCode::RANGE_NOT_SATISFIABLE)
+        }
+        Err(e) => remote_error_response(e),
+    }
+}
+
+/// headers of GET response without content
+pub(crate) fn head_response(item: &storage_models::Item) -> Response<Body> {
+    let mut builder = Response::builder().status(StatusCode::OK);
+    if !item.is_folder {
+        builder = builder
+            .header(hyper::header::ACCEPT_RANGES, "bytes")
+            .header(hyper::header::CONTENT_TYPE, content_type(&item.name))
+            .header(hyper::header::CONTENT_LENGTH, item.size.unwrap_or_default())
+            .header(hyper::header::ETAG, format!("\"{}\"", item.rev));
+        if let Some(modified) = item.client_modified {
+            builder = builder.header(hyper::header::LAST_MODIFIED, http_date(modified));
+        }
+    }
+    builder.body(Body::empty()).unwrap()
+}
+
+fn propfind_response(item: &storage_models::Item) -> String {
+    let path = item.path_display.trim_matches('/');
+    let mut props = format!("<D:displayname>{}</D:displayname>", xml_escape(&item.name));
+    if item.is_folder {
+        props.push_str("<D:resourcetype><D:collection/></D:resourcetype>");
+    } else {
+        props.push_str("<D:resourcetype/>");
+        props.push_str(&format!(
+            "<D:getcontentlength>{}</D:getcontentlength><D:getcontenttype>{}</D:getcontenttype><D:getetag>\"{}\"</D:getetag>",
+            item.size.unwrap_or_default(),
+            content_type(&item.name),
+            xml_escape(&item.rev)
+        ));
+    }
+    if let Some(modified) = item.client_modified.or(item.modified) {
+        props.push_str(&format!(
+            "<D:getlastmodified>{}</D:getlastmodified>",
+            http_date(modified)
+        ));
+    }
+    format!(
+        "<D:response><D:href>{}</D:href><D:propstat><D:prop>{}</D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>",
+        href(path, item.is_folder),
+        props
+    )
+}
+
+fn method_not_allowed(allowed: &str) -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::METHOD_NOT_ALLOWED)
+        .header(hyper::header::ALLOW, allowed)
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn root_item() -> storage_models::Item {
+    storage_models::Item {
+        name: "".to_string(),
+        id: "".to_string(),
+        is_folder: true,
+        modified: None,
+        size: None,
+        items: None,
+        path_display: "".to_string(),
+        path_lower: "".to_string(),
+        rev: "".to_string(),
+        client_modified: None,
+        is_downloadable: false,
+        content_hash: "".to_string(),
+        media_info: None,
+        is_deleted: false,
+    }
+}
+
+impl ServerData {
+    fn is_authorized(&self, req: &Request<Body>) -> bool {
+        match &self.authorization {
+            Some(expected) => req
+                .headers()
+                .get(hyper::header::AUTHORIZATION)
+                .map(|value| constant_time_eq(value.as_bytes(), expected.as_bytes()))
+                .unwrap_or(false),
+            None => true,
+        }
+    }
+
+    /// None if not found
+    async fn get_item(&self, path: &str) -> Result<Option<storage_models::Item>, Response<Body>> {
+        if path.is_empty() {
+            return Ok(Some(root_item()));
+        }
+        match dropbox::get_metadata(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            dropbox::GetMetadataParams {
+                path: api_path(path),
+                include_media_info: None,
+                include_deleted: None,
+                include_has_explicit_shared_members: None,
+            },
+            self.messages.clone(),
+            dropbox::token_messages::<call_messages::WebDav>(self.call_id),
+        )
+        .await
+        {
+            Ok(meta) => Ok(storages::item_from_meta(meta)),
+            Err(dropbox::GetMetadataCallError::PathNotFound(_)) => Ok(None),
+            Err(e) => Err(remote_error_response(e)),
+        }
+    }
+
+    async fn list(&self, path: &str) -> Result<Vec<storage_models::Item>, Response<Body>> {
+        let mut res = dropbox::list_folder(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            dropbox::ListFolderParams {
+                path: api_path(path),
+                recursive: Some(false),
+                include_deleted: Some(false),
+                include_has_explicit_shared_members: None,
+                include_mounted_folders: None,
+                limit: Some(2000),
+                include_non_downloadable_files: Some(true),
+            },
+            self.messages.clone(),
+            dropbox::token_messages::<call_messages::WebDav>(self.call_id),
+        )
+        .await
+        .map_err(remote_error_response)?;
+        let mut entries = std::mem::take(&mut res.entries);
+        while res.has_more {
+            res = dropbox::list_folder_continue(
+                self.rth.clone(),
+                self.auth_info_holder.clone(),
+                dropbox::ListFolderContinueParams { cursor: res.cursor },
+                self.messages.clone(),
+                dropbox::token_messages::<call_messages::WebDav>(self.call_id),
+            )
+            .await
+            .map_err(remote_error_response)?;
+            entries.append(&mut res.entries);
+        }
+        Ok(entries
+            .into_iter()
+            .filter_map(storages::item_from_meta)
+            .collect())
+    }
+
+    /// allprop of `path`, and of its entries if `depth` is not "0"
+    async fn propfind(&self, path: &str, depth: &str) -> Response<Body> {
+        let item = match self.get_item(path).await {
+            Ok(Some(item)) => item,
+            Ok(None) => return status_response(StatusCode::NOT_FOUND),
+            Err(rsp) => return rsp,
+        };
+        let mut xml =
+            r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:">"#.to_string();
+        xml.push_str(&propfind_response(&item));
+        if item.is_folder && depth != "0" {
+            match self.list(path).await {
+                Ok(items) => items
+                    .iter()
+                    .for_each(|item| xml.push_str(&propfind_response(item))),
+                Err(rsp) => return rsp,
+            }
+        }
+        xml.push_str("</D:multistatus>");
+        Response::builder()
+            .status(StatusCode::MULTI_STATUS)
+            .header(
+                hyper::header::CONTENT_TYPE,
+                "application/xml; charset=utf-8",
+            )
+            .body(Body::from(xml))
+            .unwrap()
+    }
+
+    /// content is streamed from the server, Range is passed through
+    async fn get(&self, path: &str, range: Option<String>) -> Response<Body> {
+        if path.is_empty() {
+            return method_not_allowed(FOLDER_ALLOWED_METHODS);
+        }
+        let res = dropbox::download_stream(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            dropbox::DownloadFileParams {
+                path: api_path(path),
+                save_to: "".to_string(),
+            },
+            range,
+            self.messages.clone(),
+            dropbox::token_messages::<call_messages::WebDav>(self.call_id),
+        )
+        .await;
+        // download of a folder fails, files are not looked up before download
+        if res.is_err() {
+            if let Ok(Some(item)) = self.get_item(path).await {
+                if item.is_folder {
+                    return method_not_allowed(FOLDER_ALLOWED_METHODS);
+                }
+            }
+        }
+        download_response(res)
+    }
+
+    async fn head(&self, path: &str) -> Response<Body> {
+        let item = match self.get_item(path).await {
+            Ok(Some(item)) => item,
+            Ok(None) => return status_response(StatusCode::NOT_FOUND),
+            Err(rsp) => return rsp,
+        };
+        head_response(&item)
+    }
+
+    /// body is received into temporary file first, existing file is replaced
+    async fn put(&self, path: &str, mut body: Body) -> Response<Body> {
+        use http_body::Body; // for body.data()
+        use tokio::io::AsyncWriteExt;
+
+        if path.is_empty() {
+            return status_response(StatusCode::METHOD_NOT_ALLOWED);
+        }
+        let temp_file = self
+            .temp_dir
+            .join(format!("{:016x}.part", rand::random::<u64>()));
+        let received: std::io::Result<()> = async {
+            tokio::fs::create_dir_all(&self.temp_dir).await?;
+            let mut file = tokio::fs::File::create(&temp_file).await?;
+            while let Some(chunk) = body.data().await {
+                let chunk = chunk.map_err(std::io::Error::other)?;
+                file.write_all(&chunk).await?;
+            }
+            file.flush().await
+        }
+        .await;
+        if let Err(e) = received {
+            let _ = tokio::fs::remove_file(&temp_file).await;
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("receive to '{}': {}", temp_file.display(), e),
+            );
+        }
+        let res = dropbox::upload(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            temp_file.to_string_lossy().to_string(),
+            dropbox::CommitInfo {
+                path: api_path(path),
+                mode: dropbox::WriteMode::Overwrite,
+                autorename: false,
+                client_modified: None,
+                mute: false,
+            },
+            self.messages.clone(),
+            dropbox::token_messages::<call_messages::WebDav>(self.call_id),
+        )
+        .await;
+        let _ = tokio::fs::remove_file(&temp_file).await;
+        match res {
+            Ok(_) => status_response(StatusCode::CREATED),
+            Err(dropbox::UploadCallError::Conflict(_)) => status_response(StatusCode::CONFLICT),
+            Err(dropbox::UploadCallError::InsufficientSpace) => {
+                status_response(StatusCode::INSUFFICIENT_STORAGE)
+            }
+            Err(dropbox::UploadCallError::DisallowedName(_)) => {
+                status_response(StatusCode::FORBIDDEN)
+            }
+            Err(e) => remote_error_response(e),
+        }
+    }
+
+    async fn mkcol(&self, path: &str) -> Response<Body> {
+        if path.is_empty() {
+            return status_response(StatusCode::METHOD_NOT_ALLOWED);
+        }
+        match dropbox::create_folder(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            dropbox::CreateFolderParams {
+                path: api_path(path),
+                autorename: false,
+            },
+            self.messages.clone(),
+            dropbox::token_messages::<call_messages::WebDav>(self.call_id),
+        )
+        .await
+        {
+            Ok(_) => status_response(StatusCode::CREATED),
+            // already exists
+            Err(dropbox::FileOpCallError::Conflict(_)) => {
+                status_response(StatusCode::METHOD_NOT_ALLOWED)
+            }
+            Err(e) => remote_error_response(e),
+        }
+    }
+
+    async fn delete(&self, path: &str) -> Result<(), dropbox::FileOpCallError> {
+        dropbox::delete(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            dropbox::DeleteParams {
+                path: api_path(path),
+                parent_rev: None,
+            },
+            self.messages.clone(),
+            dropbox::token_messages::<call_messages::WebDav>(self.call_id),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    async fn move_file(
+        &self,
+        from_path: &str,
+        to_path: &str,
+    ) -> Result<(), dropbox::FileOpCallError> {
+        dropbox::move_file(
+            self.rth.clone(),
+            self.auth_info_holder.clone(),
+            dropbox::MoveParams {
+                from_path: api_path(from_path),
+                to_path: api_path(to_path),
+                autorename: false,
+            },
+            self.messages.clone(),
+            dropbox::token_messages::<call_messages::WebDav>(self.call_id),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// existing destination is replaced unless `overwrite` is false: it is moved aside first
+    /// (move_v2 can't overwrite) and moved back if the move fails
+    async fn move_to(
+        &self,
+        path: &str,
+        destination: Option<String>,
+        overwrite: bool,
+    ) -> Response<Body> {
+        let to_path = match destination {
+            Some(to_path) if !path.is_empty() && !to_path.is_empty() => to_path,
+            _ => return status_response(StatusCode::BAD_REQUEST),
+        };
+        match self.move_file(path, &to_path).await {
+            Ok(_) => status_response(StatusCode::CREATED),
+            Err(dropbox::FileOpCallError::PathNotFound(_)) => {
+                status_response(StatusCode::NOT_FOUND)
+            }
+            Err(dropbox::FileOpCallError::Conflict(_)) if overwrite => {
+                let aside_path = format!(
+                    "{}.{:016x}.clouds_viewer_overwritten",
+                    to_path,
+                    rand::random::<u64>()
+                );
+                if let Err(e) = self.move_file(&to_path, &aside_path).await {
+                    return remote_error_response(e);
+                }
+                match self.move_file(path, &to_path).await {
+                    Ok(_) => {
+                        if let Err(e) = self.delete(&aside_path).await {
+                            log::error!(
+                                "webdav: delete of overwritten '{}' failed: {}",
+                                &aside_path,
+                                e
+                            );
+                        }
+                        status_response(StatusCode::NO_CONTENT)
+                    }
+                    Err(e) => {
+                        if let Err(restore_e) = self.move_file(&aside_path, &to_path).await {
+                            log::error!(
+                                "webdav: restore of '{}' from '{}' failed: {}",
+                                &to_path,
+                                &aside_path,
+                                restore_e
+                            );
+                        }
+                        remote_error_response(e)
+                    }
+                }
+            }
+            Err(dropbox::FileOpCallError::Conflict(_)) => {
+                status_response(StatusCode::PRECONDITION_FAILED)
+            }
+            Err(e) => remote_error_response(e),
+        }
+    }
+
+    /// compatibility stub for clients which mount read-only or refuse to write without class 2
+    /// (Finder, Windows): the token is not stored, other clients and PUT are not blocked, so
+    /// the exclusive lock reported to the client is not held
+    fn lock(&self, path: &str) -> Response<Body> {
+        use rand::Rng;
+        let token: String = rand::thread_rng()
+            .sample_iter(rand::distributions::Alphanumeric)
+            .map(char::from)
+            .take(24)
+            .collect();
+        let xml = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?><D:prop xmlns:D="DAV:"><D:lockdiscovery><D:activelock><D:locktype><D:write/></D:locktype><D:lockscope><D:exclusive/></D:lockscope><D:depth>infinity</D:depth><D:timeout>Second-3600</D:timeout><D:locktoken><D:href>opaquelocktoken:{}</D:href></D:locktoken><D:lockroot><D:href>{}</D:href></D:lockroot></D:activelock></D:lockdiscovery></D:prop>"#,
+            token,
+            href(path, false)
+        );
+        Response::builder()
+            .status(StatusCode::OK)
+            .header(
+                hyper::header::CONTENT_TYPE,
+                "application/xml; charset=utf-8",
+            )
+            .header("Lock-Token", format!("<opaquelocktoken:{}>", token))
+            .body(Body::from(xml))
+            .unwrap()
+    }
+
+    async fn handle(self, req: Request<Body>) -> Response<Body> {
+        let method = req.method().as_str().to_string();
+        let path = match decode_path(req.uri().path()) {
+            Some(path) => path,
+            None => return status_response(StatusCode::BAD_REQUEST),
+        };
+        let header = |name: &str| {
+            req.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        };
+        let rsp = if !is_local_host(&req, self.port) {
+            status_response(StatusCode::FORBIDDEN)
+        } else if !self.is_authorized(&req) {
+            Response::builder()
+                .status(StatusCode::UNAUTHORIZED)
+                .header(
+                    hyper::header::WWW_AUTHENTICATE,
+                    r#"Basic realm="clouds_viewer", charset="UTF-8""#,
+                )
+                .body(Body::empty())
+                .unwrap()
+        } else {
+            match method.as_str() {
+                // class 2 only for the LOCK stub, locks are not enforced
+                "OPTIONS" => Response::builder()
+                    .status(StatusCode::OK)
+                    .header("DAV", "1, 2")
+                    .header("MS-Author-Via", "DAV")
+                    .header(hyper::header::ALLOW, ALLOWED_METHODS)
+                    .body(Body::empty())
+                    .unwrap(),
+                "PROPFIND" => {
+                    // missing Depth means infinity, listing of the whole tree is not
+                    // supported (propfind-finite-depth)
+                    let depth = header("Depth").unwrap_or_else(|| "infinity".to_string());
+                    if depth.eq_ignore_ascii_case("infinity") {
+                        status_response(StatusCode::FORBIDDEN)
+                    } else {
+                        self.propfind(&path, &depth).await
+                    }
+                }
+                "GET" => self.get(&path, header("Range")).await,
+                "HEAD" => self.head(&path).await,
+                "PUT" => self.put(&path, req.into_body()).await,
+                "MKCOL" => self.mkcol(&path).await,
+                "DELETE" => match self.delete(&path).await {
+                    Ok(_) => status_response(StatusCode::NO_CONTENT),
+                    Err(dropbox::FileOpCallError::PathNotFound(_)) => {
+                        status_response(StatusCode::NOT_FOUND)
+                    }
+                    Err(e) => remote_error_response(e),
+                },
+                "MOVE" => {
+                    // absolute url or path
+                    let destination = header("Destination")
+                        .and_then(|value| value.parse::<http::Uri>().ok())
+                        .and_then(|uri| decode_path(uri.path()));
+                    let overwrite = !header("Overwrite").unwrap_or_default().eq("F");
+                    self.move_to(&path, destination, overwrite).await
+                }
+                "LOCK" => self.lock(&path),
+                "UNLOCK" => status_response(StatusCode::NO_CONTENT),
+                _ => method_not_allowed(ALLOWED_METHODS),
+            }
+        };
+        let request = format!("{} /{} {}", method, path, rsp.status().as_u16());
+        log::debug!("webdav: {}", &request);
+        let _ = self.messages.send(call_messages::Message {
+            call_id: Some(self.call_id),
+            data: call_messages::Data::WebDav(call_messages::WebDav::Served { request }),
+        });
+        rsp
+    }
+}
+
+impl tower::Service<Request<Body>> for ServerData {
+    type Response = Response<Body>;
+    type Error = std::convert::Infallible;
+    type Future = std::pin::Pin<
+        Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>,
+    >;
+
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let data = self.clone();
+        Box::pin(async move { Ok(data.handle(req).await) })
+    }
+}
+
+/// serves until shut down by LocalServerShutdowner canceller
+pub async fn run(
+    rth: RuntimeHolder,
+    auth_info_holder: clouds::AuthInfoHolder,
+    in_data: storage_models::webdav_in_data,
+    call_id: u64,
+    messages: MessagesSender<call_messages::Message>,
+) -> Result<(), storage_models::WebDavError> {
+    if in_data.user.is_empty() && !in_data.allow_anonymous {
+        return Err(storage_models::WebDavError::NoCredentials);
+    }
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], in_data.port));
+    let builder = hyper::Server::try_bind(&addr).map_err(|e| {
+        storage_models::WebDavError::Bind(BindError::Error {
+            addr,
+            text: e.to_string(),
+        })
+    })?;
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::WebDav(call_messages::WebDav::Binded { addr }),
+    });
+
+    let data = ServerData {
+        rth: rth.clone(),
+        auth_info_holder,
+        authorization: if in_data.user.is_empty() {
+            None
+        } else {
+            Some(
+                "Basic ".to_owned()
+                    + &base64::encode(format!("{}:{}", in_data.user, in_data.password)),
+            )
+        },
+        port: in_data.port,
+        temp_dir: in_data.temp_dir,
+        call_id,
+        messages: messages.clone(),
+    };
+    let make_service = hyper::service::make_service_fn(move |_conn| {
+        let svc = data.clone();
+        async move { Ok::<_, std::convert::Infallible>(svc) }
+    });
+
+    let (sender, receiver) = tokio::sync::oneshot::channel::<()>();
+    let (shutdown_awaiter_s, shutdown_awaiter_r) = tokio::sync::oneshot::channel::<()>();
+    let server = builder
+        .executor(rth)
+        .serve(make_service)
+        .with_graceful_shutdown(async {
+            receiver.await.ok();
+        });
+    let _ = messages.send(call_messages::Message {
+        call_id: Some(call_id),
+        data: call_messages::Data::WebDav(call_messages::WebDav::LocalServerShutdowner {
+            cancel_informer: sender,
+            cancel_awaiter: shutdown_awaiter_r,
+        }),
+    });
+
+    log::debug!("WebDAV server running at {}", &addr);
+    let res = server.await;
+    let _ = shutdown_awaiter_s.send(());
+    res.map_err(|e| storage_models::WebDavError::LocalServerError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_path_decodes_and_trims() {
+        assert_eq!(decode_path("/").as_deref(), Some(""));
+        assert_eq!(
+            decode_path("/dir/a%20b.txt/").as_deref(),
+            Some("dir/a b.txt")
+        );
+        assert_eq!(decode_path("/%D1%84%2Fx").as_deref(), Some("ф/x"));
+    }
+
+    #[test]
+    fn decode_path_rejects_invalid_escapes() {
+        assert_eq!(decode_path("/a%2"), None);
+        assert_eq!(decode_path("/a%zz"), None);
+        assert_eq!(decode_path("/a%FF"), None);
+    }
+
+    #[test]
+    fn encode_path_is_decoded_back() {
+        let path = "dir/a b+ф%.txt";
+        assert_eq!(decode_path(&encode_path(path)).as_deref(), Some(path));
+    }
+}